@@ -53,7 +53,10 @@ use near_primitives::views::{
     FinalExecutionOutcomeWithReceiptView, FinalExecutionStatus, LightClientBlockView,
     SignedTransactionView,
 };
-use near_store::{ColState, ColStateHeaders, ColStateParts, ShardTries, StoreUpdate};
+use near_store::{
+    ColBlockMisc, ColState, ColStateHeaders, ColStateParts, ShardTries, StoreUpdate,
+    STATE_SNAPSHOT_KEY,
+};
 
 #[cfg(feature = "delay_detector")]
 use delay_detector::DelayDetector;
@@ -1745,6 +1748,29 @@ impl Chain {
         Ok(())
     }
 
+    /// Returns `sync_hash` of the state snapshot imported with `neard import_state_snapshot`,
+    /// if state sync from it hasn't been completed yet.
+    pub fn get_imported_state_snapshot(&self) -> Result<Option<CryptoHash>, Error> {
+        Ok(self.store.owned_store().get_ser(ColBlockMisc, STATE_SNAPSHOT_KEY)?)
+    }
+
+    pub fn clear_imported_state_snapshot(&mut self) -> Result<(), Error> {
+        let mut store_update = self.store.owned_store().store_update();
+        store_update.delete(ColBlockMisc, STATE_SNAPSHOT_KEY);
+        Ok(store_update.commit()?)
+    }
+
+    /// Checks whether the state part was already saved, either downloaded or imported.
+    pub fn has_state_part(
+        &self,
+        shard_id: ShardId,
+        sync_hash: CryptoHash,
+        part_id: u64,
+    ) -> Result<bool, Error> {
+        let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
+        Ok(self.store.owned_store().exists(ColStateParts, &key)?)
+    }
+
     pub fn clear_downloaded_parts(
         &mut self,
         shard_id: ShardId,
@@ -2805,6 +2831,12 @@ impl<'a> ChainUpdate<'a> {
                         shard_id,
                         apply_result.receipt_result,
                     );
+                    self.chain_store_update.save_account_history(
+                        &block.hash(),
+                        block.header().height(),
+                        chunk.transactions(),
+                        &apply_result.outcomes,
+                    );
                     // Save receipt and transaction results.
                     self.chain_store_update.save_outcomes_with_proofs(
                         &block.hash(),
@@ -3468,6 +3500,12 @@ impl<'a> ChainUpdate<'a> {
         let (outcome_root, outcome_proofs) =
             ApplyTransactionResult::compute_outcomes_proof(&apply_result.outcomes);
 
+        self.chain_store_update.save_account_history(
+            block_header.hash(),
+            block_header.height(),
+            chunk.transactions(),
+            &apply_result.outcomes,
+        );
         self.chain_store_update.save_chunk(chunk);
 
         self.chain_store_update.save_trie_changes(apply_result.trie_changes);
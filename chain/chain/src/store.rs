@@ -1,6 +1,6 @@
 use std::collections::hash_map::Entry;
 use std::collections::{HashMap, HashSet};
-use std::convert::TryFrom;
+use std::convert::{TryFrom, TryInto};
 use std::io;
 use std::sync::Arc;
 
@@ -23,7 +23,7 @@ use near_primitives::syncing::{
     StateHeaderKey, StatePartKey,
 };
 use near_primitives::transaction::{
-    ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, SignedTransaction,
+    ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, GasProfile, SignedTransaction,
 };
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{
@@ -33,21 +33,22 @@ use near_primitives::types::{
 use near_primitives::utils::{get_block_shard_id, index_to_bytes, to_timestamp};
 use near_primitives::views::LightClientBlockView;
 use near_store::{
-    read_with_cache, ColBlock, ColBlockExtra, ColBlockHeader, ColBlockHeight, ColBlockInfo,
-    ColBlockMerkleTree, ColBlockMisc, ColBlockOrdinal, ColBlockPerHeight, ColBlockRefCount,
-    ColBlocksToCatchup, ColChallengedBlocks, ColChunkExtra, ColChunkHashesByHeight,
-    ColChunkPerHeightShard, ColChunks, ColEpochLightClientBlocks, ColGCCount, ColIncomingReceipts,
-    ColInvalidChunks, ColLastBlockWithNewChunk, ColNextBlockHashes, ColNextBlockWithNewChunk,
-    ColOutcomeIds, ColOutgoingReceipts, ColPartialChunks, ColProcessedBlockHeights,
-    ColReceiptIdToShardId, ColReceipts, ColState, ColStateChanges, ColStateDlInfos,
-    ColStateHeaders, ColStateParts, ColTransactionResult, ColTransactions, ColTrieChanges, DBCol,
-    KeyForStateChanges, ShardTries, Store, StoreUpdate, TrieChanges, WrappedTrieChanges,
-    CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
-    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, SHOULD_COL_GC, TAIL_KEY,
+    read_with_cache, ColAccountHistory, ColBlock, ColBlockExtra, ColBlockHeader, ColBlockHeight,
+    ColBlockInfo, ColBlockMerkleTree, ColBlockMisc, ColBlockOrdinal, ColBlockPerHeight,
+    ColBlockRefCount, ColBlocksToCatchup, ColChallengedBlocks, ColChunkExtra,
+    ColChunkHashesByHeight, ColChunkPerHeightShard, ColChunks, ColEpochLightClientBlocks,
+    ColGCCount, ColGasProfiles, ColIncomingReceipts, ColInvalidChunks, ColLastBlockWithNewChunk,
+    ColNextBlockHashes, ColNextBlockWithNewChunk, ColOutcomeIds, ColOutgoingReceipts,
+    ColPartialChunks, ColProcessedBlockHeights, ColReceiptIdToShardId, ColReceipts, ColState,
+    ColStateChanges, ColStateDlInfos, ColStateHeaders, ColStateParts, ColTransactionResult,
+    ColTransactions, ColTrieChanges, DBCol, KeyForStateChanges, ShardTries, Store, StoreUpdate,
+    TrieChanges, WrappedTrieChanges, CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY,
+    HEADER_HEAD_KEY, HEAD_KEY, LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, SHOULD_COL_GC,
+    TAIL_KEY,
 };
 
 use crate::error::{Error, ErrorKind};
-use crate::types::{Block, BlockHeader, LatestKnown};
+use crate::types::{AccountHistoryKind, AccountHistoryRecord, Block, BlockHeader, LatestKnown};
 use crate::{byzantine_assert, ReceiptResult};
 
 /// lru cache size
@@ -75,6 +76,27 @@ fn get_height_shard_id(height: BlockHeight, shard_id: ShardId) -> Vec<u8> {
     res
 }
 
+/// Account ids cannot contain `,`, so no account prefix is a prefix of another one.
+fn get_account_history_prefix(account_id: &AccountId) -> Vec<u8> {
+    let mut res = Vec::with_capacity(account_id.len() + 1);
+    res.extend_from_slice(account_id.as_bytes());
+    res.push(b',');
+    res
+}
+
+fn get_account_history_key(account_id: &AccountId, height: BlockHeight) -> Vec<u8> {
+    let mut res = get_account_history_prefix(account_id);
+    res.extend_from_slice(&height.to_be_bytes());
+    res
+}
+
+fn get_gas_profile_key(outcome_id: &CryptoHash, block_hash: &CryptoHash) -> Vec<u8> {
+    let mut res = Vec::with_capacity(64);
+    res.extend_from_slice(outcome_id.as_ref());
+    res.extend_from_slice(block_hash.as_ref());
+    res
+}
+
 /// Accesses the chain store. Used to create atomic editable views that can be reverted.
 pub trait ChainStoreAccess {
     /// Returns underlaying store.
@@ -375,6 +397,8 @@ pub struct ChainStore {
     block_ordinal_to_hash: SizedCache<Vec<u8>, CryptoHash>,
     /// Processed block heights.
     processed_block_heights: SizedCache<Vec<u8>, ()>,
+    /// Whether to maintain the account history index.
+    save_account_history: bool,
 }
 
 pub fn option_to_not_found<T>(res: io::Result<Option<T>>, field_name: &str) -> Result<T, Error> {
@@ -417,7 +441,66 @@ impl ChainStore {
             block_merkle_tree: SizedCache::with_size(CACHE_SIZE),
             block_ordinal_to_hash: SizedCache::with_size(CACHE_SIZE),
             processed_block_heights: SizedCache::with_size(CACHE_SIZE),
+            save_account_history: false,
+        }
+    }
+
+    pub fn set_save_account_history(&mut self, save_account_history: bool) {
+        self.save_account_history = save_account_history;
+    }
+
+    /// Returns the gas profile recorded for the outcome `outcome_id` in block `block_hash`, if
+    /// the node had gas profiling enabled when it applied the chunk.
+    pub fn get_gas_profile(
+        &self,
+        outcome_id: &CryptoHash,
+        block_hash: &CryptoHash,
+    ) -> Result<Option<GasProfile>, Error> {
+        Ok(self.store.get_ser(ColGasProfiles, &get_gas_profile_key(outcome_id, block_hash))?)
+    }
+
+    /// Returns the account history at or below `max_height`, most recent block first. At most
+    /// `limit` blocks are returned, each with all the transactions and receipts that touched the
+    /// account in it. Only blocks on the canonical chain are included.
+    pub fn get_account_history(
+        &mut self,
+        account_id: &AccountId,
+        max_height: BlockHeight,
+        limit: usize,
+    ) -> Result<Vec<(BlockHeight, Vec<AccountHistoryRecord>)>, Error> {
+        let prefix = get_account_history_prefix(account_id);
+        let upper_bound = get_account_history_key(account_id, max_height);
+        // Seeks right to `max_height` and stops after `limit` blocks, so the cost does not grow
+        // with the size of the whole history.
+        let store = self.store.clone();
+        let entries = store.iter_prefix_rev(ColAccountHistory, &prefix, &upper_bound).filter_map(
+            |(key, value)| {
+                let height_bytes = key[prefix.len()..].try_into().ok()?;
+                Some((BlockHeight::from_be_bytes(height_bytes), value))
+            },
+        );
+
+        let mut result = vec![];
+        for (height, value) in entries {
+            if result.len() >= limit {
+                break;
+            }
+            let canonical_hash = match self.get_block_hash_by_height(height) {
+                Ok(hash) => hash,
+                Err(err) => match err.kind() {
+                    ErrorKind::DBNotFoundErr(_) => continue,
+                    _ => return Err(err),
+                },
+            };
+            let records: Vec<_> = Vec::<AccountHistoryRecord>::try_from_slice(&value)?
+                .into_iter()
+                .filter(|record| record.block_hash == canonical_hash)
+                .collect();
+            if !records.is_empty() {
+                result.push((height, records));
+            }
         }
+        Ok(result)
     }
 
     pub fn owned_store(&self) -> Arc<Store> {
@@ -1159,6 +1242,7 @@ struct ChainStoreCacheUpdate {
     block_ordinal_to_hash: HashMap<NumBlocks, CryptoHash>,
     gc_count: HashMap<DBCol, GCCount>,
     processed_block_heights: HashSet<BlockHeight>,
+    account_history: HashMap<(AccountId, BlockHeight), Vec<AccountHistoryRecord>>,
 }
 
 /// Provides layer to update chain without touching the underlying database.
@@ -1902,6 +1986,46 @@ impl<'a> ChainStoreUpdate<'a> {
         self.chain_store_cache_update.outcome_ids.insert((*block_hash, shard_id), outcome_ids);
     }
 
+    /// Indexes the outcomes of a chunk by the accounts they touched if the account history index
+    /// is enabled. Transactions are indexed under their signer and receiver, receipts under the
+    /// account that executed them. A receipt sent by an account is not indexed under it, it can
+    /// be found in the `receipt_ids` of the outcome of the indexed transaction or receipt that
+    /// produced it.
+    pub fn save_account_history(
+        &mut self,
+        block_hash: &CryptoHash,
+        block_height: BlockHeight,
+        transactions: &[SignedTransaction],
+        outcomes: &[ExecutionOutcomeWithId],
+    ) {
+        if !self.chain_store.save_account_history {
+            return;
+        }
+        let transactions: HashMap<CryptoHash, &SignedTransaction> =
+            transactions.iter().map(|tx| (tx.get_hash(), tx)).collect();
+        for outcome_with_id in outcomes {
+            let (kind, mut account_ids) = match transactions.get(&outcome_with_id.id) {
+                Some(tx) => (
+                    AccountHistoryKind::Transaction,
+                    vec![&tx.transaction.signer_id, &tx.transaction.receiver_id],
+                ),
+                None => (AccountHistoryKind::Receipt, vec![&outcome_with_id.outcome.executor_id]),
+            };
+            account_ids.dedup();
+            for account_id in account_ids {
+                self.chain_store_cache_update
+                    .account_history
+                    .entry((account_id.clone(), block_height))
+                    .or_insert_with(Vec::new)
+                    .push(AccountHistoryRecord {
+                        block_hash: *block_hash,
+                        id: outcome_with_id.id,
+                        kind: kind.clone(),
+                    });
+            }
+        }
+    }
+
     pub fn save_trie_changes(&mut self, trie_changes: WrappedTrieChanges) {
         self.trie_changes.push(trie_changes);
     }
@@ -2282,6 +2406,10 @@ impl<'a> ChainStoreUpdate<'a> {
             let shard_id = chunk_header.shard_id();
             let outcome_ids = self.get_outcomes_by_block_hash_and_shard_id(block_hash, shard_id)?;
             for outcome_id in outcome_ids {
+                let gas_profile_key = get_gas_profile_key(&outcome_id, block_hash);
+                if self.store().exists(ColGasProfiles, &gas_profile_key)? {
+                    self.gc_col(ColGasProfiles, &gas_profile_key);
+                }
                 let mut outcomes_with_id = self.get_outcomes_by_id(&outcome_id)?;
                 outcomes_with_id.retain(|outcome| &outcome.block_hash != block_hash);
                 if outcomes_with_id.is_empty() {
@@ -2396,6 +2524,9 @@ impl<'a> ChainStoreUpdate<'a> {
             DBCol::ColOutcomeIds => {
                 store_update.delete(col, key);
             }
+            DBCol::ColGasProfiles => {
+                store_update.delete(col, key);
+            }
             DBCol::ColStateDlInfos => {
                 store_update.delete(col, key);
             }
@@ -2425,7 +2556,9 @@ impl<'a> ChainStoreUpdate<'a> {
             | DBCol::ColEpochInfo
             | DBCol::ColEpochStart
             | DBCol::ColBlockOrdinal
-            | DBCol::_ColTransactionRefCount => {
+            | DBCol::_ColTransactionRefCount
+            | DBCol::ColCachedContractCode
+            | DBCol::ColAccountHistory => {
                 unreachable!();
             }
         }
@@ -2586,6 +2719,16 @@ impl<'a> ChainStoreUpdate<'a> {
             let mut existing_outcomes = self.chain_store.get_outcomes_by_id(hash)?;
             existing_outcomes.extend_from_slice(outcomes);
             store_update.set_ser(ColTransactionResult, hash.as_ref(), &existing_outcomes)?;
+            // Gas profiles are not part of the serialized outcome, so they are stored separately.
+            for outcome in outcomes {
+                if let Some(gas_profile) = &outcome.outcome_with_id.outcome.gas_profile {
+                    store_update.set_ser(
+                        ColGasProfiles,
+                        &get_gas_profile_key(hash, &outcome.block_hash),
+                        gas_profile,
+                    )?;
+                }
+            }
         }
         for ((block_hash, shard_id), ids) in self.chain_store_cache_update.outcome_ids.iter() {
             store_update.set_ser(
@@ -2594,6 +2737,19 @@ impl<'a> ChainStoreUpdate<'a> {
                 &ids,
             )?;
         }
+        for ((account_id, height), records) in self.chain_store_cache_update.account_history.iter()
+        {
+            let key = get_account_history_key(account_id, *height);
+            let mut existing_records: Vec<AccountHistoryRecord> =
+                self.chain_store.store.get_ser(ColAccountHistory, &key)?.unwrap_or_default();
+            // A chunk can be applied more than once for the same block, e.g. during catchup.
+            for record in records {
+                if !existing_records.contains(record) {
+                    existing_records.push(record.clone());
+                }
+            }
+            store_update.set_ser(ColAccountHistory, &key, &existing_records)?;
+        }
         for (receipt_id, shard_id) in self.chain_store_cache_update.receipt_id_to_shard_id.iter() {
             let data = shard_id.try_to_vec()?;
             store_update.update_refcount(ColReceiptIdToShardId, receipt_id.as_ref(), &data, 1);
@@ -2868,12 +3024,15 @@ mod tests {
     use cached::Cached;
     use strum::IntoEnumIterator;
 
-    use near_crypto::KeyType;
+    use near_crypto::{InMemorySigner, KeyType};
     use near_primitives::block::{Block, Tip};
     #[cfg(feature = "expensive_tests")]
     use near_primitives::epoch_manager::BlockInfo;
     use near_primitives::errors::InvalidTxError;
     use near_primitives::hash::hash;
+    use near_primitives::transaction::{
+        ExecutionOutcome, ExecutionOutcomeWithId, SignedTransaction,
+    };
     use near_primitives::types::{BlockHeight, EpochId, GCCount, NumBlocks};
     use near_primitives::utils::index_to_bytes;
     use near_primitives::validator_signer::InMemoryValidatorSigner;
@@ -2884,6 +3043,7 @@ mod tests {
 
     use crate::store::{ChainStoreAccess, GCMode};
     use crate::test_utils::KeyValueRuntime;
+    use crate::types::AccountHistoryKind;
     use crate::{Chain, ChainGenesis, DoomslugThresholdMode};
 
     fn get_chain() -> Chain {
@@ -3061,6 +3221,76 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_account_history() {
+        let mut chain = get_chain();
+        chain.mut_store().set_save_account_history(true);
+        let genesis = chain.get_block_by_height(0).unwrap().clone();
+        let signer =
+            Arc::new(InMemoryValidatorSigner::from_seed("test1", KeyType::ED25519, "test1"));
+        let tx_signer = InMemorySigner::from_seed("alice", KeyType::ED25519, "alice");
+        let mut blocks = vec![];
+        let mut prev_block = genesis.clone();
+        let mut store_update = chain.mut_store().store_update();
+        for height in 1..5 {
+            let block = Block::empty_with_height(&prev_block, height, &*signer);
+            prev_block = block.clone();
+            store_update.save_block_header(block.header().clone()).unwrap();
+            store_update.update_height_if_not_challenged(height, *block.hash()).unwrap();
+            let tx = SignedTransaction::send_money(
+                height,
+                "alice".to_string(),
+                "bob".to_string(),
+                &tx_signer,
+                1,
+                *genesis.hash(),
+            );
+            let receipt_id = hash(&height.to_le_bytes());
+            let outcomes = vec![
+                ExecutionOutcomeWithId {
+                    id: tx.get_hash(),
+                    outcome: ExecutionOutcome {
+                        executor_id: "alice".to_string(),
+                        ..Default::default()
+                    },
+                },
+                ExecutionOutcomeWithId {
+                    id: receipt_id,
+                    outcome: ExecutionOutcome {
+                        executor_id: "bob".to_string(),
+                        ..Default::default()
+                    },
+                },
+            ];
+            store_update.save_account_history(block.hash(), height, &[tx], &outcomes);
+            blocks.push(block);
+        }
+        // Records of a block that is not on the canonical chain must not be returned.
+        let fork_block = Block::empty_with_height(&genesis, 2, &*signer);
+        let fork_outcome = ExecutionOutcomeWithId {
+            id: hash(b"fork"),
+            outcome: ExecutionOutcome { executor_id: "bob".to_string(), ..Default::default() },
+        };
+        store_update.save_account_history(fork_block.hash(), 2, &[], &[fork_outcome]);
+        store_update.commit().unwrap();
+
+        let history = chain.mut_store().get_account_history(&"alice".to_string(), 4, 2).unwrap();
+        assert_eq!(history.iter().map(|(height, _)| *height).collect::<Vec<_>>(), vec![4, 3]);
+        assert_eq!(history[0].1.len(), 1);
+        assert_eq!(history[0].1[0].kind, AccountHistoryKind::Transaction);
+
+        let history = chain.mut_store().get_account_history(&"bob".to_string(), 2, 10).unwrap();
+        assert_eq!(history.iter().map(|(height, _)| *height).collect::<Vec<_>>(), vec![2, 1]);
+        assert_eq!(history[0].1.len(), 2);
+        assert!(history[0].1.iter().all(|record| record.block_hash == *blocks[1].hash()));
+
+        assert!(chain
+            .mut_store()
+            .get_account_history(&"carol".to_string(), 4, 10)
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_cache_invalidation() {
         let mut chain = get_chain();
@@ -18,6 +18,8 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::serialize::to_base;
 use near_primitives::sharding::ChunkHash;
+#[cfg(feature = "sandbox")]
+use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{
     Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, SignedTransaction,
     TransferAction,
@@ -29,8 +31,8 @@ use near_primitives::types::{
 use near_primitives::validator_signer::InMemoryValidatorSigner;
 use near_primitives::version::{ProtocolVersion, PROTOCOL_VERSION};
 use near_primitives::views::{
-    AccessKeyInfoView, AccessKeyList, CallResult, EpochValidatorInfo, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, AccessKeyList, CallResult, EpochValidatorInfo, QueryError, QueryRequest,
+    QueryResponse, QueryResponseKind, ViewStateResult,
 };
 use near_store::test_utils::create_test_store;
 use near_store::{
@@ -470,6 +472,7 @@ impl RuntimeAdapter for KeyValueRuntime {
     fn validate_tx(
         &self,
         _gas_price: Balance,
+        _next_block_height: BlockHeight,
         _state_update: Option<StateRoot>,
         _transaction: &SignedTransaction,
         _verify_signature: bool,
@@ -484,6 +487,7 @@ impl RuntimeAdapter for KeyValueRuntime {
         _gas_limit: Gas,
         _shard_id: ShardId,
         _state_root: StateRoot,
+        _next_block_height: BlockHeight,
         transactions: &mut dyn PoolIterator,
         _chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         _current_protocol_version: ProtocolVersion,
@@ -633,6 +637,7 @@ impl RuntimeAdapter for KeyValueRuntime {
                         gas_burnt: 0,
                         tokens_burnt: 0,
                         executor_id: to.clone(),
+                        gas_profile: None,
                     },
                 });
             }
@@ -739,14 +744,16 @@ impl RuntimeAdapter for KeyValueRuntime {
                 block_height,
                 block_hash: *block_hash,
             }),
-            QueryRequest::ViewState { .. } => Ok(QueryResponse {
-                kind: QueryResponseKind::ViewState(ViewStateResult {
-                    values: Default::default(),
-                    proof: vec![],
-                }),
-                block_height,
-                block_hash: *block_hash,
-            }),
+            QueryRequest::ViewState { .. } | QueryRequest::ViewStateWithProof { .. } => {
+                Ok(QueryResponse {
+                    kind: QueryResponseKind::ViewState(ViewStateResult {
+                        values: Default::default(),
+                        proof: vec![],
+                    }),
+                    block_height,
+                    block_hash: *block_hash,
+                })
+            }
             QueryRequest::CallFunction { .. } => Ok(QueryResponse {
                 kind: QueryResponseKind::CallResult(CallResult {
                     result: Default::default(),
@@ -755,6 +762,15 @@ impl RuntimeAdapter for KeyValueRuntime {
                 block_height,
                 block_hash: *block_hash,
             }),
+            QueryRequest::ViewAccountWithProof { .. }
+            | QueryRequest::ViewAccessKeyWithProof { .. } => Ok(QueryResponse {
+                kind: QueryResponseKind::Error(QueryError {
+                    error: "KeyValueRuntime has no trie to prove state with".to_string(),
+                    logs: vec![],
+                }),
+                block_height,
+                block_hash: *block_hash,
+            }),
         }
     }
 
@@ -925,6 +941,15 @@ impl RuntimeAdapter for KeyValueRuntime {
         Ok(false)
     }
 
+    /// There is no trie state to patch, the records are ignored.
+    #[cfg(feature = "sandbox")]
+    fn sandbox_patch_state(&self, _records: Vec<StateRecord>) {}
+
+    #[cfg(feature = "sandbox")]
+    fn sandbox_has_pending_state_patch(&self) -> bool {
+        false
+    }
+
     fn verify_validator_or_fisherman_signature(
         &self,
         _epoch_id: &EpochId,
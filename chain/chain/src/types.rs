@@ -14,6 +14,8 @@ use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{merklize, MerklePath};
 use near_primitives::receipt::Receipt;
 use near_primitives::sharding::{ChunkHash, ReceiptList, ShardChunkHeader};
+#[cfg(feature = "sandbox")]
+use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{ExecutionOutcomeWithId, SignedTransaction};
 use near_primitives::types::{
     AccountId, ApprovalStake, Balance, BlockHeight, BlockHeightDelta, EpochId, Gas, MerkleHash,
@@ -246,6 +248,8 @@ pub trait RuntimeAdapter: Send + Sync {
     /// Validates a given signed transaction.
     /// If the state root is given, then the verification will use the account. Otherwise it will
     /// only validate the transaction math, limits and signatures.
+    /// Access keys are checked for expiration at `next_block_height`, like in
+    /// `prepare_transactions`.
     /// Returns an option of `InvalidTxError`, it contains `Some(InvalidTxError)` if there is
     /// a validation error, or `None` in case the transaction succeeded.
     /// Throws an `Error` with `ErrorKind::StorageError` in case the runtime throws
@@ -253,6 +257,7 @@ pub trait RuntimeAdapter: Send + Sync {
     fn validate_tx(
         &self,
         gas_price: Balance,
+        next_block_height: BlockHeight,
         state_root: Option<StateRoot>,
         transaction: &SignedTransaction,
         verify_signature: bool,
@@ -264,6 +269,7 @@ pub trait RuntimeAdapter: Send + Sync {
     /// against the given `chain_validate` closure and runtime's transaction verifier.
     /// If the transaction is valid for both, it's added to the result and the temporary state
     /// update is preserved for validation of next transactions.
+    /// Access keys are checked for expiration at `next_block_height`.
     /// Throws an `Error` with `ErrorKind::StorageError` in case the runtime throws
     /// `RuntimeError::StorageError`.
     fn prepare_transactions(
@@ -272,6 +278,7 @@ pub trait RuntimeAdapter: Send + Sync {
         gas_limit: Gas,
         shard_id: ShardId,
         state_root: StateRoot,
+        next_block_height: BlockHeight,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -582,6 +589,15 @@ pub trait RuntimeAdapter: Send + Sync {
         header_head: &CryptoHash,
     ) -> Result<bool, Error>;
 
+    /// Queues state records to be written into the state right after the next chunk of their
+    /// shard is applied.
+    #[cfg(feature = "sandbox")]
+    fn sandbox_patch_state(&self, records: Vec<StateRecord>);
+
+    /// Whether some of the records queued with `sandbox_patch_state` are not in the state yet.
+    #[cfg(feature = "sandbox")]
+    fn sandbox_has_pending_state_patch(&self) -> bool;
+
     /// Build receipts hashes.
     // Due to borsh serialization constraints, we have to use `&Vec<Receipt>` instead of `&[Receipt]`
     // here.
@@ -620,6 +636,21 @@ pub struct LatestKnown {
     pub seen: u64,
 }
 
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub enum AccountHistoryKind {
+    Transaction,
+    Receipt,
+}
+
+/// Transaction or receipt that touched an account, as stored in the account history index.
+/// The block hash is kept to tell apart blocks of different forks at the same height.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountHistoryRecord {
+    pub block_hash: CryptoHash,
+    pub id: CryptoHash,
+    pub kind: AccountHistoryKind,
+}
+
 #[cfg(test)]
 mod tests {
     use chrono::Utc;
@@ -653,7 +684,8 @@ mod tests {
         let b1 = Block::empty(&genesis, &signer);
         assert!(b1.header().verify_block_producer(&signer.public_key()));
         let other_signer = InMemoryValidatorSigner::from_seed("other2", KeyType::ED25519, "other2");
-        let approvals = vec![Some(Approval::new(*b1.hash(), 1, 2, &other_signer).signature)];
+        let approvals =
+            vec![Some(Approval::new(*b1.hash(), 1, 2, &other_signer).unwrap().signature)];
         let b2 = Block::empty_with_approvals(
             &b1,
             2,
@@ -678,6 +710,7 @@ mod tests {
                 gas_burnt: 100,
                 tokens_burnt: 10000,
                 executor_id: "alice".to_string(),
+                gas_profile: None,
             },
         };
         let outcome2 = ExecutionOutcomeWithId {
@@ -689,6 +722,7 @@ mod tests {
                 gas_burnt: 0,
                 tokens_burnt: 0,
                 executor_id: "bob".to_string(),
+                gas_profile: None,
             },
         };
         let outcomes = vec![outcome1, outcome2];
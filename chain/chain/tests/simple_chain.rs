@@ -55,7 +55,8 @@ fn build_chain_with_orhpans() {
         &*signer,
         last_block.header().next_bp_hash().clone(),
         CryptoHash::default(),
-    );
+    )
+    .unwrap();
     assert_eq!(
         chain
             .process_block(&None, block, Provenance::PRODUCED, |_| {}, |_| {}, |_| {})
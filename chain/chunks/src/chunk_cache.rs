@@ -188,22 +188,25 @@ mod tests {
         let mut cache = EncodedChunksCache::new();
         let signer = InMemoryValidatorSigner::from_random("test".to_string(), KeyType::ED25519);
         let partial_encoded_chunk = PartialEncodedChunkV2 {
-            header: ShardChunkHeader::V2(ShardChunkHeaderV2::new(
-                CryptoHash::default(),
-                CryptoHash::default(),
-                CryptoHash::default(),
-                CryptoHash::default(),
-                1,
-                1,
-                0,
-                0,
-                0,
-                0,
-                CryptoHash::default(),
-                CryptoHash::default(),
-                vec![],
-                &signer,
-            )),
+            header: ShardChunkHeader::V2(
+                ShardChunkHeaderV2::new(
+                    CryptoHash::default(),
+                    CryptoHash::default(),
+                    CryptoHash::default(),
+                    CryptoHash::default(),
+                    1,
+                    1,
+                    0,
+                    0,
+                    0,
+                    0,
+                    CryptoHash::default(),
+                    CryptoHash::default(),
+                    vec![],
+                    &signer,
+                )
+                .unwrap(),
+            ),
             parts: vec![],
             receipts: vec![],
         };
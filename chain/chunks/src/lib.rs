@@ -767,6 +767,19 @@ impl ShardsManager {
         self.encoded_chunks.get_chunk_headers_for_block(&prev_block_hash)
     }
 
+    /// Returns hashes of all transactions in the pools of all tracked shards.
+    pub fn get_pool_transaction_hashes(&self) -> Vec<CryptoHash> {
+        self.tx_pools
+            .values()
+            .flat_map(|pool| pool.transactions().map(|tx| tx.get_hash()))
+            .collect()
+    }
+
+    /// Returns the transaction with the given hash from the pool of any tracked shard.
+    pub fn get_pool_transaction(&self, tx_hash: &CryptoHash) -> Option<SignedTransaction> {
+        self.tx_pools.values().find_map(|pool| pool.get_transaction(tx_hash).cloned())
+    }
+
     /// Returns true if transaction is not in the pool before call
     pub fn insert_transaction(&mut self, shard_id: ShardId, tx: SignedTransaction) -> bool {
         self.tx_pools.entry(shard_id).or_insert_with(TransactionPool::new).insert_transaction(tx)
@@ -1710,6 +1723,7 @@ mod test {
     use near_network::types::PartialEncodedChunkForwardMsg;
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::sharding::{ChunkHash, PartialEncodedChunkV2};
+    use near_primitives::transaction::SignedTransaction;
     use near_primitives::version::PROTOCOL_VERSION;
     use near_store::test_utils::create_test_store;
     use std::sync::Arc;
@@ -1764,6 +1778,43 @@ mod test {
         };
     }
 
+    /// Transactions of all shard pools are listed and can be looked up by hash.
+    #[test]
+    fn test_pool_transactions() {
+        let runtime_adapter = Arc::new(KeyValueRuntime::new(create_test_store()));
+        let network_adapter = Arc::new(MockNetworkAdapter::default());
+        let mut shards_manager =
+            ShardsManager::new(Some("test".to_string()), runtime_adapter, network_adapter);
+        let signer =
+            near_crypto::InMemorySigner::from_seed("test", near_crypto::KeyType::ED25519, "test");
+        let transactions = (1..=4)
+            .map(|nonce| {
+                SignedTransaction::send_money(
+                    nonce,
+                    "test".to_string(),
+                    "other".to_string(),
+                    &signer,
+                    nonce as u128,
+                    CryptoHash::default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for (i, tx) in transactions.iter().enumerate() {
+            assert!(shards_manager.insert_transaction(i as u64 % 2, tx.clone()));
+        }
+
+        let mut pool_hashes = shards_manager.get_pool_transaction_hashes();
+        let mut expected_hashes = transactions.iter().map(|tx| tx.get_hash()).collect::<Vec<_>>();
+        pool_hashes.sort();
+        expected_hashes.sort();
+        assert_eq!(pool_hashes, expected_hashes);
+
+        for tx in &transactions {
+            assert_eq!(shards_manager.get_pool_transaction(&tx.get_hash()).as_ref(), Some(tx));
+        }
+        assert_eq!(shards_manager.get_pool_transaction(&hash(b"missing")), None);
+    }
+
     #[cfg(feature = "expensive_tests")]
     #[test]
     fn test_seal_removal() {
@@ -103,7 +103,9 @@ impl Client {
         } else {
             DoomslugThresholdMode::NoApprovals
         };
-        let chain = Chain::new(runtime_adapter.clone(), &chain_genesis, doomslug_threshold_mode)?;
+        let mut chain =
+            Chain::new(runtime_adapter.clone(), &chain_genesis, doomslug_threshold_mode)?;
+        chain.mut_store().set_save_account_history(config.account_history);
         let shards_mgr = ShardsManager::new(
             validator_signer.as_ref().map(|x| x.validator_id().clone()),
             runtime_adapter.clone(),
@@ -445,7 +447,8 @@ impl Client {
             &*validator_signer,
             next_bp_hash,
             block_merkle_root,
-        );
+        )
+        .map_err(|err| Error::BlockProducer(format!("Failed to sign block: {}", err)))?;
 
         // Update latest known even before returning block out, to prevent race conditions.
         self.chain.mut_store().save_latest_known(LatestKnown {
@@ -584,6 +587,7 @@ impl Client {
                 chunk_extra.gas_limit,
                 shard_id,
                 chunk_extra.state_root.clone(),
+                prev_block_header.height() + 1,
                 &mut iter,
                 &mut |tx: &SignedTransaction| -> bool {
                     chain
@@ -609,9 +613,13 @@ impl Client {
     pub fn send_challenges(&mut self, challenges: Arc<RwLock<Vec<ChallengeBody>>>) {
         if let Some(validator_signer) = self.validator_signer.as_ref() {
             for body in challenges.write().unwrap().drain(..) {
-                let challenge = Challenge::produce(body, &**validator_signer);
-                self.challenges.insert(challenge.hash, challenge.clone());
-                self.network_adapter.do_send(NetworkRequests::Challenge(challenge));
+                match Challenge::produce(body, &**validator_signer) {
+                    Ok(challenge) => {
+                        self.challenges.insert(challenge.hash, challenge.clone());
+                        self.network_adapter.do_send(NetworkRequests::Challenge(challenge));
+                    }
+                    Err(err) => error!(target: "client", "Failed to sign challenge: {}", err),
+                }
             }
         }
     }
@@ -668,27 +676,25 @@ impl Client {
 
         // Send out challenge if the block was found to be invalid.
         if let Some(validator_signer) = self.validator_signer.as_ref() {
-            match &result {
+            let body = match &result {
                 Err(e) => match e.kind() {
                     near_chain::ErrorKind::InvalidChunkProofs(chunk_proofs) => {
-                        self.network_adapter.do_send(NetworkRequests::Challenge(
-                            Challenge::produce(
-                                ChallengeBody::ChunkProofs(*chunk_proofs),
-                                &**validator_signer,
-                            ),
-                        ));
+                        Some(ChallengeBody::ChunkProofs(*chunk_proofs))
                     }
                     near_chain::ErrorKind::InvalidChunkState(chunk_state) => {
-                        self.network_adapter.do_send(NetworkRequests::Challenge(
-                            Challenge::produce(
-                                ChallengeBody::ChunkState(*chunk_state),
-                                &**validator_signer,
-                            ),
-                        ));
+                        Some(ChallengeBody::ChunkState(*chunk_state))
                     }
-                    _ => {}
+                    _ => None,
                 },
-                _ => {}
+                _ => None,
+            };
+            if let Some(body) = body {
+                match Challenge::produce(body, &**validator_signer) {
+                    Ok(challenge) => {
+                        self.network_adapter.do_send(NetworkRequests::Challenge(challenge))
+                    }
+                    Err(err) => error!(target: "client", "Failed to sign challenge: {}", err),
+                }
             }
         }
 
@@ -1384,7 +1390,7 @@ impl Client {
 
         if let Some(err) = self
             .runtime_adapter
-            .validate_tx(gas_price, None, &tx, true, protocol_version)
+            .validate_tx(gas_price, head.height + 1, None, &tx, true, protocol_version)
             .expect("no storage errors")
         {
             debug!(target: "client", "Invalid tx during basic validation: {:?}", err);
@@ -1411,7 +1417,14 @@ impl Client {
             };
             if let Some(err) = self
                 .runtime_adapter
-                .validate_tx(gas_price, Some(state_root), &tx, false, protocol_version)
+                .validate_tx(
+                    gas_price,
+                    head.height + 1,
+                    Some(state_root),
+                    &tx,
+                    false,
+                    protocol_version,
+                )
                 .expect("no storage errors")
             {
                 debug!(target: "client", "Invalid tx: {:?}", err);
@@ -1637,7 +1650,7 @@ mod test {
         let mut env = TestEnv::new_with_runtime(ChainGenesis::test(), 1, 1, runtimes);
         let signer = InMemoryValidatorSigner::from_seed("test0", KeyType::ED25519, "test0");
         let parent_hash = hash(&[1]);
-        let approval = Approval::new(parent_hash, 0, 1, &signer);
+        let approval = Approval::new(parent_hash, 0, 1, &signer).unwrap();
         let peer_id = PeerId::random();
         env.clients[0]
             .collect_block_approval(&approval, ApprovalType::PeerApproval(peer_id.clone()));
@@ -1663,7 +1676,7 @@ mod test {
         let signer = InMemoryValidatorSigner::from_seed("random", KeyType::ED25519, "random");
         let parent_hash = hash(&[1]);
         // Approval not from a validator. Should be dropped
-        let approval = Approval::new(parent_hash, 1, 3, &signer);
+        let approval = Approval::new(parent_hash, 1, 3, &signer).unwrap();
         let peer_id = PeerId::random();
         env.clients[0]
             .collect_block_approval(&approval, ApprovalType::PeerApproval(peer_id.clone()));
@@ -1671,7 +1684,7 @@ mod test {
         // Approval with invalid signature. Should be dropped
         let signer = InMemoryValidatorSigner::from_seed("test0", KeyType::ED25519, "random");
         let genesis_hash = *env.clients[0].chain.genesis().hash();
-        let approval = Approval::new(genesis_hash, 0, 1, &signer);
+        let approval = Approval::new(genesis_hash, 0, 1, &signer).unwrap();
         env.clients[0].collect_block_approval(&approval, ApprovalType::PeerApproval(peer_id));
         assert_eq!(env.clients[0].pending_approvals.cache_size(), 0);
     }
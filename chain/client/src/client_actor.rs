@@ -5,7 +5,7 @@ use std::sync::{Arc, RwLock};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use actix::{Actor, Addr, Arbiter, AsyncContext, Context, Handler};
+use actix::{Actor, Addr, Arbiter, AsyncContext, Context, Handler, ResponseFuture};
 use chrono::Duration as OldDuration;
 use chrono::{DateTime, Utc};
 use log::{debug, error, info, trace, warn};
@@ -24,20 +24,29 @@ use near_chain_configs::ClientConfig;
 #[cfg(feature = "adversarial")]
 use near_chain_configs::GenesisConfig;
 use near_crypto::Signature;
+use near_network::rate_limiter::RateLimits;
 #[cfg(feature = "metric_recorder")]
 use near_network::recorder::MetricRecorder;
 #[cfg(feature = "adversarial")]
 use near_network::types::NetworkAdversarialMessage;
-use near_network::types::{NetworkInfo, ReasonForBan};
+use near_network::types::{NetworkDebugInfo, NetworkInfo, ReasonForBan};
+#[cfg(feature = "sandbox")]
+use near_network::types::{NetworkSandboxMessage, SandboxResponse};
 use near_network::{
     NetworkAdapter, NetworkClientMessages, NetworkClientResponses, NetworkRequests,
+    NetworkResponses,
 };
+#[cfg(feature = "sandbox")]
+use near_primitives::block::Approval;
 use near_primitives::hash::CryptoHash;
 use near_primitives::network::{AnnounceAccount, PeerId};
+use near_primitives::transaction::SignedTransaction;
+#[cfg(feature = "sandbox")]
+use near_primitives::types::BlockHeightDelta;
 use near_primitives::types::{BlockHeight, EpochId};
 use near_primitives::unwrap_or_return;
 use near_primitives::utils::from_timestamp;
-use near_primitives::validator_signer::ValidatorSigner;
+use near_primitives::validator_signer::{SignerError, ValidatorSigner};
 use near_primitives::version::PROTOCOL_VERSION;
 use near_primitives::views::ValidatorInfo;
 #[cfg(feature = "adversarial")]
@@ -48,8 +57,9 @@ use crate::client::Client;
 use crate::info::{InfoHelper, ValidatorInfoHelper};
 use crate::sync::{highest_height_peer, StateSync, StateSyncResult};
 use crate::types::{
-    Error, GetNetworkInfo, NetworkInfoResponse, ShardSyncDownload, ShardSyncStatus, Status,
-    StatusSyncInfo, SyncStatus,
+    Error, GetMempoolTransaction, GetMempoolTransactions, GetNetworkDebugInfo, GetNetworkInfo,
+    NetworkAdmin, NetworkInfoResponse, ShardSyncDownload, ShardSyncStatus, Status, StatusSyncInfo,
+    SyncStatus,
 };
 #[cfg(feature = "adversarial")]
 use crate::AdversarialControls;
@@ -149,6 +159,8 @@ impl ClientActor {
                 received_bytes_per_sec: 0,
                 sent_bytes_per_sec: 0,
                 known_producers: vec![],
+                rate_limits: RateLimits::default(),
+                rate_limited_peers: vec![],
                 #[cfg(feature = "metric_recorder")]
                 metric_recorder: MetricRecorder::default(),
             },
@@ -288,6 +300,34 @@ impl Handler<NetworkClientMessages> for ClientActor {
                     _ => panic!("invalid adversary message"),
                 };
             }
+            #[cfg(feature = "sandbox")]
+            NetworkClientMessages::Sandbox(sandbox_msg) => {
+                return match sandbox_msg {
+                    NetworkSandboxMessage::SandboxPatchState(records) => {
+                        info!(target: "sandbox", "Patching {} state records", records.len());
+                        self.client.runtime_adapter.sandbox_patch_state(records);
+                        NetworkClientResponses::NoResponse
+                    }
+                    NetworkSandboxMessage::SandboxPatchStateStatus => {
+                        NetworkClientResponses::SandboxResult(
+                            SandboxResponse::SandboxPatchStateFinished(
+                                !self.client.runtime_adapter.sandbox_has_pending_state_patch(),
+                            ),
+                        )
+                    }
+                    NetworkSandboxMessage::SandboxFastForward(delta_height) => {
+                        info!(target: "sandbox", "Fast forwarding {} blocks", delta_height);
+                        NetworkClientResponses::SandboxResult(
+                            match self.sandbox_fast_forward(delta_height) {
+                                Ok(height) => SandboxResponse::SandboxFastForwardFinished(height),
+                                Err(err) => {
+                                    SandboxResponse::SandboxFastForwardFailed(err.to_string())
+                                }
+                            },
+                        )
+                    }
+                };
+            }
             NetworkClientMessages::Transaction { transaction, is_forwarded, check_only } => {
                 self.client.process_tx(transaction, is_forwarded, check_only)
             }
@@ -605,22 +645,72 @@ impl Handler<GetNetworkInfo> for ClientActor {
             sent_bytes_per_sec: self.network_info.sent_bytes_per_sec,
             received_bytes_per_sec: self.network_info.received_bytes_per_sec,
             known_producers: self.network_info.known_producers.clone(),
+            rate_limits: self.network_info.rate_limits.clone(),
+            rate_limited_peers: self.network_info.rate_limited_peers.clone(),
             #[cfg(feature = "metric_recorder")]
             metric_recorder: self.network_info.metric_recorder.clone(),
         })
     }
 }
 
+impl Handler<GetNetworkDebugInfo> for ClientActor {
+    type Result = ResponseFuture<Result<NetworkDebugInfo, String>>;
+
+    fn handle(&mut self, _: GetNetworkDebugInfo, _ctx: &mut Context<Self>) -> Self::Result {
+        let response = self.network_adapter.send(NetworkRequests::FetchDebugInfo);
+        Box::pin(async move {
+            match response.await.map_err(|err| err.to_string())? {
+                NetworkResponses::DebugInfo(debug_info) => Ok(*debug_info),
+                _ => Err("Network debug info is not available".to_string()),
+            }
+        })
+    }
+}
+
+impl Handler<NetworkAdmin> for ClientActor {
+    type Result = ResponseFuture<Result<(), String>>;
+
+    fn handle(&mut self, msg: NetworkAdmin, _ctx: &mut Context<Self>) -> Self::Result {
+        let response = self.network_adapter.send(NetworkRequests::Admin(msg.0));
+        Box::pin(async move {
+            match response.await.map_err(|err| err.to_string())? {
+                NetworkResponses::AdminResult(result) => result,
+                _ => Err("Network admin requests are not available".to_string()),
+            }
+        })
+    }
+}
+
+impl Handler<GetMempoolTransactions> for ClientActor {
+    type Result = Result<Vec<CryptoHash>, String>;
+
+    fn handle(&mut self, _: GetMempoolTransactions, _ctx: &mut Context<Self>) -> Self::Result {
+        #[cfg(feature = "delay_detector")]
+        let _d = DelayDetector::new("client get mempool transactions".into());
+        Ok(self.client.shards_mgr.get_pool_transaction_hashes())
+    }
+}
+
+impl Handler<GetMempoolTransaction> for ClientActor {
+    type Result = Result<Option<SignedTransaction>, String>;
+
+    fn handle(&mut self, msg: GetMempoolTransaction, _ctx: &mut Context<Self>) -> Self::Result {
+        #[cfg(feature = "delay_detector")]
+        let _d = DelayDetector::new("client get mempool transaction".into());
+        Ok(self.client.shards_mgr.get_pool_transaction(&msg.tx_hash))
+    }
+}
+
 impl ClientActor {
-    fn sign_announce_account(&self, epoch_id: &EpochId) -> Result<Signature, ()> {
+    fn sign_announce_account(&self, epoch_id: &EpochId) -> Result<Signature, SignerError> {
         if let Some(validator_signer) = self.client.validator_signer.as_ref() {
-            Ok(validator_signer.sign_account_announce(
+            validator_signer.sign_account_announce(
                 &validator_signer.validator_id(),
                 &self.node_id,
                 epoch_id,
-            ))
+            )
         } else {
-            Err(())
+            Err(SignerError::KeyNotAvailable)
         }
     }
 
@@ -661,7 +751,7 @@ impl ClientActor {
         if self.client.is_validator(&next_epoch_id, &prev_block_hash) {
             debug!(target: "client", "Sending announce account for {}", validator_signer.validator_id());
             self.last_validator_announce_time = Some(now);
-            let signature = self.sign_announce_account(&next_epoch_id).unwrap();
+            let signature = unwrap_or_return!(self.sign_announce_account(&next_epoch_id));
 
             self.network_adapter.do_send(NetworkRequests::AnnounceAccount(AnnounceAccount {
                 account_id: validator_signer.validator_id().clone(),
@@ -824,6 +914,42 @@ impl ClientActor {
         };
     }
 
+    /// Produces `delta_height` blocks on top of the head right away, without waiting for the
+    /// block production delay or for approvals of other validators, so it only works when this
+    /// node is the only validator. Returns the height of the new head.
+    #[cfg(feature = "sandbox")]
+    fn sandbox_fast_forward(
+        &mut self,
+        delta_height: BlockHeightDelta,
+    ) -> Result<BlockHeight, Error> {
+        let validator_signer = self.client.validator_signer.clone().ok_or_else(|| {
+            Error::BlockProducer("Fast forwarding needs a block producer".to_string())
+        })?;
+        for _ in 0..delta_height {
+            let head = self.client.chain.head()?;
+            let height = self.client.chain.mut_store().get_latest_known()?.height + 1;
+            // Approve the head right away instead of waiting for the doomslug timer.
+            let approval =
+                Approval::new(head.last_block_hash, head.height, height, &*validator_signer)
+                    .map_err(|err| {
+                        Error::BlockProducer(format!("Failed to sign approval: {}", err))
+                    })?;
+            let mut chain_store_update = self.client.chain.mut_store().store_update();
+            chain_store_update.save_largest_target_height(height);
+            chain_store_update.commit()?;
+            self.client.collect_block_approval(&approval, ApprovalType::SelfApproval);
+
+            self.produce_block(height)?;
+            if self.client.chain.head()?.height != height {
+                return Err(Error::BlockProducer(format!(
+                    "Failed to produce block at height {}",
+                    height
+                )));
+            }
+        }
+        Ok(self.client.chain.head()?.height)
+    }
+
     /// Produce block if we are block producer for given `next_height` height.
     /// Can return error, should be called with `produce_block` to handle errors and reschedule.
     fn produce_block(&mut self, next_height: BlockHeight) -> Result<(), Error> {
@@ -1110,7 +1236,16 @@ impl ClientActor {
     /// To prevent syncing from a fork, we move `state_fetch_horizon` steps backwards and use that epoch.
     /// Usually `state_fetch_horizon` is much less than the expected number of produced blocks on an epoch,
     /// so this is only relevant on epoch boundaries.
+    ///
+    /// If a state snapshot was imported, its block is used instead as soon as its header is known.
     fn find_sync_hash(&mut self) -> Result<CryptoHash, near_chain::Error> {
+        if let Some(snapshot_hash) = self.client.chain.get_imported_state_snapshot()? {
+            if self.client.chain.get_block_header(&snapshot_hash).is_ok() {
+                info!(target: "sync", "State sync from imported state snapshot at {}", snapshot_hash);
+                return Ok(snapshot_hash);
+            }
+            warn!(target: "sync", "Header of imported state snapshot at {} is unknown, syncing state from peers", snapshot_hash);
+        }
         let header_head = self.client.chain.header_head()?;
         let mut sync_hash = header_head.prev_block_hash;
         for _ in 0..self.client.config.state_fetch_horizon {
@@ -1293,6 +1428,11 @@ impl ClientActor {
                     }
                     StateSyncResult::Completed => {
                         info!(target: "sync", "State sync: all shards are done");
+                        if unwrap_or_run_later!(self.client.chain.get_imported_state_snapshot())
+                            == Some(sync_hash)
+                        {
+                            unwrap_or_run_later!(self.client.chain.clear_imported_state_snapshot());
+                        }
 
                         let accepted_blocks = Arc::new(RwLock::new(vec![]));
                         let blocks_missing_chunks = Arc::new(RwLock::new(vec![]));
@@ -4,7 +4,7 @@ use std::time::Instant;
 
 use actix::Addr;
 use ansi_term::Color::{Blue, Cyan, Green, White, Yellow};
-use log::info;
+use log::{error, info};
 use sysinfo::{get_current_pid, set_open_files_limit, Pid, ProcessExt, System, SystemExt};
 
 use near_chain_configs::ClientConfig;
@@ -161,7 +161,13 @@ impl InfoHelper {
         };
         // Sign telemetry if there is a signer present.
         let content = if let Some(vs) = self.validator_signer.as_ref() {
-            vs.sign_telemetry(&info)
+            match vs.sign_telemetry(&info) {
+                Ok(content) => content,
+                Err(err) => {
+                    error!(target: "stats", "Failed to sign telemetry: {}", err);
+                    return;
+                }
+            }
         } else {
             serde_json::to_value(&info).expect("Telemetry must serialize to json")
         };
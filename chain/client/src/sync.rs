@@ -723,6 +723,9 @@ impl StateSync {
             ],
             status: ShardSyncStatus::StateDownloadHeader,
         };
+        // Header and parts of an imported state snapshot are taken from the store
+        // instead of being requested from peers.
+        let use_imported_snapshot = chain.get_imported_state_snapshot()? == Some(sync_hash);
 
         for shard_id in tracking_shards {
             let mut download_timeout = false;
@@ -734,6 +737,19 @@ impl StateSync {
             let mut this_done = false;
             match shard_sync_download.status {
                 ShardSyncStatus::StateDownloadHeader => {
+                    if use_imported_snapshot && !shard_sync_download.downloads[0].done {
+                        if let Ok(shard_state_header) = chain.get_state_header(shard_id, sync_hash)
+                        {
+                            // Imported header is validated the same way as a downloaded one.
+                            match chain.set_state_header(shard_id, sync_hash, shard_state_header) {
+                                Ok(()) => shard_sync_download.downloads[0].done = true,
+                                Err(err) => {
+                                    error!(target: "sync", "Imported state header is invalid, shard = {}, hash = {}: {:?}", shard_id, sync_hash, err);
+                                    chain.clear_imported_state_snapshot()?;
+                                }
+                            }
+                        }
+                    }
                     if shard_sync_download.downloads[0].done {
                         let shard_state_header = chain.get_state_header(shard_id, sync_hash)?;
                         let state_num_parts =
@@ -753,6 +769,16 @@ impl StateSync {
                             ],
                             status: ShardSyncStatus::StateDownloadParts,
                         };
+                        if use_imported_snapshot {
+                            for (part_id, part_download) in
+                                shard_sync_download.downloads.iter_mut().enumerate()
+                            {
+                                if chain.has_state_part(shard_id, sync_hash, part_id as u64)? {
+                                    part_download.done = true;
+                                    part_download.run_me.store(false, Ordering::SeqCst);
+                                }
+                            }
+                        }
                         need_shard = true;
                     } else {
                         let prev = shard_sync_download.downloads[0].prev_update_time;
@@ -1358,6 +1384,7 @@ mod test {
                             current_height,
                             &signer,
                         )
+                        .unwrap()
                         .signature
                     })
                 })
@@ -1388,7 +1415,8 @@ mod test {
                 &*signers[3],
                 last_block.header().next_bp_hash().clone(),
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             block_merkle_tree.insert(*block.hash());
 
             all_blocks.push(block);
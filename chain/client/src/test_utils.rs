@@ -17,6 +17,7 @@ use near_chain::{
 };
 use near_chain_configs::ClientConfig;
 use near_crypto::{InMemorySigner, KeyType, PublicKey};
+use near_network::rate_limiter::RateLimits;
 #[cfg(feature = "metric_recorder")]
 use near_network::recorder::MetricRecorder;
 use near_network::routing::EdgeInfo;
@@ -474,6 +475,8 @@ pub fn setup_mock_all_validators(
                             sent_bytes_per_sec: 0,
                             received_bytes_per_sec: 0,
                             known_producers: vec![],
+                            rate_limits: RateLimits::default(),
+                            rate_limited_peers: vec![],
                             #[cfg(feature = "metric_recorder")]
                             metric_recorder: MetricRecorder::default(),
                         };
@@ -805,6 +808,8 @@ pub fn setup_mock_all_validators(
                         | NetworkRequests::FetchRoutingTable
                         | NetworkRequests::PingTo(_, _)
                         | NetworkRequests::FetchPingPongInfo
+                        | NetworkRequests::FetchDebugInfo
+                        | NetworkRequests::Admin(_)
                         | NetworkRequests::BanPeer { .. }
                         | NetworkRequests::TxStatus(_, _, _)
                         | NetworkRequests::Query { .. }
@@ -1032,6 +1037,20 @@ impl TestEnv {
         self.process_block(id, block.unwrap(), Provenance::PRODUCED);
     }
 
+    /// Starts a view client over the chain of the given client. Must be called within a running
+    /// actix system.
+    pub fn start_view_client(&self, id: usize) -> Addr<ViewClientActor> {
+        start_view_client(
+            None,
+            self.chain_genesis.clone(),
+            self.clients[id].runtime_adapter.clone(),
+            self.network_adapters[id].clone(),
+            self.clients[id].config.clone(),
+            #[cfg(feature = "adversarial")]
+            Arc::new(RwLock::new(AdversarialControls::default())),
+        )
+    }
+
     pub fn send_money(&mut self, id: usize) -> NetworkClientResponses {
         let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
         let tx = SignedTransaction::send_money(
@@ -1198,6 +1217,7 @@ pub fn create_chunk(
         &*client.validator_signer.as_ref().unwrap().clone(),
         *last_block.header().next_bp_hash(),
         block_merkle_tree.root(),
-    );
+    )
+    .unwrap();
     (chunk, merkle_paths, receipts, block)
 }
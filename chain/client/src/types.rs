@@ -8,18 +8,22 @@ use actix::Message;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
-use near_network::types::{AccountOrPeerIdOrHash, KnownProducer};
+use near_network::rate_limiter::RateLimits;
+use near_network::types::{
+    AccountOrPeerIdOrHash, KnownProducer, NetworkAdminRequest, NetworkDebugInfo, RateLimitedPeer,
+};
 use near_network::PeerInfo;
 use near_primitives::errors::InvalidTxError;
 use near_primitives::hash::CryptoHash;
 use near_primitives::merkle::{MerklePath, PartialMerkleTree};
 use near_primitives::sharding::ChunkHash;
+use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{
     AccountId, BlockHeight, BlockReference, MaybeBlockId, ShardId, TransactionOrReceiptId,
 };
 use near_primitives::utils::generate_random_string;
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
+    AccountHistoryView, BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
     FinalExecutionOutcomeViewEnum, GasPriceView, LightClientBlockLiteView, LightClientBlockView,
     QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView, StateChangesRequestView,
     StateChangesView, ValidatorStakeView,
@@ -224,6 +228,47 @@ impl Message for GetNetworkInfo {
     type Result = Result<NetworkInfoResponse, String>;
 }
 
+/// Fetches the state of the peer manager for debugging.
+pub struct GetNetworkDebugInfo {}
+
+impl Message for GetNetworkDebugInfo {
+    type Result = Result<NetworkDebugInfo, String>;
+}
+
+/// Operator request to manage the peers of this node.
+pub struct NetworkAdmin(pub NetworkAdminRequest);
+
+impl Message for NetworkAdmin {
+    type Result = Result<(), String>;
+}
+
+/// Read-only snapshot of the hashes of transactions in the transaction pool.
+pub struct GetMempoolTransactions {}
+
+impl Message for GetMempoolTransactions {
+    type Result = Result<Vec<CryptoHash>, String>;
+}
+
+/// Transaction from the transaction pool by its hash.
+pub struct GetMempoolTransaction {
+    pub tx_hash: CryptoHash,
+}
+
+impl Message for GetMempoolTransaction {
+    type Result = Result<Option<SignedTransaction>, String>;
+}
+
+/// Transactions and receipts that touched an account, served from the account history index.
+pub struct GetAccountHistory {
+    pub account_id: AccountId,
+    pub from_height: Option<BlockHeight>,
+    pub limit: Option<u64>,
+}
+
+impl Message for GetAccountHistory {
+    type Result = Result<AccountHistoryView, String>;
+}
+
 pub struct GetGasPrice {
     pub block_id: MaybeBlockId,
 }
@@ -241,6 +286,10 @@ pub struct NetworkInfoResponse {
     pub received_bytes_per_sec: u64,
     /// Accounts of known block and chunk producers from routing table.
     pub known_producers: Vec<KnownProducer>,
+    /// Limits on the messages received from each peer.
+    pub rate_limits: RateLimits,
+    /// Active peers which had messages dropped due to rate limits within the last minute.
+    pub rate_limited_peers: Vec<RateLimitedPeer>,
     #[cfg(feature = "metric_recorder")]
     pub metric_recorder: MetricRecorder,
 }
@@ -250,6 +299,8 @@ pub struct TxStatus {
     pub tx_hash: CryptoHash,
     pub signer_account_id: AccountId,
     pub fetch_receipt: bool,
+    /// Attach the gas profiles of the receipt outcomes, if the node recorded them.
+    pub fetch_gas_profile: bool,
 }
 
 #[derive(Debug)]
@@ -11,6 +11,7 @@ use actix::{Actor, Addr, Handler, SyncArbiter, SyncContext};
 use cached::{Cached, SizedCache};
 use log::{debug, error, info, trace, warn};
 
+use near_chain::types::AccountHistoryKind;
 use near_chain::{
     get_epoch_block_producers_view, Chain, ChainGenesis, ChainStoreAccess, DoomslugThresholdMode,
     ErrorKind, RuntimeAdapter,
@@ -37,22 +38,28 @@ use near_primitives::types::{
     TransactionOrReceiptId,
 };
 use near_primitives::views::{
+    AccessKeyWithProofView, AccountHistoryBlockView, AccountHistoryView, AccountWithProofView,
     BlockView, ChunkView, EpochValidatorInfo, ExecutionOutcomeWithIdView,
     FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus, GasPriceView,
-    LightClientBlockView, QueryRequest, QueryResponse, ReceiptView, StateChangesKindsView,
-    StateChangesView, ValidatorStakeView,
+    LightClientBlockView, QueryRequest, QueryResponse, QueryResponseKind, ReceiptView,
+    StateChangesKindsView, StateChangesView, ValidatorStakeView,
 };
 
 use crate::types::{
-    Error, GetBlock, GetBlockProof, GetBlockProofResponse, GetBlockWithMerkleTree,
-    GetExecutionOutcome, GetExecutionOutcomesForBlock, GetGasPrice, GetReceipt, Query, TxStatus,
-    TxStatusError,
+    Error, GetAccountHistory, GetBlock, GetBlockProof, GetBlockProofResponse,
+    GetBlockWithMerkleTree, GetExecutionOutcome, GetExecutionOutcomesForBlock, GetGasPrice,
+    GetReceipt, Query, TxStatus, TxStatusError,
 };
 use crate::{
     sync, GetChunk, GetExecutionOutcomeResponse, GetNextLightClientBlock, GetStateChanges,
     GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered,
 };
 
+/// Number of blocks returned by `GetAccountHistory` if no limit is given.
+const DEFAULT_ACCOUNT_HISTORY_LIMIT: u64 = 100;
+/// Max number of blocks returned by `GetAccountHistory`.
+const MAX_ACCOUNT_HISTORY_LIMIT: u64 = 1000;
+
 /// Max number of queries that we keep.
 const QUERY_REQUEST_LIMIT: usize = 500;
 /// Waiting time between requests, in ms
@@ -223,6 +230,9 @@ impl ViewClientActor {
             QueryRequest::ViewAccessKey { account_id, .. } => account_id,
             QueryRequest::ViewAccessKeyList { account_id, .. } => account_id,
             QueryRequest::CallFunction { account_id, .. } => account_id,
+            QueryRequest::ViewStateWithProof { account_id, .. } => account_id,
+            QueryRequest::ViewAccountWithProof { account_id, .. } => account_id,
+            QueryRequest::ViewAccessKeyWithProof { account_id, .. } => account_id,
         };
         let shard_id = self.runtime_adapter.account_id_to_shard_id(account_id);
 
@@ -230,19 +240,59 @@ impl ViewClientActor {
         // Otherwise route query to peers.
         match self.chain.get_chunk_extra(header.hash(), shard_id) {
             Ok(chunk_extra) => {
-                let state_root = chunk_extra.state_root;
-                self.runtime_adapter
+                let is_proof_request = matches!(
+                    msg.request,
+                    QueryRequest::ViewAccountWithProof { .. }
+                        | QueryRequest::ViewAccessKeyWithProof { .. }
+                );
+                if !is_proof_request {
+                    let state_root = chunk_extra.state_root;
+                    return self
+                        .runtime_adapter
+                        .query(
+                            shard_id,
+                            &state_root,
+                            header.height(),
+                            header.raw_timestamp(),
+                            header.hash(),
+                            header.epoch_id(),
+                            &msg.request,
+                        )
+                        .map(Some)
+                        .map_err(|e| e.to_string());
+                }
+                // Proofs are against the `prev_state_root` of the block header, which commits to
+                // the pre-state of the block: the state its chunks were applied to, rather than
+                // `chunk_extra.state_root` used for the other queries.
+                let state_roots = self
+                    .chain
+                    .get_block(header.hash())
+                    .map_err(|e| e.to_string())?
+                    .chunks()
+                    .iter()
+                    .map(|chunk| chunk.prev_state_root())
+                    .collect::<Vec<_>>();
+                let (_, state_root_proofs) = merklize(&state_roots);
+                let mut response = self
+                    .runtime_adapter
                     .query(
                         shard_id,
-                        &state_root,
+                        &state_roots[shard_id as usize],
                         header.height(),
                         header.raw_timestamp(),
                         header.hash(),
                         header.epoch_id(),
                         &msg.request,
                     )
-                    .map(Some)
-                    .map_err(|e| e.to_string())
+                    .map_err(|e| e.to_string())?;
+                if let QueryResponseKind::AccountWithProof(AccountWithProofView { proof, .. })
+                | QueryResponseKind::AccessKeyWithProof(AccessKeyWithProofView {
+                    proof, ..
+                }) = &mut response.kind
+                {
+                    proof.state_root_proof = state_root_proofs[shard_id as usize].clone();
+                }
+                Ok(Some(response))
             }
             Err(e) => {
                 match e.kind() {
@@ -298,6 +348,7 @@ impl ViewClientActor {
         tx_hash: CryptoHash,
         signer_account_id: AccountId,
         fetch_receipt: bool,
+        fetch_gas_profile: bool,
     ) -> Result<Option<FinalExecutionOutcomeViewEnum>, TxStatusError> {
         {
             let mut request_manager = self.request_manager.write().expect(POISONED_LOCK_ERR);
@@ -317,7 +368,7 @@ impl ViewClientActor {
             true,
         ) {
             match self.chain.get_final_transaction_result(&tx_hash) {
-                Ok(tx_result) => {
+                Ok(mut tx_result) => {
                     match &tx_result.status {
                         FinalExecutionStatus::NotStarted | FinalExecutionStatus::Started => {
                             for receipt_view in tx_result.receipts_outcome.iter() {
@@ -330,6 +381,15 @@ impl ViewClientActor {
                         FinalExecutionStatus::SuccessValue(_)
                         | FinalExecutionStatus::Failure(_) => {}
                     }
+                    if fetch_gas_profile {
+                        for receipt_view in tx_result.receipts_outcome.iter_mut() {
+                            receipt_view.outcome.gas_profile = self
+                                .chain
+                                .store()
+                                .get_gas_profile(&receipt_view.id, &receipt_view.block_hash)
+                                .map_err(|e| TxStatusError::ChainError(e))?;
+                        }
+                    }
                     if fetch_receipt {
                         let final_result = self
                             .chain
@@ -550,7 +610,12 @@ impl Handler<TxStatus> for ViewClientActor {
     type Result = Result<Option<FinalExecutionOutcomeViewEnum>, TxStatusError>;
 
     fn handle(&mut self, msg: TxStatus, _: &mut Self::Context) -> Self::Result {
-        self.get_tx_status(msg.tx_hash, msg.signer_account_id, msg.fetch_receipt)
+        self.get_tx_status(
+            msg.tx_hash,
+            msg.signer_account_id,
+            msg.fetch_receipt,
+            msg.fetch_gas_profile,
+        )
     }
 }
 
@@ -808,7 +873,9 @@ impl Handler<NetworkViewClientMessages> for ViewClientActor {
                 }
             }
             NetworkViewClientMessages::TxStatus { tx_hash, signer_account_id } => {
-                if let Ok(Some(result)) = self.get_tx_status(tx_hash, signer_account_id, false) {
+                if let Ok(Some(result)) =
+                    self.get_tx_status(tx_hash, signer_account_id, false, false)
+                {
                     // TODO: remove this legacy support in #3204
                     let result = match result {
                         FinalExecutionOutcomeViewEnum::FinalExecutionOutcome(outcome) => outcome,
@@ -1098,6 +1165,47 @@ impl Handler<GetGasPrice> for ViewClientActor {
     }
 }
 
+impl Handler<GetAccountHistory> for ViewClientActor {
+    type Result = Result<AccountHistoryView, String>;
+
+    fn handle(&mut self, msg: GetAccountHistory, _ctx: &mut Self::Context) -> Self::Result {
+        if !self.config.account_history {
+            return Err("Account history is not enabled on this node".to_string());
+        }
+        let limit =
+            msg.limit.unwrap_or(DEFAULT_ACCOUNT_HISTORY_LIMIT).min(MAX_ACCOUNT_HISTORY_LIMIT)
+                as usize;
+        let from_height = match msg.from_height {
+            Some(height) => height,
+            None => self.chain.head().map_err(|e| e.to_string())?.height,
+        };
+        // Fetch one extra block to know where the next page starts.
+        let mut history = self
+            .chain
+            .mut_store()
+            .get_account_history(&msg.account_id, from_height, limit + 1)
+            .map_err(|e| e.to_string())?;
+        let next_from_height =
+            if history.len() > limit { history.pop().map(|(height, _)| height) } else { None };
+        let blocks = history
+            .into_iter()
+            .filter_map(|(block_height, records)| {
+                let block_hash = records.first()?.block_hash;
+                let (transactions, receipts): (Vec<_>, Vec<_>) = records
+                    .into_iter()
+                    .partition(|record| record.kind == AccountHistoryKind::Transaction);
+                Some(AccountHistoryBlockView {
+                    block_height,
+                    block_hash,
+                    transactions: transactions.into_iter().map(|record| record.id).collect(),
+                    receipts: receipts.into_iter().map(|record| record.id).collect(),
+                })
+            })
+            .collect();
+        Ok(AccountHistoryView { blocks, next_from_height })
+    }
+}
+
 /// Starts the View Client in a new arbiter (thread).
 pub fn start_view_client(
     validator_account_id: Option<AccountId>,
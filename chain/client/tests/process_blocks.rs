@@ -22,6 +22,7 @@ use near_client::test_utils::{setup_client, setup_mock, TestEnv};
 use near_client::{Client, GetBlock, GetBlockWithMerkleTree};
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
 use near_logger_utils::init_test_logger;
+use near_network::rate_limiter::RateLimits;
 #[cfg(feature = "metric_recorder")]
 use near_network::recorder::MetricRecorder;
 use near_network::routing::EdgeInfo;
@@ -208,7 +209,8 @@ fn receive_network_block() {
                 &signer,
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             client.do_send(NetworkClientMessages::Block(block, PeerInfo::random().id, false));
             future::ready(())
         }));
@@ -278,7 +280,8 @@ fn produce_block_with_approvals() {
                 &signer1,
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             client.do_send(NetworkClientMessages::Block(
                 block.clone(),
                 PeerInfo::random().id,
@@ -293,7 +296,8 @@ fn produce_block_with_approvals() {
                     block.header().height(),
                     10, // the height at which "test1" is producing
                     &signer,
-                );
+                )
+                .unwrap();
                 client
                     .do_send(NetworkClientMessages::BlockApproval(approval, PeerInfo::random().id));
             }
@@ -440,7 +444,8 @@ fn invalid_blocks_common(is_requested: bool) {
                 &signer,
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             // Send block with invalid chunk mask
             let mut block = valid_block.clone();
             block.mut_header().get_mut().inner_rest.chunk_mask = vec![];
@@ -550,7 +555,8 @@ fn invalid_blocks() {
                 &signer,
                 last_block.header.next_bp_hash,
                 CryptoHash::default(),
-            );
+            )
+            .unwrap();
             block.mut_header().get_mut().inner_rest.chunk_mask = vec![];
             client.do_send(NetworkClientMessages::Block(
                 block.clone(),
@@ -581,7 +587,8 @@ fn invalid_blocks() {
                 &signer,
                 last_block.header.next_bp_hash,
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             client.do_send(NetworkClientMessages::Block(block2, PeerInfo::random().id, false));
             future::ready(())
         }));
@@ -802,6 +809,8 @@ fn client_sync_headers() {
             sent_bytes_per_sec: 0,
             received_bytes_per_sec: 0,
             known_producers: vec![],
+            rate_limits: RateLimits::default(),
+            rate_limited_peers: vec![],
             #[cfg(feature = "metric_recorder")]
             metric_recorder: MetricRecorder::default(),
         }));
@@ -936,7 +945,8 @@ fn test_invalid_approvals() {
                     KeyType::ED25519,
                     &format!("test{}", i),
                 )
-                .sign_approval(&ApprovalInner::Endorsement(*genesis.hash()), 1),
+                .sign_approval(&ApprovalInner::Endorsement(*genesis.hash()), 1)
+                .unwrap(),
             )
         })
         .collect();
@@ -1198,6 +1208,72 @@ fn test_gc_execution_outcome() {
     assert!(env.clients[0].chain.get_final_transaction_result(&tx_hash).is_err());
 }
 
+/// Gas profiles of receipts are stored when the runtime profiles gas and are garbage collected
+/// together with the outcomes.
+#[test]
+fn test_gc_gas_profiles() {
+    let epoch_length = 5;
+    let mut genesis = Genesis::test(vec!["test0", "test1"], 1);
+    genesis.config.epoch_length = epoch_length;
+    let mut chain_genesis = ChainGenesis::test();
+    chain_genesis.epoch_length = epoch_length;
+    let mut runtime = neard::NightshadeRuntime::new(
+        Path::new("."),
+        create_test_store(),
+        &genesis,
+        vec![],
+        vec![],
+    );
+    runtime.set_profile_gas(true);
+    let mut env = TestEnv::new_with_runtime(
+        chain_genesis,
+        1,
+        1,
+        vec![Arc::new(runtime) as Arc<dyn RuntimeAdapter>],
+    );
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let signer = InMemorySigner::from_seed("test0", KeyType::ED25519, "test0");
+    let tx = SignedTransaction::send_money(
+        1,
+        "test0".to_string(),
+        "test1".to_string(),
+        &signer,
+        100,
+        genesis_hash,
+    );
+    let tx_hash = tx.get_hash();
+
+    env.clients[0].process_tx(tx, false, false);
+    for i in 1..epoch_length {
+        env.produce_block(0, i);
+    }
+    let tx_outcome = env.clients[0].chain.get_execution_outcome(&tx_hash).unwrap();
+    let receipt_id = tx_outcome.outcome_with_id.outcome.receipt_ids[0];
+    let receipt_block_hash =
+        env.clients[0].chain.get_execution_outcome(&receipt_id).unwrap().block_hash;
+    // Converting a transaction into a receipt doesn't run any actions, so it has no profile.
+    assert_eq!(
+        env.clients[0].chain.store().get_gas_profile(&tx_hash, &tx_outcome.block_hash).unwrap(),
+        None
+    );
+    let gas_profile = env.clients[0]
+        .chain
+        .store()
+        .get_gas_profile(&receipt_id, &receipt_block_hash)
+        .unwrap()
+        .unwrap();
+    assert!(gas_profile.action_costs.contains_key("transfer"));
+
+    for i in epoch_length..=epoch_length * 6 + 1 {
+        env.produce_block(0, i);
+    }
+    assert!(env.clients[0].chain.get_execution_outcome(&receipt_id).is_err());
+    assert_eq!(
+        env.clients[0].chain.store().get_gas_profile(&receipt_id, &receipt_block_hash).unwrap(),
+        None
+    );
+}
+
 #[cfg(feature = "expensive_tests")]
 #[test]
 fn test_gc_after_state_sync() {
@@ -1,20 +1,30 @@
+use std::path::Path;
+use std::sync::Arc;
+
 use actix::System;
 use futures::{future, FutureExt};
 
-use near_client::test_utils::setup_no_network;
+use near_chain::{ChainGenesis, RuntimeAdapter};
+use near_chain_configs::Genesis;
+use near_client::test_utils::{setup_no_network, TestEnv};
 use near_client::{
     GetBlock, GetBlockWithMerkleTree, GetExecutionOutcomesForBlock, Query, Status, TxStatus,
 };
-use near_crypto::{InMemorySigner, KeyType};
+use near_crypto::{InMemorySigner, KeyType, Signer};
 use near_logger_utils::init_test_logger;
 use near_network::{NetworkClientMessages, NetworkClientResponses, PeerInfo};
 use near_primitives::block::{Block, BlockHeader};
 use near_primitives::transaction::SignedTransaction;
-use near_primitives::types::{BlockReference, EpochId};
+use near_primitives::types::{BlockId, BlockReference, EpochId};
 use near_primitives::utils::to_timestamp;
 use near_primitives::validator_signer::InMemoryValidatorSigner;
 use near_primitives::version::PROTOCOL_VERSION;
-use near_primitives::views::{FinalExecutionOutcomeViewEnum, QueryRequest, QueryResponseKind};
+use near_primitives::views::{
+    FinalExecutionOutcomeViewEnum, QueryRequest, QueryResponse, QueryResponseKind,
+};
+use near_store::test_utils::create_test_store;
+use near_store::{verify_access_key_proof, verify_account_proof};
+use neard::config::GenesisExt;
 use num_rational::Rational;
 use std::time::Duration;
 
@@ -72,7 +82,8 @@ fn query_status_not_crash() {
                 &signer,
                 block.header.next_bp_hash,
                 block_merkle_tree.root(),
-            );
+            )
+            .unwrap();
             next_block.mut_header().get_mut().inner_lite.timestamp =
                 to_timestamp(next_block.header().timestamp() + chrono::Duration::seconds(60));
             next_block.mut_header().resign(&signer);
@@ -134,6 +145,7 @@ fn test_execution_outcome_for_chunk() {
                     tx_hash,
                     signer_account_id: "test".to_string(),
                     fetch_receipt: false,
+                    fetch_gas_profile: false,
                 })
                 .await
                 .unwrap()
@@ -162,3 +174,97 @@ fn test_execution_outcome_for_chunk() {
     })
     .unwrap();
 }
+
+/// Account and access key returned by the view client with a proof are read from the state
+/// before the queried block and verify against the `prev_state_root` of its header.
+#[test]
+fn test_query_with_proof() {
+    init_test_logger();
+    let genesis = Genesis::test(vec!["test0", "test1"], 1);
+    let runtime_adapter: Arc<dyn RuntimeAdapter> = Arc::new(neard::NightshadeRuntime::new(
+        Path::new("."),
+        create_test_store(),
+        &genesis,
+        vec![],
+        vec![],
+    ));
+    let mut env = TestEnv::new_with_runtime(ChainGenesis::test(), 1, 1, vec![runtime_adapter]);
+    let signer = InMemorySigner::from_seed("test0", KeyType::ED25519, "test0");
+    let genesis_hash = *env.clients[0].chain.genesis().hash();
+    let tx = SignedTransaction::send_money(
+        1,
+        "test0".to_string(),
+        "test1".to_string(),
+        &signer,
+        100,
+        genesis_hash,
+    );
+    assert_eq!(env.clients[0].process_tx(tx, false, false), NetworkClientResponses::ValidTx);
+    let num_blocks = 5;
+    for height in 1..=num_blocks {
+        env.produce_block(0, height);
+    }
+    let blocks = (1..=num_blocks)
+        .map(|height| env.clients[0].chain.get_block_by_height(height).unwrap().clone())
+        .collect::<Vec<_>>();
+
+    System::run(move || {
+        let view_client = env.start_view_client(0);
+        actix::spawn(async move {
+            let query = |block: &Block, request| {
+                let block_reference = BlockReference::BlockId(BlockId::Hash(*block.hash()));
+                view_client.send(Query::new(block_reference, request))
+            };
+            let unwrap_response = |response: Result<Result<Option<QueryResponse>, String>, _>| {
+                response.unwrap().unwrap().unwrap().kind
+            };
+            let account_id = "test0".to_string();
+            for (prev_block, block) in blocks.iter().zip(blocks.iter().skip(1)) {
+                let block_prev_state_root = block.header().prev_state_root();
+                let request = QueryRequest::ViewAccountWithProof { account_id: account_id.clone() };
+                let result = match unwrap_response(query(block, request).await) {
+                    QueryResponseKind::AccountWithProof(result) => result,
+                    kind => panic!("unexpected response {:?}", kind),
+                };
+                assert!(verify_account_proof(block_prev_state_root, &account_id, &result));
+                assert!(!verify_account_proof(
+                    block_prev_state_root,
+                    &"test1".to_string(),
+                    &result
+                ));
+                // Every block has a chunk, so the pre-state of a block is the state after the
+                // previous one.
+                let request = QueryRequest::ViewAccount { account_id: account_id.clone() };
+                match unwrap_response(query(prev_block, request).await) {
+                    QueryResponseKind::ViewAccount(account) => assert_eq!(result.account, account),
+                    kind => panic!("unexpected response {:?}", kind),
+                }
+
+                let public_key = signer.public_key();
+                let request = QueryRequest::ViewAccessKeyWithProof {
+                    account_id: account_id.clone(),
+                    public_key: public_key.clone(),
+                };
+                let mut result = match unwrap_response(query(block, request).await) {
+                    QueryResponseKind::AccessKeyWithProof(result) => result,
+                    kind => panic!("unexpected response {:?}", kind),
+                };
+                assert!(verify_access_key_proof(
+                    block_prev_state_root,
+                    &account_id,
+                    &public_key,
+                    &result
+                ));
+                result.access_key.nonce += 1;
+                assert!(!verify_access_key_proof(
+                    block_prev_state_root,
+                    &account_id,
+                    &public_key,
+                    &result
+                ));
+            }
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
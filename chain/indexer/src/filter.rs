@@ -0,0 +1,327 @@
+use std::collections::HashSet;
+
+use near_primitives::types::{AccountId, NumShards, ShardId};
+use near_primitives::views;
+
+use crate::streamer::IndexerExecutionOutcomeWithReceipt;
+
+/// Kind of action a transaction or receipt must contain to pass `IndexerFilter`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    CreateAccount,
+    DeployContract,
+    /// Calls of any of the listed methods, or of any method if the list is empty
+    FunctionCall {
+        method_names: Vec<String>,
+    },
+    Transfer,
+    Stake,
+    AddKey,
+    DeleteKey,
+    DeleteAccount,
+    Delegate,
+}
+
+impl ActionKind {
+    fn matches(&self, action: &views::ActionView) -> bool {
+        match (self, action) {
+            (ActionKind::CreateAccount, views::ActionView::CreateAccount)
+            | (ActionKind::DeployContract, views::ActionView::DeployContract { .. })
+            | (ActionKind::Transfer, views::ActionView::Transfer { .. })
+            | (ActionKind::Stake, views::ActionView::Stake { .. })
+            | (ActionKind::AddKey, views::ActionView::AddKey { .. })
+            | (ActionKind::DeleteKey, views::ActionView::DeleteKey { .. })
+            | (ActionKind::DeleteAccount, views::ActionView::DeleteAccount { .. })
+            | (ActionKind::Delegate, views::ActionView::Delegate { .. }) => true,
+            (
+                ActionKind::FunctionCall { method_names },
+                views::ActionView::FunctionCall { method_name, .. },
+            ) => method_names.is_empty() || method_names.contains(method_name),
+            _ => false,
+        }
+    }
+}
+
+/// Type of state change to pass `IndexerFilter`, mirrors `views::StateChangeKindView`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StateChangeKind {
+    AccountTouched,
+    AccessKeyTouched,
+    DataTouched,
+    ContractCodeTouched,
+}
+
+/// Server-side filter of the data included into `StreamerMessage`.
+/// Every empty field matches everything, so the default filter streams all the data.
+///
+/// Only the chunks of the shards the `accounts` belong to are fetched, so a transaction sent to
+/// one of the accounts by an account from another shard is not included, its receipts are.
+#[derive(Debug, Clone, Default)]
+pub struct IndexerFilter {
+    /// Keep transactions signed by or sent to these accounts, receipts and execution outcomes
+    /// executed on these accounts and state changes of these accounts
+    pub accounts: HashSet<AccountId>,
+    /// Keep transactions and receipts having at least one action of these kinds.
+    /// Data receipts never match a non-empty list
+    pub action_kinds: Vec<ActionKind>,
+    /// Keep state changes of these types
+    pub state_change_kinds: HashSet<StateChangeKind>,
+}
+
+impl IndexerFilter {
+    pub(crate) fn matches_account(&self, account_id: &AccountId) -> bool {
+        self.accounts.is_empty() || self.accounts.contains(account_id)
+    }
+
+    /// Whether the shard can contain anything related to the filtered accounts.
+    pub(crate) fn matches_shard(&self, shard_id: ShardId, num_shards: NumShards) -> bool {
+        self.accounts.is_empty()
+            || self
+                .accounts
+                .iter()
+                .any(|account_id| neard::account_id_to_shard_id(account_id, num_shards) == shard_id)
+    }
+
+    fn matches_actions(&self, actions: &[views::ActionView]) -> bool {
+        self.action_kinds.is_empty()
+            || actions
+                .iter()
+                .any(|action| self.action_kinds.iter().any(|kind| kind.matches(action)))
+    }
+
+    pub(crate) fn matches_transaction(&self, transaction: &views::SignedTransactionView) -> bool {
+        (self.matches_account(&transaction.signer_id)
+            || self.matches_account(&transaction.receiver_id))
+            && self.matches_actions(&transaction.actions)
+    }
+
+    pub(crate) fn matches_receipt(&self, receipt: &views::ReceiptView) -> bool {
+        self.matches_account(&receipt.receiver_id)
+            && match &receipt.receipt {
+                views::ReceiptEnumView::Action { actions, .. } => self.matches_actions(actions),
+                views::ReceiptEnumView::Data { .. } => self.action_kinds.is_empty(),
+            }
+    }
+
+    /// Outcomes without a receipt can only be checked against the accounts, so they don't match
+    /// a non-empty list of action kinds.
+    pub(crate) fn matches_outcome(&self, outcome: &IndexerExecutionOutcomeWithReceipt) -> bool {
+        match &outcome.receipt {
+            Some(receipt) => self.matches_receipt(receipt),
+            None => {
+                self.matches_account(&outcome.execution_outcome.outcome.executor_id)
+                    && self.action_kinds.is_empty()
+            }
+        }
+    }
+
+    pub(crate) fn matches_state_change(&self, state_change: &views::StateChangeKindView) -> bool {
+        let (kind, account_id) = match state_change {
+            views::StateChangeKindView::AccountTouched { account_id } => {
+                (StateChangeKind::AccountTouched, account_id)
+            }
+            views::StateChangeKindView::AccessKeyTouched { account_id } => {
+                (StateChangeKind::AccessKeyTouched, account_id)
+            }
+            views::StateChangeKindView::DataTouched { account_id } => {
+                (StateChangeKind::DataTouched, account_id)
+            }
+            views::StateChangeKindView::ContractCodeTouched { account_id } => {
+                (StateChangeKind::ContractCodeTouched, account_id)
+            }
+        };
+        self.matches_account(account_id)
+            && (self.state_change_kinds.is_empty() || self.state_change_kinds.contains(&kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::{KeyType, PublicKey, Signature};
+    use near_primitives::hash::CryptoHash;
+    use near_primitives::transaction::{
+        ExecutionOutcome, ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof,
+    };
+
+    use super::*;
+
+    fn transaction(
+        signer_id: &str,
+        receiver_id: &str,
+        actions: Vec<views::ActionView>,
+    ) -> views::SignedTransactionView {
+        views::SignedTransactionView {
+            signer_id: signer_id.to_string(),
+            public_key: PublicKey::empty(KeyType::ED25519),
+            nonce: 0,
+            receiver_id: receiver_id.to_string(),
+            actions,
+            signature: Signature::empty(KeyType::ED25519),
+            hash: CryptoHash::default(),
+        }
+    }
+
+    fn receipt(receiver_id: &str, actions: Vec<views::ActionView>) -> views::ReceiptView {
+        views::ReceiptView {
+            predecessor_id: "alice".to_string(),
+            receiver_id: receiver_id.to_string(),
+            receipt_id: CryptoHash::default(),
+            receipt: views::ReceiptEnumView::Action {
+                signer_id: "alice".to_string(),
+                signer_public_key: PublicKey::empty(KeyType::ED25519),
+                gas_price: 0,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions,
+            },
+        }
+    }
+
+    fn data_receipt(receiver_id: &str) -> views::ReceiptView {
+        views::ReceiptView {
+            predecessor_id: "alice".to_string(),
+            receiver_id: receiver_id.to_string(),
+            receipt_id: CryptoHash::default(),
+            receipt: views::ReceiptEnumView::Data { data_id: CryptoHash::default(), data: None },
+        }
+    }
+
+    fn outcome(
+        executor_id: &str,
+        receipt: Option<views::ReceiptView>,
+    ) -> IndexerExecutionOutcomeWithReceipt {
+        let outcome = ExecutionOutcomeWithIdAndProof {
+            proof: vec![],
+            block_hash: CryptoHash::default(),
+            outcome_with_id: ExecutionOutcomeWithId {
+                id: CryptoHash::default(),
+                outcome: ExecutionOutcome {
+                    executor_id: executor_id.to_string(),
+                    ..Default::default()
+                },
+            },
+        };
+        IndexerExecutionOutcomeWithReceipt { execution_outcome: outcome.into(), receipt }
+    }
+
+    fn function_call(method_name: &str) -> views::ActionView {
+        views::ActionView::FunctionCall {
+            method_name: method_name.to_string(),
+            args: String::new(),
+            gas: 0,
+            deposit: 0,
+        }
+    }
+
+    fn transfer() -> views::ActionView {
+        views::ActionView::Transfer { deposit: 1 }
+    }
+
+    #[test]
+    fn test_default_filter_matches_everything() {
+        let filter = IndexerFilter::default();
+        assert!(filter.matches_transaction(&transaction("alice", "bob", vec![])));
+        assert!(filter.matches_receipt(&receipt("bob", vec![transfer()])));
+        assert!(filter.matches_receipt(&data_receipt("bob")));
+        assert!(filter.matches_outcome(&outcome("bob", None)));
+        assert!(filter.matches_state_change(&views::StateChangeKindView::DataTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!((0..4).all(|shard_id| filter.matches_shard(shard_id, 4)));
+    }
+
+    #[test]
+    fn test_accounts_filter() {
+        let filter = IndexerFilter {
+            accounts: vec!["bob".to_string()].into_iter().collect(),
+            ..Default::default()
+        };
+        assert!(filter.matches_transaction(&transaction("alice", "bob", vec![transfer()])));
+        assert!(filter.matches_transaction(&transaction("bob", "carol", vec![transfer()])));
+        assert!(!filter.matches_transaction(&transaction("alice", "carol", vec![transfer()])));
+
+        assert!(filter.matches_receipt(&receipt("bob", vec![transfer()])));
+        assert!(!filter.matches_receipt(&receipt("carol", vec![transfer()])));
+        assert!(filter.matches_receipt(&data_receipt("bob")));
+
+        assert!(filter.matches_outcome(&outcome("bob", None)));
+        assert!(!filter.matches_outcome(&outcome("carol", None)));
+        assert!(!filter.matches_outcome(&outcome("carol", Some(receipt("carol", vec![])))));
+
+        assert!(filter.matches_state_change(&views::StateChangeKindView::AccountTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!(!filter.matches_state_change(&views::StateChangeKindView::AccountTouched {
+            account_id: "carol".to_string()
+        }));
+
+        let bob_shard_id = neard::account_id_to_shard_id(&"bob".to_string(), 4);
+        assert!(filter.matches_shard(bob_shard_id, 4));
+        assert!(!filter.matches_shard((bob_shard_id + 1) % 4, 4));
+        assert!(filter.matches_shard(0, 1));
+    }
+
+    #[test]
+    fn test_action_kinds_filter() {
+        let filter = IndexerFilter {
+            action_kinds: vec![ActionKind::FunctionCall { method_names: vec!["ping".to_string()] }],
+            ..Default::default()
+        };
+        assert!(filter.matches_transaction(&transaction(
+            "alice",
+            "bob",
+            vec![transfer(), function_call("ping")]
+        )));
+        assert!(!filter.matches_transaction(&transaction(
+            "alice",
+            "bob",
+            vec![function_call("pong")]
+        )));
+        assert!(!filter.matches_transaction(&transaction("alice", "bob", vec![transfer()])));
+
+        assert!(filter.matches_receipt(&receipt("bob", vec![function_call("ping")])));
+        assert!(!filter.matches_receipt(&receipt("bob", vec![transfer()])));
+        assert!(!filter.matches_receipt(&data_receipt("bob")));
+
+        assert!(filter
+            .matches_outcome(&outcome("bob", Some(receipt("bob", vec![function_call("ping")])))));
+        assert!(!filter.matches_outcome(&outcome("bob", Some(receipt("bob", vec![transfer()])))));
+        // Without the receipt the actions are unknown.
+        assert!(!filter.matches_outcome(&outcome("bob", None)));
+
+        let any_method = IndexerFilter {
+            action_kinds: vec![ActionKind::FunctionCall { method_names: vec![] }],
+            ..Default::default()
+        };
+        assert!(any_method.matches_receipt(&receipt("bob", vec![function_call("pong")])));
+    }
+
+    #[test]
+    fn test_state_change_kinds_filter() {
+        let filter = IndexerFilter {
+            accounts: vec!["bob".to_string()].into_iter().collect(),
+            state_change_kinds: vec![
+                StateChangeKind::DataTouched,
+                StateChangeKind::AccessKeyTouched,
+            ]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        assert!(filter.matches_state_change(&views::StateChangeKindView::DataTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!(filter.matches_state_change(&views::StateChangeKindView::AccessKeyTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!(!filter.matches_state_change(&views::StateChangeKindView::AccountTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!(!filter.matches_state_change(&views::StateChangeKindView::ContractCodeTouched {
+            account_id: "bob".to_string()
+        }));
+        assert!(!filter.matches_state_change(&views::StateChangeKindView::DataTouched {
+            account_id: "carol".to_string()
+        }));
+    }
+}
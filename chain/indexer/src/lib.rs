@@ -7,15 +7,21 @@
 //! See the [example] for further details.
 //!
 //! [example]: https://github.com/nearprotocol/nearcore/tree/master/tools/indexer/example
+use std::sync::Arc;
+
 use actix::System;
 use tokio::sync::mpsc;
 
 pub use neard::{get_default_home, init_configs, NearConfig};
+mod filter;
+#[cfg(any(feature = "sink_jsonl", feature = "sink_sqlite"))]
+pub mod sinks;
 mod streamer;
 
+pub use self::filter::{ActionKind, IndexerFilter, StateChangeKind};
 pub use self::streamer::{
-    ExecutionOutcomesWithReceipts, IndexerChunkView, IndexerExecutionOutcomeWithReceipt,
-    IndexerTransactionWithOutcome, StreamerMessage,
+    BlockAcknowledger, ExecutionOutcomesWithReceipts, IndexerChunkView,
+    IndexerExecutionOutcomeWithReceipt, IndexerTransactionWithOutcome, StreamerMessage,
 };
 pub use near_primitives;
 
@@ -24,7 +30,8 @@ pub use near_primitives;
 pub enum SyncModeEnum {
     /// Real-time syncing, always taking the latest finalized block to stream
     LatestSynced,
-    /// Starts syncing from the block NEAR Indexer was interrupted last time  
+    /// Starts syncing from the block NEAR Indexer was interrupted last time.
+    /// With `Indexer::streamer_with_ack` it is the block after the last acknowledged one.
     FromInterruption,
     /// Specific block height to start syncing from
     BlockHeight(u64),
@@ -37,6 +44,8 @@ pub struct IndexerConfig {
     pub home_dir: std::path::PathBuf,
     /// Mode of syncing for NEAR Indexer instance
     pub sync_mode: SyncModeEnum,
+    /// Data to include into `StreamerMessage`, everything by default
+    pub filter: IndexerFilter,
 }
 
 /// This is the core component, which handles `nearcore` and internal `streamer`.
@@ -46,6 +55,7 @@ pub struct Indexer {
     actix_runtime: actix::SystemRunner,
     view_client: actix::Addr<near_client::ViewClientActor>,
     client: actix::Addr<near_client::ClientActor>,
+    db: Arc<rocksdb::DB>,
 }
 
 impl Indexer {
@@ -63,17 +73,31 @@ impl Indexer {
         );
         let (client, view_client, _) =
             neard::start_with_config(&indexer_config.home_dir, near_config.clone());
-        Self { actix_runtime: system, view_client, client, near_config, indexer_config }
+        let db = streamer::open_indexer_db(&indexer_config.home_dir);
+        Self { actix_runtime: system, view_client, client, near_config, indexer_config, db }
     }
 
     /// Boots up `near_indexer::streamer`, so it monitors the new blocks with chunks, transactions, receipts, and execution outcomes inside. The returned stream handler should be drained and handled on the user side.
     pub fn streamer(&self) -> mpsc::Receiver<streamer::StreamerMessage> {
+        self.start_streamer(false)
+    }
+
+    /// Same as `streamer`, but with at-least-once delivery: the consumer acknowledges every processed block with the returned `BlockAcknowledger`, and `SyncModeEnum::FromInterruption` replays the blocks after the last acknowledged one.
+    pub fn streamer_with_ack(
+        &self,
+    ) -> (mpsc::Receiver<streamer::StreamerMessage>, BlockAcknowledger) {
+        (self.start_streamer(true), BlockAcknowledger::new(self.db.clone()))
+    }
+
+    fn start_streamer(&self, resume_from_ack: bool) -> mpsc::Receiver<streamer::StreamerMessage> {
         let (sender, receiver) = mpsc::channel(16);
         actix::spawn(streamer::start(
             self.view_client.clone(),
             self.client.clone(),
             self.near_config.clone(),
             self.indexer_config.clone(),
+            self.db.clone(),
+            resume_from_ack,
             sender,
         ));
         receiver
@@ -0,0 +1,100 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use crate::StreamerMessage;
+
+use super::{Sink, SinkError};
+
+struct JsonLinesFile {
+    writer: BufWriter<File>,
+    size: u64,
+}
+
+/// Writes every `StreamerMessage` as a single JSON line.
+///
+/// Files are named `blocks-<height>.jsonl` after the first block they contain, so listing
+/// them in lexicographical order lists them in the order of blocks. A new file is started
+/// once the current one exceeds `max_file_size` bytes.
+pub struct JsonLinesSink {
+    dir: PathBuf,
+    max_file_size: u64,
+    file: Option<JsonLinesFile>,
+}
+
+impl JsonLinesSink {
+    pub fn new(dir: PathBuf, max_file_size: u64) -> Result<Self, io::Error> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir, max_file_size, file: None })
+    }
+
+    fn open_file(&self, block_height: u64) -> Result<JsonLinesFile, io::Error> {
+        let path = self.dir.join(format!("blocks-{:020}.jsonl", block_height));
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(JsonLinesFile { writer: BufWriter::new(file), size })
+    }
+}
+
+impl Sink for JsonLinesSink {
+    fn write(&mut self, streamer_message: &StreamerMessage) -> Result<(), SinkError> {
+        let mut line = serde_json::to_vec(streamer_message)?;
+        line.push(b'\n');
+
+        let needs_new_file = match &self.file {
+            Some(file) => file.size >= self.max_file_size,
+            None => true,
+        };
+        if needs_new_file {
+            self.file = Some(self.open_file(streamer_message.block.header.height)?);
+        }
+        let file = self.file.as_mut().expect("File is opened above");
+        file.writer.write_all(&line)?;
+        file.writer.flush()?;
+        file.writer.get_ref().sync_data()?;
+        file.size += line.len() as u64;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufRead;
+
+    use super::*;
+    use crate::sinks::test_utils::test_streamer_message;
+
+    fn read_lines(path: PathBuf) -> Vec<serde_json::Value> {
+        io::BufReader::new(File::open(path).unwrap())
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_json_lines_rotation() {
+        let dir = tempfile::Builder::new().prefix("jsonl").tempdir().unwrap();
+        // Every file gets exactly two blocks.
+        let line_size = serde_json::to_vec(&test_streamer_message(1)).unwrap().len() as u64 + 1;
+        let mut sink = JsonLinesSink::new(dir.path().to_path_buf(), line_size + 1).unwrap();
+        for height in 1..=3 {
+            sink.write(&test_streamer_message(height)).unwrap();
+        }
+
+        let mut files: Vec<_> =
+            fs::read_dir(dir.path()).unwrap().map(|entry| entry.unwrap().path()).collect();
+        files.sort();
+        assert_eq!(
+            files.iter().map(|path| path.file_name().unwrap().to_owned()).collect::<Vec<_>>(),
+            vec!["blocks-00000000000000000001.jsonl", "blocks-00000000000000000003.jsonl"]
+        );
+        let lines = read_lines(files[0].clone());
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["block"]["header"]["height"], 2);
+        assert_eq!(
+            lines[1]["chunks"][0]["transactions"][0]["transaction"]["receiver_id"],
+            "bob.near"
+        );
+        assert_eq!(read_lines(files[1].clone()).len(), 1);
+    }
+}
@@ -0,0 +1,199 @@
+//! Ready-made consumers of the `StreamerMessage` stream.
+//!
+//! Every sink is enabled by its own cargo feature:
+//!  * `sink_jsonl` - `JsonLinesSink` writes the messages into rotating JSON-lines files
+//!  * `sink_sqlite` - `SqliteSink` inserts normalized rows into a SQLite database
+//!
+//! Use `run_sink` to drain the stream returned by `Indexer::streamer_with_ack` into a sink.
+use std::fmt;
+use std::io;
+
+use tokio::sync::mpsc;
+use tracing::error;
+
+use crate::{BlockAcknowledger, StreamerMessage};
+
+#[cfg(feature = "sink_jsonl")]
+pub use self::jsonl::JsonLinesSink;
+#[cfg(feature = "sink_sqlite")]
+pub use self::sqlite::{SqliteSink, SQLITE_SCHEMA};
+
+#[cfg(feature = "sink_jsonl")]
+mod jsonl;
+#[cfg(feature = "sink_sqlite")]
+mod sqlite;
+
+const INDEXER_SINK: &str = "indexer_sink";
+
+#[derive(Debug)]
+pub enum SinkError {
+    Io(io::Error),
+    Serialization(serde_json::Error),
+    #[cfg(feature = "sink_sqlite")]
+    Sqlite(rusqlite::Error),
+}
+
+impl fmt::Display for SinkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SinkError::Io(err) => write!(f, "IO error: {}", err),
+            SinkError::Serialization(err) => write!(f, "Serialization error: {}", err),
+            #[cfg(feature = "sink_sqlite")]
+            SinkError::Sqlite(err) => write!(f, "SQLite error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SinkError {}
+
+impl From<io::Error> for SinkError {
+    fn from(err: io::Error) -> Self {
+        SinkError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for SinkError {
+    fn from(err: serde_json::Error) -> Self {
+        SinkError::Serialization(err)
+    }
+}
+
+#[cfg(feature = "sink_sqlite")]
+impl From<rusqlite::Error> for SinkError {
+    fn from(err: rusqlite::Error) -> Self {
+        SinkError::Sqlite(err)
+    }
+}
+
+/// Destination of the streamed blocks. The same block can be written more than once when
+/// the Indexer replays unacknowledged blocks, so sinks should tolerate duplicates.
+pub trait Sink {
+    /// Writes the message. The block is acknowledged as soon as this returns, so the data
+    /// must be durably stored by then.
+    fn write(&mut self, streamer_message: &StreamerMessage) -> Result<(), SinkError>;
+}
+
+/// Writes every received message into the sink and acknowledges it afterwards.
+/// Stops at the first failed write, so the failed block is replayed after a restart.
+pub async fn run_sink<S: Sink>(
+    mut stream: mpsc::Receiver<StreamerMessage>,
+    mut sink: S,
+    acknowledger: BlockAcknowledger,
+) {
+    while let Some(streamer_message) = stream.recv().await {
+        let block_height = streamer_message.block.header.height;
+        if let Err(err) = sink.write(&streamer_message) {
+            error!(target: INDEXER_SINK, "Failed to write block #{}: {}", block_height, err);
+            return;
+        }
+        if let Err(err) = acknowledger.ack(block_height) {
+            error!(target: INDEXER_SINK, "Failed to acknowledge block #{}: {}", block_height, err);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test_utils {
+    use std::collections::HashMap;
+
+    use near_crypto::{InMemorySigner, KeyType};
+    use near_primitives::block::{genesis_chunks, Block};
+    use near_primitives::hash::{hash, CryptoHash};
+    use near_primitives::transaction::{
+        ExecutionOutcome, ExecutionOutcomeWithId, ExecutionOutcomeWithIdAndProof, SignedTransaction,
+    };
+    use near_primitives::utils::from_timestamp;
+    use near_primitives::version::PROTOCOL_VERSION;
+    use near_primitives::views;
+
+    use crate::{
+        IndexerChunkView, IndexerExecutionOutcomeWithReceipt, IndexerTransactionWithOutcome,
+        StreamerMessage,
+    };
+
+    fn outcome(
+        id: CryptoHash,
+        block_hash: CryptoHash,
+        executor_id: &str,
+    ) -> IndexerExecutionOutcomeWithReceipt {
+        IndexerExecutionOutcomeWithReceipt {
+            execution_outcome: ExecutionOutcomeWithIdAndProof {
+                proof: vec![],
+                block_hash,
+                outcome_with_id: ExecutionOutcomeWithId {
+                    id,
+                    outcome: ExecutionOutcome {
+                        executor_id: executor_id.to_string(),
+                        ..Default::default()
+                    },
+                },
+            }
+            .into(),
+            receipt: None,
+        }
+    }
+
+    /// Block with a single chunk containing one transfer, one receipt and one state change.
+    pub(crate) fn test_streamer_message(height: u64) -> StreamerMessage {
+        let chunks = genesis_chunks(vec![CryptoHash::default()], 1, 1_000_000, 0, PROTOCOL_VERSION);
+        let block = Block::genesis(
+            PROTOCOL_VERSION,
+            chunks.iter().map(|chunk| chunk.cloned_header()).collect(),
+            from_timestamp(1_600_000_000_000_000_000),
+            height,
+            100,
+            1_000_000,
+            CryptoHash::default(),
+        );
+        let block = views::BlockView::from_author_block("test.near".to_string(), block);
+        let block_hash = block.header.hash;
+
+        let signer = InMemorySigner::from_seed("alice.near", KeyType::ED25519, "alice.near");
+        let transaction: views::SignedTransactionView = SignedTransaction::send_money(
+            height,
+            "alice.near".to_string(),
+            "bob.near".to_string(),
+            &signer,
+            10,
+            block_hash,
+        )
+        .into();
+        let receipt_id = hash(&height.to_le_bytes());
+        let receipt = views::ReceiptView {
+            predecessor_id: "alice.near".to_string(),
+            receiver_id: "bob.near".to_string(),
+            receipt_id,
+            receipt: views::ReceiptEnumView::Action {
+                signer_id: "alice.near".to_string(),
+                signer_public_key: signer.public_key.clone(),
+                gas_price: 100,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions: vec![views::ActionView::Transfer { deposit: 10 }],
+            },
+        };
+
+        let mut receipt_execution_outcomes = HashMap::new();
+        let mut receipt_outcome = outcome(receipt_id, block_hash, "bob.near");
+        receipt_outcome.receipt = Some(receipt.clone());
+        receipt_execution_outcomes.insert(receipt_id, receipt_outcome);
+
+        StreamerMessage {
+            chunks: vec![IndexerChunkView {
+                author: "test.near".to_string(),
+                header: chunks[0].cloned_header().into(),
+                transactions: vec![IndexerTransactionWithOutcome {
+                    outcome: outcome(transaction.hash, block_hash, "alice.near"),
+                    transaction,
+                }],
+                receipts: vec![receipt],
+            }],
+            block,
+            receipt_execution_outcomes,
+            state_changes: vec![views::StateChangeKindView::AccountTouched {
+                account_id: "bob.near".to_string(),
+            }],
+        }
+    }
+}
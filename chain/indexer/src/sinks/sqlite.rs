@@ -0,0 +1,215 @@
+use std::path::Path;
+
+use rusqlite::{params, Connection};
+
+use near_primitives::views;
+
+use crate::{IndexerExecutionOutcomeWithReceipt, StreamerMessage};
+
+use super::{Sink, SinkError};
+
+/// Schema of the tables `SqliteSink` writes into, see the comments inside for the details.
+pub const SQLITE_SCHEMA: &str = include_str!("sqlite_schema.sql");
+
+/// Inserts normalized rows for blocks, chunks, transactions, receipts, execution outcomes and
+/// state changes into a SQLite database with `SQLITE_SCHEMA`. Every block is written in a
+/// single database transaction.
+pub struct SqliteSink {
+    connection: Connection,
+}
+
+impl SqliteSink {
+    /// Opens or creates the database at `path` and creates the missing tables.
+    pub fn open(path: &Path) -> Result<Self, SinkError> {
+        Self::new(Connection::open(path)?)
+    }
+
+    pub fn new(connection: Connection) -> Result<Self, SinkError> {
+        connection.execute_batch(SQLITE_SCHEMA)?;
+        Ok(Self { connection })
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+}
+
+fn insert_execution_outcome(
+    connection: &Connection,
+    outcome_kind: &str,
+    outcome: &IndexerExecutionOutcomeWithReceipt,
+) -> Result<(), SinkError> {
+    let views::ExecutionOutcomeWithIdView { block_hash, id, outcome, .. } =
+        &outcome.execution_outcome;
+    connection.execute(
+        "INSERT OR REPLACE INTO execution_outcomes (id, block_hash, outcome_kind, \
+         executor_account_id, gas_burnt, tokens_burnt, status, logs, receipt_ids) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+        params![
+            id.to_string(),
+            block_hash.to_string(),
+            outcome_kind,
+            outcome.executor_id,
+            outcome.gas_burnt as i64,
+            outcome.tokens_burnt.to_string(),
+            serde_json::to_string(&outcome.status)?,
+            serde_json::to_string(&outcome.logs)?,
+            serde_json::to_string(&outcome.receipt_ids)?,
+        ],
+    )?;
+    Ok(())
+}
+
+impl Sink for SqliteSink {
+    fn write(&mut self, streamer_message: &StreamerMessage) -> Result<(), SinkError> {
+        let transaction = self.connection.transaction()?;
+        let block = &streamer_message.block;
+        let block_hash = block.header.hash.to_string();
+        transaction.execute(
+            "INSERT OR REPLACE INTO blocks (block_hash, block_height, prev_block_hash, \
+             block_timestamp, author_account_id, gas_price, total_supply) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                block_hash,
+                block.header.height as i64,
+                block.header.prev_hash.to_string(),
+                block.header.timestamp as i64,
+                block.author,
+                block.header.gas_price.to_string(),
+                block.header.total_supply.to_string(),
+            ],
+        )?;
+
+        for chunk in &streamer_message.chunks {
+            let chunk_hash = chunk.header.chunk_hash.to_string();
+            transaction.execute(
+                "INSERT OR REPLACE INTO chunks (chunk_hash, block_hash, shard_id, \
+                 author_account_id, gas_used, gas_limit) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    chunk_hash,
+                    block_hash,
+                    chunk.header.shard_id as i64,
+                    chunk.author,
+                    chunk.header.gas_used as i64,
+                    chunk.header.gas_limit as i64,
+                ],
+            )?;
+
+            for (index, transaction_with_outcome) in chunk.transactions.iter().enumerate() {
+                let signed_transaction = &transaction_with_outcome.transaction;
+                transaction.execute(
+                    "INSERT OR REPLACE INTO transactions (transaction_hash, block_hash, \
+                     chunk_hash, index_in_chunk, signer_account_id, signer_public_key, nonce, \
+                     receiver_account_id, signature, actions) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                    params![
+                        signed_transaction.hash.to_string(),
+                        block_hash,
+                        chunk_hash,
+                        index as i64,
+                        signed_transaction.signer_id,
+                        signed_transaction.public_key.to_string(),
+                        signed_transaction.nonce as i64,
+                        signed_transaction.receiver_id,
+                        signed_transaction.signature.to_string(),
+                        serde_json::to_string(&signed_transaction.actions)?,
+                    ],
+                )?;
+                insert_execution_outcome(
+                    &transaction,
+                    "TRANSACTION",
+                    &transaction_with_outcome.outcome,
+                )?;
+            }
+
+            for (index, receipt) in chunk.receipts.iter().enumerate() {
+                let receipt_kind = match receipt.receipt {
+                    views::ReceiptEnumView::Action { .. } => "ACTION",
+                    views::ReceiptEnumView::Data { .. } => "DATA",
+                };
+                transaction.execute(
+                    "INSERT OR REPLACE INTO receipts (receipt_id, block_hash, chunk_hash, \
+                     index_in_chunk, predecessor_account_id, receiver_account_id, receipt_kind, \
+                     receipt) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    params![
+                        receipt.receipt_id.to_string(),
+                        block_hash,
+                        chunk_hash,
+                        index as i64,
+                        receipt.predecessor_id,
+                        receipt.receiver_id,
+                        receipt_kind,
+                        serde_json::to_string(&receipt.receipt)?,
+                    ],
+                )?;
+            }
+        }
+
+        for outcome in streamer_message.receipt_execution_outcomes.values() {
+            insert_execution_outcome(&transaction, "RECEIPT", outcome)?;
+        }
+
+        for (index, state_change) in streamer_message.state_changes.iter().enumerate() {
+            let (change_kind, account_id) = match state_change {
+                views::StateChangeKindView::AccountTouched { account_id } => {
+                    ("ACCOUNT_TOUCHED", account_id)
+                }
+                views::StateChangeKindView::AccessKeyTouched { account_id } => {
+                    ("ACCESS_KEY_TOUCHED", account_id)
+                }
+                views::StateChangeKindView::DataTouched { account_id } => {
+                    ("DATA_TOUCHED", account_id)
+                }
+                views::StateChangeKindView::ContractCodeTouched { account_id } => {
+                    ("CONTRACT_CODE_TOUCHED", account_id)
+                }
+            };
+            transaction.execute(
+                "INSERT OR REPLACE INTO state_changes (block_hash, index_in_block, change_kind, \
+                 account_id) VALUES (?1, ?2, ?3, ?4)",
+                params![block_hash, index as i64, change_kind, account_id],
+            )?;
+        }
+
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sinks::test_utils::test_streamer_message;
+
+    fn count(sink: &SqliteSink, table: &str) -> i64 {
+        sink.connection()
+            .query_row(&format!("SELECT COUNT(*) FROM {}", table), params![], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sqlite_sink() {
+        let mut sink = SqliteSink::new(Connection::open_in_memory().unwrap()).unwrap();
+        let streamer_message = test_streamer_message(1);
+        sink.write(&streamer_message).unwrap();
+        // Replayed blocks must not produce duplicates.
+        sink.write(&streamer_message).unwrap();
+        sink.write(&test_streamer_message(2)).unwrap();
+
+        assert_eq!(count(&sink, "blocks"), 2);
+        assert_eq!(count(&sink, "transactions"), 2);
+        assert_eq!(count(&sink, "receipts"), 2);
+        assert_eq!(count(&sink, "execution_outcomes"), 4);
+        assert_eq!(count(&sink, "state_changes"), 2);
+
+        let receiver: String = sink
+            .connection()
+            .query_row(
+                "SELECT receiver_account_id FROM transactions WHERE transaction_hash = ?1",
+                params![streamer_message.chunks[0].transactions[0].transaction.hash.to_string()],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(receiver, "bob.near");
+    }
+}
@@ -0,0 +1,91 @@
+-- Schema of the database written by `near_indexer::sinks::SqliteSink`.
+--
+-- Hashes, public keys and signatures are stored in their usual base58 string form,
+-- balances are stored as decimal strings since they do not fit into SQLite integers,
+-- and nested structures are stored as JSON.
+--
+-- Every table has a primary key and rows are inserted with `INSERT OR REPLACE`, so
+-- writing the same block again (e.g. when it is replayed after a restart) is a no-op.
+
+CREATE TABLE IF NOT EXISTS blocks (
+    block_hash TEXT PRIMARY KEY NOT NULL,
+    block_height INTEGER NOT NULL,
+    prev_block_hash TEXT NOT NULL,
+    -- nanoseconds since the Unix epoch
+    block_timestamp INTEGER NOT NULL,
+    author_account_id TEXT NOT NULL,
+    gas_price TEXT NOT NULL,
+    total_supply TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS blocks_height_idx ON blocks (block_height);
+
+-- Only chunks included into the block for the first time
+CREATE TABLE IF NOT EXISTS chunks (
+    chunk_hash TEXT PRIMARY KEY NOT NULL,
+    block_hash TEXT NOT NULL REFERENCES blocks (block_hash),
+    shard_id INTEGER NOT NULL,
+    author_account_id TEXT NOT NULL,
+    gas_used INTEGER NOT NULL,
+    gas_limit INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS transactions (
+    transaction_hash TEXT PRIMARY KEY NOT NULL,
+    block_hash TEXT NOT NULL REFERENCES blocks (block_hash),
+    chunk_hash TEXT NOT NULL REFERENCES chunks (chunk_hash),
+    index_in_chunk INTEGER NOT NULL,
+    signer_account_id TEXT NOT NULL,
+    signer_public_key TEXT NOT NULL,
+    nonce INTEGER NOT NULL,
+    receiver_account_id TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    -- JSON array of `ActionView`
+    actions TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS transactions_signer_idx ON transactions (signer_account_id);
+CREATE INDEX IF NOT EXISTS transactions_receiver_idx ON transactions (receiver_account_id);
+
+-- Receipts included into the chunks, local receipts included
+CREATE TABLE IF NOT EXISTS receipts (
+    receipt_id TEXT PRIMARY KEY NOT NULL,
+    block_hash TEXT NOT NULL REFERENCES blocks (block_hash),
+    chunk_hash TEXT NOT NULL REFERENCES chunks (chunk_hash),
+    index_in_chunk INTEGER NOT NULL,
+    predecessor_account_id TEXT NOT NULL,
+    receiver_account_id TEXT NOT NULL,
+    -- 'ACTION' or 'DATA'
+    receipt_kind TEXT NOT NULL,
+    -- JSON of `ReceiptEnumView`
+    receipt TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS receipts_receiver_idx ON receipts (receiver_account_id);
+
+-- Execution outcomes of both transactions and receipts
+CREATE TABLE IF NOT EXISTS execution_outcomes (
+    -- transaction hash or receipt id
+    id TEXT PRIMARY KEY NOT NULL,
+    block_hash TEXT NOT NULL REFERENCES blocks (block_hash),
+    -- 'TRANSACTION' or 'RECEIPT'
+    outcome_kind TEXT NOT NULL,
+    executor_account_id TEXT NOT NULL,
+    gas_burnt INTEGER NOT NULL,
+    tokens_burnt TEXT NOT NULL,
+    -- JSON of `ExecutionStatusView`
+    status TEXT NOT NULL,
+    -- JSON array of strings
+    logs TEXT NOT NULL,
+    -- JSON array of the ids of the produced receipts
+    receipt_ids TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS execution_outcomes_executor_idx
+    ON execution_outcomes (executor_account_id);
+
+CREATE TABLE IF NOT EXISTS state_changes (
+    block_hash TEXT NOT NULL REFERENCES blocks (block_hash),
+    index_in_block INTEGER NOT NULL,
+    -- 'ACCOUNT_TOUCHED', 'ACCESS_KEY_TOUCHED', 'DATA_TOUCHED' or 'CONTRACT_CODE_TOUCHED'
+    change_kind TEXT NOT NULL,
+    account_id TEXT NOT NULL,
+    PRIMARY KEY (block_hash, index_in_block)
+);
+CREATE INDEX IF NOT EXISTS state_changes_account_idx ON state_changes (account_id);
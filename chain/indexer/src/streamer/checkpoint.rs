@@ -0,0 +1,143 @@
+use std::io;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rocksdb::DB;
+
+use near_primitives::types::BlockHeight;
+
+const LAST_SYNCED_BLOCK_HEIGHT_KEY: &[u8] = b"last_synced_block_height";
+const LAST_ACKED_BLOCK_HEIGHT_KEY: &[u8] = b"last_acked_block_height";
+
+/// Opens the database the streamer keeps its progress in.
+pub(crate) fn open_indexer_db(home_dir: &Path) -> Arc<DB> {
+    let mut indexer_db_path = neard::get_store_path(home_dir);
+    indexer_db_path.push_str("/indexer");
+    // TODO: implement proper error handling
+    Arc::new(DB::open_default(indexer_db_path).unwrap())
+}
+
+fn get_block_height(db: &DB, key: &[u8]) -> Option<BlockHeight> {
+    db.get(key).unwrap().map(|value| String::from_utf8(value).unwrap().parse().unwrap())
+}
+
+/// Height of the last block sent to the consumer.
+pub(crate) fn get_last_synced_block_height(db: &DB) -> Option<BlockHeight> {
+    get_block_height(db, LAST_SYNCED_BLOCK_HEIGHT_KEY)
+}
+
+pub(crate) fn set_last_synced_block_height(db: &DB, block_height: BlockHeight) {
+    db.put(LAST_SYNCED_BLOCK_HEIGHT_KEY, &block_height.to_string()).unwrap();
+}
+
+/// Height of the last block the consumer acknowledged with `BlockAcknowledger`.
+pub(crate) fn get_last_acked_block_height(db: &DB) -> Option<BlockHeight> {
+    get_block_height(db, LAST_ACKED_BLOCK_HEIGHT_KEY)
+}
+
+/// Height of the first block to stream when the streamer starts.
+pub(crate) fn get_start_syncing_block_height(
+    db: &DB,
+    sync_mode: &crate::SyncModeEnum,
+    resume_from_ack: bool,
+    latest_block_height: BlockHeight,
+) -> BlockHeight {
+    match sync_mode {
+        crate::SyncModeEnum::FromInterruption if resume_from_ack => {
+            match get_last_acked_block_height(db) {
+                Some(last_acked_block_height) => last_acked_block_height + 1,
+                None => latest_block_height,
+            }
+        }
+        crate::SyncModeEnum::FromInterruption => {
+            get_last_synced_block_height(db).unwrap_or(latest_block_height)
+        }
+        crate::SyncModeEnum::LatestSynced => latest_block_height,
+        crate::SyncModeEnum::BlockHeight(height) => *height,
+    }
+}
+
+/// Handle returned by `Indexer::streamer_with_ack` to acknowledge processed blocks.
+///
+/// Acknowledging a block height acknowledges all the blocks before it as well, so blocks
+/// must be acknowledged in the order they were received. After a restart with
+/// `SyncModeEnum::FromInterruption`, streaming resumes right after the last acknowledged
+/// block, hence every block is delivered at least once.
+#[derive(Clone)]
+pub struct BlockAcknowledger {
+    db: Arc<DB>,
+    /// Serializes acks, so that a lower height can't overwrite a higher one.
+    lock: Arc<Mutex<()>>,
+}
+
+impl BlockAcknowledger {
+    pub(crate) fn new(db: Arc<DB>) -> Self {
+        Self { db, lock: Arc::new(Mutex::new(())) }
+    }
+
+    /// Persists `block_height` as the last processed block. Heights that are not higher than
+    /// the already acknowledged one are ignored, so the checkpoint never moves backwards.
+    pub fn ack(&self, block_height: BlockHeight) -> Result<(), io::Error> {
+        let _guard = self.lock.lock().expect("BlockAcknowledger lock is poisoned");
+        if get_last_acked_block_height(&self.db)
+            .map_or(false, |last_acked_block_height| block_height <= last_acked_block_height)
+        {
+            return Ok(());
+        }
+        self.db
+            .put(LAST_ACKED_BLOCK_HEIGHT_KEY, &block_height.to_string())
+            .map_err(|err| io::Error::new(io::ErrorKind::Other, err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SyncModeEnum;
+
+    fn open_db(dir: &tempfile::TempDir) -> Arc<DB> {
+        Arc::new(DB::open_default(dir.path()).unwrap())
+    }
+
+    #[test]
+    fn test_ack_is_monotonic() {
+        let dir = tempfile::Builder::new().prefix("indexer").tempdir().unwrap();
+        let db = open_db(&dir);
+        let acknowledger = BlockAcknowledger::new(db.clone());
+        assert_eq!(get_last_acked_block_height(&db), None);
+        acknowledger.ack(10).unwrap();
+        acknowledger.ack(12).unwrap();
+        assert_eq!(get_last_acked_block_height(&db), Some(12));
+        acknowledger.ack(11).unwrap();
+        acknowledger.ack(12).unwrap();
+        assert_eq!(get_last_acked_block_height(&db), Some(12));
+    }
+
+    #[test]
+    fn test_resume_after_restart() {
+        let dir = tempfile::Builder::new().prefix("indexer").tempdir().unwrap();
+        {
+            let db = open_db(&dir);
+            set_last_synced_block_height(&db, 20);
+            BlockAcknowledger::new(db).ack(15).unwrap();
+        }
+
+        let db = open_db(&dir);
+        let mode = SyncModeEnum::FromInterruption;
+        // Blocks after the last acknowledged one are replayed.
+        assert_eq!(get_start_syncing_block_height(&db, &mode, true, 30), 16);
+        // Without acks, streaming resumes from the last block sent.
+        assert_eq!(get_start_syncing_block_height(&db, &mode, false, 30), 20);
+        assert_eq!(get_start_syncing_block_height(&db, &SyncModeEnum::LatestSynced, true, 30), 30);
+        assert_eq!(get_start_syncing_block_height(&db, &SyncModeEnum::BlockHeight(5), true, 30), 5);
+    }
+
+    #[test]
+    fn test_resume_without_checkpoint() {
+        let dir = tempfile::Builder::new().prefix("indexer").tempdir().unwrap();
+        let db = open_db(&dir);
+        let mode = SyncModeEnum::FromInterruption;
+        assert_eq!(get_start_syncing_block_height(&db, &mode, true, 30), 30);
+        assert_eq!(get_start_syncing_block_height(&db, &mode, false, 30), 30);
+    }
+}
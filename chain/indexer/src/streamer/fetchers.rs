@@ -1,5 +1,7 @@
 //! Streamer watches the network and collects all the blocks and related chunks
 //! into one struct and pushes in in to the given queue
+use std::collections::HashSet;
+
 use actix::Addr;
 use futures::stream::StreamExt;
 use tracing::warn;
@@ -10,6 +12,7 @@ pub use near_primitives::{types, views};
 use super::errors::FailedToFetchData;
 use super::types::{ExecutionOutcomesWithReceipts, IndexerExecutionOutcomeWithReceipt};
 use super::INDEXER;
+use crate::IndexerFilter;
 
 pub(crate) async fn fetch_status(
     client: &Addr<near_client::ClientActor>,
@@ -75,9 +78,18 @@ async fn fetch_single_chunk(
 
 /// Fetch all ExecutionOutcomeWithId for current block
 /// Returns a HashMap where the key is Receipt id or Transaction hash and the value is ExecutionOutcome wth id and proof
+///
+/// Outcomes of the `transaction_hashes` are always included and the ones of the
+/// `skipped_transaction_hashes` never are. Outcomes of the shards that do not match the `filter`
+/// are left out, and receipts are not fetched for the outcomes executed on the accounts that do
+/// not match it. The rest of the filter has to be applied once the local receipts are attached.
 pub(crate) async fn fetch_outcomes(
     client: &Addr<near_client::ViewClientActor>,
     block_hash: CryptoHash,
+    transaction_hashes: &HashSet<CryptoHash>,
+    skipped_transaction_hashes: &HashSet<CryptoHash>,
+    num_shards: types::NumShards,
+    filter: &IndexerFilter,
 ) -> Result<ExecutionOutcomesWithReceipts, FailedToFetchData> {
     let outcomes = client
         .send(near_client::GetExecutionOutcomesForBlock { block_hash })
@@ -85,8 +97,26 @@ pub(crate) async fn fetch_outcomes(
         .map_err(FailedToFetchData::String)?;
 
     let mut outcomes_with_receipts = ExecutionOutcomesWithReceipts::new();
-    for (_, shard_outcomes) in outcomes {
+    for (shard_id, shard_outcomes) in outcomes {
+        if !filter.matches_shard(shard_id, num_shards) {
+            continue;
+        }
         for outcome in shard_outcomes {
+            if transaction_hashes.contains(&outcome.id) {
+                outcomes_with_receipts.insert(
+                    outcome.id,
+                    IndexerExecutionOutcomeWithReceipt {
+                        execution_outcome: outcome,
+                        receipt: None,
+                    },
+                );
+                continue;
+            }
+            if skipped_transaction_hashes.contains(&outcome.id)
+                || !filter.matches_account(&outcome.outcome.executor_id)
+            {
+                continue;
+            }
             let receipt = match fetch_receipt_by_id(&client, outcome.id).await {
                 Ok(res) => res,
                 Err(e) => {
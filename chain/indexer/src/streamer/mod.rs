@@ -1,3 +1,5 @@
+use std::collections::HashSet;
+use std::sync::Arc;
 use std::time::Duration;
 
 use actix::Addr;
@@ -8,8 +10,11 @@ use tracing::{debug, info};
 
 pub use near_primitives::views;
 
-use crate::IndexerConfig;
+use crate::{IndexerConfig, IndexerFilter};
 
+pub(crate) use self::checkpoint::open_indexer_db;
+pub use self::checkpoint::BlockAcknowledger;
+use self::checkpoint::{get_start_syncing_block_height, set_last_synced_block_height};
 use self::errors::FailedToFetchData;
 use self::fetchers::{
     fetch_block_by_height, fetch_chunks, fetch_latest_block, fetch_outcomes, fetch_state_changes,
@@ -21,6 +26,7 @@ pub use self::types::{
 };
 use self::utils::convert_transactions_sir_into_local_receipts;
 
+mod checkpoint;
 mod errors;
 mod fetchers;
 mod types;
@@ -32,16 +38,23 @@ const INTERVAL: Duration = Duration::from_millis(500);
 /// This function supposed to return the entire `StreamerMessage`.
 /// It fetches the block and all related parts (chunks, outcomes, state changes etc.)
 /// and returns everything together in one struct
+///
+/// Transactions, receipts, execution outcomes and state changes that do not match the filter
+/// of `IndexerConfig` are skipped, and so is fetching the data they need.
 async fn build_streamer_message(
     client: &Addr<near_client::ViewClientActor>,
     block: views::BlockView,
     near_config: &neard::NearConfig,
+    filter: &IndexerFilter,
 ) -> Result<StreamerMessage, FailedToFetchData> {
+    let num_shards = block.chunks.len() as near_primitives::types::NumShards;
     let chunks_to_fetch = block
         .chunks
         .iter()
         .filter_map(|c| {
-            if c.height_included == block.header.height {
+            if c.height_included == block.header.height
+                && filter.matches_shard(c.shard_id, num_shards)
+            {
                 Some(c.chunk_hash)
             } else {
                 None
@@ -50,8 +63,26 @@ async fn build_streamer_message(
         .collect::<Vec<_>>();
     let chunks = fetch_chunks(&client, chunks_to_fetch).await?;
 
+    let mut transaction_hashes = HashSet::new();
+    let mut skipped_transaction_hashes = HashSet::new();
+    for transaction in chunks.iter().flat_map(|chunk| chunk.transactions.iter()) {
+        if filter.matches_transaction(transaction) {
+            transaction_hashes.insert(transaction.hash);
+        } else {
+            skipped_transaction_hashes.insert(transaction.hash);
+        }
+    }
+
     let mut local_receipts: Vec<views::ReceiptView> = vec![];
-    let mut outcomes = fetch_outcomes(&client, block.header.hash).await?;
+    let mut outcomes = fetch_outcomes(
+        &client,
+        block.header.hash,
+        &transaction_hashes,
+        &skipped_transaction_hashes,
+        num_shards,
+        filter,
+    )
+    .await?;
     let mut indexer_chunks: Vec<IndexerChunkView> = vec![];
 
     for chunk in chunks {
@@ -60,6 +91,7 @@ async fn build_streamer_message(
 
         let indexer_transactions = transactions
             .into_iter()
+            .filter(|transaction| transaction_hashes.contains(&transaction.hash))
             .map(|transaction| {
                 IndexerTransactionWithOutcome {
                     outcome: outcomes.remove(&transaction.hash).expect("The transaction execution outcome should always present in the same block as the transaction itself"),
@@ -83,6 +115,7 @@ async fn build_streamer_message(
 
         let mut chunk_receipts = chunk_local_receipts;
         chunk_receipts.extend(chunk_non_local_receipts);
+        chunk_receipts.retain(|receipt| filter.matches_receipt(receipt));
 
         indexer_chunks.push(IndexerChunkView {
             author,
@@ -99,8 +132,11 @@ async fn build_streamer_message(
             outcome.receipt = Some(receipt.clone());
         }
     }
+    // Local receipts are not stored, so their outcomes can only be filtered once they are attached
+    outcomes.retain(|_, outcome| filter.matches_outcome(outcome));
 
-    let state_changes = fetch_state_changes(&client, block.header.hash).await?;
+    let mut state_changes = fetch_state_changes(&client, block.header.hash).await?;
+    state_changes.retain(|state_change| filter.matches_state_change(state_change));
 
     Ok(StreamerMessage {
         block,
@@ -114,19 +150,19 @@ async fn build_streamer_message(
 /// compares to already fetched block height and in case it differs fetches new block of given height.
 ///
 /// We have to pass `client: Addr<near_client::ClientActor>` and `view_client: Addr<near_client::ViewClientActor>`.
+///
+/// If `resume_from_ack` is set, `SyncModeEnum::FromInterruption` resumes right after the last
+/// block acknowledged by the consumer instead of the last block sent to it.
 pub(crate) async fn start(
     view_client: Addr<near_client::ViewClientActor>,
     client: Addr<near_client::ClientActor>,
     near_config: neard::NearConfig,
     indexer_config: IndexerConfig,
+    db: Arc<DB>,
+    resume_from_ack: bool,
     mut blocks_sink: mpsc::Sender<StreamerMessage>,
 ) {
     info!(target: INDEXER, "Starting Streamer...");
-    let mut indexer_db_path = neard::get_store_path(&indexer_config.home_dir);
-    indexer_db_path.push_str("/indexer");
-
-    // TODO: implement proper error handling
-    let db = DB::open_default(indexer_db_path).unwrap();
     let mut last_synced_block_height: Option<near_primitives::types::BlockHeight> = None;
 
     'main: loop {
@@ -145,22 +181,17 @@ pub(crate) async fn start(
         };
 
         let latest_block_height = block.header.height;
-        let start_syncing_block_height = if let Some(last_synced_block_height) =
-            last_synced_block_height
-        {
-            last_synced_block_height + 1
-        } else {
-            match indexer_config.sync_mode {
-                crate::SyncModeEnum::FromInterruption => {
-                    match db.get(b"last_synced_block_height").unwrap() {
-                        Some(value) => String::from_utf8(value).unwrap().parse::<u64>().unwrap(),
-                        None => latest_block_height,
-                    }
-                }
-                crate::SyncModeEnum::LatestSynced => latest_block_height,
-                crate::SyncModeEnum::BlockHeight(height) => height,
-            }
-        };
+        let start_syncing_block_height =
+            if let Some(last_synced_block_height) = last_synced_block_height {
+                last_synced_block_height + 1
+            } else {
+                get_start_syncing_block_height(
+                    &db,
+                    &indexer_config.sync_mode,
+                    resume_from_ack,
+                    latest_block_height,
+                )
+            };
 
         debug!(
             target: INDEXER,
@@ -170,7 +201,13 @@ pub(crate) async fn start(
         );
         for block_height in start_syncing_block_height..=latest_block_height {
             if let Ok(block) = fetch_block_by_height(&view_client, block_height).await {
-                let response = build_streamer_message(&view_client, block, &near_config).await;
+                let response = build_streamer_message(
+                    &view_client,
+                    block,
+                    &near_config,
+                    &indexer_config.filter,
+                )
+                .await;
 
                 match response {
                     Ok(streamer_message) => {
@@ -192,7 +229,7 @@ pub(crate) async fn start(
                     }
                 }
             }
-            db.put(b"last_synced_block_height", &block_height.to_string()).unwrap();
+            set_last_synced_block_height(&db, block_height);
             last_synced_block_height = Some(block_height);
         }
     }
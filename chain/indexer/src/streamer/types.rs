@@ -1,12 +1,14 @@
 use std::collections::HashMap;
 
+use serde::Serialize;
+
 pub use near_primitives::hash::CryptoHash;
 pub use near_primitives::{types, views};
 
 pub type ExecutionOutcomesWithReceipts = HashMap<CryptoHash, IndexerExecutionOutcomeWithReceipt>;
 
 /// Resulting struct represents block with chunks
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct StreamerMessage {
     pub block: views::BlockView,
     pub chunks: Vec<IndexerChunkView>,
@@ -14,7 +16,7 @@ pub struct StreamerMessage {
     pub state_changes: views::StateChangesKindsView,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct IndexerChunkView {
     pub author: types::AccountId,
     pub header: views::ChunkHeaderView,
@@ -22,13 +24,13 @@ pub struct IndexerChunkView {
     pub receipts: Vec<views::ReceiptView>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct IndexerTransactionWithOutcome {
     pub transaction: views::SignedTransactionView,
     pub outcome: IndexerExecutionOutcomeWithReceipt,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct IndexerExecutionOutcomeWithReceipt {
     pub execution_outcome: views::ExecutionOutcomeWithIdView,
     pub receipt: Option<views::ReceiptView>,
@@ -15,6 +15,9 @@ pub(crate) async fn convert_transactions_sir_into_local_receipts(
     txs: Vec<&IndexerTransactionWithOutcome>,
     block: &views::BlockView,
 ) -> Result<Vec<views::ReceiptView>, FailedToFetchData> {
+    if txs.is_empty() {
+        return Ok(vec![]);
+    }
     let prev_block = fetch_block_by_hash(&client, block.header.prev_hash).await?;
     let prev_block_gas_price = prev_block.header.gas_price;
 
@@ -7,12 +7,14 @@ use serde::Serialize;
 
 use near_primitives::hash::CryptoHash;
 use near_primitives::rpc::{
-    RpcQueryRequest, RpcStateChangesRequest, RpcStateChangesResponse, RpcValidatorsOrderedRequest,
+    RpcAccountHistoryRequest, RpcQueryRequest, RpcStateChangesRequest, RpcStateChangesResponse,
+    RpcTxStatusOptions, RpcValidatorsOrderedRequest,
 };
 use near_primitives::types::{BlockId, BlockReference, MaybeBlockId, ShardId};
 use near_primitives::views::{
-    BlockView, ChunkView, EpochValidatorInfo, FinalExecutionOutcomeView, GasPriceView,
-    QueryResponse, StatusResponse, ValidatorStakeView,
+    AccountHistoryView, BlockView, ChunkView, EpochValidatorInfo, FinalExecutionOutcomeView,
+    FinalExecutionOutcomeWithReceiptView, GasPriceView, QueryResponse, StatusResponse,
+    ValidatorStakeView,
 };
 
 use crate::message::{from_slice, Message, RpcError};
@@ -227,6 +229,29 @@ impl JsonRpcClient {
     ) -> RpcRequest<Vec<ValidatorStakeView>> {
         call_method(&self.client, &self.server_addr, "EXPERIMENTAL_validators_ordered", request)
     }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_account_history(
+        &self,
+        request: RpcAccountHistoryRequest,
+    ) -> RpcRequest<AccountHistoryView> {
+        call_method(&self.client, &self.server_addr, "EXPERIMENTAL_account_history", request)
+    }
+
+    #[allow(non_snake_case)]
+    pub fn EXPERIMENTAL_tx_status(
+        &self,
+        hash: String,
+        account_id: String,
+        options: RpcTxStatusOptions,
+    ) -> RpcRequest<FinalExecutionOutcomeWithReceiptView> {
+        call_method(
+            &self.client,
+            &self.server_addr,
+            "EXPERIMENTAL_tx_status",
+            (hash, account_id, options),
+        )
+    }
 }
 
 fn create_client() -> Client {
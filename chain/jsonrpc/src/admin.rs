@@ -0,0 +1,125 @@
+//! Admin JSON-RPC to manage the peers of the node at runtime.
+//!
+//! It is served on its own address, which should not be publicly reachable, and every request
+//! has to carry the configured token in an `Authorization: Bearer <token>` header.
+use std::str::FromStr;
+
+use actix::Addr;
+use actix_web::{
+    http, middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer,
+};
+use futures::{Future, FutureExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use near_client::{ClientActor, NetworkAdmin};
+use near_jsonrpc_client::message::{Message, Request, RpcError};
+use near_network::types::NetworkAdminRequest;
+use near_network::PeerInfo;
+use near_primitives::network::PeerId;
+
+use crate::{jsonify, parse_params};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AdminRpcConfig {
+    pub addr: String,
+    /// Token which must be sent as a bearer token with every request.
+    pub auth_token: String,
+}
+
+#[derive(Deserialize)]
+struct PeerIdRequest {
+    peer_id: PeerId,
+}
+
+#[derive(Deserialize)]
+struct ConnectPeerRequest {
+    /// Peer in the same format as `boot_nodes`: `<peer_id>@<ip>:<port>`.
+    peer_info: String,
+}
+
+struct AdminRpcHandler {
+    client_addr: Addr<ClientActor>,
+    auth_token: String,
+}
+
+/// Compares in constant time, so the token can't be guessed from the response times.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl AdminRpcHandler {
+    fn is_authorized(&self, request: &HttpRequest) -> bool {
+        let expected = format!("Bearer {}", self.auth_token);
+        request
+            .headers()
+            .get(http::header::AUTHORIZATION)
+            .map_or(false, |value| constant_time_eq(value.as_bytes(), expected.as_bytes()))
+    }
+
+    async fn process_request(&self, request: Request) -> Result<Value, RpcError> {
+        let admin_request = match request.method.as_ref() {
+            "admin_ban_peer" => {
+                let PeerIdRequest { peer_id } = parse_params(request.params)?;
+                NetworkAdminRequest::BanPeer(peer_id)
+            }
+            "admin_unban_peer" => {
+                let PeerIdRequest { peer_id } = parse_params(request.params)?;
+                NetworkAdminRequest::UnbanPeer(peer_id)
+            }
+            "admin_connect_peer" => {
+                let ConnectPeerRequest { peer_info } = parse_params(request.params)?;
+                let peer_info = PeerInfo::from_str(&peer_info).map_err(|err| {
+                    RpcError::invalid_params(format!("Failed parsing peer info: {}", err))
+                })?;
+                NetworkAdminRequest::ConnectPeer(peer_info)
+            }
+            "admin_disconnect_peer" => {
+                let PeerIdRequest { peer_id } = parse_params(request.params)?;
+                NetworkAdminRequest::DisconnectPeer(peer_id)
+            }
+            _ => return Err(RpcError::method_not_found(request.method)),
+        };
+        jsonify(self.client_addr.send(NetworkAdmin(admin_request)).await)
+    }
+}
+
+fn admin_rpc_handler(
+    request: HttpRequest,
+    message: web::Json<Message>,
+    handler: web::Data<AdminRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let response = async move {
+        if !handler.is_authorized(&request) {
+            return Ok(HttpResponse::Unauthorized().finish());
+        }
+        let id = message.id();
+        let message = match message.0 {
+            Message::Request(request) => {
+                Message::response(id, handler.process_request(request).await)
+            }
+            _ => Message::error(RpcError::invalid_request()),
+        };
+        Ok(HttpResponse::Ok().json(message))
+    };
+    response.boxed()
+}
+
+pub(crate) fn start_admin_http(config: AdminRpcConfig, client_addr: Addr<ClientActor>) {
+    let AdminRpcConfig { addr, auth_token } = config;
+    assert!(!auth_token.is_empty(), "Admin RPC requires a non-empty auth_token");
+    HttpServer::new(move || {
+        App::new()
+            .data(AdminRpcHandler {
+                client_addr: client_addr.clone(),
+                auth_token: auth_token.clone(),
+            })
+            .wrap(middleware::Logger::default())
+            .service(web::resource("/").route(web::post().to(admin_rpc_handler)))
+    })
+    .bind(addr)
+    .unwrap()
+    .workers(1)
+    .shutdown_timeout(5)
+    .run();
+}
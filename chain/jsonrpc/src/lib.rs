@@ -4,7 +4,10 @@ use std::time::Duration;
 
 use actix::{Addr, MailboxError};
 use actix_cors::{Cors, CorsFactory};
-use actix_web::{http, middleware, web, App, Error as HttpError, HttpResponse, HttpServer};
+use actix_web::{
+    http, middleware, web, App, Error as HttpError, HttpRequest, HttpResponse, HttpServer,
+};
+use actix_web_actors::ws;
 use borsh::BorshDeserialize;
 use futures::Future;
 use futures::{FutureExt, TryFutureExt};
@@ -14,11 +17,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use tokio::time::{delay_for, timeout};
 
+pub use admin::AdminRpcConfig;
 use near_chain_configs::GenesisConfig;
 use near_client::{
-    ClientActor, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome, GetGasPrice,
-    GetNetworkInfo, GetNextLightClientBlock, GetStateChanges, GetStateChangesInBlock,
-    GetValidatorInfo, GetValidatorOrdered, Query, Status, TxStatus, TxStatusError, ViewClientActor,
+    ClientActor, GetAccountHistory, GetBlock, GetBlockProof, GetChunk, GetExecutionOutcome,
+    GetGasPrice, GetNetworkDebugInfo, GetNetworkInfo, GetNextLightClientBlock, GetStateChanges,
+    GetStateChangesInBlock, GetValidatorInfo, GetValidatorOrdered, Query, Status, TxStatus,
+    TxStatusError, ViewClientActor,
 };
 pub use near_jsonrpc_client as client;
 use near_jsonrpc_client::message::{Message, Request, RpcError};
@@ -26,23 +31,31 @@ use near_jsonrpc_client::ChunkId;
 use near_metrics::{Encoder, TextEncoder};
 #[cfg(feature = "adversarial")]
 use near_network::types::{NetworkAdversarialMessage, NetworkViewClientMessages};
+#[cfg(feature = "sandbox")]
+use near_network::types::{NetworkSandboxMessage, SandboxResponse};
 use near_network::{NetworkClientMessages, NetworkClientResponses};
 use near_primitives::errors::{InvalidTxError, TxExecutionError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::rpc::{
-    RpcBroadcastTxSyncResponse, RpcLightClientExecutionProofRequest,
+    RpcAccountHistoryRequest, RpcBroadcastTxSyncResponse, RpcLightClientExecutionProofRequest,
     RpcLightClientExecutionProofResponse, RpcQueryRequest, RpcStateChangesInBlockRequest,
     RpcStateChangesInBlockResponse, RpcStateChangesRequest, RpcStateChangesResponse,
-    RpcValidatorsOrderedRequest, TransactionInfo,
+    RpcTxStatusOptions, RpcValidatorsOrderedRequest, TransactionInfo,
 };
+#[cfg(feature = "sandbox")]
+use near_primitives::rpc::{RpcSandboxFastForwardRequest, RpcSandboxPatchStateRequest};
 use near_primitives::serialize::{from_base, from_base64, BaseEncode};
+#[cfg(feature = "sandbox")]
+use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::SignedTransaction;
 use near_primitives::types::{AccountId, BlockId, BlockReference, MaybeBlockId};
 use near_primitives::utils::is_valid_account_id;
 use near_primitives::views::{
     FinalExecutionOutcomeView, FinalExecutionOutcomeViewEnum, QueryRequest,
 };
+mod admin;
 mod metrics;
+mod websocket;
 
 /// Max size of the query path (soft-deprecated)
 const QUERY_DATA_MAX_SIZE: usize = 10 * 1024;
@@ -81,6 +94,9 @@ pub struct RpcConfig {
     pub polling_config: RpcPollingConfig,
     #[serde(default)]
     pub limits_config: RpcLimitsConfig,
+    /// Admin RPC to manage the peers of the node, disabled if not set.
+    #[serde(default)]
+    pub admin: Option<AdminRpcConfig>,
 }
 
 impl Default for RpcConfig {
@@ -90,6 +106,7 @@ impl Default for RpcConfig {
             cors_allowed_origins: vec!["*".to_owned()],
             polling_config: Default::default(),
             limits_config: Default::default(),
+            admin: None,
         }
     }
 }
@@ -226,6 +243,21 @@ impl JsonRpcHandler {
             }
         }
 
+        #[cfg(feature = "sandbox")]
+        {
+            let params = request.params.clone();
+
+            let res = match request.method.as_ref() {
+                "sandbox_patch_state" => Some(self.sandbox_patch_state(params).await),
+                "sandbox_fast_forward" => Some(self.sandbox_fast_forward(params).await),
+                _ => None,
+            };
+
+            if let Some(res) = res {
+                return res;
+            }
+        }
+
         match request.method.as_ref() {
             "broadcast_tx_async" => self.send_tx_async(request.params).await,
             "EXPERIMENTAL_broadcast_tx_sync" => self.send_tx_sync(request.params).await,
@@ -249,7 +281,9 @@ impl JsonRpcHandler {
             }
             "light_client_proof" => self.light_client_execution_outcome_proof(request.params).await,
             "network_info" => self.network_info().await,
+            "EXPERIMENTAL_network_debug" => self.network_debug().await,
             "gas_price" => self.gas_price(request.params).await,
+            "EXPERIMENTAL_account_history" => self.account_history(request.params).await,
             _ => Err(RpcError::method_not_found(request.method)),
         }
     }
@@ -280,6 +314,7 @@ impl JsonRpcHandler {
                         tx_hash,
                         signer_account_id: signer_account_id.clone(),
                         fetch_receipt: false,
+                        fetch_gas_profile: false,
                     })
                     .await
                 {
@@ -306,6 +341,7 @@ impl JsonRpcHandler {
         &self,
         tx_info: TransactionInfo,
         fetch_receipt: bool,
+        fetch_gas_profile: bool,
     ) -> Result<FinalExecutionOutcomeViewEnum, TxStatusError> {
         let (tx_hash, account_id) = match &tx_info {
             TransactionInfo::Transaction(tx) => (tx.get_hash(), tx.transaction.signer_id.clone()),
@@ -319,6 +355,7 @@ impl JsonRpcHandler {
                         tx_hash,
                         signer_account_id: account_id.clone(),
                         fetch_receipt,
+                        fetch_gas_profile,
                     })
                     .await;
                 match tx_status_result {
@@ -350,7 +387,7 @@ impl JsonRpcHandler {
     async fn tx_polling(&self, tx_info: TransactionInfo) -> Result<Value, RpcError> {
         timeout(self.polling_config.polling_timeout, async {
             loop {
-                match self.tx_status_fetch(tx_info.clone(), false).await {
+                match self.tx_status_fetch(tx_info.clone(), false, false).await {
                     Ok(tx_status) => break jsonify(Ok(Ok(tx_status))),
                     // If transaction is missing, keep polling.
                     Err(TxStatusError::MissingTransaction(_)) => {}
@@ -457,7 +494,7 @@ impl JsonRpcHandler {
 
     async fn send_tx_commit(&self, params: Option<Value>) -> Result<Value, RpcError> {
         let tx = parse_tx(params)?;
-        match self.tx_status_fetch(TransactionInfo::Transaction(tx.clone()), false).await {
+        match self.tx_status_fetch(TransactionInfo::Transaction(tx.clone()), false, false).await {
             Ok(outcome) => {
                 return jsonify(Ok(Ok(outcome)));
             }
@@ -590,22 +627,28 @@ impl JsonRpcHandler {
         params: Option<Value>,
         fetch_receipt: bool,
     ) -> Result<Value, RpcError> {
-        let tx_status_request =
-            if let Ok((hash, account_id)) = parse_params::<(CryptoHash, String)>(params.clone()) {
-                if !is_valid_account_id(&account_id) {
-                    return Err(RpcError::invalid_params(format!(
-                        "Invalid account id: {}",
-                        account_id
-                    )));
-                }
-                TransactionInfo::TransactionId { hash, account_id }
-            } else {
-                let tx = parse_tx(params)?;
-                TransactionInfo::Transaction(tx)
-            };
+        let tx_id = parse_params::<(CryptoHash, String, RpcTxStatusOptions)>(params.clone())
+            .or_else(|_| {
+                parse_params::<(CryptoHash, String)>(params.clone())
+                    .map(|(hash, account_id)| (hash, account_id, RpcTxStatusOptions::default()))
+            });
+        let (tx_status_request, options) = if let Ok((hash, account_id, options)) = tx_id {
+            if !is_valid_account_id(&account_id) {
+                return Err(RpcError::invalid_params(format!(
+                    "Invalid account id: {}",
+                    account_id
+                )));
+            }
+            (TransactionInfo::TransactionId { hash, account_id }, options)
+        } else {
+            let tx = parse_tx(params)?;
+            (TransactionInfo::Transaction(tx), RpcTxStatusOptions::default())
+        };
 
+        // Gas profiles are only part of the `EXPERIMENTAL_tx_status` response.
+        let fetch_gas_profile = fetch_receipt && options.gas_profile;
         jsonify(Ok(self
-            .tx_status_fetch(tx_status_request, fetch_receipt)
+            .tx_status_fetch(tx_status_request, fetch_receipt, fetch_gas_profile)
             .await
             .map_err(|err| err.into())))
     }
@@ -713,11 +756,22 @@ impl JsonRpcHandler {
         jsonify(self.client_addr.send(GetNetworkInfo {}).await)
     }
 
+    async fn network_debug(&self) -> Result<Value, RpcError> {
+        jsonify(self.client_addr.send(GetNetworkDebugInfo {}).await)
+    }
+
     async fn gas_price(&self, params: Option<Value>) -> Result<Value, RpcError> {
         let (block_id,) = parse_params::<(MaybeBlockId,)>(params)?;
         jsonify(self.view_client_addr.send(GetGasPrice { block_id }).await)
     }
 
+    async fn account_history(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcAccountHistoryRequest { account_id, from_height, limit } = parse_params(params)?;
+        jsonify(
+            self.view_client_addr.send(GetAccountHistory { account_id, from_height, limit }).await,
+        )
+    }
+
     pub async fn metrics(&self) -> Result<String, FromUtf8Error> {
         // Gather metrics and return them as a String
         let mut buffer = vec![];
@@ -854,6 +908,79 @@ impl JsonRpcHandler {
     }
 }
 
+#[cfg(feature = "sandbox")]
+impl JsonRpcHandler {
+    /// Queues the records to be written into the state with the next block and waits until they
+    /// are there.
+    async fn sandbox_patch_state(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcSandboxPatchStateRequest { records } =
+            parse_params::<RpcSandboxPatchStateRequest>(params)?;
+        if records.iter().any(|record| {
+            matches!(
+                record,
+                StateRecord::PostponedReceipt(_)
+                    | StateRecord::DelayedReceipt(_)
+                    | StateRecord::ReceivedData { .. }
+            )
+        }) {
+            return Err(RpcError::invalid_params(
+                "Receipts and received data can't be patched into the state",
+            ));
+        }
+        self.client_addr
+            .send(NetworkClientMessages::Sandbox(NetworkSandboxMessage::SandboxPatchState(records)))
+            .await
+            .map_err(|err| RpcError::server_error(Some(err.to_string())))?;
+
+        timeout(self.polling_config.polling_timeout, async {
+            loop {
+                match self
+                    .client_addr
+                    .send(NetworkClientMessages::Sandbox(
+                        NetworkSandboxMessage::SandboxPatchStateStatus,
+                    ))
+                    .await
+                {
+                    Ok(NetworkClientResponses::SandboxResult(
+                        SandboxResponse::SandboxPatchStateFinished(true),
+                    )) => break Ok(Value::String("".to_string())),
+                    Ok(NetworkClientResponses::SandboxResult(
+                        SandboxResponse::SandboxPatchStateFinished(false),
+                    )) => {}
+                    _ => break Err(RpcError::server_error::<String>(None)),
+                }
+                let _ = delay_for(self.polling_config.polling_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| {
+            near_metrics::inc_counter(&metrics::RPC_TIMEOUT_TOTAL);
+            timeout_err()
+        })?
+    }
+
+    /// Produces `delta_height` blocks right away and returns the height of the new head.
+    async fn sandbox_fast_forward(&self, params: Option<Value>) -> Result<Value, RpcError> {
+        let RpcSandboxFastForwardRequest { delta_height } =
+            parse_params::<RpcSandboxFastForwardRequest>(params)?;
+        match self
+            .client_addr
+            .send(NetworkClientMessages::Sandbox(NetworkSandboxMessage::SandboxFastForward(
+                delta_height,
+            )))
+            .await
+        {
+            Ok(NetworkClientResponses::SandboxResult(
+                SandboxResponse::SandboxFastForwardFinished(height),
+            )) => jsonify(Ok(Ok(height))),
+            Ok(NetworkClientResponses::SandboxResult(
+                SandboxResponse::SandboxFastForwardFailed(err),
+            )) => Err(RpcError::server_error(Some(err))),
+            _ => Err(RpcError::server_error::<String>(None)),
+        }
+    }
+}
+
 fn rpc_handler(
     message: web::Json<Message>,
     handler: web::Data<JsonRpcHandler>,
@@ -917,6 +1044,19 @@ fn prometheus_handler(
     response.boxed()
 }
 
+fn ws_handler(
+    request: HttpRequest,
+    stream: web::Payload,
+    handler: web::Data<JsonRpcHandler>,
+) -> impl Future<Output = Result<HttpResponse, HttpError>> {
+    let session = websocket::WebSocketSession::new(
+        handler.view_client_addr.clone(),
+        handler.polling_config.polling_interval,
+    );
+    let response = async move { ws::start(session, &request, stream) };
+    response.boxed()
+}
+
 fn get_cors(cors_allowed_origins: &[String]) -> CorsFactory {
     let mut cors = Cors::new();
     if cors_allowed_origins != ["*".to_string()] {
@@ -937,7 +1077,10 @@ pub fn start_http(
     client_addr: Addr<ClientActor>,
     view_client_addr: Addr<ViewClientActor>,
 ) {
-    let RpcConfig { addr, cors_allowed_origins, polling_config, limits_config } = config;
+    let RpcConfig { addr, cors_allowed_origins, polling_config, limits_config, admin } = config;
+    if let Some(admin_config) = admin {
+        admin::start_admin_http(admin_config, client_addr.clone());
+    }
     HttpServer::new(move || {
         App::new()
             .wrap(get_cors(&cors_allowed_origins))
@@ -962,6 +1105,7 @@ pub fn start_http(
             )
             .service(web::resource("/network_info").route(web::get().to(network_info_handler)))
             .service(web::resource("/metrics").route(web::get().to(prometheus_handler)))
+            .service(web::resource("/ws").route(web::get().to(ws_handler)))
     })
     .bind(addr)
     .unwrap()
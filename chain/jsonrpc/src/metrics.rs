@@ -1,5 +1,5 @@
 use lazy_static::lazy_static;
-use near_metrics::{HistogramVec, IntCounter, IntCounterVec};
+use near_metrics::{HistogramVec, IntCounter, IntCounterVec, IntGauge};
 
 lazy_static! {
     pub static ref RPC_PROCESSING_TIME: near_metrics::Result<HistogramVec> =
@@ -29,4 +29,15 @@ lazy_static! {
             "http_status_requests_total",
             "Total count of HTTP Status requests received"
         );
+    pub static ref WS_RPC_REQUEST_COUNT: near_metrics::Result<IntCounterVec> =
+        near_metrics::try_create_int_counter_vec(
+            "ws_rpc_requests_total",
+            "Total count of WebSocket RPC requests received, by method",
+            &["method"]
+        );
+    pub static ref WS_ACTIVE_SESSIONS: near_metrics::Result<IntGauge> =
+        near_metrics::try_create_int_gauge(
+            "ws_rpc_active_sessions",
+            "Number of currently open WebSocket RPC sessions"
+        );
 }
@@ -0,0 +1,350 @@
+//! WebSocket subscriptions to new final blocks, chunks and state changes.
+//!
+//! A client opens a WebSocket connection to the `/ws` endpoint and sends JSON-RPC `subscribe`
+//! and `unsubscribe` requests. Every session polls the view client for the latest final block
+//! and, for every final block it has not seen yet, sends a `subscription` notification per
+//! matching subscription. Final blocks are never reverted, so clients do not have to handle
+//! forks, and the session walks forward height by height so no final block is skipped between
+//! two polls. If the data for a subscription can't be fetched, a `subscription_error`
+//! notification is sent for that block instead, and the session moves on.
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use actix::{Actor, ActorContext, ActorFuture, Addr, AsyncContext, StreamHandler, WrapFuture};
+use actix_web_actors::ws;
+use log::{debug, warn};
+use serde_json::Value;
+
+use near_client::{GetBlock, GetChunk, GetStateChanges, ViewClientActor};
+use near_jsonrpc_client::message::{self, Message, Request, RpcError};
+use near_primitives::rpc::{
+    RpcStateChangesResponse, RpcSubscriptionError, RpcSubscriptionNotification,
+    RpcSubscriptionRequest, RpcUnsubscribeRequest,
+};
+use near_primitives::types::{BlockHeight, BlockId, BlockReference, Finality};
+use near_primitives::views::BlockView;
+
+use crate::metrics;
+
+/// Maximum number of active subscriptions per WebSocket connection.
+const MAX_SUBSCRIPTIONS_PER_CONNECTION: usize = 32;
+/// Maximum number of heights looked at per poll. If the session falls further behind, it
+/// catches up over the following polls.
+const MAX_HEIGHTS_PER_POLL: BlockHeight = 100;
+
+pub type SubscriptionId = u64;
+
+pub struct WebSocketSession {
+    view_client_addr: Addr<ViewClientActor>,
+    polling_interval: Duration,
+    subscriptions: BTreeMap<SubscriptionId, RpcSubscriptionRequest>,
+    next_subscription_id: SubscriptionId,
+    /// Height up to which notifications were produced for the final blocks.
+    last_final_height: Option<BlockHeight>,
+    /// Whether a poll of the view client is currently running.
+    polling: bool,
+}
+
+impl WebSocketSession {
+    pub fn new(view_client_addr: Addr<ViewClientActor>, polling_interval: Duration) -> Self {
+        Self {
+            view_client_addr,
+            polling_interval,
+            subscriptions: BTreeMap::new(),
+            next_subscription_id: 0,
+            last_final_height: None,
+            polling: false,
+        }
+    }
+
+    fn process_text(&mut self, text: &str) -> Message {
+        match message::from_str(text) {
+            Ok(Message::Request(request)) => {
+                let id = request.id.clone();
+                Message::response(id, self.process_request(request))
+            }
+            Ok(_) => Message::error(RpcError::invalid_request()),
+            Err(broken) => broken.reply(),
+        }
+    }
+
+    fn process_request(&mut self, request: Request) -> Result<Value, RpcError> {
+        near_metrics::inc_counter_vec(&metrics::WS_RPC_REQUEST_COUNT, &[request.method.as_ref()]);
+        match request.method.as_ref() {
+            "subscribe" => {
+                let subscription = crate::parse_params::<RpcSubscriptionRequest>(request.params)?;
+                if self.subscriptions.len() >= MAX_SUBSCRIPTIONS_PER_CONNECTION {
+                    return Err(RpcError::server_error(Some(format!(
+                        "Too many subscriptions, at most {} are allowed per connection",
+                        MAX_SUBSCRIPTIONS_PER_CONNECTION
+                    ))));
+                }
+                let subscription_id = self.next_subscription_id;
+                self.next_subscription_id += 1;
+                self.subscriptions.insert(subscription_id, subscription);
+                Ok(Value::from(subscription_id))
+            }
+            "unsubscribe" => {
+                let RpcUnsubscribeRequest { subscription_id } =
+                    crate::parse_params(request.params)?;
+                Ok(Value::Bool(self.subscriptions.remove(&subscription_id).is_some()))
+            }
+            _ => Err(RpcError::method_not_found(request.method)),
+        }
+    }
+
+    fn poll(&mut self, ctx: &mut ws::WebsocketContext<Self>) {
+        if self.polling || self.subscriptions.is_empty() {
+            return;
+        }
+        self.polling = true;
+        let view_client_addr = self.view_client_addr.clone();
+        let last_final_height = self.last_final_height;
+        let subscriptions: Vec<_> =
+            self.subscriptions.iter().map(|(id, request)| (*id, request.clone())).collect();
+        ctx.spawn(
+            async move {
+                let (blocks, last_height) =
+                    fetch_new_final_blocks(&view_client_addr, last_final_height).await?;
+                let mut notifications = vec![];
+                for block in blocks {
+                    notifications.extend(
+                        collect_notifications(&view_client_addr, &subscriptions, block).await,
+                    );
+                }
+                Ok::<_, String>((last_height, notifications))
+            }
+            .into_actor(self)
+            .map(|result, act, ctx| {
+                act.polling = false;
+                match result {
+                    Ok((last_height, notifications)) => {
+                        act.last_final_height = last_height;
+                        for (subscription_id, message) in notifications {
+                            // The client may have unsubscribed while the poll was running.
+                            if act.subscriptions.contains_key(&subscription_id) {
+                                ctx.text(Into::<String>::into(message));
+                            }
+                        }
+                    }
+                    Err(err) => {
+                        warn!(target: "jsonrpc", "WebSocket subscription poll failed: {}", err)
+                    }
+                }
+            }),
+        );
+    }
+}
+
+/// Returns final blocks after `last_final_height`, oldest first, looking at no more than
+/// `MAX_HEIGHTS_PER_POLL` heights, and the height the next poll has to continue after.
+/// If nothing was seen yet, only the current final block is returned.
+///
+/// Heights without a block, as well as blocks that can't be read anymore (e.g. garbage
+/// collected), are skipped. If the view client is unavailable, the blocks fetched so far
+/// are returned and the next poll retries from there.
+async fn fetch_new_final_blocks(
+    view_client_addr: &Addr<ViewClientActor>,
+    last_final_height: Option<BlockHeight>,
+) -> Result<(Vec<BlockView>, Option<BlockHeight>), String> {
+    let final_block = view_client_addr
+        .send(GetBlock(BlockReference::Finality(Finality::Final)))
+        .await
+        .map_err(|err| err.to_string())??;
+    let last_height = match last_final_height {
+        Some(last_height) => last_height,
+        None => {
+            let final_height = final_block.header.height;
+            return Ok((vec![final_block], Some(final_height)));
+        }
+    };
+    let final_height = final_block.header.height;
+    let to_height = std::cmp::min(final_height, last_height + MAX_HEIGHTS_PER_POLL);
+    let mut blocks = vec![];
+    let mut checked_height = last_height;
+    for height in last_height + 1..=to_height {
+        if height == final_height {
+            blocks.push(final_block);
+            checked_height = height;
+            break;
+        }
+        match view_client_addr
+            .send(GetBlock(BlockReference::BlockId(BlockId::Height(height))))
+            .await
+        {
+            Ok(Ok(block)) => blocks.push(block),
+            Ok(Err(err)) => {
+                debug!(target: "jsonrpc", "No final block at height {}: {}", height, err)
+            }
+            Err(err) => {
+                warn!(target: "jsonrpc", "Failed to fetch the block at height {}: {}", height, err);
+                break;
+            }
+        }
+        checked_height = height;
+    }
+    Ok((blocks, Some(checked_height)))
+}
+
+/// Returns the messages to send for the given block, a notification or an error per
+/// subscription.
+async fn collect_notifications(
+    view_client_addr: &Addr<ViewClientActor>,
+    subscriptions: &[(SubscriptionId, RpcSubscriptionRequest)],
+    block: BlockView,
+) -> Vec<(SubscriptionId, Message)> {
+    let block_hash = block.header.hash;
+    let block_height = block.header.height;
+    let new_chunk_shards: Vec<_> = block
+        .chunks
+        .iter()
+        .filter(|chunk| chunk.height_included == block_height)
+        .map(|chunk| chunk.shard_id)
+        .collect();
+    let block_value = serde_json::to_value(block).map_err(|err| err.to_string());
+    let mut messages = vec![];
+    for (subscription_id, request) in subscriptions {
+        let result = match request {
+            RpcSubscriptionRequest::FinalBlocks => block_value.clone().map(Some),
+            RpcSubscriptionRequest::Chunks { shard_id } => {
+                if !new_chunk_shards.contains(shard_id) {
+                    continue;
+                }
+                view_client_addr
+                    .send(GetChunk::BlockHash(block_hash, *shard_id))
+                    .await
+                    .map_err(|err| err.to_string())
+                    .and_then(|chunk| chunk)
+                    .and_then(|chunk| serde_json::to_value(chunk).map_err(|err| err.to_string()))
+                    .map(Some)
+            }
+            RpcSubscriptionRequest::StateChanges { state_changes_request } => view_client_addr
+                .send(GetStateChanges {
+                    block_hash,
+                    state_changes_request: state_changes_request.clone(),
+                })
+                .await
+                .map_err(|err| err.to_string())
+                .and_then(|changes| changes.map_err(|err| err.to_string()))
+                .and_then(|changes| {
+                    if changes.is_empty() {
+                        return Ok(None);
+                    }
+                    serde_json::to_value(RpcStateChangesResponse { block_hash, changes })
+                        .map(Some)
+                        .map_err(|err| err.to_string())
+                }),
+        };
+        let subscription_id = *subscription_id;
+        let message = match result {
+            Ok(Some(result)) => Message::notification(
+                "subscription".to_string(),
+                serde_json::to_value(RpcSubscriptionNotification { subscription_id, result }).ok(),
+            ),
+            Ok(None) => continue,
+            Err(error) => {
+                warn!(
+                    target: "jsonrpc",
+                    "WebSocket subscription {} failed at block {}: {}",
+                    subscription_id, block_hash, error
+                );
+                Message::notification(
+                    "subscription_error".to_string(),
+                    serde_json::to_value(RpcSubscriptionError {
+                        subscription_id,
+                        block_hash,
+                        error,
+                    })
+                    .ok(),
+                )
+            }
+        };
+        messages.push((subscription_id, message));
+    }
+    messages
+}
+
+impl Actor for WebSocketSession {
+    type Context = ws::WebsocketContext<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        near_metrics::inc_gauge(&metrics::WS_ACTIVE_SESSIONS);
+        ctx.run_interval(self.polling_interval, |act, ctx| act.poll(ctx));
+    }
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        near_metrics::dec_gauge(&metrics::WS_ACTIVE_SESSIONS);
+    }
+}
+
+impl StreamHandler<Result<ws::Message, ws::ProtocolError>> for WebSocketSession {
+    fn handle(&mut self, msg: Result<ws::Message, ws::ProtocolError>, ctx: &mut Self::Context) {
+        match msg {
+            Ok(ws::Message::Text(text)) => {
+                let response = self.process_text(&text);
+                ctx.text(Into::<String>::into(response));
+            }
+            Ok(ws::Message::Ping(bytes)) => ctx.pong(&bytes),
+            Ok(ws::Message::Close(reason)) => {
+                ctx.close(reason);
+                ctx.stop();
+            }
+            Ok(_) => {}
+            Err(err) => {
+                debug!(target: "jsonrpc", "WebSocket protocol error: {}", err);
+                ctx.stop();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix::System;
+
+    use near_chain::ChainGenesis;
+    use near_client::test_utils::TestEnv;
+
+    use super::*;
+
+    /// A session that fell more than `MAX_HEIGHTS_PER_POLL` behind catches up over several polls
+    /// without skipping any final block.
+    #[test]
+    fn test_catch_up_final_blocks() {
+        let mut env = TestEnv::new(ChainGenesis::test(), 1, 1);
+        for height in 1..=150 {
+            env.produce_block(0, height);
+        }
+        System::run(move || {
+            let view_client_addr = env.start_view_client(0);
+            actix::spawn(async move {
+                let (blocks, last_height) =
+                    fetch_new_final_blocks(&view_client_addr, Some(0)).await.unwrap();
+                let heights: Vec<_> = blocks.iter().map(|block| block.header.height).collect();
+                assert_eq!(heights, (1..=MAX_HEIGHTS_PER_POLL).collect::<Vec<_>>());
+                assert_eq!(last_height, Some(MAX_HEIGHTS_PER_POLL));
+
+                let final_height = view_client_addr
+                    .send(GetBlock(BlockReference::Finality(Finality::Final)))
+                    .await
+                    .unwrap()
+                    .unwrap()
+                    .header
+                    .height;
+                assert!(final_height > MAX_HEIGHTS_PER_POLL);
+                let (blocks, last_height) =
+                    fetch_new_final_blocks(&view_client_addr, last_height).await.unwrap();
+                let heights: Vec<_> = blocks.iter().map(|block| block.header.height).collect();
+                assert_eq!(heights, (MAX_HEIGHTS_PER_POLL + 1..=final_height).collect::<Vec<_>>());
+                assert_eq!(last_height, Some(final_height));
+
+                let (blocks, last_height) =
+                    fetch_new_final_blocks(&view_client_addr, last_height).await.unwrap();
+                assert!(blocks.is_empty());
+                assert_eq!(last_height, Some(final_height));
+                System::current().stop();
+            });
+        })
+        .unwrap();
+    }
+}
@@ -0,0 +1,91 @@
+use actix::System;
+use futures::{future, FutureExt, TryFutureExt};
+use serde_json::json;
+
+use near_client::test_utils::setup_no_network_with_validity_period;
+use near_jsonrpc::{start_http, AdminRpcConfig, RpcConfig};
+use near_jsonrpc_client::message::Message;
+use near_logger_utils::init_test_logger;
+use near_network::test_utils::open_port;
+
+pub mod test_utils;
+
+const AUTH_TOKEN: &str = "secret";
+
+/// Starts a node with the admin RPC enabled and returns its admin address.
+fn start_admin() -> String {
+    let (client_addr, view_client_addr) =
+        setup_no_network_with_validity_period(vec!["test1"], "test1", true, 100, false);
+    let admin_addr = format!("127.0.0.1:{}", open_port());
+    let mut config = RpcConfig::new(&format!("127.0.0.1:{}", open_port()));
+    config.admin =
+        Some(AdminRpcConfig { addr: admin_addr.clone(), auth_token: AUTH_TOKEN.to_string() });
+    start_http(config, test_utils::TEST_GENESIS_CONFIG.clone(), client_addr, view_client_addr);
+    admin_addr
+}
+
+fn ban_peer_request() -> Message {
+    Message::request(
+        "admin_ban_peer".to_string(),
+        Some(json!({"peer_id": "ed25519:7PGseFbWxvYVgZ89K1uTJKYoKetWs7BJtbyXDzfbAcqX"})),
+    )
+}
+
+/// Requests without the auth token are rejected.
+#[test]
+fn test_admin_unauthorized() {
+    init_test_logger();
+
+    System::run(|| {
+        let admin_addr = start_admin();
+        let client = awc::Client::new();
+        let requests = vec![None, Some("Bearer wrong"), Some(AUTH_TOKEN)].into_iter().map(
+            move |authorization| {
+                let mut request = client.post(format!("http://{}", admin_addr));
+                if let Some(authorization) = authorization {
+                    request = request.header("Authorization", authorization);
+                }
+                request.send_json(&ban_peer_request()).map_ok(|response| {
+                    assert_eq!(response.status(), awc::http::StatusCode::UNAUTHORIZED);
+                })
+            },
+        );
+        actix::spawn(future::try_join_all(requests).then(|res| {
+            res.unwrap();
+            System::current().stop();
+            future::ready(())
+        }));
+    })
+    .unwrap();
+}
+
+/// Authorized requests are dispatched, unknown methods are reported.
+#[test]
+fn test_admin_unknown_method() {
+    init_test_logger();
+
+    System::run(|| {
+        let admin_addr = start_admin();
+        let request = Message::request("admin_restart".to_string(), None);
+        actix::spawn(
+            awc::Client::new()
+                .post(format!("http://{}", admin_addr))
+                .header("Authorization", format!("Bearer {}", AUTH_TOKEN))
+                .send_json(&request)
+                .then(|response| async move {
+                    let mut response = response.unwrap();
+                    assert!(response.status().is_success());
+                    let message: Message = response.json().await.unwrap();
+                    match message {
+                        Message::Response(response) => {
+                            let err = response.result.unwrap_err();
+                            assert_eq!(err.code, -32601);
+                        }
+                        _ => panic!("Expected a response, got {:?}", message),
+                    }
+                    System::current().stop();
+                }),
+        );
+    })
+    .unwrap();
+}
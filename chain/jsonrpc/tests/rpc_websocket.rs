@@ -0,0 +1,84 @@
+use actix::System;
+use futures::{SinkExt, Stream, StreamExt};
+use serde_json::json;
+
+use near_jsonrpc_client::message::{from_slice, Message};
+use near_logger_utils::init_test_logger;
+
+pub mod test_utils;
+
+async fn next_message<S>(connection: &mut S) -> Message
+where
+    S: Stream<Item = Result<awc::ws::Frame, awc::ws::ProtocolError>> + Unpin,
+{
+    loop {
+        if let awc::ws::Frame::Text(bytes) = connection.next().await.unwrap().unwrap() {
+            break from_slice(&bytes).unwrap();
+        }
+    }
+}
+
+/// Subscribe to final blocks over WebSocket and receive the genesis block.
+#[test]
+fn test_subscribe_final_blocks() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+            let request = json!({
+                "jsonrpc": "2.0",
+                "id": "dontcare",
+                "method": "subscribe",
+                "params": {"subscription_type": "final_blocks"},
+            });
+            connection.send(awc::ws::Message::Text(request.to_string())).await.unwrap();
+
+            let subscription_id = match next_message(&mut connection).await {
+                Message::Response(response) => response.result.unwrap(),
+                message => panic!("Unexpected message {:?}", message),
+            };
+            assert_eq!(subscription_id, json!(0));
+
+            match next_message(&mut connection).await {
+                Message::Notification(notification) => {
+                    assert_eq!(notification.method, "subscription");
+                    let params = notification.params.unwrap();
+                    assert_eq!(params["subscription_id"], json!(0));
+                    assert_eq!(params["result"]["header"]["height"], json!(0));
+                }
+                message => panic!("Unexpected message {:?}", message),
+            }
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
+
+/// Unknown methods are rejected without closing the connection.
+#[test]
+fn test_unknown_websocket_method() {
+    init_test_logger();
+
+    System::run(|| {
+        let (_view_client_addr, addr) = test_utils::start_all(test_utils::NodeType::NonValidator);
+
+        actix::spawn(async move {
+            let (_response, mut connection) =
+                awc::Client::new().ws(format!("ws://{}/ws", addr)).connect().await.unwrap();
+            for _ in 0..2 {
+                let request = json!({"jsonrpc": "2.0", "id": 1, "method": "block", "params": []});
+                connection.send(awc::ws::Message::Text(request.to_string())).await.unwrap();
+                match next_message(&mut connection).await {
+                    Message::Response(response) => assert!(response.result.is_err()),
+                    message => panic!("Unexpected message {:?}", message),
+                }
+            }
+            System::current().stop();
+        });
+    })
+    .unwrap();
+}
@@ -200,6 +200,11 @@ impl RouteBackCache {
         }
     }
 
+    /// Iterates over all records as (hash, target, time the record was inserted in milliseconds).
+    pub fn iter(&self) -> impl Iterator<Item = (&CryptoHash, &PeerId, Time)> {
+        self.main.iter().map(|(hash, (time, target))| (hash, target, *time))
+    }
+
     pub fn insert(&mut self, hash: CryptoHash, target: PeerId) {
         if self.main.contains_key(&hash) {
             return;
@@ -134,9 +134,10 @@ mod test {
     use near_primitives::transaction::{SignedTransaction, Transaction};
     use near_primitives::{
         types::EpochId,
-        version::{OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION, PROTOCOL_VERSION},
+        version::{ProtocolVersion, OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION, PROTOCOL_VERSION},
     };
 
+    use crate::encryption::{is_encrypted_version, EphemeralSecret};
     use crate::routing::EdgeInfo;
     use crate::types::{
         Handshake, HandshakeFailureReason, HandshakeV2, PeerChainInfo, PeerChainInfoV2,
@@ -230,14 +231,19 @@ mod test {
         })
     }
 
-    #[test]
-    fn test_peer_message_handshake() {
-        let peer_info = PeerInfo::random();
-        let fake_handshake = Handshake {
-            version: PROTOCOL_VERSION,
+    fn fake_handshake(version: ProtocolVersion) -> Handshake {
+        let secret_key = SecretKey::from_random(KeyType::ED25519);
+        let peer_id = PeerId(secret_key.public_key());
+        let ephemeral_key = if is_encrypted_version(version) {
+            Some(EphemeralSecret::new().sign(&secret_key, &peer_id, &peer_id, version))
+        } else {
+            None
+        };
+        Handshake {
+            version,
             oldest_supported_version: OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION,
-            peer_id: peer_info.id.clone(),
-            target_peer_id: peer_info.id,
+            peer_id: peer_id.clone(),
+            target_peer_id: peer_id,
             listen_port: None,
             chain_info: PeerChainInfoV2 {
                 genesis_id: Default::default(),
@@ -246,11 +252,38 @@ mod test {
                 archival: false,
             },
             edge_info: EdgeInfo::default(),
-        };
-        let msg = PeerMessage::Handshake(fake_handshake);
+            ephemeral_key,
+        }
+    }
+
+    #[test]
+    fn test_peer_message_handshake() {
+        let msg = PeerMessage::Handshake(fake_handshake(PROTOCOL_VERSION));
         test_codec(msg);
     }
 
+    #[test]
+    #[cfg(feature = "protocol_feature_encrypted_peer_connection")]
+    fn test_peer_message_handshake_without_encryption() {
+        use near_primitives::version::{ProtocolFeature, PROTOCOL_FEATURES_TO_VERSION_MAPPING};
+
+        let encrypted_version =
+            PROTOCOL_FEATURES_TO_VERSION_MAPPING[&ProtocolFeature::EncryptedPeerConnection];
+        // Peers before the encrypted connections don't send nor expect the ephemeral key.
+        let mut handshake = fake_handshake(encrypted_version - 1);
+        handshake.ephemeral_key = fake_handshake(encrypted_version).ephemeral_key;
+        let with_key = peer_message_to_bytes(PeerMessage::Handshake(handshake.clone())).unwrap();
+        handshake.ephemeral_key = None;
+        let without_key = peer_message_to_bytes(PeerMessage::Handshake(handshake.clone())).unwrap();
+        assert_eq!(with_key, without_key);
+        assert_eq!(bytes_to_peer_message(&without_key).unwrap(), PeerMessage::Handshake(handshake));
+
+        // The ephemeral key is required once the peers support encrypted connections.
+        let mut handshake = fake_handshake(encrypted_version);
+        handshake.ephemeral_key = None;
+        assert!(peer_message_to_bytes(PeerMessage::Handshake(handshake)).is_err());
+    }
+
     #[test]
     fn test_peer_message_handshake_v2() {
         let peer_info = PeerInfo::random();
@@ -0,0 +1,267 @@
+//! Encrypted peer connections.
+//!
+//! The session is set up during the handshake, in the spirit of the Noise `NN` pattern with the
+//! static keys replaced by signatures: each side generates a fresh X25519 key for the connection,
+//! signs it with the secret key behind its `PeerId` and sends it in the `Handshake`. Once both
+//! ephemeral keys are known, both sides derive one ChaCha20-Poly1305 key per direction from the
+//! Diffie-Hellman shared secret, and every following frame is encrypted with a counter nonce.
+//!
+//! The signature also covers the highest protocol version of the sender. The inbound peer answers
+//! with the version the outbound peer proposed, or with a `HandshakeFailure` if it can't talk it,
+//! so an outbound peer that offered an encrypted session drops the connection if the answer is a
+//! plain handshake: the handshakes were rewritten to downgrade the connection. A `HandshakeFailure`
+//! isn't signed, the same as in older versions, so an outbound peer only retries with a version
+//! without encryption if the other side isn't known to support encryption, i.e. it had no encrypted
+//! connection with this node since the node started. The first connection to a peer can still be
+//! downgraded this way, as it can't be told apart from a connection to an old node.
+use borsh::{BorshDeserialize, BorshSerialize};
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::ChaCha20Poly1305;
+use curve25519_dalek::constants::X25519_BASEPOINT;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use curve25519_dalek::scalar::Scalar;
+use rand::rngs::OsRng;
+use serde::Serialize;
+
+use near_crypto::{SecretKey, Signature};
+use near_primitives::checked_feature;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::network::PeerId;
+use near_primitives::version::ProtocolVersion;
+
+use crate::types::PeerType;
+
+/// Name of the protocol, mixed into every hash so keys can't be reused by other protocols.
+const PROTOCOL_NAME: &[u8] = b"near-network-x25519-chacha20poly1305-sha256";
+const NONCE_LEN: usize = 12;
+
+/// Whether peers talking with the given protocol version encrypt the connection.
+pub fn is_encrypted_version(protocol_version: ProtocolVersion) -> bool {
+    checked_feature!(
+        "protocol_feature_encrypted_peer_connection",
+        EncryptedPeerConnection,
+        protocol_version
+    )
+}
+
+/// Ephemeral public key sent in the handshake, signed by the sender's `PeerId`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct EphemeralKey {
+    pub key: [u8; 32],
+    /// Highest protocol version supported by the sender.
+    pub max_protocol_version: ProtocolVersion,
+    pub signature: Signature,
+}
+
+impl EphemeralKey {
+    fn build_hash(
+        peer_id: &PeerId,
+        target_peer_id: &PeerId,
+        max_protocol_version: ProtocolVersion,
+        key: &[u8; 32],
+    ) -> CryptoHash {
+        let mut buffer = PROTOCOL_NAME.to_vec();
+        let peer_id: Vec<u8> = peer_id.clone().into();
+        buffer.extend_from_slice(peer_id.as_slice());
+        let target_peer_id: Vec<u8> = target_peer_id.clone().into();
+        buffer.extend_from_slice(target_peer_id.as_slice());
+        buffer.extend_from_slice(&max_protocol_version.to_le_bytes());
+        buffer.extend_from_slice(key);
+        hash(buffer.as_slice())
+    }
+
+    /// Checks that the key was signed by `peer_id` for a connection to `target_peer_id`.
+    pub fn verify(&self, peer_id: &PeerId, target_peer_id: &PeerId) -> bool {
+        let data =
+            EphemeralKey::build_hash(peer_id, target_peer_id, self.max_protocol_version, &self.key);
+        self.signature.verify(data.as_ref(), &peer_id.public_key())
+    }
+}
+
+/// Secret half of the ephemeral key of this node. A new one is generated for every connection.
+pub struct EphemeralSecret {
+    secret: Scalar,
+    public: MontgomeryPoint,
+}
+
+#[allow(clippy::new_without_default)]
+impl EphemeralSecret {
+    pub fn new() -> Self {
+        let secret = Scalar::random(&mut OsRng);
+        Self { secret, public: X25519_BASEPOINT * secret }
+    }
+
+    /// Signs the public half for the connection from `peer_id` to `target_peer_id`, along with
+    /// the highest protocol version `peer_id` supports.
+    pub fn sign(
+        &self,
+        secret_key: &SecretKey,
+        peer_id: &PeerId,
+        target_peer_id: &PeerId,
+        max_protocol_version: ProtocolVersion,
+    ) -> EphemeralKey {
+        let key = self.public.to_bytes();
+        let data = EphemeralKey::build_hash(peer_id, target_peer_id, max_protocol_version, &key);
+        EphemeralKey { key, max_protocol_version, signature: secret_key.sign(data.as_ref()) }
+    }
+}
+
+/// Keys and nonces of an established encrypted session.
+pub struct SessionCipher {
+    send_cipher: ChaCha20Poly1305,
+    recv_cipher: ChaCha20Poly1305,
+    send_nonce: u64,
+    recv_nonce: u64,
+}
+
+impl SessionCipher {
+    /// Derives the session from our ephemeral secret and the (already verified) ephemeral key of
+    /// the other peer. `peer_type` is the direction of the connection from our side, the
+    /// outbound peer is the initiator of the session.
+    /// Returns `None` if the other key is a low order point and the shared secret is zero.
+    pub fn new(
+        secret: &EphemeralSecret,
+        remote: &EphemeralKey,
+        peer_type: PeerType,
+    ) -> Option<Self> {
+        let shared = MontgomeryPoint(remote.key) * secret.secret;
+        if shared.to_bytes() == [0u8; 32] {
+            return None;
+        }
+        let local = secret.public.to_bytes();
+        let (initiator_key, responder_key) = match peer_type {
+            PeerType::Outbound => (&local, &remote.key),
+            PeerType::Inbound => (&remote.key, &local),
+        };
+        let derive_key = |label: &[u8]| {
+            let mut buffer = PROTOCOL_NAME.to_vec();
+            buffer.extend_from_slice(shared.as_bytes());
+            buffer.extend_from_slice(initiator_key);
+            buffer.extend_from_slice(responder_key);
+            buffer.extend_from_slice(label);
+            ChaCha20Poly1305::new(GenericArray::from_slice(hash(&buffer).as_ref()))
+        };
+        let initiator_cipher = derive_key(b"initiator");
+        let responder_cipher = derive_key(b"responder");
+        let (send_cipher, recv_cipher) = match peer_type {
+            PeerType::Outbound => (initiator_cipher, responder_cipher),
+            PeerType::Inbound => (responder_cipher, initiator_cipher),
+        };
+        Some(Self { send_cipher, recv_cipher, send_nonce: 0, recv_nonce: 0 })
+    }
+
+    /// Returns `None` once the nonces are exhausted, the session can't be used after that.
+    fn nonce(counter: &mut u64) -> Option<[u8; NONCE_LEN]> {
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce[NONCE_LEN - 8..].copy_from_slice(&counter.to_le_bytes());
+        *counter = counter.checked_add(1)?;
+        Some(nonce)
+    }
+
+    /// Encrypts the next frame sent to the peer. Fails if the session ran out of nonces.
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Option<Vec<u8>> {
+        let nonce = SessionCipher::nonce(&mut self.send_nonce)?;
+        Some(
+            self.send_cipher
+                .encrypt(GenericArray::from_slice(&nonce), plaintext)
+                .expect("Encryption with ChaCha20Poly1305 can't fail"),
+        )
+    }
+
+    /// Decrypts the next frame received from the peer. Fails if the frame was tampered with,
+    /// replayed, reordered or dropped, or if the session ran out of nonces.
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Option<Vec<u8>> {
+        let nonce = SessionCipher::nonce(&mut self.recv_nonce)?;
+        self.recv_cipher.decrypt(GenericArray::from_slice(&nonce), ciphertext).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use near_crypto::KeyType;
+    use near_primitives::version::PROTOCOL_VERSION;
+
+    use super::*;
+
+    fn session() -> (SessionCipher, SessionCipher) {
+        let sk0 = SecretKey::from_seed(KeyType::ED25519, "test0");
+        let sk1 = SecretKey::from_seed(KeyType::ED25519, "test1");
+        let (peer0, peer1) = (PeerId(sk0.public_key()), PeerId(sk1.public_key()));
+        let (secret0, secret1) = (EphemeralSecret::new(), EphemeralSecret::new());
+        let key0 = secret0.sign(&sk0, &peer0, &peer1, PROTOCOL_VERSION);
+        let key1 = secret1.sign(&sk1, &peer1, &peer0, PROTOCOL_VERSION);
+        assert!(key0.verify(&peer0, &peer1));
+        assert!(key1.verify(&peer1, &peer0));
+        (
+            SessionCipher::new(&secret0, &key1, PeerType::Outbound).unwrap(),
+            SessionCipher::new(&secret1, &key0, PeerType::Inbound).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_session_roundtrip() {
+        let (mut outbound, mut inbound) = session();
+        for i in 0..3u8 {
+            let frame = outbound.encrypt(&[i; 10]).unwrap();
+            assert_ne!(frame[..10], [i; 10]);
+            assert_eq!(inbound.decrypt(&frame).unwrap(), vec![i; 10]);
+            let frame = inbound.encrypt(&[i; 5]).unwrap();
+            assert_eq!(outbound.decrypt(&frame).unwrap(), vec![i; 5]);
+        }
+    }
+
+    #[test]
+    fn test_session_rejects_tampered_and_replayed_frames() {
+        let (mut outbound, mut inbound) = session();
+        let mut frame = outbound.encrypt(b"block").unwrap();
+        frame[0] ^= 1;
+        assert!(inbound.decrypt(&frame).is_none());
+
+        let (mut outbound, mut inbound) = session();
+        let frame = outbound.encrypt(b"block").unwrap();
+        assert!(inbound.decrypt(&frame).is_some());
+        assert!(inbound.decrypt(&frame).is_none());
+    }
+
+    #[test]
+    fn test_session_nonce_exhausted() {
+        let (mut outbound, mut inbound) = session();
+        outbound.send_nonce = u64::MAX - 1;
+        inbound.recv_nonce = u64::MAX - 1;
+        let frame = outbound.encrypt(b"block").unwrap();
+        assert!(inbound.decrypt(&frame).is_some());
+        assert!(outbound.encrypt(b"block").is_none());
+        assert!(inbound.decrypt(&frame).is_none());
+    }
+
+    #[test]
+    fn test_ephemeral_key_signature() {
+        let sk0 = SecretKey::from_seed(KeyType::ED25519, "test0");
+        let sk1 = SecretKey::from_seed(KeyType::ED25519, "test1");
+        let (peer0, peer1) = (PeerId(sk0.public_key()), PeerId(sk1.public_key()));
+        let key = EphemeralSecret::new().sign(&sk0, &peer0, &peer1, PROTOCOL_VERSION);
+        assert!(key.verify(&peer0, &peer1));
+        // Signed for another connection.
+        assert!(!key.verify(&peer1, &peer0));
+        // Supported version lowered by a man in the middle.
+        let mut downgraded_key = key.clone();
+        downgraded_key.max_protocol_version -= 1;
+        assert!(!downgraded_key.verify(&peer0, &peer1));
+        // Ephemeral key replaced by a man in the middle.
+        let mut key = key;
+        key.key = EphemeralSecret::new().public.to_bytes();
+        assert!(!key.verify(&peer0, &peer1));
+    }
+
+    #[test]
+    fn test_low_order_key_rejected() {
+        let sk0 = SecretKey::from_seed(KeyType::ED25519, "test0");
+        let key = EphemeralKey {
+            key: [0u8; 32],
+            max_protocol_version: PROTOCOL_VERSION,
+            signature: sk0.sign(&[]),
+        };
+        assert!(SessionCipher::new(&EphemeralSecret::new(), &key, PeerType::Inbound).is_none());
+    }
+}
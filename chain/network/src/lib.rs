@@ -9,11 +9,13 @@ pub use types::{
 
 mod cache;
 mod codec;
+pub mod encryption;
 pub mod metrics;
 mod peer;
 mod peer_manager;
 pub mod peer_store;
 mod rate_counter;
+pub mod rate_limiter;
 #[cfg(feature = "metric_recorder")]
 pub mod recorder;
 pub mod routing;
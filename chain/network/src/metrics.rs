@@ -31,6 +31,11 @@ lazy_static! {
             "near_peer_transaction_received_total",
             "Number of transactions received by peers"
         );
+    pub static ref PEER_MESSAGE_RATE_LIMITED_TOTAL: near_metrics::Result<IntCounter> =
+        try_create_int_counter(
+            "near_peer_message_rate_limited_total",
+            "Number of messages from peers dropped due to rate limits"
+        );
 
     // Routing table metrics
     pub static ref ROUTING_TABLE_RECALCULATIONS: near_metrics::Result<IntCounter> =
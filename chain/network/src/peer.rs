@@ -14,6 +14,7 @@ use actix::{
 };
 use tracing::{debug, error, info, trace, warn};
 
+use near_crypto::SecretKey;
 use near_metrics;
 use near_primitives::block::GenesisId;
 use near_primitives::hash::CryptoHash;
@@ -25,17 +26,19 @@ use near_primitives::version::{
 };
 
 use crate::codec::{self, bytes_to_peer_message, peer_message_to_bytes, Codec};
+use crate::encryption::{is_encrypted_version, EphemeralSecret, SessionCipher};
 use crate::rate_counter::RateCounter;
+use crate::rate_limiter::{PeerRateLimiter, RateLimits};
 #[cfg(feature = "metric_recorder")]
 use crate::recorder::{PeerMessageMetadata, Status};
 use crate::routing::{Edge, EdgeInfo};
 use crate::types::{
     Ban, Consolidate, ConsolidateResponse, Handshake, HandshakeFailureReason, HandshakeV2,
     NetworkClientMessages, NetworkClientResponses, NetworkRequests, NetworkViewClientMessages,
-    NetworkViewClientResponses, PeerChainInfo, PeerChainInfoV2, PeerInfo, PeerManagerRequest,
-    PeerMessage, PeerRequest, PeerResponse, PeerStatsResult, PeerStatus, PeerType, PeersRequest,
-    PeersResponse, QueryPeerStats, ReasonForBan, RoutedMessage, RoutedMessageBody,
-    RoutedMessageFrom, SendMessage, StateResponseInfo, Unregister,
+    NetworkViewClientResponses, PeerChainInfo, PeerChainInfoV2, PeerIdOrHash, PeerInfo,
+    PeerManagerRequest, PeerMessage, PeerRequest, PeerResponse, PeerStatsResult, PeerStatus,
+    PeerType, PeersRequest, PeersResponse, QueryPeerStats, ReasonForBan, RoutedMessage,
+    RoutedMessageBody, RoutedMessageFrom, SendMessage, StateResponseInfo, Unregister,
     UPDATE_INTERVAL_LAST_TIME_RECEIVED_MESSAGE,
 };
 use crate::PeerManagerActor;
@@ -154,8 +157,22 @@ pub struct Peer {
     pub peer_status: PeerStatus,
     /// Protocol version to communicate with this peer.
     pub protocol_version: ProtocolVersion,
+    /// Highest protocol version this node is allowed to use.
+    max_protocol_version: ProtocolVersion,
+    /// Whether the peer is known to support encrypted connections.
+    encrypted_peer: bool,
     /// Framed wrapper to send messages through the TCP connection.
     framed: FramedWrite<WriteHalf, Codec>,
+    /// This node's secret key, used to sign the ephemeral key of the session.
+    secret_key: SecretKey,
+    /// Ephemeral key of this node for this connection.
+    ephemeral_secret: EphemeralSecret,
+    /// Session derived from the peer's handshake, which is used after our handshake is sent.
+    pending_session: Option<SessionCipher>,
+    /// Messages sent while waiting for `pending_session` to start.
+    pending_messages: Vec<PeerMessage>,
+    /// Encrypted session, all frames are encrypted once it is established.
+    session: Option<SessionCipher>,
     /// Handshake timeout.
     handshake_timeout: Duration,
     /// Peer manager recipient to break the dependency loop.
@@ -166,6 +183,8 @@ pub struct Peer {
     view_client_addr: Recipient<NetworkViewClientMessages>,
     /// Tracker for requests and responses.
     tracker: Tracker,
+    /// Limits on the messages received from this peer.
+    rate_limiter: PeerRateLimiter,
     /// This node genesis id.
     genesis_id: GenesisId,
     /// Latest chain info from the peer.
@@ -187,11 +206,15 @@ impl Peer {
         peer_info: Option<PeerInfo>,
         peer_type: PeerType,
         framed: FramedWrite<WriteHalf, Codec>,
+        secret_key: SecretKey,
+        max_protocol_version: ProtocolVersion,
+        encrypted_peer: bool,
         handshake_timeout: Duration,
         peer_manager_addr: Addr<PeerManagerActor>,
         client_addr: Recipient<NetworkClientMessages>,
         view_client_addr: Recipient<NetworkViewClientMessages>,
         edge_info: Option<EdgeInfo>,
+        rate_limits: &RateLimits,
         network_metrics: NetworkMetrics,
         txns_since_last_block: Arc<AtomicUsize>,
     ) -> Self {
@@ -201,13 +224,21 @@ impl Peer {
             peer_info: peer_info.into(),
             peer_type,
             peer_status: PeerStatus::Connecting,
-            protocol_version: PROTOCOL_VERSION,
+            protocol_version: max_protocol_version,
+            max_protocol_version,
+            encrypted_peer,
             framed,
+            secret_key,
+            ephemeral_secret: EphemeralSecret::new(),
+            pending_session: None,
+            pending_messages: vec![],
+            session: None,
             handshake_timeout,
             peer_manager_addr,
             client_addr,
             view_client_addr,
             tracker: Default::default(),
+            rate_limiter: PeerRateLimiter::new(rate_limits),
             genesis_id: Default::default(),
             chain_info: Default::default(),
             edge_info,
@@ -234,6 +265,15 @@ impl Peer {
             PeerMessage::BlockRequest(h) => self.tracker.push_request(*h),
             _ => (),
         };
+        // The peer can't read anything but our handshake until the encrypted session starts, so
+        // the messages are sent once it does.
+        if self.pending_session.is_some()
+            && self.peer_status == PeerStatus::Ready
+            && !matches!(msg, PeerMessage::Handshake(_))
+        {
+            self.pending_messages.push(msg);
+            return;
+        }
         #[cfg(feature = "metric_recorder")]
         let metadata = {
             let mut metadata: PeerMessageMetadata = (&msg).into();
@@ -249,6 +289,18 @@ impl Peer {
                 #[cfg(feature = "metric_recorder")]
                 self.peer_manager_addr.do_send(metadata.set_size(bytes.len()));
                 self.tracker.increment_sent(bytes.len() as u64);
+                let bytes = match self.session.as_mut() {
+                    Some(session) => match session.encrypt(&bytes) {
+                        Some(bytes) => bytes,
+                        None => {
+                            warn!(target: "network", "Session with {} ran out of nonces. Disconnecting.", self.peer_info);
+                            // The actor stops once the writer is closed.
+                            self.framed.close();
+                            return;
+                        }
+                    },
+                    None => bytes,
+                };
                 self.framed.write(bytes);
             }
             Err(err) => error!(target: "network", "Error converting message to bytes: {}", err),
@@ -290,6 +342,16 @@ impl Peer {
                     tracked_shards,
                     archival,
                 }) => {
+                    let ephemeral_key = if is_encrypted_version(act.protocol_version) {
+                        Some(act.ephemeral_secret.sign(
+                            &act.secret_key,
+                            &act.node_id(),
+                            &act.peer_id().unwrap(),
+                            act.max_protocol_version,
+                        ))
+                    } else {
+                        None
+                    };
                     let handshake = match act.protocol_version {
                         39..=PROTOCOL_VERSION => PeerMessage::Handshake(Handshake::new(
                             act.protocol_version,
@@ -298,6 +360,7 @@ impl Peer {
                             act.node_info.addr_port(),
                             PeerChainInfoV2 { genesis_id, height, tracked_shards, archival },
                             act.edge_info.as_ref().unwrap().clone(),
+                            ephemeral_key,
                         )),
                         34..=38 => PeerMessage::HandshakeV2(HandshakeV2::new(
                             act.protocol_version,
@@ -314,6 +377,13 @@ impl Peer {
                     };
 
                     act.send_message(handshake);
+                    // Everything after the handshake is encrypted if the session was negotiated.
+                    if let Some(session) = act.pending_session.take() {
+                        act.session = Some(session);
+                        for msg in std::mem::take(&mut act.pending_messages) {
+                            act.send_message(msg);
+                        }
+                    }
                     actix::fut::ready(())
                 }
                 Err(err) => {
@@ -659,6 +729,17 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                 return ();
             }
         };
+        let msg = match self.session.as_mut() {
+            Some(session) => match session.decrypt(&msg) {
+                Some(msg) => msg,
+                None => {
+                    info!(target: "network", "Failed to decrypt message from {}. Disconnecting.", self.peer_info);
+                    ctx.stop();
+                    return;
+                }
+            },
+            None => msg,
+        };
 
         near_metrics::inc_counter_by(&metrics::PEER_DATA_RECEIVED_BYTES, msg.len() as i64);
         near_metrics::inc_counter(&metrics::PEER_MESSAGE_RECEIVED_TOTAL);
@@ -703,6 +784,24 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                 return;
             }
         };
+        // Routed messages to other nodes are only forwarded by the peer, which isn't their author.
+        let msg_type = match &peer_msg {
+            PeerMessage::Routed(routed_message)
+                if routed_message.target != PeerIdOrHash::PeerId(self.node_id()) =>
+            {
+                None
+            }
+            _ => Some(peer_msg.msg_variant()),
+        };
+        if !self.rate_limiter.check(msg_type, msg.len() as u64) {
+            near_metrics::inc_counter(&metrics::PEER_MESSAGE_RATE_LIMITED_TOTAL);
+            if self.rate_limiter.is_abusive() {
+                self.ban_peer(ctx, ReasonForBan::RateLimitExceeded);
+            } else {
+                debug!(target: "network", "Dropping {} from {} over the rate limits", peer_msg, self.peer_info);
+            }
+            return;
+        }
         if let PeerMessage::Routed(RoutedMessage {
             body: RoutedMessageBody::ForwardTx(_), ..
         }) = &peer_msg
@@ -751,9 +850,16 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                         version,
                         oldest_supported_version,
                     } => {
-                        let target_version = std::cmp::min(version, PROTOCOL_VERSION);
+                        let target_version = std::cmp::min(version, self.max_protocol_version);
 
-                        if target_version
+                        // The failure isn't signed, so a peer known to support encryption can't
+                        // make us retry in plain text: a man in the middle could have sent it.
+                        if self.encrypted_peer
+                            && is_encrypted_version(self.protocol_version)
+                            && !is_encrypted_version(target_version)
+                        {
+                            warn!(target: "network", "Peer {} known to support encryption asked for version {}. Disconnecting.", peer_info, version);
+                        } else if target_version
                             >= std::cmp::max(
                                 oldest_supported_version,
                                 OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION,
@@ -771,6 +877,9 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                         debug!(target: "network", "Peer found was not what expected. Updating peer info with {:?}", peer_info);
                         self.peer_manager_addr.do_send(PeerRequest::UpdatePeerInfo(peer_info));
                     }
+                    HandshakeFailureReason::NotAllowed => {
+                        warn!(target: "network", "Node {} only accepts connections from its allowed peers", peer_info);
+                    }
                 }
                 ctx.stop();
             }
@@ -782,7 +891,32 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                         && handshake.version <= PROTOCOL_VERSION
                 );
 
-                let target_version = std::cmp::min(handshake.version, PROTOCOL_VERSION);
+                // Older nodes can't parse a handshake with a newer version and answer with a
+                // `HandshakeFailure`, so do the same to make the outbound peer retry.
+                if self.peer_type == PeerType::Inbound
+                    && handshake.version > self.max_protocol_version
+                {
+                    self.send_message(PeerMessage::HandshakeFailure(
+                        self.node_info.clone(),
+                        HandshakeFailureReason::ProtocolVersionMismatch {
+                            version: self.max_protocol_version,
+                            oldest_supported_version: OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION,
+                        },
+                    ));
+                    return;
+                }
+
+                let target_version = std::cmp::min(handshake.version, self.max_protocol_version);
+                // The inbound peer answers with the version we proposed, so a plain handshake in
+                // response to an encrypted one means that a man in the middle downgraded them.
+                if self.peer_type == PeerType::Outbound
+                    && is_encrypted_version(self.protocol_version)
+                    && !is_encrypted_version(target_version)
+                {
+                    warn!(target: "network", "Peer {} answered the encrypted handshake with version {}. Disconnecting.", handshake.peer_id, handshake.version);
+                    ctx.stop();
+                    return;
+                }
                 self.protocol_version = target_version;
 
                 if handshake.chain_info.genesis_id != self.genesis_id {
@@ -825,6 +959,30 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                     return;
                 }
 
+                // Derive the encrypted session from the ephemeral key signed by the peer.
+                if is_encrypted_version(self.protocol_version) {
+                    let session = handshake
+                        .ephemeral_key
+                        .as_ref()
+                        .filter(|key| key.max_protocol_version >= handshake.version)
+                        .filter(|key| key.verify(&handshake.peer_id, &self.node_id()))
+                        .and_then(|key| {
+                            SessionCipher::new(&self.ephemeral_secret, key, self.peer_type)
+                        });
+                    match session {
+                        // Our handshake was already sent, the outbound peer starts right away.
+                        Some(session) if self.peer_type == PeerType::Outbound => {
+                            self.session = Some(session)
+                        }
+                        Some(session) => self.pending_session = Some(session),
+                        None => {
+                            warn!(target: "network", "Received invalid ephemeral key on handshake. Disconnecting peer {}", handshake.peer_id);
+                            self.ban_peer(ctx, ReasonForBan::InvalidSignature);
+                            return;
+                        }
+                    }
+                }
+
                 // Check that received nonce on handshake match our proposed nonce.
                 if self.peer_type == PeerType::Outbound {
                     if handshake.edge_info.nonce
@@ -848,7 +1006,9 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                     .send(Consolidate {
                         actor: ctx.address(),
                         peer_info: peer_info.clone(),
+                        peer_addr: self.peer_addr,
                         peer_type: self.peer_type,
+                        protocol_version: self.protocol_version,
                         chain_info: handshake.chain_info.clone(),
                         this_edge_info: self.edge_info.clone(),
                         other_edge_info: handshake.edge_info.clone(),
@@ -866,6 +1026,14 @@ impl StreamHandler<Result<Vec<u8>, ReasonForBan>> for Peer {
                                 }
                                 actix::fut::ready(())
                             },
+                            Ok(ConsolidateResponse::NotAllowed) => {
+                                act.send_message(PeerMessage::HandshakeFailure(
+                                    act.node_info.clone(),
+                                    HandshakeFailureReason::NotAllowed,
+                                ));
+                                // Connection will be closed by a handshake timeout
+                                actix::fut::ready(())
+                            }
                             Ok(ConsolidateResponse::InvalidNonce(edge)) => {
                                 debug!(target: "network", "{:?}: Received invalid nonce from peer {:?} sending evidence.", act.node_id(), act.peer_addr);
                                 act.send_message(PeerMessage::LastEdge(*edge));
@@ -1025,6 +1193,7 @@ impl Handler<QueryPeerStats> for Peer {
                 self.tracker.sent_bytes.count_per_min(),
                 self.tracker.received_bytes.count_per_min(),
             ),
+            dropped_messages_per_min: self.rate_limiter.dropped_per_min(),
         }
     }
 }
@@ -26,6 +26,7 @@ use near_primitives::utils::from_timestamp;
 use near_store::Store;
 
 use crate::codec::Codec;
+use crate::encryption::is_encrypted_version;
 use crate::metrics;
 use crate::peer::Peer;
 use crate::peer_store::{PeerStore, TrustLevel};
@@ -33,16 +34,18 @@ use crate::peer_store::{PeerStore, TrustLevel};
 use crate::recorder::{MetricRecorder, PeerMessageMetadata};
 use crate::routing::{Edge, EdgeInfo, EdgeType, ProcessEdgeResult, RoutingTable};
 use crate::types::{
-    AccountOrPeerIdOrHash, Ban, BlockedPorts, Consolidate, ConsolidateResponse, FullPeerInfo,
-    InboundTcpConnect, KnownPeerStatus, KnownProducer, NetworkInfo, NetworkViewClientMessages,
+    AccountOrPeerIdOrHash, ActivePeerDebugInfo, Ban, BlockedPorts, Consolidate,
+    ConsolidateResponse, FullPeerInfo, InboundTcpConnect, KnownPeerDebugInfo, KnownPeerStatus,
+    KnownProducer, NetworkDebugInfo, NetworkInfo, NetworkViewClientMessages,
     NetworkViewClientResponses, OutboundTcpConnect, PeerIdOrHash, PeerList, PeerManagerRequest,
     PeerMessage, PeerRequest, PeerResponse, PeerType, PeersRequest, PeersResponse, Ping, Pong,
-    QueryPeerStats, RawRoutedMessage, ReasonForBan, RoutedMessage, RoutedMessageBody,
-    RoutedMessageFrom, SendMessage, StateResponseInfo, SyncData, Unregister,
+    QueryPeerStats, RateLimitedPeer, RawRoutedMessage, ReasonForBan, RouteBackDebugInfo,
+    RoutedMessage, RoutedMessageBody, RoutedMessageFrom, SendMessage, StateResponseInfo, SyncData,
+    Unregister,
 };
 use crate::types::{
-    EdgeList, KnownPeerState, NetworkClientMessages, NetworkConfig, NetworkRequests,
-    NetworkResponses, PeerInfo,
+    EdgeList, KnownPeerState, NetworkAdminRequest, NetworkClientMessages, NetworkConfig,
+    NetworkRequests, NetworkResponses, PeerInfo,
 };
 #[cfg(feature = "delay_detector")]
 use delay_detector::DelayDetector;
@@ -90,6 +93,8 @@ struct ActivePeer {
     received_bytes_per_sec: u64,
     /// Number of bytes we've sent to the peer.
     sent_bytes_per_sec: u64,
+    /// Number of messages from the peer dropped within the last minute due to rate limits.
+    dropped_messages_per_min: u64,
     /// Last time requested peers.
     last_time_peer_requested: Instant,
     /// Last time we received a message from this peer.
@@ -203,6 +208,15 @@ impl PeerManagerActor {
         }
     }
 
+    /// Whether the peer is allowed to connect to this node, i.e. this node is not in private mode
+    /// or the peer is one of its allowed peers.
+    fn is_peer_allowed(&self, peer_id: &PeerId) -> bool {
+        self.config
+            .allowed_peers
+            .as_ref()
+            .map_or(true, |allowed_peers| allowed_peers.contains(peer_id))
+    }
+
     /// Register a direct connection to a new peer. This will be called after successfully
     /// establishing a connection with another peer. It become part of the active peers.
     ///
@@ -244,6 +258,7 @@ impl PeerManagerActor {
                 full_peer_info,
                 sent_bytes_per_sec: 0,
                 received_bytes_per_sec: 0,
+                dropped_messages_per_min: 0,
                 last_time_peer_requested: Instant::now(),
                 last_time_received_message: Instant::now(),
                 connection_established_time: Instant::now(),
@@ -376,6 +391,65 @@ impl PeerManagerActor {
         }
     }
 
+    /// Handles an operator request from the admin RPC.
+    fn handle_admin_request(
+        &mut self,
+        ctx: &mut Context<Self>,
+        request: NetworkAdminRequest,
+    ) -> Result<(), String> {
+        info!(target: "network", "Admin request: {:?}", request);
+        match request {
+            NetworkAdminRequest::BanPeer(peer_id) => {
+                if peer_id == self.peer_id {
+                    return Err("Can't ban this node".to_string());
+                }
+                // Make sure the peer is in the peer store, so the ban is persisted.
+                self.peer_store
+                    .add_trusted_peer(
+                        PeerInfo { id: peer_id.clone(), addr: None, account_id: None },
+                        TrustLevel::Direct,
+                    )
+                    .map_err(|err| err.to_string())?;
+                self.try_ban_peer(ctx, &peer_id, ReasonForBan::Manual);
+                Ok(())
+            }
+            NetworkAdminRequest::UnbanPeer(peer_id) => {
+                if !self.peer_store.is_banned(&peer_id) {
+                    return Err(format!("Peer {} is not banned", peer_id));
+                }
+                self.peer_store.peer_unban(&peer_id).map_err(|err| err.to_string())
+            }
+            NetworkAdminRequest::ConnectPeer(peer_info) => {
+                if peer_info.id == self.peer_id {
+                    return Err("Can't connect to this node".to_string());
+                }
+                if peer_info.addr.is_none() {
+                    return Err(format!("Peer {} has no address", peer_info));
+                }
+                if self.peer_store.is_banned(&peer_info.id) {
+                    return Err(format!("Peer {} is banned", peer_info.id));
+                }
+                self.peer_store
+                    .add_trusted_peer(peer_info.clone(), TrustLevel::Direct)
+                    .map_err(|err| err.to_string())?;
+                if !self.active_peers.contains_key(&peer_info.id)
+                    && self.outgoing_peers.insert(peer_info.id.clone())
+                {
+                    ctx.notify(OutboundTcpConnect { peer_info });
+                }
+                Ok(())
+            }
+            NetworkAdminRequest::DisconnectPeer(peer_id) => {
+                if let Some(active_peer) = self.active_peers.get(&peer_id) {
+                    let _ = active_peer.addr.do_send(PeerManagerRequest::UnregisterPeer);
+                    Ok(())
+                } else {
+                    Err(format!("Peer {} is not connected", peer_id))
+                }
+            }
+        }
+    }
+
     /// Connects peer with given TcpStream and optional information if it's outbound.
     /// This might fail if the other peers drop listener at its endpoint while establishing connection.
     fn try_connect_peer(
@@ -389,7 +463,14 @@ impl PeerManagerActor {
         let peer_id = self.peer_id.clone();
         let account_id = self.config.account_id.clone();
         let server_addr = self.config.addr;
+        let is_private = self.config.allowed_peers.is_some();
         let handshake_timeout = self.config.handshake_timeout;
+        let secret_key = self.config.secret_key.clone();
+        let protocol_version = self.config.protocol_version;
+        let encrypted_peer = peer_info
+            .as_ref()
+            .map_or(false, |peer_info| self.peer_store.is_encrypted(&peer_info.id));
+        let rate_limits = self.config.rate_limits.clone();
         let client_addr = self.client_addr.clone();
         let view_client_addr = self.view_client_addr.clone();
 
@@ -434,17 +515,23 @@ impl PeerManagerActor {
                 ctx,
             );
 
+            // Private nodes don't tell their address, so it's never gossiped.
+            let addr = if is_private { None } else { Some(server_addr) };
             Peer::new(
-                PeerInfo { id: peer_id, addr: Some(server_addr), account_id },
+                PeerInfo { id: peer_id, addr, account_id },
                 remote_addr,
                 peer_info,
                 peer_type,
                 FramedWrite::new(write, Codec::new(), ctx),
+                secret_key,
+                protocol_version,
+                encrypted_peer,
                 handshake_timeout,
                 recipient,
                 client_addr,
                 view_client_addr,
                 edge_info,
+                &rate_limits,
                 network_metrics,
                 txns_since_last_block,
             )
@@ -649,6 +736,7 @@ impl PeerManagerActor {
                             active_peer.full_peer_info.chain_info = res.chain_info;
                             active_peer.sent_bytes_per_sec = res.sent_bytes_per_sec;
                             active_peer.received_bytes_per_sec = res.received_bytes_per_sec;
+                            active_peer.dropped_messages_per_min = res.dropped_messages_per_min;
                         }
                     });
                 })
@@ -777,6 +865,8 @@ impl PeerManagerActor {
                     || self.config.addr == peer_state.peer_info.addr
                     // Or to peers we are currently trying to connect to
                     || self.outgoing_peers.contains(&peer_state.peer_info.id)
+                    // Or to peers other than the allowed peers in private mode
+                    || !self.is_peer_allowed(&peer_state.peer_info.id)
             }) {
                 // Start monitor_peers_attempts from start after we discover the first healthy peer
                 if !self.started_connect_attempts {
@@ -1038,6 +1128,57 @@ impl PeerManagerActor {
         });
     }
 
+    fn get_network_debug_info(&self) -> NetworkDebugInfo {
+        let ban_window = self.config.ban_window.as_nanos() as u64;
+        NetworkDebugInfo {
+            peer_id: self.peer_id.clone(),
+            active_peers: self
+                .active_peers
+                .values()
+                .map(|active_peer| ActivePeerDebugInfo {
+                    peer_info: active_peer.full_peer_info.peer_info.clone(),
+                    peer_type: active_peer.peer_type,
+                    chain_info: active_peer.full_peer_info.chain_info.clone(),
+                    sent_bytes_per_sec: active_peer.sent_bytes_per_sec,
+                    received_bytes_per_sec: active_peer.received_bytes_per_sec,
+                    dropped_messages_per_min: active_peer.dropped_messages_per_min,
+                    connected_for_secs: active_peer.connection_established_time.elapsed().as_secs(),
+                    last_message_secs_ago: active_peer
+                        .last_time_received_message
+                        .elapsed()
+                        .as_secs(),
+                })
+                .collect(),
+            known_peers: self
+                .peer_store
+                .iter()
+                .map(|(_, peer_state)| KnownPeerDebugInfo {
+                    peer_info: peer_state.peer_info.clone(),
+                    status: peer_state.status.clone(),
+                    first_seen: peer_state.first_seen,
+                    last_seen: peer_state.last_seen,
+                    ban_expires_at: match peer_state.status {
+                        KnownPeerStatus::Banned(_, last_banned) => {
+                            Some(last_banned.saturating_add(ban_window))
+                        }
+                        _ => None,
+                    },
+                })
+                .collect(),
+            edges: self.routing_table.edges_info.values().cloned().collect(),
+            route_back: self
+                .routing_table
+                .route_back
+                .iter()
+                .map(|(hash, peer_id, received_at)| RouteBackDebugInfo {
+                    hash: *hash,
+                    peer_id: peer_id.clone(),
+                    received_at,
+                })
+                .collect(),
+        }
+    }
+
     pub(crate) fn get_network_info(&mut self) -> NetworkInfo {
         let (sent_bytes_per_sec, received_bytes_per_sec) = self.get_total_bytes_per_sec();
         NetworkInfo {
@@ -1062,6 +1203,16 @@ impl PeerManagerActor {
                     addr: None,
                 })
                 .collect(),
+            rate_limits: self.config.rate_limits.clone(),
+            rate_limited_peers: self
+                .active_peers
+                .iter()
+                .filter(|(_, active_peer)| active_peer.dropped_messages_per_min > 0)
+                .map(|(peer_id, active_peer)| RateLimitedPeer {
+                    peer_id: peer_id.clone(),
+                    dropped_messages_per_min: active_peer.dropped_messages_per_min,
+                })
+                .collect(),
             #[cfg(feature = "metric_recorder")]
             metric_recorder: self.metric_recorder.clone(),
         }
@@ -1520,6 +1671,12 @@ impl Handler<NetworkRequests> for PeerManagerActor {
                 let (pings, pongs) = self.routing_table.fetch_ping_pong();
                 NetworkResponses::PingPongInfo { pings, pongs }
             }
+            NetworkRequests::FetchDebugInfo => {
+                NetworkResponses::DebugInfo(Box::new(self.get_network_debug_info()))
+            }
+            NetworkRequests::Admin(request) => {
+                NetworkResponses::AdminResult(self.handle_admin_request(ctx, request))
+            }
         }
     }
 }
@@ -1590,12 +1747,18 @@ impl Handler<Consolidate> for PeerManagerActor {
     fn handle(&mut self, msg: Consolidate, ctx: &mut Self::Context) -> Self::Result {
         #[cfg(feature = "delay_detector")]
         let _d = DelayDetector::new("consolidate".into());
-        // Check if this is a blacklisted peer.
-        if msg.peer_info.addr.as_ref().map_or(true, |addr| self.is_blacklisted(addr)) {
-            debug!(target: "network", "Dropping connection from blacklisted peer or unknown address: {:?}", msg.peer_info);
+        // Check if this is a blacklisted peer. Private nodes don't send their listen address,
+        // so fall back to the address of the connection.
+        if self.is_blacklisted(msg.peer_info.addr.as_ref().unwrap_or(&msg.peer_addr)) {
+            debug!(target: "network", "Dropping connection from blacklisted peer: {:?}", msg.peer_info);
             return ConsolidateResponse::Reject;
         }
 
+        if msg.peer_type == PeerType::Inbound && !self.is_peer_allowed(&msg.peer_info.id) {
+            debug!(target: "network", "Dropping connection from peer not in the allowed peers: {:?}", msg.peer_info.id);
+            return ConsolidateResponse::NotAllowed;
+        }
+
         if self.peer_store.is_banned(&msg.peer_info.id) {
             debug!(target: "network", "Dropping connection from banned peer: {:?}", msg.peer_info.id);
             return ConsolidateResponse::Reject;
@@ -1651,6 +1814,10 @@ impl Handler<Consolidate> for PeerManagerActor {
 
         let edge_info_response = if require_response { Some(edge_info.clone()) } else { None };
 
+        if is_encrypted_version(msg.protocol_version) {
+            self.peer_store.peer_encrypted(&msg.peer_info.id);
+        }
+
         // TODO: double check that address is connectable and add account id.
         self.register_peer(
             FullPeerInfo {
@@ -1,6 +1,6 @@
 use std::collections::{
     hash_map::{Entry, Iter},
-    HashMap,
+    HashMap, HashSet,
 };
 use std::convert::TryInto;
 use std::net::SocketAddr;
@@ -52,6 +52,9 @@ pub struct PeerStore {
     // It can happens that some peers don't have known address, so
     // they will not be present in this list, otherwise they will be present.
     addr_peers: HashMap<SocketAddr, VerifiedPeer>,
+    /// Peers which had an encrypted connection with this node since it started. They are never
+    /// talked to in plain text, so a man in the middle can't downgrade their connections.
+    encrypted_peers: HashSet<PeerId>,
 }
 
 impl PeerStore {
@@ -109,7 +112,7 @@ impl PeerStore {
                 }
             }
         }
-        Ok(PeerStore { store, peer_states, addr_peers })
+        Ok(PeerStore { store, peer_states, addr_peers, encrypted_peers: HashSet::default() })
     }
 
     pub fn len(&self) -> usize {
@@ -139,6 +142,16 @@ impl PeerStore {
         store_update.commit().map_err(|err| err.into())
     }
 
+    /// Records that the peer supports encrypted connections.
+    pub fn peer_encrypted(&mut self, peer_id: &PeerId) {
+        self.encrypted_peers.insert(peer_id.clone());
+    }
+
+    /// Whether the peer is known to support encrypted connections.
+    pub fn is_encrypted(&self, peer_id: &PeerId) -> bool {
+        self.encrypted_peers.contains(peer_id)
+    }
+
     pub fn peer_disconnected(
         &mut self,
         peer_id: &PeerId,
@@ -0,0 +1,301 @@
+use std::collections::{BTreeMap, HashMap};
+use std::time::Instant;
+
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use strum::VariantNames;
+
+use crate::rate_counter::RateCounter;
+use crate::types::{PeerMessage, RoutedMessageBody};
+
+/// Token bucket parameters.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Size of the bucket, i.e. the largest burst allowed after the peer was idle.
+    pub burst: u64,
+    /// Number of tokens added to the bucket every second.
+    pub rate: u64,
+}
+
+/// Limits on the messages received from a single peer.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct RateLimits {
+    /// Limits on the number of messages, by message type as reported in the metrics
+    /// (the variant name of `PeerMessage`, or of `RoutedMessageBody` for routed messages).
+    /// Message types without a limit are not limited. Routed messages are only limited if they
+    /// are addressed to this node, the peer isn't the author of the messages it forwards.
+    #[serde(deserialize_with = "deserialize_message_limits")]
+    pub messages: BTreeMap<String, RateLimit>,
+    /// Limit on the number of bytes received over all messages.
+    pub bandwidth: Option<RateLimit>,
+    /// Messages over the limits are dropped, and a peer which keeps exceeding the limits
+    /// is banned once more than this number of messages was dropped within a minute.
+    pub max_dropped_messages_per_min: u64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        let messages = vec![
+            ("BlockHeadersRequest", RateLimit { burst: 50, rate: 10 }),
+            ("StateRequestPart", RateLimit { burst: 200, rate: 50 }),
+            ("PartialEncodedChunkRequest", RateLimit { burst: 2000, rate: 500 }),
+        ];
+        RateLimits {
+            messages: messages
+                .into_iter()
+                .map(|(msg_type, limit)| (msg_type.to_string(), limit))
+                .collect(),
+            bandwidth: None,
+            max_dropped_messages_per_min: 1000,
+        }
+    }
+}
+
+/// Whether `msg_type` is a message type as reported by `PeerMessage::msg_variant`.
+fn is_message_type(msg_type: &str) -> bool {
+    (msg_type != "Routed" && PeerMessage::VARIANTS.contains(&msg_type))
+        || RoutedMessageBody::VARIANTS.contains(&msg_type)
+}
+
+/// Rejects limits on unknown message types, which would be silently ignored otherwise.
+fn deserialize_message_limits<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, RateLimit>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let messages = BTreeMap::<String, RateLimit>::deserialize(deserializer)?;
+    match messages.keys().find(|msg_type| !is_message_type(msg_type)) {
+        Some(msg_type) => Err(D::Error::custom(format!("unknown message type {}", msg_type))),
+        None => Ok(messages),
+    }
+}
+
+/// Bucket that is refilled at a constant rate, and from which every message takes tokens.
+pub struct TokenBucket {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(limit: RateLimit) -> Self {
+        TokenBucket { limit, tokens: limit.burst as f64, last_refill: Instant::now() }
+    }
+
+    /// Takes `amount` tokens from the bucket if there are enough of them. An amount larger than
+    /// the bucket is taken once the bucket is full, and it's paid back before the next one.
+    pub fn try_acquire(&mut self, amount: u64) -> bool {
+        self.try_acquire_at(amount, Instant::now())
+    }
+
+    fn try_acquire_at(&mut self, amount: u64, now: Instant) -> bool {
+        let allowed = self.can_acquire_at(amount, now);
+        if allowed {
+            self.acquire(amount);
+        }
+        allowed
+    }
+
+    /// Refills the bucket and checks whether `amount` tokens can be taken, without taking them.
+    fn can_acquire_at(&mut self, amount: u64, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.rate as f64).min(self.limit.burst as f64);
+        self.last_refill = now;
+        self.tokens >= amount.min(self.limit.burst) as f64
+    }
+
+    fn acquire(&mut self, amount: u64) {
+        self.tokens -= amount as f64;
+    }
+}
+
+/// Enforces `RateLimits` on the messages received from a single peer.
+pub struct PeerRateLimiter {
+    messages: HashMap<String, TokenBucket>,
+    bandwidth: Option<TokenBucket>,
+    /// Messages dropped because they were over the limits.
+    dropped: RateCounter,
+    max_dropped_messages_per_min: u64,
+}
+
+impl PeerRateLimiter {
+    pub fn new(limits: &RateLimits) -> Self {
+        PeerRateLimiter {
+            messages: limits
+                .messages
+                .iter()
+                .map(|(msg_type, limit)| (msg_type.clone(), TokenBucket::new(*limit)))
+                .collect(),
+            bandwidth: limits.bandwidth.map(TokenBucket::new),
+            dropped: RateCounter::new(),
+            max_dropped_messages_per_min: limits.max_dropped_messages_per_min,
+        }
+    }
+
+    /// Checks whether a message of `msg_type` and `size` bytes is within the limits.
+    /// Messages over the limits should be dropped, they are accounted in `dropped_per_min`.
+    /// `msg_type` is `None` for messages the peer only forwards to other nodes: they are only
+    /// limited by the bandwidth and are not accounted, as the peer isn't their author.
+    pub fn check(&mut self, msg_type: Option<&str>, size: u64) -> bool {
+        self.check_at(msg_type, size, Instant::now())
+    }
+
+    /// Tokens are only taken from the buckets if the message is within all the limits, so a
+    /// dropped message doesn't count against the limits of the messages let through.
+    fn check_at(&mut self, msg_type: Option<&str>, size: u64, now: Instant) -> bool {
+        let mut message_bucket = msg_type.and_then(|msg_type| self.messages.get_mut(msg_type));
+        let within_limits = message_bucket.as_mut().map_or(true, |b| b.can_acquire_at(1, now))
+            && self.bandwidth.as_mut().map_or(true, |b| b.can_acquire_at(size, now));
+        if within_limits {
+            if let Some(bucket) = message_bucket {
+                bucket.acquire(1);
+            }
+            if let Some(bucket) = self.bandwidth.as_mut() {
+                bucket.acquire(size);
+            }
+        } else if msg_type.is_some() {
+            self.dropped.increment(size);
+        }
+        within_limits
+    }
+
+    /// Number of messages dropped within the last minute.
+    pub fn dropped_per_min(&self) -> u64 {
+        self.dropped.count_per_min()
+    }
+
+    /// Whether the peer keeps exceeding the limits and should be banned.
+    pub fn is_abusive(&self) -> bool {
+        self.dropped_per_min() > self.max_dropped_messages_per_min
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_token_bucket() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit { burst: 3, rate: 2 });
+        bucket.last_refill = start;
+        for _ in 0..3 {
+            assert!(bucket.try_acquire_at(1, start));
+        }
+        assert!(!bucket.try_acquire_at(1, start));
+        // Two tokens are added every second.
+        assert!(bucket.try_acquire_at(1, start + Duration::from_millis(500)));
+        assert!(!bucket.try_acquire_at(1, start + Duration::from_millis(500)));
+        // The bucket never holds more than `burst` tokens.
+        let later = start + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(bucket.try_acquire_at(1, later));
+        }
+        assert!(!bucket.try_acquire_at(1, later));
+    }
+
+    #[test]
+    fn test_token_bucket_large_amount() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit { burst: 100, rate: 100 });
+        bucket.last_refill = start;
+        assert!(bucket.try_acquire_at(250, start));
+        // The bucket has to be refilled for the excess before anything else is let through.
+        assert!(!bucket.try_acquire_at(1, start + Duration::from_secs(1)));
+        assert!(bucket.try_acquire_at(1, start + Duration::from_millis(1600)));
+    }
+
+    #[test]
+    fn test_peer_rate_limiter() {
+        let mut limits = RateLimits::default();
+        limits.messages.insert("BlockRequest".to_string(), RateLimit { burst: 2, rate: 0 });
+        limits.max_dropped_messages_per_min = 2;
+        let mut limiter = PeerRateLimiter::new(&limits);
+
+        assert!(limiter.check(Some("BlockRequest"), 10));
+        assert!(limiter.check(Some("BlockRequest"), 10));
+        // Messages without limits are never dropped.
+        assert!(limiter.check(Some("Block"), 10));
+        assert!(!limiter.check(Some("BlockRequest"), 10));
+        assert!(!limiter.check(Some("BlockRequest"), 10));
+        assert_eq!(limiter.dropped_per_min(), 2);
+        assert!(!limiter.is_abusive());
+        assert!(!limiter.check(Some("BlockRequest"), 10));
+        assert!(limiter.is_abusive());
+    }
+
+    #[test]
+    fn test_peer_rate_limiter_bandwidth() {
+        let limits = RateLimits {
+            messages: BTreeMap::new(),
+            bandwidth: Some(RateLimit { burst: 100, rate: 0 }),
+            max_dropped_messages_per_min: 10,
+        };
+        let mut limiter = PeerRateLimiter::new(&limits);
+        assert!(limiter.check(Some("Block"), 60));
+        assert!(!limiter.check(Some("Block"), 60));
+        assert!(limiter.check(Some("Block"), 40));
+        assert!(!limiter.check(Some("Transaction"), 1));
+    }
+
+    #[test]
+    fn test_peer_rate_limiter_message_and_bandwidth() {
+        let mut limits = RateLimits::default();
+        limits.messages.insert("BlockRequest".to_string(), RateLimit { burst: 2, rate: 0 });
+        limits.bandwidth = Some(RateLimit { burst: 100, rate: 0 });
+        let mut limiter = PeerRateLimiter::new(&limits);
+        let now = Instant::now();
+
+        assert!(limiter.check_at(Some("BlockRequest"), 50, now));
+        // Dropped over the bandwidth, without taking a BlockRequest token.
+        assert!(!limiter.check_at(Some("BlockRequest"), 60, now));
+        assert!(limiter.check_at(Some("BlockRequest"), 10, now));
+        // Dropped over the BlockRequest limit, without taking bandwidth.
+        assert!(!limiter.check_at(Some("BlockRequest"), 10, now));
+        assert!(limiter.check_at(Some("Block"), 40, now));
+        assert!(!limiter.check_at(Some("Block"), 1, now));
+        assert_eq!(limiter.dropped_per_min(), 3);
+    }
+
+    #[test]
+    fn test_peer_rate_limiter_forwarded() {
+        let mut limits = RateLimits::default();
+        limits.messages.insert("BlockRequest".to_string(), RateLimit { burst: 1, rate: 0 });
+        limits.bandwidth = Some(RateLimit { burst: 100, rate: 0 });
+        limits.max_dropped_messages_per_min = 0;
+        let mut limiter = PeerRateLimiter::new(&limits);
+
+        // Forwarded messages are not limited by their type.
+        assert!(limiter.check(None, 10));
+        assert!(limiter.check(None, 10));
+        assert!(limiter.check(Some("BlockRequest"), 10));
+        // Forwarded messages over the bandwidth are dropped, but never make the peer abusive.
+        assert!(!limiter.check(None, 100));
+        assert_eq!(limiter.dropped_per_min(), 0);
+        assert!(!limiter.is_abusive());
+        assert!(!limiter.check(Some("BlockRequest"), 10));
+        assert!(limiter.is_abusive());
+    }
+
+    #[test]
+    fn test_rate_limits_unknown_message_type() {
+        let limits: RateLimits = serde_json::from_str(
+            r#"{"messages": {"StateRequestPart": {"burst": 1, "rate": 1}, "Ping": {"burst": 1, "rate": 1}}}"#,
+        )
+        .unwrap();
+        assert_eq!(limits.messages.len(), 2);
+        assert_eq!(
+            limits.max_dropped_messages_per_min,
+            RateLimits::default().max_dropped_messages_per_min
+        );
+        for msg_type in &["Routed", "BlockRequests"] {
+            let config =
+                format!(r#"{{"messages": {{"{}": {{"burst": 1, "rate": 1}}}}}}"#, msg_type);
+            assert!(serde_json::from_str::<RateLimits>(&config).is_err());
+        }
+    }
+}
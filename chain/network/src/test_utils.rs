@@ -13,7 +13,9 @@ use near_primitives::hash::hash;
 use near_primitives::network::PeerId;
 use near_primitives::types::EpochId;
 use near_primitives::utils::index_to_bytes;
+use near_primitives::version::PROTOCOL_VERSION;
 
+use crate::rate_limiter::RateLimits;
 use crate::types::{NetworkConfig, NetworkInfo, PeerInfo, ReasonForBan, ROUTED_MESSAGE_TTL};
 use crate::{NetworkAdapter, NetworkRequests, NetworkResponses, PeerManagerActor};
 use futures::future::BoxFuture;
@@ -78,8 +80,11 @@ impl NetworkConfig {
             highest_peer_horizon: 5,
             push_info_period: Duration::from_millis(100),
             blacklist: HashMap::new(),
+            allowed_peers: None,
             outbound_disabled: false,
             archive: false,
+            rate_limits: RateLimits::default(),
+            protocol_version: PROTOCOL_VERSION,
         }
     }
 }
@@ -26,8 +26,12 @@ use near_primitives::sharding::{
     ChunkHash, PartialEncodedChunk, PartialEncodedChunkPart, PartialEncodedChunkV1,
     PartialEncodedChunkWithArcReceipts, ReceiptProof, ShardChunkHeader,
 };
+#[cfg(feature = "sandbox")]
+use near_primitives::state_record::StateRecord;
 use near_primitives::syncing::{ShardStateSyncResponse, ShardStateSyncResponseV1};
 use near_primitives::transaction::{ExecutionOutcomeWithIdAndProof, SignedTransaction};
+#[cfg(feature = "sandbox")]
+use near_primitives::types::BlockHeightDelta;
 use near_primitives::types::{AccountId, BlockHeight, BlockReference, EpochId, ShardId};
 use near_primitives::utils::{from_timestamp, to_timestamp};
 use near_primitives::version::{
@@ -35,7 +39,9 @@ use near_primitives::version::{
 };
 use near_primitives::views::{FinalExecutionOutcomeView, QueryRequest, QueryResponse};
 
+use crate::encryption::{is_encrypted_version, EphemeralKey};
 use crate::peer::Peer;
+use crate::rate_limiter::RateLimits;
 #[cfg(feature = "metric_recorder")]
 use crate::recorder::MetricRecorder;
 use crate::routing::{Edge, EdgeInfo, RoutingTableInfo};
@@ -166,7 +172,7 @@ impl From<PeerChainInfo> for PeerChainInfoV2 {
 }
 
 /// Peer type.
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Serialize, Copy, Clone, Debug, Eq, PartialEq)]
 pub enum PeerType {
     /// Inbound session
     Inbound,
@@ -187,9 +193,14 @@ pub enum PeerStatus {
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, PartialEq, Eq, Clone, Debug)]
 pub enum HandshakeFailureReason {
-    ProtocolVersionMismatch { version: u32, oldest_supported_version: u32 },
+    ProtocolVersionMismatch {
+        version: u32,
+        oldest_supported_version: u32,
+    },
     GenesisMismatch(GenesisId),
     InvalidTarget,
+    /// The node only accepts connections from its allowed peers.
+    NotAllowed,
 }
 
 impl fmt::Display for HandshakeFailureReason {
@@ -200,7 +211,7 @@ impl fmt::Display for HandshakeFailureReason {
 
 impl std::error::Error for HandshakeFailureReason {}
 
-#[derive(BorshSerialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[derive(Serialize, PartialEq, Eq, Clone, Debug)]
 pub struct Handshake {
     pub version: u32,
     /// Oldest supported protocol version.
@@ -215,6 +226,9 @@ pub struct Handshake {
     pub chain_info: PeerChainInfoV2,
     /// Info for new edge.
     pub edge_info: EdgeInfo,
+    /// Sender's ephemeral key for the encrypted session. Only present on the wire starting from
+    /// `ProtocolFeature::EncryptedPeerConnection`, where it is required.
+    pub ephemeral_key: Option<EphemeralKey>,
 }
 
 /// Struct describing the layout for Handshake.
@@ -247,6 +261,7 @@ impl Handshake {
         listen_port: Option<u16>,
         chain_info: PeerChainInfoV2,
         edge_info: EdgeInfo,
+        ephemeral_key: Option<EphemeralKey>,
     ) -> Self {
         Handshake {
             version,
@@ -256,7 +271,33 @@ impl Handshake {
             listen_port,
             chain_info,
             edge_info,
+            ephemeral_key,
+        }
+    }
+}
+
+// Use custom serializer for Handshake, the ephemeral key is only sent to peers that support it.
+impl BorshSerialize for Handshake {
+    fn serialize<W: io::Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.version.serialize(writer)?;
+        self.oldest_supported_version.serialize(writer)?;
+        self.peer_id.serialize(writer)?;
+        self.target_peer_id.serialize(writer)?;
+        self.listen_port.serialize(writer)?;
+        self.chain_info.serialize(writer)?;
+        self.edge_info.serialize(writer)?;
+        if is_encrypted_version(self.version) {
+            match &self.ephemeral_key {
+                Some(ephemeral_key) => ephemeral_key.serialize(writer)?,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Handshake requires an ephemeral key",
+                    ))
+                }
+            }
         }
+        Ok(())
     }
 }
 
@@ -276,7 +317,11 @@ impl BorshDeserialize for Handshake {
 
         if OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION <= version && version <= PROTOCOL_VERSION {
             // If we support this version, then try to deserialize with custom deserializer
-            HandshakeAutoDes::deserialize(buf).map(Into::into)
+            let mut handshake: Handshake = HandshakeAutoDes::deserialize(buf)?.into();
+            if is_encrypted_version(version) {
+                handshake.ephemeral_key = Some(EphemeralKey::deserialize(buf)?);
+            }
+            Ok(handshake)
         } else {
             Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidData,
@@ -299,6 +344,7 @@ impl From<HandshakeAutoDes> for Handshake {
             listen_port: handshake.listen_port,
             chain_info: handshake.chain_info,
             edge_info: handshake.edge_info,
+            ephemeral_key: None,
         }
     }
 }
@@ -405,6 +451,7 @@ impl From<HandshakeV2> for Handshake {
             listen_port: handshake.listen_port,
             chain_info: handshake.chain_info.into(),
             edge_info: handshake.edge_info,
+            ephemeral_key: None,
         }
     }
 }
@@ -878,6 +925,10 @@ pub struct NetworkConfig {
     /// Peers on blacklist by IP:Port.
     /// Nodes will not accept or try to establish connection to such peers.
     pub blacklist: HashMap<IpAddr, BlockedPorts>,
+    /// Private mode, for validators running behind sentry nodes. If set, only these peers can
+    /// connect to this node and it only connects to them on its own. Its address is not sent in
+    /// handshakes, so peers don't gossip it.
+    pub allowed_peers: Option<HashSet<PeerId>>,
     /// Flag to disable outbound connections. When this flag is active, nodes will not try to
     /// establish connection with other nodes, but will accept incoming connection if other requirements
     /// are satisfied.
@@ -885,6 +936,11 @@ pub struct NetworkConfig {
     pub outbound_disabled: bool,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
+    /// Limits on the messages received from each peer.
+    pub rate_limits: RateLimits,
+    /// Highest protocol version used to talk with peers.
+    /// This should ALWAYS be `PROTOCOL_VERSION`. Only set lower in tests to emulate old peers.
+    pub protocol_version: ProtocolVersion,
 }
 
 impl NetworkConfig {
@@ -1040,7 +1096,11 @@ pub struct SendMessage {
 pub struct Consolidate {
     pub actor: Addr<Peer>,
     pub peer_info: PeerInfo,
+    /// Address of the other end of the connection.
+    pub peer_addr: SocketAddr,
     pub peer_type: PeerType,
+    /// Protocol version negotiated with the peer.
+    pub protocol_version: ProtocolVersion,
     pub chain_info: PeerChainInfoV2,
     // Edge information from this node.
     // If this is None it implies we are outbound connection, so we need to create our
@@ -1059,6 +1119,8 @@ pub enum ConsolidateResponse {
     Accept(Option<EdgeInfo>),
     InvalidNonce(Box<Edge>),
     Reject,
+    /// The peer is not allowed to connect to this node.
+    NotAllowed,
 }
 
 /// Unregister message from Peer to PeerManager.
@@ -1133,6 +1195,8 @@ pub enum ReasonForBan {
     InvalidPeerId = 8,
     InvalidHash = 9,
     InvalidEdge = 10,
+    RateLimitExceeded = 11,
+    Manual = 12,
 }
 
 /// Banning signal sent from Peer instance to PeerManager
@@ -1244,9 +1308,28 @@ pub enum NetworkRequests {
     PingTo(usize, PeerId),
     /// Fetch all received ping and pong so far.
     FetchPingPongInfo,
+    /// Fetch the state of the peer manager for debugging.
+    FetchDebugInfo,
 
     /// A challenge to invalidate a block.
     Challenge(Challenge),
+
+    /// Operator request from the admin RPC.
+    Admin(NetworkAdminRequest),
+}
+
+/// Operator requests to manage the peers of this node at runtime.
+/// Changes to the peer store are persisted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkAdminRequest {
+    /// Ban the peer for `ban_window`, disconnecting it if it is connected.
+    BanPeer(PeerId),
+    /// Lift the ban of a banned peer.
+    UnbanPeer(PeerId),
+    /// Add the peer to the peer store as a trusted peer and connect to it.
+    ConnectPeer(PeerInfo),
+    /// Drop the connection with an active peer.
+    DisconnectPeer(PeerId),
 }
 
 /// Messages from PeerManager to Peer
@@ -1271,6 +1354,13 @@ pub struct FullPeerInfo {
     pub edge_info: EdgeInfo,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct RateLimitedPeer {
+    pub peer_id: PeerId,
+    /// Number of messages dropped within the last minute.
+    pub dropped_messages_per_min: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct KnownProducer {
     pub account_id: AccountId,
@@ -1278,6 +1368,52 @@ pub struct KnownProducer {
     pub peer_id: PeerId,
 }
 
+/// State of the peer manager, used to debug connectivity.
+#[derive(Serialize, Debug)]
+pub struct NetworkDebugInfo {
+    pub peer_id: PeerId,
+    pub active_peers: Vec<ActivePeerDebugInfo>,
+    /// All peers in the peer store, including banned ones.
+    pub known_peers: Vec<KnownPeerDebugInfo>,
+    /// Latest known update of every edge in the routing table, including removed edges.
+    pub edges: Vec<Edge>,
+    /// Routed messages whose responses should be sent back to the given peer.
+    pub route_back: Vec<RouteBackDebugInfo>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ActivePeerDebugInfo {
+    pub peer_info: PeerInfo,
+    pub peer_type: PeerType,
+    pub chain_info: PeerChainInfoV2,
+    pub sent_bytes_per_sec: u64,
+    pub received_bytes_per_sec: u64,
+    pub dropped_messages_per_min: u64,
+    /// Seconds since the connection was established.
+    pub connected_for_secs: u64,
+    /// Seconds since the last message was received from the peer.
+    pub last_message_secs_ago: u64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct KnownPeerDebugInfo {
+    pub peer_info: PeerInfo,
+    pub status: KnownPeerStatus,
+    /// Timestamps in nanoseconds.
+    pub first_seen: u64,
+    pub last_seen: u64,
+    /// Timestamp in nanoseconds at which a banned peer is unbanned.
+    pub ban_expires_at: Option<u64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RouteBackDebugInfo {
+    pub hash: CryptoHash,
+    pub peer_id: PeerId,
+    /// Timestamp in milliseconds at which the routed message was received.
+    pub received_at: u64,
+}
+
 #[derive(Debug)]
 pub struct NetworkInfo {
     pub active_peers: Vec<FullPeerInfo>,
@@ -1288,6 +1424,10 @@ pub struct NetworkInfo {
     pub received_bytes_per_sec: u64,
     /// Accounts of known block and chunk producers from routing table.
     pub known_producers: Vec<KnownProducer>,
+    /// Limits on the messages received from each peer.
+    pub rate_limits: RateLimits,
+    /// Active peers which had messages dropped due to rate limits within the last minute.
+    pub rate_limited_peers: Vec<RateLimitedPeer>,
     #[cfg(feature = "metric_recorder")]
     pub metric_recorder: MetricRecorder,
 }
@@ -1312,6 +1452,8 @@ pub enum NetworkResponses {
     BanPeer(ReasonForBan),
     EdgeUpdate(Box<Edge>),
     RouteNotFound,
+    DebugInfo(Box<NetworkDebugInfo>),
+    AdminResult(Result<(), String>),
 }
 
 impl<A, M> MessageResponse<A, M> for NetworkResponses
@@ -1385,6 +1527,26 @@ pub enum NetworkAdversarialMessage {
     AdvSetSyncInfo(u64),
 }
 
+#[cfg(feature = "sandbox")]
+#[derive(Debug)]
+pub enum NetworkSandboxMessage {
+    /// Write the records into the state with the next block.
+    SandboxPatchState(Vec<StateRecord>),
+    /// Whether the records of the last `SandboxPatchState` are in the state already.
+    SandboxPatchStateStatus,
+    /// Produce the given number of blocks right away.
+    SandboxFastForward(BlockHeightDelta),
+}
+
+#[cfg(feature = "sandbox")]
+#[derive(Eq, PartialEq, Debug)]
+pub enum SandboxResponse {
+    SandboxPatchStateFinished(bool),
+    /// Height of the head after fast forwarding.
+    SandboxFastForwardFinished(BlockHeight),
+    SandboxFastForwardFailed(String),
+}
+
 #[derive(Debug, strum::AsRefStr)]
 // TODO(#1313): Use Box
 #[allow(clippy::large_enum_variant)]
@@ -1392,6 +1554,9 @@ pub enum NetworkClientMessages {
     #[cfg(feature = "adversarial")]
     Adversarial(NetworkAdversarialMessage),
 
+    #[cfg(feature = "sandbox")]
+    Sandbox(NetworkSandboxMessage),
+
     /// Received transaction.
     Transaction {
         transaction: SignedTransaction,
@@ -1433,6 +1598,10 @@ pub enum NetworkClientResponses {
     #[cfg(feature = "adversarial")]
     AdvResult(u64),
 
+    /// Sandbox controls.
+    #[cfg(feature = "sandbox")]
+    SandboxResult(SandboxResponse),
+
     /// No response.
     NoResponse,
     /// Valid transaction inserted into mempool as response to Transaction.
@@ -1556,6 +1725,8 @@ pub struct PeerStatsResult {
     pub is_abusive: bool,
     /// Counts of incoming/outgoing messages from given peer.
     pub message_counts: (u64, u64),
+    /// Number of messages from the peer dropped within the last minute due to rate limits.
+    pub dropped_messages_per_min: u64,
 }
 
 impl<A, M> MessageResponse<A, M> for PeerStatsResult
@@ -1,6 +1,8 @@
 pub use runner::*;
 use std::time::Duration;
 
+use near_network::types::NetworkAdminRequest;
+
 mod runner;
 
 /// Check we don't try to connect to a banned peer and we don't accept
@@ -51,3 +53,27 @@ fn connect_to_unbanned_peer() {
 
     start_test(runner);
 }
+
+/// Check that peers banned and unbanned through admin requests disconnect and connect again.
+#[test]
+fn admin_ban_and_unban_peer() {
+    let mut runner = Runner::new(2, 2)
+        .enable_outbound()
+        .use_boot_nodes(vec![0, 1])
+        .ban_window(Duration::from_secs(60));
+
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::CheckRoutingTable(1, vec![(0, vec![0])]));
+
+    runner.push_action(admin_request(0, 1, NetworkAdminRequest::BanPeer));
+    runner.push(Action::Wait(1000));
+    runner.push(Action::CheckRoutingTable(0, vec![]));
+    runner.push(Action::CheckRoutingTable(1, vec![]));
+
+    // The ban would last for a minute, but it's lifted right away.
+    runner.push_action(admin_request(0, 1, NetworkAdminRequest::UnbanPeer));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::CheckRoutingTable(1, vec![(0, vec![0])]));
+
+    start_test(runner);
+}
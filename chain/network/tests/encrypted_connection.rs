@@ -0,0 +1,135 @@
+#![cfg(feature = "protocol_feature_encrypted_peer_connection")]
+pub use runner::*;
+
+use near_primitives::version::{
+    ProtocolFeature, ProtocolVersion, PROTOCOL_FEATURES_TO_VERSION_MAPPING,
+};
+
+mod runner;
+
+/// Latest protocol version without encrypted connections.
+fn old_protocol_version() -> ProtocolVersion {
+    PROTOCOL_FEATURES_TO_VERSION_MAPPING[&ProtocolFeature::EncryptedPeerConnection] - 1
+}
+
+/// Check that routed messages go through encrypted connections.
+#[test]
+fn encrypted_ping_pong() {
+    let mut runner = Runner::new(3, 3);
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::AddEdge(1, 2));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1]), (2, vec![1])]));
+    runner.push(Action::PingTo(0, 0, 2));
+    runner.push(Action::CheckPingPong(2, vec![(0, 0)], vec![]));
+    runner.push(Action::CheckPingPong(0, vec![], vec![(0, 2)]));
+
+    start_test(runner);
+}
+
+/// Check that a node connects to an old node which doesn't support encryption.
+#[test]
+fn connect_to_old_peer() {
+    let mut runner = Runner::new(2, 2).set_protocol_version(1, old_protocol_version());
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::CheckRoutingTable(1, vec![(0, vec![0])]));
+    runner.push(Action::PingTo(0, 0, 1));
+    runner.push(Action::CheckPingPong(1, vec![(0, 0)], vec![]));
+    runner.push(Action::CheckPingPong(0, vec![], vec![(0, 1)]));
+
+    start_test(runner);
+}
+
+/// Check that an old node which doesn't support encryption connects to a node.
+#[test]
+fn connect_from_old_peer() {
+    let mut runner = Runner::new(2, 2).set_protocol_version(0, old_protocol_version());
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::CheckRoutingTable(1, vec![(0, vec![0])]));
+    runner.push(Action::PingTo(1, 0, 0));
+    runner.push(Action::CheckPingPong(0, vec![(0, 1)], vec![]));
+    runner.push(Action::CheckPingPong(1, vec![], vec![(0, 0)]));
+
+    start_test(runner);
+}
+
+/// Check that messages are routed through a mix of encrypted and plain connections.
+#[test]
+fn mixed_network_ping_pong() {
+    let mut runner = Runner::new(3, 3).set_protocol_version(1, old_protocol_version());
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::AddEdge(1, 2));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1]), (2, vec![1])]));
+    runner.push(Action::CheckRoutingTable(2, vec![(1, vec![1]), (0, vec![1])]));
+    runner.push(Action::PingTo(0, 0, 2));
+    runner.push(Action::CheckPingPong(2, vec![(0, 0)], vec![]));
+    runner.push(Action::CheckPingPong(0, vec![], vec![(0, 2)]));
+
+    start_test(runner);
+}
+
+/// Check that encrypted connections work through a proxy which doesn't tamper with them.
+#[test]
+fn encrypted_connection_through_proxy() {
+    let mut runner = Runner::new(2, 2);
+
+    runner.push(Action::AddEdgeThroughProxy(0, 1, None));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::PingTo(0, 0, 1));
+    runner.push(Action::CheckPingPong(1, vec![(0, 0)], vec![]));
+    runner.push(Action::CheckPingPong(0, vec![], vec![(0, 1)]));
+
+    start_test(runner);
+}
+
+/// Check that a node doesn't fall back to a plain connection when a man in the middle rewrites
+/// its handshake to a version without encryption.
+#[test]
+fn downgraded_handshake_rejected() {
+    let mut runner = Runner::new(2, 2);
+
+    let attack = ProxyAttack::DowngradeHandshake(old_protocol_version());
+    runner.push(Action::AddEdgeThroughProxy(0, 1, Some(attack)));
+    runner.push(Action::Wait(1000));
+    runner.push(Action::CheckRoutingTable(0, vec![]));
+
+    start_test(runner);
+}
+
+/// Check that a node retries in plain text when a `HandshakeFailure` asks for a version without
+/// encryption and the peer isn't known to support it, since it could be an old node.
+#[test]
+fn handshake_failure_from_unknown_peer_accepted() {
+    let mut runner = Runner::new(2, 2);
+
+    let attack = ProxyAttack::ForgeHandshakeFailure(old_protocol_version());
+    runner.push(Action::AddEdgeThroughProxy(0, 1, Some(attack)));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+
+    start_test(runner);
+}
+
+/// Check that a node doesn't retry in plain text when a man in the middle answers its handshake
+/// with a forged `HandshakeFailure` and the peer already had an encrypted connection with it.
+#[test]
+fn forged_handshake_failure_rejected() {
+    let mut runner = Runner::new(2, 2);
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1])]));
+    runner.push(Action::Stop(1));
+    runner.push(Action::CheckRoutingTable(0, vec![]));
+    runner.push_action(restart(1));
+
+    let attack = ProxyAttack::ForgeHandshakeFailure(old_protocol_version());
+    runner.push(Action::AddEdgeThroughProxy(0, 1, Some(attack)));
+    runner.push(Action::Wait(1000));
+    runner.push(Action::CheckRoutingTable(0, vec![]));
+
+    start_test(runner);
+}
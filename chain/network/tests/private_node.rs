@@ -0,0 +1,25 @@
+pub use runner::*;
+
+mod runner;
+
+/// Check that a private node only accepts connections from its allowed peers, and that its
+/// account is still reachable through them.
+#[test]
+fn private_node_behind_sentry() {
+    let mut runner = Runner::new(3, 1).set_allowed_peers(0, vec![1]);
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::AddEdge(2, 1));
+    runner.push(Action::AddEdge(2, 0));
+    // Wait for the connection from node 2 to be rejected.
+    runner.push(Action::Wait(1000));
+
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1]), (2, vec![1])]));
+    runner.push(Action::CheckRoutingTable(2, vec![(0, vec![1]), (1, vec![1])]));
+    runner.push(Action::CheckDebugInfo(0, vec![1]));
+    runner.push(Action::CheckAccountId(2, vec![0]));
+    runner.push(Action::PingTo(2, 0, 0));
+    runner.push(Action::CheckPingPong(0, vec![(0, 2)], vec![]));
+
+    start_test(runner);
+}
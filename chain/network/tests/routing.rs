@@ -276,3 +276,16 @@ fn archival_node() {
 
     start_test(runner);
 }
+
+#[test]
+fn network_debug_info() {
+    let mut runner = Runner::new(3, 2);
+
+    runner.push(Action::AddEdge(0, 1));
+    runner.push(Action::AddEdge(1, 2));
+    runner.push(Action::CheckRoutingTable(0, vec![(1, vec![1]), (2, vec![1])]));
+    runner.push(Action::CheckDebugInfo(0, vec![1]));
+    runner.push(Action::CheckDebugInfo(1, vec![0, 2]));
+
+    start_test(runner);
+}
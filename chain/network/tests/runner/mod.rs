@@ -1,10 +1,14 @@
 use std::collections::HashSet;
+use std::io::{self, Read, Write};
 use std::iter::Iterator;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock};
+use std::thread;
 use std::time::Duration;
 
 use actix::{Actor, Addr, AsyncContext, Context, Handler, Message, System};
+use borsh::{BorshDeserialize, BorshSerialize};
 use chrono::{DateTime, Utc};
 use futures::{future, FutureExt, TryFutureExt};
 
@@ -18,13 +22,20 @@ use near_network::test_utils::{
     convert_boot_nodes, expected_routing_tables, open_port, peer_id_from_seed, BanPeerSignal,
     GetInfo, StopSignal, WaitOrTimeout,
 };
-use near_network::types::{OutboundTcpConnect, ROUTED_MESSAGE_TTL};
+use near_network::types::{
+    HandshakeFailureReason, NetworkAdminRequest, OutboundTcpConnect, PeerMessage,
+    ROUTED_MESSAGE_TTL,
+};
 use near_network::utils::blacklist_from_iter;
 use near_network::{
     NetworkConfig, NetworkRecipient, NetworkRequests, NetworkResponses, PeerInfo, PeerManagerActor,
 };
+use near_primitives::network::PeerId;
 use near_primitives::types::{AccountId, ValidatorId};
 use near_primitives::validator_signer::InMemoryValidatorSigner;
+use near_primitives::version::{
+    ProtocolVersion, OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION, PROTOCOL_VERSION,
+};
 use near_store::test_utils::create_test_store;
 use near_telemetry::{TelemetryActor, TelemetryConfig};
 
@@ -106,15 +117,88 @@ pub fn setup_network_node(
     peer_manager
 }
 
+fn read_frame(stream: &mut TcpStream) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    stream.read_exact(&mut len)?;
+    let mut frame = vec![0u8; u32::from_le_bytes(len) as usize];
+    stream.read_exact(&mut frame)?;
+    Ok(frame)
+}
+
+fn write_frame(stream: &mut TcpStream, frame: &[u8]) -> io::Result<()> {
+    stream.write_all(&(frame.len() as u32).to_le_bytes())?;
+    stream.write_all(frame)
+}
+
+/// How a proxy tampers with the connection going through it, like a man in the middle would.
+#[derive(Clone, Copy)]
+pub enum ProxyAttack {
+    /// Rewrites the first handshake to the given version and strips its ephemeral key.
+    DowngradeHandshake(ProtocolVersion),
+    /// Answers the first handshake, instead of forwarding it, with a `HandshakeFailure` asking
+    /// for the given version.
+    ForgeHandshakeFailure(ProtocolVersion),
+}
+
+/// Starts a proxy for a single connection to `target` and returns its address.
+fn start_proxy(target: PeerInfo, attack: Option<ProxyAttack>) -> SocketAddr {
+    let listener = TcpListener::bind(("127.0.0.1", open_port())).unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || -> io::Result<()> {
+        let (mut client, _) = listener.accept()?;
+        let mut server = TcpStream::connect(target.addr.unwrap())?;
+        let (mut client_out, mut server_in) = (client.try_clone()?, server.try_clone()?);
+        thread::spawn(move || {
+            let _ = io::copy(&mut server_in, &mut client_out);
+            let _ = client_out.shutdown(Shutdown::Both);
+        });
+
+        let mut handshake = read_frame(&mut client)?;
+        match attack {
+            Some(ProxyAttack::DowngradeHandshake(version)) => {
+                if let Ok(PeerMessage::Handshake(mut message)) =
+                    PeerMessage::try_from_slice(&handshake)
+                {
+                    message.version = version;
+                    message.ephemeral_key = None;
+                    handshake = PeerMessage::Handshake(message).try_to_vec()?;
+                }
+            }
+            Some(ProxyAttack::ForgeHandshakeFailure(version)) => {
+                let failure = PeerMessage::HandshakeFailure(
+                    target,
+                    HandshakeFailureReason::ProtocolVersionMismatch {
+                        version,
+                        oldest_supported_version: OLDEST_BACKWARD_COMPATIBLE_PROTOCOL_VERSION,
+                    },
+                );
+                write_frame(&mut client, &failure.try_to_vec()?)?;
+                handshake = read_frame(&mut client)?;
+            }
+            None => {}
+        }
+        write_frame(&mut server, &handshake)?;
+        let _ = io::copy(&mut client, &mut server);
+        server.shutdown(Shutdown::Both)
+    });
+    addr
+}
+
 // TODO: Deprecate this in favor of separate functions.
 pub enum Action {
     AddEdge(usize, usize),
+    // Connect `source` to `target` through a proxy, which tampers with the connection if an
+    // attack is given.
+    AddEdgeThroughProxy(usize, usize, Option<ProxyAttack>),
     CheckRoutingTable(usize, Vec<(usize, Vec<usize>)>),
     CheckAccountId(usize, Vec<usize>),
     // Send ping from `source` with `nonce` to `target`
     PingTo(usize, usize, usize),
     // Check for `source` received pings and pongs.
     CheckPingPong(usize, Vec<(usize, usize)>, Vec<(usize, usize)>),
+    // Check that `source` reports exactly the given active peers, and an edge to each of them,
+    // in its debug info.
+    CheckDebugInfo(usize, Vec<usize>),
     // Send stop signal to some node.
     Stop(usize),
     // Wait time in milliseconds
@@ -164,6 +248,29 @@ impl StateMachine {
                     },
                 ));
             }
+            Action::AddEdgeThroughProxy(u, v, attack) => {
+                self.actions.push(Box::new(
+                    move |info: SharedRunningInfo,
+                          flag: Arc<AtomicBool>,
+                          _ctx: &mut Context<WaitOrTimeout>,
+                          _runner| {
+                        let addr = info.read().unwrap().pm_addr[u].clone();
+                        let mut peer_info = info.read().unwrap().peers_info[v].clone();
+                        peer_info.addr = Some(start_proxy(peer_info.clone(), attack));
+                        actix::spawn(addr.send(OutboundTcpConnect { peer_info }).then(
+                            move |res| match res {
+                                Ok(_) => {
+                                    flag.store(true, Ordering::Relaxed);
+                                    future::ready(())
+                                }
+                                Err(e) => {
+                                    panic!("Error adding edge. {:?}", e);
+                                }
+                            },
+                        ));
+                    },
+                ));
+            }
             Action::CheckRoutingTable(u, expected) => self.actions.push(Box::new(
                 move |info: SharedRunningInfo,
                       flag: Arc<AtomicBool>,
@@ -350,6 +457,54 @@ impl StateMachine {
                     },
                 ));
             }
+            Action::CheckDebugInfo(source, active_peers) => {
+                self.actions.push(Box::new(
+                    move |info: SharedRunningInfo,
+                          flag: Arc<AtomicBool>,
+                          _ctx: &mut Context<WaitOrTimeout>,
+                          _runner| {
+                        let source_id = info.read().unwrap().peers_info[source].id.clone();
+                        let expected: HashSet<_> = active_peers
+                            .iter()
+                            .map(|peer| info.read().unwrap().peers_info[*peer].id.clone())
+                            .collect();
+
+                        actix::spawn(
+                            info.read()
+                                .unwrap()
+                                .pm_addr
+                                .get(source)
+                                .unwrap()
+                                .send(NetworkRequests::FetchDebugInfo)
+                                .map_err(|_| ())
+                                .and_then(move |res| {
+                                    if let NetworkResponses::DebugInfo(debug_info) = res {
+                                        let active: HashSet<_> = debug_info
+                                            .active_peers
+                                            .iter()
+                                            .map(|peer| peer.peer_info.id.clone())
+                                            .collect();
+                                        let edges_ok = expected.iter().all(|peer_id| {
+                                            debug_info.edges.iter().any(|edge| {
+                                                edge.contains_peer(&source_id)
+                                                    && edge.contains_peer(peer_id)
+                                            })
+                                        });
+                                        if debug_info.peer_id == source_id
+                                            && active == expected
+                                            && edges_ok
+                                        {
+                                            flag.store(true, Ordering::Relaxed);
+                                        }
+                                    }
+
+                                    future::ok(())
+                                })
+                                .map(drop),
+                        );
+                    },
+                ));
+            }
         }
     }
 }
@@ -365,6 +520,8 @@ struct TestConfig {
     minimum_outbound_peers: Option<u32>,
     safe_set_size: Option<u32>,
     archive: bool,
+    protocol_version: ProtocolVersion,
+    allowed_peers: Option<Vec<usize>>,
 }
 
 impl TestConfig {
@@ -380,6 +537,8 @@ impl TestConfig {
             minimum_outbound_peers: None,
             safe_set_size: None,
             archive: false,
+            protocol_version: PROTOCOL_VERSION,
+            allowed_peers: None,
         }
     }
 }
@@ -434,6 +593,18 @@ impl Runner {
         self
     }
 
+    /// Set highest protocol version of node `u`, to emulate a node running an older version.
+    pub fn set_protocol_version(mut self, u: usize, protocol_version: ProtocolVersion) -> Self {
+        self.test_config[u].protocol_version = protocol_version;
+        self
+    }
+
+    /// Run node `u` in private mode, only connecting with `allowed_peers`.
+    pub fn set_allowed_peers(mut self, u: usize, allowed_peers: Vec<usize>) -> Self {
+        self.test_config[u].allowed_peers = Some(allowed_peers);
+        self
+    }
+
     /// Specify boot nodes. By default there are no boot nodes.
     pub fn use_boot_nodes(mut self, boot_nodes: Vec<usize>) -> Self {
         self.apply_all(move |test_config| {
@@ -549,6 +720,15 @@ impl Runner {
         network_config.outbound_disabled = test_config.outbound_disabled;
         network_config.boot_nodes = boot_nodes;
         network_config.archive = test_config.archive;
+        network_config.protocol_version = test_config.protocol_version;
+        network_config.allowed_peers = test_config.allowed_peers.as_ref().map(|allowed_peers| {
+            convert_boot_nodes(
+                allowed_peers.iter().map(|ix| (accounts_id[*ix].as_str(), ports[*ix])).collect(),
+            )
+            .into_iter()
+            .map(|peer_info| peer_info.id)
+            .collect()
+        });
 
         network_config.ideal_connections_lo =
             test_config.ideal_connections.map_or(network_config.ideal_connections_lo, |(lo, _)| lo);
@@ -799,6 +979,37 @@ pub fn ban_peer(target_peer: usize, banned_peer: usize) -> ActionFn {
     )
 }
 
+/// Send an admin request about `peer` to `target_peer`, and check that it succeeds.
+pub fn admin_request(
+    target_peer: usize,
+    peer: usize,
+    request: fn(PeerId) -> NetworkAdminRequest,
+) -> ActionFn {
+    Box::new(
+        move |info: SharedRunningInfo,
+              flag: Arc<AtomicBool>,
+              _ctx: &mut Context<WaitOrTimeout>,
+              _runner| {
+            let info = info.read().unwrap();
+            let peer_id = info.peers_info[peer].id.clone();
+            actix::spawn(
+                info.pm_addr
+                    .get(target_peer)
+                    .unwrap()
+                    .send(NetworkRequests::Admin(request(peer_id)))
+                    .map_err(|_| ())
+                    .and_then(move |res| {
+                        if let NetworkResponses::AdminResult(Ok(())) = res {
+                            flag.store(true, Ordering::Relaxed);
+                        }
+                        future::ok(())
+                    })
+                    .map(drop),
+            );
+        },
+    )
+}
+
 /// Change account id from a stopped peer. Notice this will also change its peer id, since
 /// peer_id is derived from account id with NetworkConfig::from_seed
 pub fn change_account_id(node_id: usize, account_id: String) -> ActionFn {
@@ -99,6 +99,19 @@ impl TransactionPool {
         }
     }
 
+    /// Iterates over all transactions in the pool in no particular order.
+    pub fn transactions(&self) -> impl Iterator<Item = &SignedTransaction> {
+        self.transactions.values().flatten()
+    }
+
+    /// Returns the transaction with the given hash if it is in the pool.
+    pub fn get_transaction(&self, tx_hash: &CryptoHash) -> Option<&SignedTransaction> {
+        if !self.unique_transactions.contains(tx_hash) {
+            return None;
+        }
+        self.transactions().find(|tx| &tx.get_hash() == tx_hash)
+    }
+
     pub fn len(&self) -> usize {
         self.unique_transactions.len()
     }
@@ -450,4 +463,30 @@ mod tests {
         new_nonces.sort();
         assert_ne!(nonces, new_nonces);
     }
+
+    /// Transactions inserted into the pool can be listed and looked up by hash.
+    #[test]
+    fn test_get_transaction() {
+        let mut transactions = generate_transactions("alice.near", "alice.near", 1, 5);
+        transactions.extend(generate_transactions("bob.near", "bob.near", 1, 5));
+        let mut pool = TransactionPool::new();
+        for tx in transactions.clone() {
+            pool.insert_transaction(tx);
+        }
+
+        let mut pool_hashes = pool.transactions().map(|tx| tx.get_hash()).collect::<Vec<_>>();
+        let mut expected_hashes = transactions.iter().map(|tx| tx.get_hash()).collect::<Vec<_>>();
+        pool_hashes.sort();
+        expected_hashes.sort();
+        assert_eq!(pool_hashes, expected_hashes);
+
+        for tx in &transactions {
+            assert_eq!(pool.get_transaction(&tx.get_hash()), Some(tx));
+        }
+        assert_eq!(pool.get_transaction(&hash(b"missing")), None);
+
+        pool.remove_transactions(&transactions[..1]);
+        assert_eq!(pool.get_transaction(&transactions[0].get_hash()), None);
+        assert_eq!(pool.transactions().count(), transactions.len() - 1);
+    }
 }
@@ -387,6 +387,31 @@ pub(crate) async fn collect_transactions(
     }
 }
 
+/// Converts a transaction from the mempool into a Rosetta Transaction. Since the transaction is
+/// not executed yet, the operations are only an estimate derived from its actions: they do not
+/// include fees, refunds or the outcome of function calls.
+pub(crate) fn convert_mempool_transaction(
+    signed_transaction: near_primitives::transaction::SignedTransaction,
+) -> crate::models::Transaction {
+    let transaction_hash = signed_transaction.get_hash();
+    let near_primitives::transaction::Transaction { signer_id, receiver_id, actions, .. } =
+        signed_transaction.transaction;
+    crate::models::Transaction {
+        transaction_identifier: crate::models::TransactionIdentifier {
+            hash: transaction_hash.to_base(),
+        },
+        operations: NearActions {
+            sender_account_id: signer_id,
+            receiver_account_id: receiver_id,
+            actions,
+        }
+        .into(),
+        metadata: crate::models::TransactionMetadata {
+            type_: crate::models::TransactionType::Transaction,
+        },
+    }
+}
+
 /// This is used as a common denominator for matching Rosetta Operations to
 /// and from NEAR Actions (see From and TryFrom implementations).
 ///
@@ -611,6 +636,65 @@ impl From<NearActions> for Vec<crate::models::Operation> {
                     );
                     operations.push(deploy_contract_operation);
                 }
+
+                near_primitives::transaction::Action::Delegate(action) => {
+                    let near_primitives::transaction::SignedDelegateAction {
+                        delegate_action,
+                        signature,
+                    } = action;
+
+                    let initiate_delegate_action_operation_id =
+                        crate::models::OperationIdentifier::new(&operations);
+                    operations.push(
+                        validated_operations::InitiateDelegateActionOperation {
+                            sender_account: sender_account_identifier.clone(),
+                        }
+                        .into_operation(initiate_delegate_action_operation_id.clone()),
+                    );
+
+                    let delegate_action_operation_id =
+                        crate::models::OperationIdentifier::new(&operations);
+                    operations.push(
+                        validated_operations::DelegateActionOperation {
+                            receiver_account: delegate_action.receiver_id.clone().into(),
+                            public_key: (&delegate_action.public_key).into(),
+                            nonce: delegate_action.nonce,
+                            max_block_height: delegate_action.max_block_height,
+                        }
+                        .into_related_operation(
+                            delegate_action_operation_id.clone(),
+                            vec![initiate_delegate_action_operation_id],
+                        ),
+                    );
+
+                    // The inner actions are sent from the delegate action sender to its receiver.
+                    // Their operations are numbered after the operations above.
+                    let inner_operations: Vec<crate::models::Operation> = NearActions {
+                        sender_account_id: delegate_action.sender_id,
+                        receiver_account_id: delegate_action.receiver_id,
+                        actions: delegate_action.actions,
+                    }
+                    .into();
+                    let offset = crate::models::OperationIdentifier::new(&operations).index;
+                    operations.extend(inner_operations.into_iter().map(|mut operation| {
+                        operation.operation_identifier.index += offset;
+                        for related_operation in operation.related_operations.iter_mut().flatten() {
+                            related_operation.index += offset;
+                        }
+                        operation
+                    }));
+
+                    operations.push(
+                        validated_operations::SignedDelegateActionOperation {
+                            sender_account: receiver_account_identifier.clone(),
+                            signature,
+                        }
+                        .into_related_operation(
+                            crate::models::OperationIdentifier::new(&operations),
+                            vec![delegate_action_operation_id],
+                        ),
+                    );
+                }
             }
         }
         operations
@@ -626,6 +710,38 @@ impl std::convert::TryFrom<Vec<crate::models::Operation>> for NearActions {
     /// above to understand how a single NEAR Action is represented with Rosetta
     /// Operations. The implementations are bijective (there is a test below).
     fn try_from(operations: Vec<crate::models::Operation>) -> Result<Self, Self::Error> {
+        let (sender_account_id, receiver_account_id, actions) = Self::parse_operations(operations)?;
+
+        let receiver_account_id = receiver_account_id
+            .ok_or_else(|| {
+                crate::errors::ErrorKind::InvalidInput(
+                    "There are no operations specifying receiver account".to_string(),
+                )
+            })?
+            .address;
+        Ok(Self {
+            sender_account_id: sender_account_id
+                .map(|account_identifier| account_identifier.address)
+                .unwrap_or_else(|| receiver_account_id.clone()),
+            receiver_account_id,
+            actions,
+        })
+    }
+}
+
+impl NearActions {
+    /// Converts Rosetta Operations to NEAR Actions along with the sender and the receiver accounts
+    /// if the operations specify them.
+    fn parse_operations(
+        operations: Vec<crate::models::Operation>,
+    ) -> Result<
+        (
+            Option<crate::models::AccountIdentifier>,
+            Option<crate::models::AccountIdentifier>,
+            Vec<near_primitives::transaction::Action>,
+        ),
+        crate::errors::ErrorKind,
+    > {
         let mut sender_account_id = crate::utils::InitializeOnce::new(
             "A single transaction cannot be send from multiple senders",
         );
@@ -842,12 +958,102 @@ impl std::convert::TryFrom<Vec<crate::models::Operation>> for NearActions {
                     )
                 }
 
+                crate::models::OperationType::SignedDelegateAction => {
+                    let signed_delegate_action_operation =
+                        validated_operations::SignedDelegateActionOperation::try_from(
+                            tail_operation,
+                        )?;
+                    receiver_account_id
+                        .try_set(&signed_delegate_action_operation.sender_account)?;
+
+                    // The operations of the inner actions are located between the DELEGATE_ACTION
+                    // and the SIGNED_DELEGATE_ACTION operations.
+                    let mut inner_operations = vec![];
+                    let delegate_action_operation = loop {
+                        match operations.next() {
+                            Some(operation)
+                                if operation.type_
+                                    == crate::models::OperationType::DelegateAction =>
+                            {
+                                break validated_operations::DelegateActionOperation::try_from(
+                                    operation,
+                                )?;
+                            }
+                            Some(operation)
+                                if operation.type_
+                                    == crate::models::OperationType::SignedDelegateAction =>
+                            {
+                                return Err(crate::errors::ErrorKind::InvalidInput(
+                                    "Delegate actions cannot be nested".to_string(),
+                                ));
+                            }
+                            Some(operation) => inner_operations.push(operation),
+                            None => {
+                                return Err(crate::errors::ErrorKind::InvalidInput(
+                                    "DELEGATE_ACTION operation is missing".to_string(),
+                                ))
+                            }
+                        }
+                    };
+                    inner_operations.reverse();
+                    let (inner_sender_account_id, inner_receiver_account_id, inner_actions) =
+                        Self::parse_operations(inner_operations)?;
+                    if inner_sender_account_id.map_or(false, |account_id| {
+                        account_id != signed_delegate_action_operation.sender_account
+                    }) {
+                        return Err(crate::errors::ErrorKind::InvalidInput(
+                            "Inner actions of a delegate action must be sent by the account that signed it"
+                                .to_string(),
+                        ));
+                    }
+                    if inner_receiver_account_id.map_or(false, |account_id| {
+                        account_id != delegate_action_operation.receiver_account
+                    }) {
+                        return Err(crate::errors::ErrorKind::InvalidInput(
+                            "Inner actions of a delegate action must be sent to its receiver"
+                                .to_string(),
+                        ));
+                    }
+
+                    let initiate_delegate_action_operation =
+                        validated_operations::InitiateDelegateActionOperation::try_from_option(
+                            operations.next(),
+                        )?;
+                    sender_account_id
+                        .try_set(&initiate_delegate_action_operation.sender_account)?;
+
+                    let public_key =
+                        (&delegate_action_operation.public_key).try_into().map_err(|_| {
+                            crate::errors::ErrorKind::InvalidInput(format!(
+                                "Invalid public_key: {:?}",
+                                delegate_action_operation.public_key
+                            ))
+                        })?;
+
+                    actions.push(
+                        near_primitives::transaction::SignedDelegateAction {
+                            delegate_action: near_primitives::transaction::DelegateAction {
+                                sender_id: signed_delegate_action_operation.sender_account.address,
+                                receiver_id: delegate_action_operation.receiver_account.address,
+                                actions: inner_actions,
+                                nonce: delegate_action_operation.nonce,
+                                max_block_height: delegate_action_operation.max_block_height,
+                                public_key,
+                            },
+                            signature: signed_delegate_action_operation.signature,
+                        }
+                        .into(),
+                    )
+                }
+
                 crate::models::OperationType::InitiateCreateAccount
                 | crate::models::OperationType::InitiateDeleteAccount
                 | crate::models::OperationType::InitiateAddKey
                 | crate::models::OperationType::InitiateDeleteKey
                 | crate::models::OperationType::InitiateDeployContract
                 | crate::models::OperationType::InitiateFunctionCall
+                | crate::models::OperationType::InitiateDelegateAction
+                | crate::models::OperationType::DelegateAction
                 | crate::models::OperationType::DeleteAccount => {
                     return Err(crate::errors::ErrorKind::InvalidInput(format!(
                         "Unexpected operation `{:?}`",
@@ -861,22 +1067,7 @@ impl std::convert::TryFrom<Vec<crate::models::Operation>> for NearActions {
         // backwards.
         actions.reverse();
 
-        let receiver_account_id = receiver_account_id
-            .into_inner()
-            .ok_or_else(|| {
-                crate::errors::ErrorKind::InvalidInput(
-                    "There are no operations specifying receiver account".to_string(),
-                )
-            })?
-            .address;
-        Ok(Self {
-            sender_account_id: sender_account_id
-                .into_inner()
-                .map(|account_identifier| account_identifier.address)
-                .unwrap_or_else(|| receiver_account_id.clone()),
-            receiver_account_id,
-            actions,
-        })
+        Ok((sender_account_id.into_inner(), receiver_account_id.into_inner(), actions))
     }
 }
 
@@ -1132,6 +1323,69 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_near_actions_bijection_delegate() {
+        let signer = near_crypto::InMemorySigner::from_random(
+            "receiver.near".into(),
+            near_crypto::KeyType::ED25519,
+        );
+        let delegate = |receiver_id: &str, actions| {
+            let delegate_action = near_primitives::transaction::DelegateAction {
+                sender_id: "receiver.near".into(),
+                receiver_id: receiver_id.into(),
+                actions,
+                nonce: 123,
+                max_block_height: 456,
+                public_key: near_crypto::Signer::public_key(&signer),
+            };
+            let signature = near_crypto::Signer::sign(&signer, delegate_action.get_hash().as_ref());
+            near_primitives::transaction::Action::from(
+                near_primitives::transaction::SignedDelegateAction { delegate_action, signature },
+            )
+        };
+        let transfer_action: near_primitives::transaction::Action =
+            near_primitives::transaction::TransferAction { deposit: 789 }.into();
+        let function_call_action: near_primitives::transaction::Action =
+            near_primitives::transaction::FunctionCallAction {
+                method_name: "method-name".into(),
+                args: b"args".to_vec(),
+                gas: 100500,
+                deposit: 1,
+            }
+            .into();
+
+        let delegate_actions = vec![
+            vec![delegate("contract.near", vec![])],
+            vec![delegate("contract.near", vec![function_call_action.clone()])],
+            vec![delegate("receiver.near", vec![transfer_action.clone()])],
+            vec![
+                transfer_action.clone(),
+                delegate("contract.near", vec![transfer_action, function_call_action]),
+            ],
+        ];
+
+        for actions in delegate_actions {
+            let near_actions = NearActions {
+                sender_account_id: "relayer.near".into(),
+                receiver_account_id: "receiver.near".into(),
+                actions,
+            };
+            let operations: Vec<crate::models::Operation> = near_actions.clone().into();
+            for (index, operation) in operations.iter().enumerate() {
+                assert_eq!(operation.operation_identifier.index, index as i64);
+            }
+
+            let near_actions_recreated = NearActions::try_from(operations).unwrap();
+
+            assert_eq!(near_actions_recreated.sender_account_id, near_actions.sender_account_id);
+            assert_eq!(
+                near_actions_recreated.receiver_account_id,
+                near_actions.receiver_account_id
+            );
+            assert_eq!(near_actions_recreated.actions, near_actions.actions);
+        }
+    }
+
     #[test]
     fn test_near_actions_invalid_transfer_no_amount() {
         let operations = vec![crate::models::Operation {
@@ -1563,4 +1817,47 @@ mod tests {
             Err(crate::errors::ErrorKind::InvalidInput(_))
         ));
     }
+
+    #[test]
+    fn test_convert_mempool_transaction() {
+        let signer = near_crypto::InMemorySigner::from_random(
+            "sender.near".into(),
+            near_crypto::KeyType::ED25519,
+        );
+        let actions = vec![
+            near_primitives::transaction::TransferAction { deposit: 789 }.into(),
+            near_primitives::transaction::FunctionCallAction {
+                method_name: "method-name".into(),
+                args: b"args".to_vec(),
+                gas: 100500,
+                deposit: 1,
+            }
+            .into(),
+        ];
+        let signed_transaction = near_primitives::transaction::SignedTransaction::from_actions(
+            1,
+            "sender.near".into(),
+            "receiver.near".into(),
+            &signer,
+            actions.clone(),
+            near_primitives::hash::CryptoHash::default(),
+        );
+        let transaction_hash = signed_transaction.get_hash();
+
+        let transaction = convert_mempool_transaction(signed_transaction);
+        assert_eq!(transaction.transaction_identifier.hash, transaction_hash.to_base());
+        assert_eq!(transaction.metadata.type_, crate::models::TransactionType::Transaction);
+        let expected_operations: Vec<crate::models::Operation> = NearActions {
+            sender_account_id: "sender.near".into(),
+            receiver_account_id: "receiver.near".into(),
+            actions: actions.clone(),
+        }
+        .into();
+        assert_eq!(transaction.operations, expected_operations);
+        // Without fees or refunds, the operations map back to exactly the transaction actions.
+        let near_actions = NearActions::try_from(transaction.operations).unwrap();
+        assert_eq!(near_actions.sender_account_id, "sender.near");
+        assert_eq!(near_actions.receiver_account_id, "receiver.near");
+        assert_eq!(near_actions.actions, actions);
+    }
 }
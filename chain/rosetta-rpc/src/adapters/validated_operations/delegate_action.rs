@@ -0,0 +1,63 @@
+use super::ValidatedOperation;
+
+pub(crate) struct DelegateActionOperation {
+    pub(crate) receiver_account: crate::models::AccountIdentifier,
+    pub(crate) public_key: crate::models::PublicKey,
+    pub(crate) nonce: near_primitives::types::Nonce,
+    pub(crate) max_block_height: near_primitives::types::BlockHeight,
+}
+
+impl ValidatedOperation for DelegateActionOperation {
+    const OPERATION_TYPE: crate::models::OperationType =
+        crate::models::OperationType::DelegateAction;
+
+    fn into_operation(
+        self,
+        operation_identifier: crate::models::OperationIdentifier,
+    ) -> crate::models::Operation {
+        crate::models::Operation {
+            operation_identifier,
+
+            account: self.receiver_account,
+            amount: None,
+            metadata: Some(crate::models::OperationMetadata {
+                public_key: Some(self.public_key),
+                nonce: Some(self.nonce.into()),
+                max_block_height: Some(self.max_block_height.into()),
+                ..Default::default()
+            }),
+
+            related_operations: None,
+            type_: Self::OPERATION_TYPE,
+            status: None,
+        }
+    }
+}
+
+fn required_fields_error() -> crate::errors::ErrorKind {
+    crate::errors::ErrorKind::InvalidInput(
+        "DELEGATE_ACTION operation requires `public_key`, non-negative `nonce`, and non-negative `max_block_height` being passed in the metadata".into(),
+    )
+}
+
+impl std::convert::TryFrom<crate::models::Operation> for DelegateActionOperation {
+    type Error = crate::errors::ErrorKind;
+
+    fn try_from(operation: crate::models::Operation) -> Result<Self, Self::Error> {
+        Self::validate_operation_type(operation.type_)?;
+        let metadata = operation.metadata.ok_or_else(required_fields_error)?;
+        let public_key = metadata.public_key.ok_or_else(required_fields_error)?;
+        let nonce = metadata.nonce.ok_or_else(required_fields_error)?;
+        let max_block_height = metadata.max_block_height.ok_or_else(required_fields_error)?;
+        if !nonce.is_positive() || !max_block_height.is_positive() {
+            return Err(required_fields_error());
+        }
+
+        Ok(Self {
+            receiver_account: operation.account,
+            public_key,
+            nonce: nonce.absolute_difference(),
+            max_block_height: max_block_height.absolute_difference(),
+        })
+    }
+}
@@ -2,33 +2,39 @@ use std::convert::TryFrom;
 
 pub(crate) use self::add_key::AddKeyOperation;
 pub(crate) use self::create_account::CreateAccountOperation;
+pub(crate) use self::delegate_action::DelegateActionOperation;
 pub(crate) use self::delete_account::DeleteAccountOperation;
 pub(crate) use self::delete_key::DeleteKeyOperation;
 pub(crate) use self::deploy_contract::DeployContractOperation;
 pub(crate) use self::function_call::FunctionCallOperation;
 pub(crate) use self::initiate_add_key::InitiateAddKeyOperation;
 pub(crate) use self::initiate_create_account::InitiateCreateAccountOperation;
+pub(crate) use self::initiate_delegate_action::InitiateDelegateActionOperation;
 pub(crate) use self::initiate_delete_account::InitiateDeleteAccountOperation;
 pub(crate) use self::initiate_delete_key::InitiateDeleteKeyOperation;
 pub(crate) use self::initiate_deploy_contract::InitiateDeployContractOperation;
 pub(crate) use self::initiate_function_call::InitiateFunctionCallOperation;
 pub(crate) use self::refund_delete_account::RefundDeleteAccountOperation;
+pub(crate) use self::signed_delegate_action::SignedDelegateActionOperation;
 pub(crate) use self::stake::StakeOperation;
 pub(crate) use self::transfer::TransferOperation;
 
 mod add_key;
 mod create_account;
+mod delegate_action;
 mod delete_account;
 mod delete_key;
 mod deploy_contract;
 mod function_call;
 mod initiate_add_key;
 mod initiate_create_account;
+mod initiate_delegate_action;
 mod initiate_delete_account;
 mod initiate_delete_key;
 mod initiate_deploy_contract;
 mod initiate_function_call;
 mod refund_delete_account;
+mod signed_delegate_action;
 mod stake;
 mod transfer;
 
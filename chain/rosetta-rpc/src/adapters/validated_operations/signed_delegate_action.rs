@@ -0,0 +1,54 @@
+use std::convert::TryFrom;
+
+use super::ValidatedOperation;
+
+pub(crate) struct SignedDelegateActionOperation {
+    pub(crate) sender_account: crate::models::AccountIdentifier,
+    pub(crate) signature: near_crypto::Signature,
+}
+
+impl ValidatedOperation for SignedDelegateActionOperation {
+    const OPERATION_TYPE: crate::models::OperationType =
+        crate::models::OperationType::SignedDelegateAction;
+
+    fn into_operation(
+        self,
+        operation_identifier: crate::models::OperationIdentifier,
+    ) -> crate::models::Operation {
+        crate::models::Operation {
+            operation_identifier,
+
+            account: self.sender_account,
+            amount: None,
+            metadata: Some(crate::models::OperationMetadata {
+                signature: Some(self.signature.to_string()),
+                ..Default::default()
+            }),
+
+            related_operations: None,
+            type_: Self::OPERATION_TYPE,
+            status: None,
+        }
+    }
+}
+
+fn required_fields_error() -> crate::errors::ErrorKind {
+    crate::errors::ErrorKind::InvalidInput(
+        "SIGNED_DELEGATE_ACTION operation requires a valid `signature` being passed in the metadata"
+            .into(),
+    )
+}
+
+impl std::convert::TryFrom<crate::models::Operation> for SignedDelegateActionOperation {
+    type Error = crate::errors::ErrorKind;
+
+    fn try_from(operation: crate::models::Operation) -> Result<Self, Self::Error> {
+        Self::validate_operation_type(operation.type_)?;
+        let metadata = operation.metadata.ok_or_else(required_fields_error)?;
+        let signature = metadata.signature.ok_or_else(required_fields_error)?;
+        let signature =
+            near_crypto::Signature::try_from(signature).map_err(|_| required_fields_error())?;
+
+        Ok(Self { sender_account: operation.account, signature })
+    }
+}
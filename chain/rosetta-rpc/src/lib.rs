@@ -1,4 +1,4 @@
-use std::convert::{AsRef, TryInto};
+use std::convert::{AsRef, TryFrom, TryInto};
 use std::sync::Arc;
 
 use actix::Addr;
@@ -27,6 +27,21 @@ mod utils;
 pub const BASE_PATH: &str = "";
 pub const API_VERSION: &str = "1.4.4";
 
+/// Verifies that the request is addressed to the network (chain id) this node is running.
+async fn check_network_identifier(
+    client_addr: &web::Data<Addr<ClientActor>>,
+    network_identifier: models::NetworkIdentifier,
+) -> Result<(), models::Error> {
+    let status = client_addr
+        .send(near_client::Status { is_health_check: false })
+        .await?
+        .map_err(errors::ErrorKind::InternalError)?;
+    if status.chain_id != network_identifier.network {
+        return Err(errors::ErrorKind::WrongNetwork(network_identifier.network).into());
+    }
+    Ok(())
+}
+
 /// Get List of Available Networks
 ///
 /// This endpoint returns a list of NetworkIdentifiers that the Rosetta server
@@ -412,20 +427,34 @@ async fn account_balance(
 }
 
 #[api_v2_operation]
-/// Get All Mempool Transactions (not implemented)
+/// Get All Mempool Transactions
 ///
 /// Get all Transaction Identifiers in the mempool
 ///
-/// NOTE: The mempool is short-lived, so it is currently not implemented.
+/// NOTE: The mempool is short-lived and only contains transactions for the
+/// shards tracked by this node.
 async fn mempool(
-    _client_addr: web::Data<Addr<ClientActor>>,
-    _body: Json<models::NetworkRequest>,
+    client_addr: web::Data<Addr<ClientActor>>,
+    body: Json<models::NetworkRequest>,
 ) -> Result<Json<models::MempoolResponse>, models::Error> {
-    Ok(Json(models::MempoolResponse { transaction_identifiers: vec![] }))
+    let Json(models::NetworkRequest { network_identifier }) = body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    let transaction_hashes = client_addr
+        .send(near_client::GetMempoolTransactions {})
+        .await?
+        .map_err(errors::ErrorKind::InternalError)?;
+    Ok(Json(models::MempoolResponse {
+        transaction_identifiers: transaction_hashes
+            .into_iter()
+            .map(|hash| models::TransactionIdentifier { hash: hash.to_base() })
+            .collect(),
+    }))
 }
 
 #[api_v2_operation]
-/// Get a Mempool Transaction (not implemented)
+/// Get a Mempool Transaction
 ///
 /// Get a transaction in the mempool by its Transaction Identifier. This is a
 /// separate request than fetching a block transaction (/block/transaction)
@@ -436,13 +465,29 @@ async fn mempool(
 /// endpoint, it is ok that returned transactions are only estimates of what may
 /// actually be included in a block.
 ///
-/// NOTE: The mempool is short-lived, so this method does not make a lot of
-/// sense to be implemented.
+/// NOTE: The operations are estimated from the transaction actions, so they
+/// do not include fees, refunds or the effects of function calls.
 async fn mempool_transaction(
-    _client_addr: web::Data<Addr<ClientActor>>,
-    _body: Json<models::MempoolTransactionRequest>,
+    client_addr: web::Data<Addr<ClientActor>>,
+    body: Json<models::MempoolTransactionRequest>,
 ) -> Result<Json<models::MempoolTransactionResponse>, models::Error> {
-    Err(errors::ErrorKind::InternalError("Not implemented yet".to_string()).into())
+    let Json(models::MempoolTransactionRequest { network_identifier, transaction_identifier }) =
+        body;
+
+    check_network_identifier(&client_addr, network_identifier).await?;
+
+    let tx_hash = near_primitives::hash::CryptoHash::try_from(transaction_identifier.hash.as_str())
+        .map_err(|_| errors::ErrorKind::InvalidInput("Invalid transaction hash".to_string()))?;
+    let signed_transaction = client_addr
+        .send(near_client::GetMempoolTransaction { tx_hash })
+        .await?
+        .map_err(errors::ErrorKind::InternalError)?
+        .ok_or_else(|| {
+            errors::ErrorKind::NotFound("Transaction is not in the mempool".to_string())
+        })?;
+    Ok(Json(models::MempoolTransactionResponse {
+        transaction: adapters::convert_mempool_transaction(signed_transaction),
+    }))
 }
 
 #[api_v2_operation]
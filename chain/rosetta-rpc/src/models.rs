@@ -708,6 +708,9 @@ pub(crate) enum OperationType {
     DeployContract,
     InitiateFunctionCall,
     FunctionCall,
+    InitiateDelegateAction,
+    DelegateAction,
+    SignedDelegateAction,
 }
 
 #[derive(
@@ -755,6 +758,15 @@ pub(crate) struct OperationMetadata {
     /// Has to be specified for FUNCTION_CALL operation
     #[serde(skip_serializing_if = "Option::is_none")]
     pub attached_gas: Option<crate::utils::SignedDiff<near_primitives::types::Gas>>,
+    /// Has to be specified for DELEGATE_ACTION operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub nonce: Option<crate::utils::SignedDiff<near_primitives::types::Nonce>>,
+    /// Has to be specified for DELEGATE_ACTION operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_block_height: Option<crate::utils::SignedDiff<near_primitives::types::BlockHeight>>,
+    /// Has to be specified for SIGNED_DELEGATE_ACTION operation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
 }
 
 /// Operations contain all balance-changing information within a transaction.
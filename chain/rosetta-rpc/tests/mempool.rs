@@ -0,0 +1,115 @@
+use std::sync::Arc;
+
+use actix::System;
+use futures::{future, FutureExt};
+use serde_json::{json, Value};
+
+use near_chain_configs::Genesis;
+use near_client::test_utils::setup_no_network;
+use near_logger_utils::init_test_logger;
+use near_network::test_utils::open_port;
+use near_primitives::hash::hash;
+use near_primitives::serialize::BaseEncode;
+use near_rosetta_rpc::{start_rosetta_rpc, RosettaRpcConfig};
+
+/// Starts a node serving the Rosetta API and returns the API address.
+fn start_rosetta() -> String {
+    let (client_addr, view_client_addr) = setup_no_network(vec!["test1"], "test1", true, false);
+    let addr = format!("127.0.0.1:{}", open_port());
+    start_rosetta_rpc(
+        RosettaRpcConfig::new(&addr),
+        Arc::new(Genesis::default()),
+        client_addr,
+        view_client_addr,
+    );
+    addr
+}
+
+fn network_identifier(network: &str) -> Value {
+    json!({ "blockchain": "nearprotocol", "network": network })
+}
+
+async fn post(addr: &str, path: &str, body: Value) -> Value {
+    awc::Client::new()
+        .post(format!("http://{}{}", addr, path))
+        .send_json(&body)
+        .await
+        .unwrap()
+        .json::<Value>()
+        .await
+        .unwrap()
+}
+
+/// Mempool of a node without transactions is empty and transactions are not found in it.
+#[test]
+fn test_mempool_unknown_transaction() {
+    init_test_logger();
+
+    System::run(|| {
+        let addr = start_rosetta();
+        actix::spawn(
+            async move {
+                let response = post(
+                    &addr,
+                    "/mempool",
+                    json!({ "network_identifier": network_identifier("unittest") }),
+                )
+                .await;
+                assert_eq!(response, json!({ "transaction_identifiers": [] }));
+
+                let response = post(
+                    &addr,
+                    "/mempool/transaction",
+                    json!({
+                        "network_identifier": network_identifier("unittest"),
+                        "transaction_identifier": { "hash": hash(b"missing").to_base() },
+                    }),
+                )
+                .await;
+                assert_eq!(response["code"], 404, "unexpected response: {}", response);
+            }
+            .then(|_| {
+                System::current().stop();
+                future::ready(())
+            }),
+        );
+    })
+    .unwrap();
+}
+
+/// Mempool requests for a network other than the one of the node are rejected.
+#[test]
+fn test_mempool_wrong_network() {
+    init_test_logger();
+
+    System::run(|| {
+        let addr = start_rosetta();
+        actix::spawn(
+            async move {
+                let response = post(
+                    &addr,
+                    "/mempool",
+                    json!({ "network_identifier": network_identifier("othernet") }),
+                )
+                .await;
+                assert_eq!(response["code"], 403, "unexpected response: {}", response);
+
+                let response = post(
+                    &addr,
+                    "/mempool/transaction",
+                    json!({
+                        "network_identifier": network_identifier("othernet"),
+                        "transaction_identifier": { "hash": hash(b"missing").to_base() },
+                    }),
+                )
+                .await;
+                assert_eq!(response["code"], 403, "unexpected response: {}", response);
+            }
+            .then(|_| {
+                System::current().stop();
+                future::ready(())
+            }),
+        );
+    })
+    .unwrap();
+}
@@ -75,6 +75,10 @@ pub struct ClientConfig {
     pub tracked_shards: Vec<ShardId>,
     /// Not clear old data, set `true` for archive nodes.
     pub archive: bool,
+    /// Maintain an index of transactions and receipts per account. Only used by archive nodes.
+    pub account_history: bool,
+    /// Record a gas profile for every applied receipt, served by `EXPERIMENTAL_tx_status`.
+    pub gas_profiling: bool,
     /// Number of threads for ViewClientActor pool.
     pub view_client_threads: usize,
 }
@@ -127,6 +131,8 @@ impl ClientConfig {
             tracked_accounts: vec![],
             tracked_shards: vec![],
             archive,
+            account_history: false,
+            gas_profiling: false,
             view_client_threads: 1,
         }
     }
@@ -16,3 +16,17 @@ pub enum ParseKeyError {
     #[error("invalid key data: {0}")]
     InvalidData(String),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum KeyFileError {
+    #[error("failed to read key file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("failed to parse key file: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("invalid key file: {0}")]
+    InvalidFormat(String),
+    #[error("key file is encrypted, passphrase is required")]
+    PassphraseRequired,
+    #[error("wrong passphrase or corrupted key file")]
+    DecryptionFailed,
+}
@@ -1,12 +1,56 @@
-use std::fs::File;
+use std::ffi::OsString;
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::os::unix::fs::PermissionsExt;
+use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 
+use chacha20poly1305::aead::generic_array::GenericArray;
+use chacha20poly1305::aead::{Aead, NewAead, Payload};
+use chacha20poly1305::ChaCha20Poly1305;
+use rand::rngs::OsRng;
+use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
+use crate::errors::KeyFileError;
 use crate::{PublicKey, SecretKey};
 
+/// Default scrypt cost (2^15 iterations, 32Mb of memory).
+const DEFAULT_SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 32;
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+
+/// Environment variable with the passphrase for encrypted key files.
+pub const KEY_PASSPHRASE_ENV: &str = "NEAR_KEY_PASSPHRASE";
+
+/// Writes the key file readable only by the owner. The content goes to a temporary file next to
+/// `path` first, so an existing key file is only replaced once the new one is fully on disk.
+fn write_owner_only(path: &Path, content: &str) {
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().expect("Key file path must name a file."));
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+    // Left over by a crash, it could have been created with other permissions.
+    let _ = fs::remove_file(&tmp_path);
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(u32::from(libc::S_IWUSR | libc::S_IRUSR))
+        .open(&tmp_path)
+        .expect("Failed to create / write a key file.");
+    if let Err(err) = file.write_all(content.as_bytes()).and_then(|_| file.sync_all()) {
+        panic!("Failed to write a key file {}", err);
+    }
+    fs::rename(&tmp_path, path).expect("Failed to replace the key file.");
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir).and_then(|dir| dir.sync_all()).expect("Failed to sync the key file directory.");
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct KeyFile {
     pub account_id: String,
@@ -16,15 +60,8 @@ pub struct KeyFile {
 
 impl KeyFile {
     pub fn write_to_file(&self, path: &Path) {
-        let mut file = File::create(path).expect("Failed to create / write a key file.");
-        let mut perm =
-            file.metadata().expect("Failed to retrieve key file metadata.").permissions();
-        perm.set_mode(u32::from(libc::S_IWUSR | libc::S_IRUSR));
-        file.set_permissions(perm).expect("Failed to set permissions for a key file.");
         let str = serde_json::to_string_pretty(self).expect("Error serializing the key file.");
-        if let Err(err) = file.write_all(str.as_bytes()) {
-            panic!("Failed to write a key file {}", err);
-        }
+        write_owner_only(path, &str);
     }
 
     pub fn from_file(path: &Path) -> Self {
@@ -33,4 +70,226 @@ impl KeyFile {
         file.read_to_string(&mut content).expect("Could not read from key file.");
         serde_json::from_str(&content).expect("Failed to deserialize KeyFile")
     }
+
+    /// Reads either a plain or an encrypted key file, the latter can only be read with `passphrase`.
+    pub fn from_file_with_passphrase(
+        path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<Self, KeyFileError> {
+        let value: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if value.get("encrypted_secret_key").is_some() {
+            let encrypted_key_file: EncryptedKeyFile = serde_json::from_value(value)?;
+            encrypted_key_file.decrypt(passphrase.ok_or(KeyFileError::PassphraseRequired)?)
+        } else {
+            Ok(serde_json::from_value(value)?)
+        }
+    }
+
+    /// Encrypts the secret key with a key derived from `passphrase`.
+    pub fn encrypt(&self, passphrase: &str) -> EncryptedKeyFile {
+        self.encrypt_with_cost(passphrase, DEFAULT_SCRYPT_LOG_N)
+    }
+
+    fn encrypt_with_cost(&self, passphrase: &str, log_n: u8) -> EncryptedKeyFile {
+        let mut salt = [0u8; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let kdf_params = ScryptParams { log_n, r: SCRYPT_R, p: SCRYPT_P, salt: salt.to_vec() };
+        let key = kdf_params.derive_key(passphrase).expect("Default scrypt params are valid");
+        let ciphertext = ChaCha20Poly1305::new(GenericArray::from_slice(&key))
+            .encrypt(
+                GenericArray::from_slice(&nonce),
+                Payload {
+                    msg: self.secret_key.to_string().as_bytes(),
+                    aad: &associated_data(&self.account_id, &self.public_key),
+                },
+            )
+            .expect("Encryption of the secret key failed");
+        EncryptedKeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            encrypted_secret_key: EncryptedSecretKey {
+                kdf: Kdf::Scrypt(kdf_params),
+                cipher: Cipher::ChaCha20Poly1305,
+                nonce: bs58::encode(nonce).into_string(),
+                ciphertext: bs58::encode(ciphertext).into_string(),
+            },
+        }
+    }
+}
+
+/// Account id and public key are stored in plain text, but can't be changed without the
+/// passphrase since they are authenticated together with the secret key.
+fn associated_data(account_id: &str, public_key: &PublicKey) -> Vec<u8> {
+    format!("{}:{}", account_id, public_key).into_bytes()
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScryptParams {
+    pub log_n: u8,
+    pub r: u32,
+    pub p: u32,
+    #[serde(with = "base58_format")]
+    pub salt: Vec<u8>,
+}
+
+impl ScryptParams {
+    fn derive_key(&self, passphrase: &str) -> Result<[u8; KEY_LEN], KeyFileError> {
+        let params = scrypt::ScryptParams::new(self.log_n, self.r, self.p)
+            .map_err(|_| KeyFileError::InvalidFormat("invalid scrypt params".to_string()))?;
+        let mut key = [0u8; KEY_LEN];
+        scrypt::scrypt(passphrase.as_bytes(), &self.salt, &params, &mut key)
+            .expect("Key length is valid");
+        Ok(key)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum Kdf {
+    Scrypt(ScryptParams),
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cipher {
+    ChaCha20Poly1305,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedSecretKey {
+    pub kdf: Kdf,
+    pub cipher: Cipher,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Key file with the secret key encrypted by a passphrase-derived key (scrypt + ChaCha20-Poly1305).
+#[derive(Serialize, Deserialize)]
+pub struct EncryptedKeyFile {
+    pub account_id: String,
+    pub public_key: PublicKey,
+    pub encrypted_secret_key: EncryptedSecretKey,
+}
+
+impl EncryptedKeyFile {
+    pub fn write_to_file(&self, path: &Path) {
+        let str = serde_json::to_string_pretty(self).expect("Error serializing the key file.");
+        write_owner_only(path, &str);
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, KeyFileError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn decrypt(&self, passphrase: &str) -> Result<KeyFile, KeyFileError> {
+        let EncryptedSecretKey { kdf, cipher, nonce, ciphertext } = &self.encrypted_secret_key;
+        let key = match kdf {
+            Kdf::Scrypt(params) => params.derive_key(passphrase)?,
+        };
+        let nonce = decode_base58(nonce)?;
+        if nonce.len() != NONCE_LEN {
+            return Err(KeyFileError::InvalidFormat("invalid nonce length".to_string()));
+        }
+        let plaintext = match cipher {
+            Cipher::ChaCha20Poly1305 => ChaCha20Poly1305::new(GenericArray::from_slice(&key))
+                .decrypt(
+                    GenericArray::from_slice(&nonce),
+                    Payload {
+                        msg: &decode_base58(ciphertext)?,
+                        aad: &associated_data(&self.account_id, &self.public_key),
+                    },
+                )
+                .map_err(|_| KeyFileError::DecryptionFailed)?,
+        };
+        let secret_key: SecretKey = String::from_utf8(plaintext)
+            .map_err(|_| KeyFileError::DecryptionFailed)?
+            .parse()
+            .map_err(|_| KeyFileError::DecryptionFailed)?;
+        if secret_key.public_key() != self.public_key {
+            return Err(KeyFileError::InvalidFormat(
+                "secret key doesn't match the public key".to_string(),
+            ));
+        }
+        Ok(KeyFile {
+            account_id: self.account_id.clone(),
+            public_key: self.public_key.clone(),
+            secret_key,
+        })
+    }
+}
+
+fn decode_base58(value: &str) -> Result<Vec<u8>, KeyFileError> {
+    bs58::decode(value).into_vec().map_err(|err| KeyFileError::InvalidFormat(err.to_string()))
+}
+
+mod base58_format {
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(data: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&bs58::encode(data).into_string())
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        bs58::decode(s).into_vec().map_err(|err| de::Error::custom(err.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyType;
+
+    fn key_file() -> KeyFile {
+        let secret_key = SecretKey::from_seed(KeyType::ED25519, "test");
+        KeyFile { account_id: "test".to_string(), public_key: secret_key.public_key(), secret_key }
+    }
+
+    #[test]
+    fn test_encrypted_key_file_roundtrip() {
+        let key_file = key_file();
+        let encrypted = key_file.encrypt_with_cost("passphrase", 4);
+        let json = serde_json::to_string(&encrypted).unwrap();
+        assert!(!json.contains(&key_file.secret_key.to_string()));
+
+        let encrypted: EncryptedKeyFile = serde_json::from_str(&json).unwrap();
+        let decrypted = encrypted.decrypt("passphrase").unwrap();
+        assert_eq!(decrypted.secret_key, key_file.secret_key);
+        assert!(matches!(encrypted.decrypt("wrong"), Err(KeyFileError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn test_encrypted_key_file_authenticates_account() {
+        let mut encrypted = key_file().encrypt_with_cost("passphrase", 4);
+        encrypted.account_id = "attacker".to_string();
+        assert!(matches!(encrypted.decrypt("passphrase"), Err(KeyFileError::DecryptionFailed)));
+    }
+
+    #[test]
+    fn test_from_file_with_passphrase() {
+        let dir = tempfile::tempdir().unwrap();
+        let key_file = key_file();
+        let plain_path = dir.path().join("plain.json");
+        let encrypted_path = dir.path().join("encrypted.json");
+        key_file.write_to_file(&plain_path);
+        key_file.encrypt_with_cost("passphrase", 4).write_to_file(&encrypted_path);
+
+        let plain = KeyFile::from_file_with_passphrase(&plain_path, None).unwrap();
+        assert_eq!(plain.secret_key, key_file.secret_key);
+        assert!(matches!(
+            KeyFile::from_file_with_passphrase(&encrypted_path, None),
+            Err(KeyFileError::PassphraseRequired)
+        ));
+        let decrypted =
+            KeyFile::from_file_with_passphrase(&encrypted_path, Some("passphrase")).unwrap();
+        assert_eq!(decrypted.secret_key, key_file.secret_key);
+    }
 }
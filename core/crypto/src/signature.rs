@@ -552,6 +552,50 @@ impl TryFrom<&[u8]> for Secp256K1Signature {
     }
 }
 
+/// Order of the secp256k1 curve, big-endian.
+const SECP256K1_N: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+/// `SECP256K1_N / 2 + 1`, big-endian.
+const SECP256K1_N_HALF_ONE: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0x5d, 0x57, 0x6e, 0x73, 0x57, 0xa4, 0x50, 0x1d, 0xdf, 0xe9, 0x2f, 0x46, 0x68, 0x1b, 0x20, 0xa1,
+];
+
+impl Secp256K1Signature {
+    /// Checks that `r` and `s` are within `[1, n)`. With `reject_upper` also requires `s` to be
+    /// in the lower half of the range, which rejects malleable signatures (see EIP-2).
+    pub fn check_signature_values(&self, reject_upper: bool) -> bool {
+        let mut r = [0u8; 32];
+        r.copy_from_slice(&self.0[0..32]);
+        let mut s = [0u8; 32];
+        s.copy_from_slice(&self.0[32..64]);
+        let s_check = if reject_upper { s < SECP256K1_N_HALF_ONE } else { s < SECP256K1_N };
+        r < SECP256K1_N && r != [0u8; 32] && s_check && s != [0u8; 32]
+    }
+
+    /// Recovers the public key that signed the 32 bytes message `msg`.
+    pub fn recover(&self, msg: [u8; 32]) -> Result<Secp256K1PublicKey, crate::ParseSignatureError> {
+        let invalid_data =
+            |err: secp256k1::Error| crate::ParseSignatureError::InvalidData(err.to_string());
+        let recovery_id =
+            secp256k1::RecoveryId::from_i32(i32::from(self.0[64])).map_err(invalid_data)?;
+        let recoverable_signature =
+            secp256k1::RecoverableSignature::from_compact(&SECP256K1, &self.0[0..64], recovery_id)
+                .map_err(invalid_data)?;
+        let public_key = SECP256K1
+            .recover(&secp256k1::Message::from(msg), &recoverable_signature)
+            .map_err(invalid_data)?
+            .serialize_vec(&SECP256K1, false);
+        // The uncompressed serialization is prefixed with 0x04.
+        let mut data = [0u8; 64];
+        data.copy_from_slice(&public_key[1..65]);
+        Ok(Secp256K1PublicKey(data))
+    }
+}
+
 impl Eq for Secp256K1Signature {}
 
 impl PartialEq for Secp256K1Signature {
@@ -775,6 +819,26 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_secp256k1_recover() {
+        let secret_key = SecretKey::from_seed(KeyType::SECP256K1, "test");
+        use sha2::Digest;
+        let data = sha2::Sha256::digest(b"123");
+        let mut msg = [0u8; 32];
+        msg.copy_from_slice(&data);
+        let signature = match secret_key.sign(&msg) {
+            Signature::SECP256K1(signature) => signature,
+            _ => unreachable!(),
+        };
+        assert!(signature.check_signature_values(false));
+        let public_key = signature.recover(msg).unwrap();
+        assert_eq!(PublicKey::SECP256K1(public_key), secret_key.public_key());
+
+        let mut invalid = signature.clone();
+        invalid.0[32..64].copy_from_slice(&SECP256K1_N);
+        assert!(!invalid.check_signature_values(false));
+    }
+
     #[test]
     fn test_json_serialize_ed25519() {
         let sk = SecretKey::from_seed(KeyType::ED25519, "test");
@@ -1,6 +1,7 @@
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::errors::KeyFileError;
 use crate::key_conversion::convert_secret_key;
 use crate::key_file::KeyFile;
 use crate::{KeyType, PublicKey, SecretKey, Signature};
@@ -60,6 +61,14 @@ impl InMemorySigner {
     pub fn from_file(path: &Path) -> Self {
         KeyFile::from_file(path).into()
     }
+
+    /// Same as `from_file`, but also accepts key files encrypted with `passphrase`.
+    pub fn from_file_with_passphrase(
+        path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<Self, KeyFileError> {
+        Ok(KeyFile::from_file_with_passphrase(path, passphrase)?.into())
+    }
 }
 
 impl Signer for InMemorySigner {
@@ -64,6 +64,7 @@ fn create_block() -> Block {
         CryptoHash::default(),
         CryptoHash::default(),
     )
+    .unwrap()
 }
 
 fn create_account() -> Account {
@@ -2,8 +2,8 @@ use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 
 use crate::hash::CryptoHash;
-use crate::serialize::{option_u128_dec_format, u128_dec_format_compatible};
-use crate::types::{AccountId, Balance, Nonce, StorageUsage};
+use crate::serialize::{option_u128_dec_format, u128_dec_format, u128_dec_format_compatible};
+use crate::types::{AccountId, Balance, BlockHeight, Nonce, StorageUsage};
 
 /// Per account information stored in the state.
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
@@ -55,6 +55,10 @@ pub enum AccessKeyPermission {
     /// Grants full access to the account.
     /// NOTE: It's used to replace account-level public keys.
     FullAccess,
+
+    /// Function call permission that also bounds the attached deposit and the lifetime of the
+    /// key. Available with `ProtocolFeature::FunctionCallPermissionV2`.
+    FunctionCallV2(FunctionCallPermissionV2),
 }
 
 /// Grants limited permission to make transactions with FunctionCallActions
@@ -83,6 +87,31 @@ pub struct FunctionCallPermission {
     pub method_names: Vec<String>,
 }
 
+/// Same as `FunctionCallPermission`, but also allows attaching a limited deposit to the function
+/// call and can expire at a given block height.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Debug,
+)]
+pub struct FunctionCallPermissionV2 {
+    /// See `FunctionCallPermission::allowance`. The attached deposit is also paid from it.
+    #[serde(with = "option_u128_dec_format")]
+    pub allowance: Option<Balance>,
+
+    /// The access key only allows transactions with the given receiver's account id.
+    pub receiver_id: AccountId,
+
+    /// A list of method names that can be used. Empty list means any method name can be used.
+    pub method_names: Vec<String>,
+
+    /// The largest deposit that can be attached to a single function call.
+    #[serde(with = "u128_dec_format")]
+    pub max_deposit_per_call: Balance,
+
+    /// The access key can't be used in blocks with this or a greater height.
+    /// `None` means the access key never expires.
+    pub expires_at_height: Option<BlockHeight>,
+}
+
 #[cfg(test)]
 mod tests {
     use borsh::BorshSerialize;
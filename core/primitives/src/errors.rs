@@ -1,5 +1,6 @@
 use crate::serialize::u128_dec_format;
-use crate::types::{AccountId, Balance, EpochId, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, EpochId, Gas, Nonce};
+use crate::version::ProtocolVersion;
 use borsh::{BorshDeserialize, BorshSerialize};
 use near_crypto::PublicKey;
 use serde::{Deserialize, Serialize};
@@ -166,6 +167,19 @@ pub enum InvalidAccessKeyError {
     },
     /// Having a deposit with a function call action is not allowed with a function call access key.
     DepositWithFunctionCall,
+    /// The deposit attached to the function call exceeds the limit of the access key.
+    DepositExceedsLimit {
+        #[serde(with = "u128_dec_format")]
+        deposit: Balance,
+        #[serde(with = "u128_dec_format")]
+        max_deposit_per_call: Balance,
+    },
+    /// The access key expired at the given block height.
+    AccessKeyExpired {
+        account_id: AccountId,
+        public_key: PublicKey,
+        expires_at_height: BlockHeight,
+    },
 }
 
 /// Describes the error for validating a list of actions.
@@ -197,6 +211,10 @@ pub enum ActionsValidationError {
     UnsuitableStakingKey { public_key: PublicKey },
     /// The attached amount of gas in a FunctionCall action has to be a positive number.
     FunctionCallZeroAttachedGas,
+    /// The action uses a feature that isn't enabled in the current protocol version.
+    UnsupportedProtocolFeature { protocol_feature: String, version: ProtocolVersion },
+    /// A delegate action can't contain other delegate actions.
+    DelegateActionMustNotBeNested,
 }
 
 /// Describes the error for validating a receipt.
@@ -311,6 +329,16 @@ impl Display for ActionsValidationError {
                 f,
                 "The attached amount of gas in a FunctionCall action has to be a positive number",
             ),
+            ActionsValidationError::UnsupportedProtocolFeature { protocol_feature, version } => {
+                write!(
+                    f,
+                    "{} is not supported in the protocol version {}",
+                    protocol_feature, version
+                )
+            }
+            ActionsValidationError::DelegateActionMustNotBeNested => {
+                write!(f, "A delegate action can't contain other delegate actions")
+            }
         }
     }
 }
@@ -387,6 +415,16 @@ pub enum ActionErrorKind {
     /// Error occurs when a `CreateAccount` action is called on hex-characters account of length 64.
     /// See implicit account creation NEP: https://github.com/nearprotocol/NEPs/pull/71
     OnlyImplicitAccountCreationAllowed { account_id: AccountId },
+    /// The signature of the delegate action doesn't match its public key.
+    DelegateActionInvalidSignature,
+    /// The sender of the delegate action is not the receiver of the transaction that carries it.
+    DelegateActionSenderDoesNotMatchReceiver { sender_id: AccountId, receiver_id: AccountId },
+    /// The delegate action is included after its `max_block_height`.
+    DelegateActionExpired { max_block_height: BlockHeight, block_height: BlockHeight },
+    /// The access key that signed the delegate action is missing or doesn't allow its actions.
+    DelegateActionAccessKeyError(InvalidAccessKeyError),
+    /// The nonce of the delegate action has to be greater than the nonce of the access key.
+    DelegateActionInvalidNonce { delegate_nonce: Nonce, ak_nonce: Nonce },
 }
 
 impl From<ActionErrorKind> for ActionError {
@@ -483,6 +521,18 @@ impl Display for InvalidAccessKeyError {
             InvalidAccessKeyError::DepositWithFunctionCall => {
                 write!(f, "Having a deposit with a function call action is not allowed with a function call access key.")
             }
+            InvalidAccessKeyError::DepositExceedsLimit { deposit, max_deposit_per_call } => write!(
+                f,
+                "The deposit {} exceeds the limit {} of the access key",
+                deposit, max_deposit_per_call
+            ),
+            InvalidAccessKeyError::AccessKeyExpired { account_id, public_key, expires_at_height } => {
+                write!(
+                    f,
+                    "Access key for public key {} of account {} expired at height {}",
+                    public_key, account_id, expires_at_height
+                )
+            }
         }
     }
 }
@@ -674,7 +724,16 @@ impl Display for ActionErrorKind {
                 write!(f, "An new action receipt created during a FunctionCall is not valid: {}", e)
             }
             ActionErrorKind::InsufficientStake { account_id, stake, minimum_stake } => write!(f, "Account {} tries to stake {} but minimum required stake is {}", account_id, stake, minimum_stake),
-            ActionErrorKind::OnlyImplicitAccountCreationAllowed { account_id } => write!(f, "CreateAccount action is called on hex-characters account of length 64 {}", account_id)
+            ActionErrorKind::OnlyImplicitAccountCreationAllowed { account_id } => write!(f, "CreateAccount action is called on hex-characters account of length 64 {}", account_id),
+            ActionErrorKind::DelegateActionInvalidSignature => {
+                write!(f, "The signature of the delegate action doesn't match its public key")
+            }
+            ActionErrorKind::DelegateActionSenderDoesNotMatchReceiver { sender_id, receiver_id } => write!(f, "Delegate action sender {:?} doesn't match the receiver of the transaction {:?}", sender_id, receiver_id),
+            ActionErrorKind::DelegateActionExpired { max_block_height, block_height } => write!(f, "Delegate action expired at height {}, current height is {}", max_block_height, block_height),
+            ActionErrorKind::DelegateActionAccessKeyError(e) => {
+                write!(f, "The access key of the delegate action is invalid: {}", e)
+            }
+            ActionErrorKind::DelegateActionInvalidNonce { delegate_nonce, ak_nonce } => write!(f, "Delegate action nonce {} must be larger than the access key nonce {}", delegate_nonce, ak_nonce),
         }
     }
 }
@@ -6,8 +6,12 @@ use serde::{Deserialize, Serialize};
 
 use crate::hash::CryptoHash;
 use crate::merkle::MerklePath;
+use crate::state_record::StateRecord;
 use crate::transaction::SignedTransaction;
-use crate::types::{AccountId, BlockReference, MaybeBlockId, TransactionOrReceiptId};
+use crate::types::{
+    AccountId, BlockHeight, BlockHeightDelta, BlockReference, MaybeBlockId, ShardId,
+    TransactionOrReceiptId,
+};
 use crate::views::{
     ExecutionOutcomeWithIdView, LightClientBlockLiteView, QueryRequest, StateChangeWithCauseView,
     StateChangesKindsView, StateChangesRequestView,
@@ -78,3 +82,68 @@ pub enum TransactionInfo {
 pub struct RpcValidatorsOrderedRequest {
     pub block_id: MaybeBlockId,
 }
+
+/// Optional last parameter of `EXPERIMENTAL_tx_status`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct RpcTxStatusOptions {
+    /// Include the gas profiles of the receipts, if the node records them.
+    #[serde(default)]
+    pub gas_profile: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcAccountHistoryRequest {
+    pub account_id: AccountId,
+    /// Only blocks at or below this height are returned. Defaults to the chain head.
+    #[serde(default)]
+    pub from_height: Option<BlockHeight>,
+    /// Maximum number of blocks to return.
+    #[serde(default)]
+    pub limit: Option<u64>,
+}
+
+/// Records to write into the state of a sandbox node, in the genesis records format.
+#[derive(Serialize, Deserialize)]
+pub struct RpcSandboxPatchStateRequest {
+    pub records: Vec<StateRecord>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcSandboxFastForwardRequest {
+    pub delta_height: BlockHeightDelta,
+}
+
+/// Kinds of events a WebSocket client can subscribe to.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(tag = "subscription_type", rename_all = "snake_case")]
+pub enum RpcSubscriptionRequest {
+    /// Every new final block.
+    FinalBlocks,
+    /// Every new chunk of the given shard included into a final block.
+    Chunks { shard_id: ShardId },
+    /// State changes matching the filter in every new final block.
+    StateChanges {
+        #[serde(flatten)]
+        state_changes_request: StateChangesRequestView,
+    },
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcUnsubscribeRequest {
+    pub subscription_id: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RpcSubscriptionNotification {
+    pub subscription_id: u64,
+    pub result: serde_json::Value,
+}
+
+/// Sent instead of `RpcSubscriptionNotification` when the data of a subscription for the given
+/// block couldn't be fetched.
+#[derive(Serialize, Deserialize)]
+pub struct RpcSubscriptionError {
+    pub subscription_id: u64,
+    pub block_hash: CryptoHash,
+    pub error: String,
+}
@@ -9,7 +9,7 @@ use crate::merkle::{combine_hash, merklize, MerklePath};
 use crate::receipt::Receipt;
 use crate::transaction::SignedTransaction;
 use crate::types::{Balance, BlockHeight, Gas, MerkleHash, ShardId, StateRoot, ValidatorStake};
-use crate::validator_signer::ValidatorSigner;
+use crate::validator_signer::{SignerError, ValidatorSigner};
 use crate::version::{ProtocolVersion, ProtocolVersionRange, SHARD_CHUNK_HEADER_UPGRADE_VERSION};
 use reed_solomon_erasure::ReconstructShard;
 use std::sync::Arc;
@@ -85,6 +85,22 @@ pub struct ShardChunkHeaderInner {
     pub validator_proposals: Vec<ValidatorStake>,
 }
 
+/// Chunk header versions differ in how the chunk hash is computed from the inner header.
+#[derive(BorshSerialize, BorshDeserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShardChunkHeaderVersion {
+    V1,
+    V2,
+}
+
+impl ShardChunkHeaderVersion {
+    pub fn compute_hash(self, inner: &ShardChunkHeaderInner) -> ChunkHash {
+        match self {
+            Self::V1 => ShardChunkHeaderV1::compute_hash(inner),
+            Self::V2 => ShardChunkHeaderV2::compute_hash(inner),
+        }
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Clone, PartialEq, Eq, Debug)]
 #[borsh_init(init)]
 pub struct ShardChunkHeaderV1 {
@@ -140,7 +156,7 @@ impl ShardChunkHeaderV2 {
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
         signer: &dyn ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, SignerError> {
         let inner = ShardChunkHeaderInner {
             prev_block_hash,
             prev_state_root,
@@ -156,9 +172,8 @@ impl ShardChunkHeaderV2 {
             tx_root,
             validator_proposals,
         };
-        let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
-        Self { inner, height_included: 0, signature, hash }
+        let (hash, signature) = signer.sign_chunk_header(&inner, ShardChunkHeaderVersion::V2)?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -370,7 +385,7 @@ impl ShardChunkHeaderV1 {
         tx_root: CryptoHash,
         validator_proposals: Vec<ValidatorStake>,
         signer: &dyn ValidatorSigner,
-    ) -> Self {
+    ) -> Result<Self, SignerError> {
         let inner = ShardChunkHeaderInner {
             prev_block_hash,
             prev_state_root,
@@ -386,9 +401,8 @@ impl ShardChunkHeaderV1 {
             tx_root,
             validator_proposals,
         };
-        let hash = Self::compute_hash(&inner);
-        let signature = signer.sign_chunk_hash(&hash);
-        Self { inner, height_included: 0, signature, hash }
+        let (hash, signature) = signer.sign_chunk_header(&inner, ShardChunkHeaderVersion::V1)?;
+        Ok(Self { inner, height_included: 0, signature, hash })
     }
 }
 
@@ -868,7 +882,8 @@ impl EncodedShardChunk {
             rs,
             signer,
             protocol_version,
-        );
+        )
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
         Ok((new_chunk, merkle_paths))
     }
 
@@ -892,7 +907,7 @@ impl EncodedShardChunk {
 
         signer: &dyn ValidatorSigner,
         protocol_version: ProtocolVersion,
-    ) -> (Self, Vec<MerklePath>) {
+    ) -> Result<(Self, Vec<MerklePath>), SignerError> {
         let mut content = EncodedShardChunkBody { parts };
         content.reconstruct(rs).unwrap();
         let (encoded_merkle_root, merkle_paths) = content.get_merkle_hash_and_paths();
@@ -913,9 +928,9 @@ impl EncodedShardChunk {
                 tx_root,
                 validator_proposals,
                 signer,
-            );
+            )?;
             let chunk = EncodedShardChunkV1 { header, content };
-            (Self::V1(chunk), merkle_paths)
+            Ok((Self::V1(chunk), merkle_paths))
         } else {
             let header = ShardChunkHeaderV2::new(
                 prev_block_hash,
@@ -932,9 +947,9 @@ impl EncodedShardChunk {
                 tx_root,
                 validator_proposals,
                 signer,
-            );
+            )?;
             let chunk = EncodedShardChunkV2 { header: ShardChunkHeader::V2(header), content };
-            (Self::V2(chunk), merkle_paths)
+            Ok((Self::V2(chunk), merkle_paths))
         }
     }
 
@@ -252,11 +252,13 @@ impl BlockHeader {
     }
 
     pub fn resign(&mut self, signer: &dyn ValidatorSigner) {
-        let (hash, signature) = signer.sign_block_header_parts(
-            *self.prev_hash(),
-            &self.inner_lite_bytes(),
-            &self.inner_rest_bytes(),
-        );
+        let (hash, signature) = signer
+            .sign_block_header_parts(
+                *self.prev_hash(),
+                &self.inner_lite_bytes(),
+                &self.inner_rest_bytes(),
+            )
+            .unwrap();
         let mut header = self.get_mut();
         header.hash = hash;
         header.signature = signature;
@@ -394,6 +396,7 @@ impl Block {
             next_bp_hash,
             block_merkle_root,
         )
+        .unwrap()
     }
 }
 
@@ -1,4 +1,5 @@
 use std::borrow::Borrow;
+use std::collections::BTreeMap;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
@@ -13,7 +14,7 @@ use crate::hash::{hash, CryptoHash};
 use crate::logging;
 use crate::merkle::MerklePath;
 use crate::serialize::{base64_format, u128_dec_format, u128_dec_format_compatible};
-use crate::types::{AccountId, Balance, Gas, Nonce};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, Nonce};
 
 pub type LogEntry = String;
 
@@ -56,6 +57,7 @@ pub enum Action {
     AddKey(AddKeyAction),
     DeleteKey(DeleteKeyAction),
     DeleteAccount(DeleteAccountAction),
+    Delegate(SignedDelegateAction),
 }
 
 impl Action {
@@ -198,6 +200,85 @@ impl From<DeleteAccountAction> for Action {
     }
 }
 
+/// Actions that `sender_id` signs off-chain to be executed as if `sender_id` sent them to
+/// `receiver_id`. A relayer submits them wrapped into `Action::Delegate` with its own transaction
+/// to `sender_id` and pays for the gas and the attached deposits.
+#[derive(BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct DelegateAction {
+    /// The account on whose behalf the actions are executed.
+    pub sender_id: AccountId,
+    /// The receiver of the actions.
+    pub receiver_id: AccountId,
+    /// The actions to execute. Can't contain delegate actions.
+    pub actions: Vec<Action>,
+    /// Nonce of the access key. It has to be greater than the nonce of the access key, which is
+    /// then set to this nonce, the same way as for transactions.
+    pub nonce: Nonce,
+    /// The last block height at which the actions can be executed.
+    pub max_block_height: BlockHeight,
+    /// The public key of the access key of `sender_id` that signed the delegate action.
+    pub public_key: PublicKey,
+}
+
+/// Index of `Action::Delegate` in the borsh encoding of `Action`.
+const DELEGATE_ACTION_BORSH_TAG: u8 = 8;
+
+// Use custom deserializer for DelegateAction to reject nested delegate actions before reading
+// them, so that a deeply nested input can't exhaust the stack.
+impl BorshDeserialize for DelegateAction {
+    fn deserialize(buf: &mut &[u8]) -> std::io::Result<Self> {
+        let sender_id = AccountId::deserialize(buf)?;
+        let receiver_id = AccountId::deserialize(buf)?;
+        let len = u32::deserialize(buf)?;
+        let mut actions = vec![];
+        for _ in 0..len {
+            if buf.first() == Some(&DELEGATE_ACTION_BORSH_TAG) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "Delegate actions can't be nested",
+                ));
+            }
+            actions.push(Action::deserialize(buf)?);
+        }
+        Ok(Self {
+            sender_id,
+            receiver_id,
+            actions,
+            nonce: Nonce::deserialize(buf)?,
+            max_block_height: BlockHeight::deserialize(buf)?,
+            public_key: PublicKey::deserialize(buf)?,
+        })
+    }
+}
+
+impl DelegateAction {
+    /// Computes a hash of the delegate action for signing.
+    pub fn get_hash(&self) -> CryptoHash {
+        let bytes = self.try_to_vec().expect("Failed to serialize");
+        hash(&bytes)
+    }
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug)]
+pub struct SignedDelegateAction {
+    pub delegate_action: DelegateAction,
+    pub signature: Signature,
+}
+
+impl SignedDelegateAction {
+    /// Checks the signature against the public key of the delegate action.
+    pub fn verify(&self) -> bool {
+        let hash = self.delegate_action.get_hash();
+        self.signature.verify(hash.as_ref(), &self.delegate_action.public_key)
+    }
+}
+
+impl From<SignedDelegateAction> for Action {
+    fn from(signed_delegate_action: SignedDelegateAction) -> Self {
+        Self::Delegate(signed_delegate_action)
+    }
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Eq, Debug, Clone)]
 #[borsh_init(init)]
 pub struct SignedTransaction {
@@ -335,6 +416,11 @@ pub struct ExecutionOutcome {
     /// The id of the account on which the execution happens. For transaction this is signer_id,
     /// for receipt this is receiver_id.
     pub executor_id: AccountId,
+    /// Breakdown of `gas_burnt` of a receipt, only present if gas profiling is enabled.
+    /// It is neither stored with the outcome nor covered by the outcome proofs.
+    #[borsh_skip]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<GasProfile>,
     /// Execution status. Contains the result in case of successful execution.
     /// NOTE: Should be the latest field since it contains unparsable by light client
     /// ExecutionStatus::Failure
@@ -365,6 +451,40 @@ impl fmt::Debug for ExecutionOutcome {
     }
 }
 
+/// Gas burnt by a receipt broken down by what it was charged for.
+#[derive(
+    BorshSerialize, BorshDeserialize, Serialize, Deserialize, PartialEq, Eq, Clone, Debug, Default,
+)]
+pub struct GasProfile {
+    /// Gas burnt for executing Wasm instructions.
+    pub wasm_gas: Gas,
+    /// Gas burnt by host functions, by the name of the `ExtCosts` cost.
+    pub host_costs: BTreeMap<String, Gas>,
+    /// Gas burnt for action fees, by the name of the action. Includes both the fees of the
+    /// receipt's own actions and the fees of the receipts created by the contract.
+    pub action_costs: BTreeMap<String, Gas>,
+}
+
+impl GasProfile {
+    pub fn add_host_cost(&mut self, cost: &str, gas: Gas) {
+        *self.host_costs.entry(cost.to_string()).or_default() += gas;
+    }
+
+    pub fn add_action_cost(&mut self, action: &str, gas: Gas) {
+        *self.action_costs.entry(action.to_string()).or_default() += gas;
+    }
+
+    pub fn merge(&mut self, other: GasProfile) {
+        self.wasm_gas += other.wasm_gas;
+        for (cost, gas) in other.host_costs {
+            self.add_host_cost(&cost, gas);
+        }
+        for (action, gas) in other.action_costs {
+            self.add_action_cost(&action, gas);
+        }
+    }
+}
+
 /// Execution outcome with the identifier.
 /// For a signed transaction, the ID is the hash of the transaction.
 /// For a receipt, the ID is the receipt ID.
@@ -490,6 +610,36 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_delegate_action_serialization() {
+        let signer = InMemorySigner::from_seed("alice.near", KeyType::ED25519, "alice.near");
+        let delegate_action = DelegateAction {
+            sender_id: "alice.near".to_string(),
+            receiver_id: "bob.near".to_string(),
+            actions: vec![Action::Transfer(TransferAction { deposit: 1 })],
+            nonce: 1,
+            max_block_height: 100,
+            public_key: signer.public_key(),
+        };
+        let signature = signer.sign(delegate_action.get_hash().as_ref());
+        let signed_delegate_action = SignedDelegateAction { delegate_action, signature };
+        assert!(signed_delegate_action.verify());
+
+        let action = Action::Delegate(signed_delegate_action.clone());
+        let bytes = action.try_to_vec().unwrap();
+        assert_eq!(bytes[0], DELEGATE_ACTION_BORSH_TAG);
+        assert_eq!(Action::try_from_slice(&bytes).unwrap(), action);
+
+        let mut nested = signed_delegate_action.clone();
+        nested.delegate_action.actions = vec![action];
+        let bytes = Action::Delegate(nested).try_to_vec().unwrap();
+        assert!(Action::try_from_slice(&bytes).is_err());
+
+        let mut tampered = signed_delegate_action;
+        tampered.delegate_action.nonce = 2;
+        assert!(!tampered.verify());
+    }
+
     #[test]
     fn test_outcome_to_hashes() {
         let outcome = ExecutionOutcome {
@@ -499,6 +649,7 @@ mod tests {
             gas_burnt: 123,
             tokens_burnt: 1234000,
             executor_id: "alice".to_string(),
+            gas_profile: None,
         };
         let hashes = outcome.to_hashes();
         assert_eq!(hashes.len(), 3);
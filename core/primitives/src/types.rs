@@ -70,7 +70,7 @@ impl Default for Finality {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccountWithPublicKey {
     pub account_id: AccountId,
     pub public_key: PublicKey,
@@ -210,7 +210,7 @@ pub enum StateChangesRequest {
     DataChanges { account_ids: Vec<AccountId>, key_prefix: StoreKey },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum StateChangeValue {
     AccountUpdate { account_id: AccountId, account: Account },
     AccountDeletion { account_id: AccountId },
@@ -222,7 +222,7 @@ pub enum StateChangeValue {
     ContractCodeDeletion { account_id: AccountId },
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct StateChangeWithCause {
     pub cause: StateChangeCause,
     pub value: StateChangeValue,
@@ -1,18 +1,42 @@
+use std::fmt;
 use std::path::Path;
 use std::sync::Arc;
 
 use borsh::BorshSerialize;
 
-use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
+use near_crypto::{InMemorySigner, KeyFileError, KeyType, PublicKey, Signature, Signer};
 
 use crate::block::{Approval, ApprovalInner, BlockHeader};
 use crate::challenge::ChallengeBody;
 use crate::hash::{hash, CryptoHash};
 use crate::network::{AnnounceAccount, PeerId};
-use crate::sharding::ChunkHash;
+use crate::sharding::{ChunkHash, ShardChunkHeaderInner, ShardChunkHeaderVersion};
 use crate::telemetry::TelemetryInfo;
 use crate::types::{AccountId, BlockHeight, EpochId};
 
+/// Error of a validator signer that doesn't keep the key in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerError {
+    /// The signer couldn't be reached or sent an unexpected response.
+    Unavailable(String),
+    /// The signer refused to sign, e.g. because it already signed something else at this height.
+    Refused(String),
+    /// The secret key is not accessible, so it can't be written to a file.
+    KeyNotAvailable,
+}
+
+impl fmt::Display for SignerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SignerError::Unavailable(err) => write!(f, "Signer is unavailable: {}", err),
+            SignerError::Refused(reason) => write!(f, "Signer refused to sign: {}", reason),
+            SignerError::KeyNotAvailable => write!(f, "Secret key is not available"),
+        }
+    }
+}
+
+impl std::error::Error for SignerError {}
+
 /// Validator signer that is used to sign blocks and approvals.
 pub trait ValidatorSigner: Sync + Send {
     /// Account id of the given validator.
@@ -22,7 +46,7 @@ pub trait ValidatorSigner: Sync + Send {
     fn public_key(&self) -> PublicKey;
 
     /// Serializes telemetry info to JSON and signs it, returning JSON with "signature" field.
-    fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value;
+    fn sign_telemetry(&self, info: &TelemetryInfo) -> Result<serde_json::Value, SignerError>;
 
     /// Signs given parts of the header.
     fn sign_block_header_parts(
@@ -30,16 +54,27 @@ pub trait ValidatorSigner: Sync + Send {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature);
+    ) -> Result<(CryptoHash, Signature), SignerError>;
 
     /// Signs given inner of the chunk header.
-    fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature;
+    fn sign_chunk_header(
+        &self,
+        inner: &ShardChunkHeaderInner,
+        version: ShardChunkHeaderVersion,
+    ) -> Result<(ChunkHash, Signature), SignerError>;
 
     /// Signs approval of given parent hash and reference hash.
-    fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature;
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+    ) -> Result<Signature, SignerError>;
 
     /// Signs challenge body.
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature);
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), SignerError>;
 
     /// Signs account announce.
     fn sign_account_announce(
@@ -47,15 +82,15 @@ pub trait ValidatorSigner: Sync + Send {
         account_id: &AccountId,
         peer_id: &PeerId,
         epoch_id: &EpochId,
-    ) -> Signature;
+    ) -> Result<Signature, SignerError>;
 
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof);
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), SignerError>;
 
-    /// Used by test infrastructure, only implement if make sense for testing otherwise raise `unimplemented`.
-    fn write_to_file(&self, path: &Path);
+    /// Used by test infrastructure, fails if the signer doesn't have the secret key.
+    fn write_to_file(&self, path: &Path) -> Result<(), SignerError>;
 }
 
 /// Test-only signer that "signs" everything with 0s.
@@ -74,8 +109,8 @@ impl ValidatorSigner for EmptyValidatorSigner {
         PublicKey::empty(KeyType::ED25519)
     }
 
-    fn sign_telemetry(&self, _info: &TelemetryInfo) -> serde_json::Value {
-        serde_json::Value::default()
+    fn sign_telemetry(&self, _info: &TelemetryInfo) -> Result<serde_json::Value, SignerError> {
+        Ok(serde_json::Value::default())
     }
 
     fn sign_block_header_parts(
@@ -83,22 +118,33 @@ impl ValidatorSigner for EmptyValidatorSigner {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature) {
+    ) -> Result<(CryptoHash, Signature), SignerError> {
         let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
-        (hash, Signature::default())
+        Ok((hash, Signature::default()))
     }
 
-    fn sign_chunk_hash(&self, _chunk_hash: &ChunkHash) -> Signature {
-        Signature::default()
+    fn sign_chunk_header(
+        &self,
+        inner: &ShardChunkHeaderInner,
+        version: ShardChunkHeaderVersion,
+    ) -> Result<(ChunkHash, Signature), SignerError> {
+        Ok((version.compute_hash(inner), Signature::default()))
     }
 
-    fn sign_approval(&self, _inner: &ApprovalInner, _target_height: BlockHeight) -> Signature {
-        Signature::default()
+    fn sign_approval(
+        &self,
+        _inner: &ApprovalInner,
+        _target_height: BlockHeight,
+    ) -> Result<Signature, SignerError> {
+        Ok(Signature::default())
     }
 
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature) {
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), SignerError> {
         let hash = hash(&challenge_body.try_to_vec().expect("Failed to serialize"));
-        (hash, Signature::default())
+        Ok((hash, Signature::default()))
     }
 
     fn sign_account_announce(
@@ -106,19 +152,19 @@ impl ValidatorSigner for EmptyValidatorSigner {
         _account_id: &String,
         _peer_id: &PeerId,
         _epoch_id: &EpochId,
-    ) -> Signature {
-        Signature::default()
+    ) -> Result<Signature, SignerError> {
+        Ok(Signature::default())
     }
 
     fn compute_vrf_with_proof(
         &self,
         _data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
-        unimplemented!()
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), SignerError> {
+        Err(SignerError::KeyNotAvailable)
     }
 
-    fn write_to_file(&self, _path: &Path) {
-        unimplemented!()
+    fn write_to_file(&self, _path: &Path) -> Result<(), SignerError> {
+        Err(SignerError::KeyNotAvailable)
     }
 }
 
@@ -152,6 +198,15 @@ impl InMemoryValidatorSigner {
         let signer = InMemorySigner::from_file(path);
         Self { account_id: signer.account_id.clone(), signer: Arc::new(signer) }
     }
+
+    /// Same as `from_file`, but also accepts key files encrypted with `passphrase`.
+    pub fn from_file_with_passphrase(
+        path: &Path,
+        passphrase: Option<&str>,
+    ) -> Result<Self, KeyFileError> {
+        let signer = InMemorySigner::from_file_with_passphrase(path, passphrase)?;
+        Ok(Self { account_id: signer.account_id.clone(), signer: Arc::new(signer) })
+    }
 }
 
 impl ValidatorSigner for InMemoryValidatorSigner {
@@ -163,11 +218,11 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         self.signer.public_key()
     }
 
-    fn sign_telemetry(&self, info: &TelemetryInfo) -> serde_json::Value {
+    fn sign_telemetry(&self, info: &TelemetryInfo) -> Result<serde_json::Value, SignerError> {
         let mut value = serde_json::to_value(info).expect("Telemetry must serialize to JSON");
         let content = serde_json::to_string(&value).expect("Telemetry must serialize to JSON");
         value["signature"] = format!("{}", self.signer.sign(content.as_bytes())).into();
-        value
+        Ok(value)
     }
 
     fn sign_block_header_parts(
@@ -175,23 +230,36 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         prev_hash: CryptoHash,
         inner_lite: &[u8],
         inner_rest: &[u8],
-    ) -> (CryptoHash, Signature) {
+    ) -> Result<(CryptoHash, Signature), SignerError> {
         let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
-        (hash, self.signer.sign(hash.as_ref()))
+        Ok((hash, self.signer.sign(hash.as_ref())))
     }
 
-    fn sign_chunk_hash(&self, chunk_hash: &ChunkHash) -> Signature {
-        self.signer.sign(chunk_hash.as_ref())
+    fn sign_chunk_header(
+        &self,
+        inner: &ShardChunkHeaderInner,
+        version: ShardChunkHeaderVersion,
+    ) -> Result<(ChunkHash, Signature), SignerError> {
+        let hash = version.compute_hash(inner);
+        let signature = self.signer.sign(hash.as_ref());
+        Ok((hash, signature))
     }
 
-    fn sign_approval(&self, inner: &ApprovalInner, target_height: BlockHeight) -> Signature {
-        self.signer.sign(&Approval::get_data_for_sig(&inner, target_height))
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+    ) -> Result<Signature, SignerError> {
+        Ok(self.signer.sign(&Approval::get_data_for_sig(&inner, target_height)))
     }
 
-    fn sign_challenge(&self, challenge_body: &ChallengeBody) -> (CryptoHash, Signature) {
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), SignerError> {
         let hash = hash(&challenge_body.try_to_vec().expect("Failed to serialize"));
         let signature = self.signer.sign(hash.as_ref());
-        (hash, signature)
+        Ok((hash, signature))
     }
 
     fn sign_account_announce(
@@ -199,19 +267,20 @@ impl ValidatorSigner for InMemoryValidatorSigner {
         account_id: &AccountId,
         peer_id: &PeerId,
         epoch_id: &EpochId,
-    ) -> Signature {
+    ) -> Result<Signature, SignerError> {
         let hash = AnnounceAccount::build_header_hash(&account_id, &peer_id, epoch_id);
-        self.signer.sign(hash.as_ref())
+        Ok(self.signer.sign(hash.as_ref()))
     }
 
     fn compute_vrf_with_proof(
         &self,
         data: &[u8],
-    ) -> (near_crypto::vrf::Value, near_crypto::vrf::Proof) {
-        self.signer.compute_vrf_with_proof(data)
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), SignerError> {
+        Ok(self.signer.compute_vrf_with_proof(data))
     }
 
-    fn write_to_file(&self, path: &Path) {
+    fn write_to_file(&self, path: &Path) -> Result<(), SignerError> {
         self.signer.write_to_file(path);
+        Ok(())
     }
 }
@@ -16,7 +16,7 @@ pub struct Version {
 pub type DbVersion = u32;
 
 /// Current version of the database.
-pub const DB_VERSION: DbVersion = 15;
+pub const DB_VERSION: DbVersion = 18;
 
 /// Protocol version type.
 pub type ProtocolVersion = u32;
@@ -75,6 +75,21 @@ impl ProtocolVersionRange {
 pub enum ProtocolFeature {
     #[cfg(feature = "protocol_feature_forward_chunk_parts")]
     ForwardChunkParts,
+    /// Adds `ed25519_verify` and `ecrecover` host functions.
+    #[cfg(feature = "protocol_feature_signature_verification")]
+    SignatureVerification,
+    /// Adds `ripemd160`, `blake2b` and alt_bn128 host functions.
+    #[cfg(feature = "protocol_feature_math_extension")]
+    MathExtension,
+    /// Adds `FunctionCallV2` access keys, which can attach a bounded deposit and expire.
+    #[cfg(feature = "protocol_feature_function_call_permission_v2")]
+    FunctionCallPermissionV2,
+    /// Adds `Delegate` actions, which let a relayer pay for actions signed by another account.
+    #[cfg(feature = "protocol_feature_delegate_action")]
+    DelegateAction,
+    /// Peers exchange signed ephemeral keys in the handshake and encrypt the connection afterwards.
+    #[cfg(feature = "protocol_feature_encrypted_peer_connection")]
+    EncryptedPeerConnection,
 }
 
 /// Current latest stable version of the protocol.
@@ -83,7 +98,7 @@ pub const PROTOCOL_VERSION: ProtocolVersion = 40;
 
 /// Current latest nightly version of the protocol.
 #[cfg(feature = "nightly_protocol")]
-pub const PROTOCOL_VERSION: ProtocolVersion = 41;
+pub const PROTOCOL_VERSION: ProtocolVersion = 46;
 
 lazy_static! {
     static ref STABLE_PROTOCOL_FEATURES_TO_VERSION_MAPPING: HashMap<ProtocolFeature, ProtocolVersion> = vec![
@@ -106,7 +121,22 @@ lazy_static! {
         let nightly_protocol_features_to_version_mapping: HashMap<
             ProtocolFeature,
             ProtocolVersion,
-        > = vec![(ProtocolFeature::ForwardChunkParts, 41)].into_iter().collect();
+        > = vec![
+            #[cfg(feature = "protocol_feature_forward_chunk_parts")]
+            (ProtocolFeature::ForwardChunkParts, 41),
+            #[cfg(feature = "protocol_feature_signature_verification")]
+            (ProtocolFeature::SignatureVerification, 42),
+            #[cfg(feature = "protocol_feature_math_extension")]
+            (ProtocolFeature::MathExtension, 43),
+            #[cfg(feature = "protocol_feature_function_call_permission_v2")]
+            (ProtocolFeature::FunctionCallPermissionV2, 44),
+            #[cfg(feature = "protocol_feature_delegate_action")]
+            (ProtocolFeature::DelegateAction, 45),
+            #[cfg(feature = "protocol_feature_encrypted_peer_connection")]
+            (ProtocolFeature::EncryptedPeerConnection, 46),
+        ]
+        .into_iter()
+        .collect();
         for (stable_protocol_feature, stable_protocol_version) in
             STABLE_PROTOCOL_FEATURES_TO_VERSION_MAPPING.iter()
         {
@@ -125,7 +155,7 @@ macro_rules! checked_feature {
         #[cfg(feature = $feature_name)]
         let is_feature_enabled = near_primitives::version::PROTOCOL_FEATURES_TO_VERSION_MAPPING
             [&near_primitives::version::ProtocolFeature::$feature]
-            >= $current_protocol_version;
+            <= $current_protocol_version;
         #[cfg(not(feature = $feature_name))]
         let is_feature_enabled = {
             // Workaround unused variable warning
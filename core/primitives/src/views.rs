@@ -12,7 +12,9 @@ use serde::{Deserialize, Serialize};
 
 use near_crypto::{PublicKey, Signature};
 
-use crate::account::{AccessKey, AccessKeyPermission, Account, FunctionCallPermission};
+use crate::account::{
+    AccessKey, AccessKeyPermission, Account, FunctionCallPermission, FunctionCallPermissionV2,
+};
 use crate::block::{Block, BlockHeader};
 use crate::block_header::{
     BlockHeaderInnerLite, BlockHeaderInnerRest, BlockHeaderInnerRestV2, BlockHeaderV1,
@@ -32,9 +34,10 @@ use crate::sharding::{
     ChunkHash, ShardChunk, ShardChunkHeader, ShardChunkHeaderInner, ShardChunkHeaderV2,
 };
 use crate::transaction::{
-    Action, AddKeyAction, CreateAccountAction, DeleteAccountAction, DeleteKeyAction,
-    DeployContractAction, ExecutionOutcome, ExecutionOutcomeWithIdAndProof, ExecutionStatus,
-    FunctionCallAction, SignedTransaction, StakeAction, TransferAction,
+    Action, AddKeyAction, CreateAccountAction, DelegateAction, DeleteAccountAction,
+    DeleteKeyAction, DeployContractAction, ExecutionOutcome, ExecutionOutcomeWithIdAndProof,
+    ExecutionStatus, FunctionCallAction, GasProfile, SignedDelegateAction, SignedTransaction,
+    StakeAction, TransferAction,
 };
 use crate::types::{
     AccountId, AccountWithPublicKey, Balance, BlockHeight, EpochId, FunctionArgs, Gas, Nonce,
@@ -102,6 +105,15 @@ pub enum AccessKeyPermissionView {
         method_names: Vec<String>,
     },
     FullAccess,
+    FunctionCallV2 {
+        #[serde(with = "option_u128_dec_format")]
+        allowance: Option<Balance>,
+        receiver_id: AccountId,
+        method_names: Vec<String>,
+        #[serde(with = "u128_dec_format")]
+        max_deposit_per_call: Balance,
+        expires_at_height: Option<BlockHeight>,
+    },
 }
 
 impl From<AccessKeyPermission> for AccessKeyPermissionView {
@@ -113,6 +125,15 @@ impl From<AccessKeyPermission> for AccessKeyPermissionView {
                 method_names: func_call.method_names,
             },
             AccessKeyPermission::FullAccess => AccessKeyPermissionView::FullAccess,
+            AccessKeyPermission::FunctionCallV2(func_call) => {
+                AccessKeyPermissionView::FunctionCallV2 {
+                    allowance: func_call.allowance,
+                    receiver_id: func_call.receiver_id,
+                    method_names: func_call.method_names,
+                    max_deposit_per_call: func_call.max_deposit_per_call,
+                    expires_at_height: func_call.expires_at_height,
+                }
+            }
         }
     }
 }
@@ -128,6 +149,19 @@ impl From<AccessKeyPermissionView> for AccessKeyPermission {
                 })
             }
             AccessKeyPermissionView::FullAccess => AccessKeyPermission::FullAccess,
+            AccessKeyPermissionView::FunctionCallV2 {
+                allowance,
+                receiver_id,
+                method_names,
+                max_deposit_per_call,
+                expires_at_height,
+            } => AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                allowance,
+                receiver_id,
+                method_names,
+                max_deposit_per_call,
+                expires_at_height,
+            }),
         }
     }
 }
@@ -167,6 +201,30 @@ pub struct ViewStateResult {
     pub proof: TrieProofPath,
 }
 
+/// Proof of a state item at a block, checkable with only the block's `BlockHeaderInnerLiteView`.
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct StateProofView {
+    /// `prev_state_root` of the chunk of the shard included in the block, i.e. the state the
+    /// block's chunks were applied to.
+    pub state_root: StateRoot,
+    /// Merkle path of `state_root` in the `prev_state_root` of the block header.
+    pub state_root_proof: MerklePath,
+    /// Trie nodes read to get the item, proving it against `state_root`.
+    pub trie_proof: TrieProofPath,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct AccountWithProofView {
+    pub account: AccountView,
+    pub proof: StateProofView,
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
+pub struct AccessKeyWithProofView {
+    pub access_key: AccessKeyView,
+    pub proof: StateProofView,
+}
+
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
 pub struct CallResult {
     pub result: Vec<u8>,
@@ -205,6 +263,8 @@ pub enum QueryResponseKind {
     Error(QueryError),
     AccessKey(AccessKeyView),
     AccessKeyList(AccessKeyList),
+    AccountWithProof(AccountWithProofView),
+    AccessKeyWithProof(AccessKeyWithProofView),
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -231,6 +291,25 @@ pub enum QueryRequest {
         #[serde(rename = "args_base64", with = "base64_format")]
         args: FunctionArgs,
     },
+    /// Same as `ViewState`, but the result also contains the trie nodes proving the returned
+    /// values against the state root of the queried block.
+    ViewStateWithProof {
+        account_id: AccountId,
+        #[serde(rename = "prefix_base64", with = "base64_format")]
+        prefix: StoreKey,
+    },
+    /// Same as `ViewAccount`, but proven against the `prev_state_root` of the header of the
+    /// queried block. The header only commits to the state before its chunks were applied, so
+    /// unlike `ViewAccount` the account is read from this pre-state, see `StateProofView`.
+    ViewAccountWithProof {
+        account_id: AccountId,
+    },
+    /// Same as `ViewAccessKey`, but read from the pre-state of the queried block and proven
+    /// against its header, as `ViewAccountWithProof`.
+    ViewAccessKeyWithProof {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Serialize, Deserialize, Debug, PartialEq, Eq, Clone)]
@@ -681,6 +760,15 @@ pub enum ActionView {
     DeleteAccount {
         beneficiary_id: AccountId,
     },
+    Delegate {
+        sender_id: AccountId,
+        receiver_id: AccountId,
+        actions: Vec<ActionView>,
+        nonce: Nonce,
+        max_block_height: BlockHeight,
+        public_key: PublicKey,
+        signature: Signature,
+    },
 }
 
 impl From<Action> for ActionView {
@@ -708,6 +796,18 @@ impl From<Action> for ActionView {
             Action::DeleteAccount(action) => {
                 ActionView::DeleteAccount { beneficiary_id: action.beneficiary_id }
             }
+            Action::Delegate(action) => {
+                let SignedDelegateAction { delegate_action, signature } = action;
+                ActionView::Delegate {
+                    sender_id: delegate_action.sender_id,
+                    receiver_id: delegate_action.receiver_id,
+                    actions: delegate_action.actions.into_iter().map(Into::into).collect(),
+                    nonce: delegate_action.nonce,
+                    max_block_height: delegate_action.max_block_height,
+                    public_key: delegate_action.public_key,
+                    signature,
+                }
+            }
         }
     }
 }
@@ -742,6 +842,28 @@ impl TryFrom<ActionView> for Action {
             ActionView::DeleteAccount { beneficiary_id } => {
                 Action::DeleteAccount(DeleteAccountAction { beneficiary_id })
             }
+            ActionView::Delegate {
+                sender_id,
+                receiver_id,
+                actions,
+                nonce,
+                max_block_height,
+                public_key,
+                signature,
+            } => Action::Delegate(SignedDelegateAction {
+                delegate_action: DelegateAction {
+                    sender_id,
+                    receiver_id,
+                    actions: actions
+                        .into_iter()
+                        .map(TryInto::try_into)
+                        .collect::<Result<Vec<_>, _>>()?,
+                    nonce,
+                    max_block_height,
+                    public_key,
+                },
+                signature,
+            }),
         })
     }
 }
@@ -876,6 +998,11 @@ pub struct ExecutionOutcomeView {
     pub executor_id: AccountId,
     /// Execution status. Contains the result in case of successful execution.
     pub status: ExecutionStatusView,
+    /// Breakdown of `gas_burnt` of a receipt, only returned when requested from a node that
+    /// records gas profiles.
+    #[borsh_skip]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gas_profile: Option<GasProfile>,
 }
 
 impl From<ExecutionOutcome> for ExecutionOutcomeView {
@@ -887,6 +1014,7 @@ impl From<ExecutionOutcome> for ExecutionOutcomeView {
             tokens_burnt: outcome.tokens_burnt,
             executor_id: outcome.executor_id,
             status: outcome.status.into(),
+            gas_profile: outcome.gas_profile,
         }
     }
 }
@@ -1168,11 +1296,28 @@ pub struct GasPriceView {
     pub gas_price: Balance,
 }
 
+/// Transactions and receipts that touched an account in a single block.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountHistoryBlockView {
+    pub block_height: BlockHeight,
+    pub block_hash: CryptoHash,
+    pub transactions: Vec<CryptoHash>,
+    pub receipts: Vec<CryptoHash>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AccountHistoryView {
+    /// Blocks in which the account was touched, most recent first.
+    pub blocks: Vec<AccountHistoryBlockView>,
+    /// Height to pass as `from_height` to fetch the next page, `None` if there are no more blocks.
+    pub next_from_height: Option<BlockHeight>,
+}
+
 /// It is a [serializable view] of [`StateChangesRequest`].
 ///
 /// [serializable view]: ./index.html
 /// [`StateChangesRequest`]: ../types/struct.StateChangesRequest.html
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "changes_type", rename_all = "snake_case")]
 pub enum StateChangesRequestView {
     AccountChanges {
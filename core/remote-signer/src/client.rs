@@ -0,0 +1,185 @@
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use borsh::BorshSerialize;
+use log::error;
+
+use near_crypto::{PublicKey, Signature};
+use near_primitives::block::{ApprovalInner, BlockHeader};
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::network::PeerId;
+use near_primitives::sharding::{ChunkHash, ShardChunkHeaderInner, ShardChunkHeaderVersion};
+use near_primitives::telemetry::TelemetryInfo;
+use near_primitives::types::{AccountId, BlockHeight, EpochId};
+use near_primitives::validator_signer::{SignerError, ValidatorSigner};
+
+use crate::protocol::{read_message, write_message, SignerRequest, SignerResponse, SignerStream};
+
+/// Validator signer that sends every signing request to a separate signer process.
+///
+/// If the signer is unreachable or refuses to sign, the error is returned to the caller, so
+/// nothing is produced instead of a message with an invalid signature.
+pub struct RemoteValidatorSigner {
+    address: String,
+    auth_token: Option<String>,
+    account_id: AccountId,
+    public_key: PublicKey,
+    stream: Mutex<Option<SignerStream>>,
+}
+
+impl RemoteValidatorSigner {
+    /// Connects to the signer and fetches the validator account and public key.
+    pub fn connect(address: &str, auth_token: Option<&str>) -> io::Result<Self> {
+        let mut stream = SignerStream::connect(address, auth_token)?;
+        write_message(&mut stream, &SignerRequest::ValidatorInfo)?;
+        match read_message(&mut stream)? {
+            SignerResponse::ValidatorInfo { account_id, public_key } => Ok(Self {
+                address: address.to_string(),
+                auth_token: auth_token.map(str::to_string),
+                account_id,
+                public_key,
+                stream: Mutex::new(Some(stream)),
+            }),
+            response => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected signer response {:?}", response),
+            )),
+        }
+    }
+
+    /// Sends the request, reconnecting once if the connection was lost.
+    fn request(&self, request: &SignerRequest) -> io::Result<SignerResponse> {
+        let mut stream = self.stream.lock().expect("Signer stream lock poisoned");
+        let mut last_error = None;
+        for _ in 0..2 {
+            if stream.is_none() {
+                *stream = Some(SignerStream::connect(&self.address, self.auth_token.as_deref())?);
+            }
+            let connection = stream.as_mut().unwrap();
+            match write_message(connection, request).and_then(|_| read_message(connection)) {
+                Ok(response) => return Ok(response),
+                Err(err) => {
+                    *stream = None;
+                    last_error = Some(err);
+                }
+            }
+        }
+        Err(last_error.unwrap())
+    }
+
+    /// Sends the request, turning refusals of the signer into errors.
+    fn signer_request(&self, request: SignerRequest) -> Result<SignerResponse, SignerError> {
+        match self.request(&request) {
+            Ok(SignerResponse::Refused(reason)) => {
+                error!(target: "signer", "Remote signer refused to sign {:?}: {}", request, reason);
+                Err(SignerError::Refused(reason))
+            }
+            Ok(response) => Ok(response),
+            Err(err) => {
+                error!(target: "signer", "Remote signer request failed: {}", err);
+                Err(SignerError::Unavailable(err.to_string()))
+            }
+        }
+    }
+
+    fn request_signature(&self, request: SignerRequest) -> Result<Signature, SignerError> {
+        match self.signer_request(request)? {
+            SignerResponse::Signature(signature) => Ok(signature),
+            response => Err(unexpected_response(response)),
+        }
+    }
+}
+
+fn unexpected_response(response: SignerResponse) -> SignerError {
+    SignerError::Unavailable(format!("unexpected response {:?}", response))
+}
+
+impl ValidatorSigner for RemoteValidatorSigner {
+    fn validator_id(&self) -> &AccountId {
+        &self.account_id
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_key.clone()
+    }
+
+    fn sign_telemetry(&self, info: &TelemetryInfo) -> Result<serde_json::Value, SignerError> {
+        let mut value = serde_json::to_value(info).expect("Telemetry must serialize to JSON");
+        let content = serde_json::to_string(&value).expect("Telemetry must serialize to JSON");
+        let signature = self.request_signature(SignerRequest::Telemetry(content))?;
+        value["signature"] = format!("{}", signature).into();
+        Ok(value)
+    }
+
+    fn sign_block_header_parts(
+        &self,
+        prev_hash: CryptoHash,
+        inner_lite: &[u8],
+        inner_rest: &[u8],
+    ) -> Result<(CryptoHash, Signature), SignerError> {
+        let hash = BlockHeader::compute_hash(prev_hash, inner_lite, inner_rest);
+        let signature = self.request_signature(SignerRequest::BlockHeader {
+            prev_hash,
+            inner_lite: inner_lite.to_vec(),
+            inner_rest: inner_rest.to_vec(),
+        })?;
+        Ok((hash, signature))
+    }
+
+    fn sign_chunk_header(
+        &self,
+        inner: &ShardChunkHeaderInner,
+        version: ShardChunkHeaderVersion,
+    ) -> Result<(ChunkHash, Signature), SignerError> {
+        let hash = version.compute_hash(inner);
+        let signature =
+            self.request_signature(SignerRequest::ChunkHeader { inner: inner.clone(), version })?;
+        Ok((hash, signature))
+    }
+
+    fn sign_approval(
+        &self,
+        inner: &ApprovalInner,
+        target_height: BlockHeight,
+    ) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::Approval { inner: inner.clone(), target_height })
+    }
+
+    fn sign_challenge(
+        &self,
+        challenge_body: &ChallengeBody,
+    ) -> Result<(CryptoHash, Signature), SignerError> {
+        let hash = hash(&challenge_body.try_to_vec().expect("Failed to serialize"));
+        let signature = self.request_signature(SignerRequest::Challenge(challenge_body.clone()))?;
+        Ok((hash, signature))
+    }
+
+    fn sign_account_announce(
+        &self,
+        account_id: &AccountId,
+        peer_id: &PeerId,
+        epoch_id: &EpochId,
+    ) -> Result<Signature, SignerError> {
+        self.request_signature(SignerRequest::AccountAnnounce {
+            account_id: account_id.clone(),
+            peer_id: peer_id.clone(),
+            epoch_id: epoch_id.clone(),
+        })
+    }
+
+    fn compute_vrf_with_proof(
+        &self,
+        data: &[u8],
+    ) -> Result<(near_crypto::vrf::Value, near_crypto::vrf::Proof), SignerError> {
+        match self.signer_request(SignerRequest::Vrf(data.to_vec()))? {
+            SignerResponse::Vrf { value, proof } => Ok((value, proof)),
+            response => Err(unexpected_response(response)),
+        }
+    }
+
+    fn write_to_file(&self, _path: &Path) -> Result<(), SignerError> {
+        Err(SignerError::KeyNotAvailable)
+    }
+}
@@ -0,0 +1,87 @@
+//! Blocks, chunks, challenges and account announcements are all signed as a hash of some data,
+//! without a tag telling what kind of message it is. Changing that needs a protocol upgrade, so
+//! instead the signer only signs a hash if its preimage can't be read as the preimage of
+//! another kind of message, otherwise the signature could be replayed as that message.
+//! Approvals and telemetry are signed as is, never as 32 bytes, so they can't be confused with
+//! the hashes.
+use borsh::BorshDeserialize;
+
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::network::PeerId;
+use near_primitives::sharding::ShardChunkHeaderInner;
+use near_primitives::types::{AccountId, EpochId};
+
+/// Size of the preimage of hashes combined from two other hashes.
+const COMBINED_HASH_PREIMAGE_LEN: usize = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Domain {
+    /// Hash combined from two other hashes: blocks and chunk headers starting from V2.
+    CombinedHash,
+    /// Inner part of the chunk header V1.
+    ChunkHeaderInner,
+    Challenge,
+    AccountAnnounce,
+}
+
+const DOMAINS: [Domain; 4] =
+    [Domain::CombinedHash, Domain::ChunkHeaderInner, Domain::Challenge, Domain::AccountAnnounce];
+
+impl Domain {
+    fn is_preimage(self, data: &[u8]) -> bool {
+        match self {
+            Domain::CombinedHash => data.len() == COMBINED_HASH_PREIMAGE_LEN,
+            Domain::ChunkHeaderInner => ShardChunkHeaderInner::try_from_slice(data).is_ok(),
+            Domain::Challenge => ChallengeBody::try_from_slice(data).is_ok(),
+            Domain::AccountAnnounce => <(AccountId, PeerId, EpochId)>::try_from_slice(data).is_ok(),
+        }
+    }
+}
+
+/// Returns the hash to sign for the preimage of the given domain, or an error if the preimage
+/// also reads as the preimage of another domain.
+pub fn signing_hash(domain: Domain, preimage: &[u8]) -> Result<CryptoHash, String> {
+    if let Some(other) =
+        DOMAINS.iter().find(|other| **other != domain && other.is_preimage(preimage))
+    {
+        return Err(format!("{:?} data is also a valid {:?}", domain, other));
+    }
+    Ok(hash(preimage))
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use near_crypto::{KeyType, PublicKey};
+    use near_primitives::challenge::BlockDoubleSign;
+
+    use super::*;
+
+    #[test]
+    fn test_combined_hash_is_not_challenge() {
+        let preimage = [hash(&[1]).as_ref(), hash(&[2]).as_ref()].concat();
+        assert_eq!(signing_hash(Domain::CombinedHash, &preimage).unwrap(), hash(&preimage));
+
+        // A challenge with headers of 27 and 28 bytes is 64 bytes long, just as two hashes.
+        let body = ChallengeBody::BlockDoubleSign(BlockDoubleSign {
+            left_block_header: vec![1; 27],
+            right_block_header: vec![2; 28],
+        });
+        let preimage = body.try_to_vec().unwrap();
+        assert_eq!(preimage.len(), COMBINED_HASH_PREIMAGE_LEN);
+        assert!(signing_hash(Domain::Challenge, &preimage).is_err());
+        assert!(signing_hash(Domain::CombinedHash, &preimage).is_err());
+    }
+
+    #[test]
+    fn test_account_announce_is_not_challenge() {
+        let preimage =
+            ("test".to_string(), PeerId(PublicKey::empty(KeyType::ED25519)), EpochId::default())
+                .try_to_vec()
+                .unwrap();
+        assert_eq!(signing_hash(Domain::AccountAnnounce, &preimage).unwrap(), hash(&preimage));
+        assert!(signing_hash(Domain::Challenge, &preimage).is_err());
+    }
+}
@@ -0,0 +1,328 @@
+//! Double-sign protection: remembers what was signed at every height and refuses to sign
+//! anything different at the same height, even after a restart of the signer.
+//!
+//! Blocks, approvals and chunks are the only messages tied to a height. Signing two different
+//! challenges, account announcements or telemetry reports is not misbehavior.
+//!
+//! The state is persisted as a snapshot of the whole signing window plus a log of the records
+//! signed since the snapshot was written. Signing only appends one record to the log, the
+//! snapshot is rewritten and the log emptied when old heights are pruned.
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use near_primitives::hash::CryptoHash;
+use near_primitives::types::{BlockHeight, ShardId};
+
+/// Number of most recent heights remembered. Anything below that is refused.
+const KEEP_HEIGHTS: BlockHeight = 10_000;
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct SigningGuard {
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Log of the records signed after the snapshot at `path` was written.
+    #[serde(skip)]
+    log: Option<File>,
+    /// Height -> hash of the block signed at this height.
+    blocks: BTreeMap<BlockHeight, CryptoHash>,
+    /// Target height -> hash of the approval data signed for this height.
+    approvals: BTreeMap<BlockHeight, CryptoHash>,
+    /// Height -> shard -> hash of the chunk signed at this height in this shard.
+    #[serde(default)]
+    chunks: BTreeMap<BlockHeight, BTreeMap<ShardId, CryptoHash>>,
+    /// Nothing below this height is signed since it was already forgotten.
+    min_height: BlockHeight,
+}
+
+/// Message signed at some height, as stored in the log.
+#[derive(Serialize, Deserialize, Debug)]
+enum SignedRecord {
+    Block { height: BlockHeight, hash: CryptoHash },
+    Approval { target_height: BlockHeight, hash: CryptoHash },
+    Chunk { height: BlockHeight, shard_id: ShardId, hash: CryptoHash },
+}
+
+impl SignedRecord {
+    fn height(&self) -> BlockHeight {
+        match self {
+            SignedRecord::Block { height, .. } | SignedRecord::Chunk { height, .. } => *height,
+            SignedRecord::Approval { target_height, .. } => *target_height,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            SignedRecord::Block { .. } => "block",
+            SignedRecord::Approval { .. } => "approval",
+            SignedRecord::Chunk { .. } => "chunk",
+        }
+    }
+}
+
+impl SigningGuard {
+    /// Loads the guard state from `path` and its log if they exist. Without `path` the state is
+    /// kept in memory only, which only protects from double signing until the signer restarts.
+    pub fn open(path: Option<PathBuf>) -> io::Result<Self> {
+        let mut guard = match &path {
+            Some(path) if path.exists() => serde_json::from_slice(&fs::read(path)?)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+            _ => Self::default(),
+        };
+        if let Some(path) = &path {
+            let log_path = path.with_extension("log");
+            let content = if log_path.exists() { fs::read(&log_path)? } else { vec![] };
+            // A crash while appending leaves a partial last record. Its signature was never
+            // returned since the record wasn't synced yet, so it is dropped.
+            let valid_len =
+                content.iter().rposition(|byte| *byte == b'\n').map_or(0, |pos| pos + 1);
+            for line in content[..valid_len].split(|byte| *byte == b'\n') {
+                if line.is_empty() {
+                    continue;
+                }
+                let record: SignedRecord = serde_json::from_slice(line)
+                    .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                // Records below the window are left over from a crash during compaction.
+                if record.height() >= guard.min_height {
+                    guard
+                        .record(&record)
+                        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+                }
+            }
+            let log = OpenOptions::new().create(true).append(true).open(&log_path)?;
+            if valid_len < content.len() {
+                log.set_len(valid_len as u64)?;
+            }
+            guard.log = Some(log);
+        }
+        guard.path = path;
+        Ok(guard)
+    }
+
+    pub fn check_block(
+        &mut self,
+        height: BlockHeight,
+        block_hash: CryptoHash,
+    ) -> Result<(), String> {
+        self.check(SignedRecord::Block { height, hash: block_hash })
+    }
+
+    pub fn check_approval(
+        &mut self,
+        target_height: BlockHeight,
+        approval_hash: CryptoHash,
+    ) -> Result<(), String> {
+        self.check(SignedRecord::Approval { target_height, hash: approval_hash })
+    }
+
+    pub fn check_chunk(
+        &mut self,
+        height: BlockHeight,
+        shard_id: ShardId,
+        chunk_hash: CryptoHash,
+    ) -> Result<(), String> {
+        self.check(SignedRecord::Chunk { height, shard_id, hash: chunk_hash })
+    }
+
+    /// The record must be stored before the signature is returned, otherwise a crash right after
+    /// signing would allow signing a conflicting message after restart.
+    fn check(&mut self, record: SignedRecord) -> Result<(), String> {
+        if record.height() < self.min_height {
+            return Err(format!(
+                "{} height {} is below the signing window",
+                record.kind(),
+                record.height()
+            ));
+        }
+        if self.record(&record)? {
+            self.append(&record).map_err(|err| format!("failed to log signed record: {}", err))?;
+            self.prune().map_err(|err| format!("failed to persist signer state: {}", err))?;
+        }
+        Ok(())
+    }
+
+    /// Returns whether the record is new, or an error if something else was signed instead.
+    fn record(&mut self, record: &SignedRecord) -> Result<bool, String> {
+        match *record {
+            SignedRecord::Block { height, hash } => {
+                Self::check_and_record(&mut self.blocks, height, hash, "block", height)
+            }
+            SignedRecord::Approval { target_height, hash } => Self::check_and_record(
+                &mut self.approvals,
+                target_height,
+                hash,
+                "approval",
+                target_height,
+            ),
+            SignedRecord::Chunk { height, shard_id, hash } => {
+                let shard_chunks = self.chunks.entry(height).or_default();
+                Self::check_and_record(shard_chunks, shard_id, hash, "chunk", height)
+            }
+        }
+    }
+
+    fn check_and_record<K: Ord>(
+        signed: &mut BTreeMap<K, CryptoHash>,
+        key: K,
+        hash: CryptoHash,
+        kind: &str,
+        height: BlockHeight,
+    ) -> Result<bool, String> {
+        match signed.get(&key) {
+            Some(signed_hash) if signed_hash != &hash => Err(format!(
+                "already signed {} {} at height {}, refusing to sign {}",
+                kind, signed_hash, height, hash
+            )),
+            Some(_) => Ok(false),
+            None => {
+                signed.insert(key, hash);
+                Ok(true)
+            }
+        }
+    }
+
+    fn append(&mut self, record: &SignedRecord) -> io::Result<()> {
+        if let Some(log) = &mut self.log {
+            let mut line = serde_json::to_vec(record)?;
+            line.push(b'\n');
+            log.write_all(&line)?;
+            log.sync_data()?;
+        }
+        Ok(())
+    }
+
+    /// Forgets old heights once the window grew to twice its size, and folds the log into the
+    /// snapshot at the same time.
+    fn prune(&mut self) -> io::Result<()> {
+        let max_height = self
+            .blocks
+            .keys()
+            .next_back()
+            .into_iter()
+            .chain(self.approvals.keys().next_back())
+            .chain(self.chunks.keys().next_back())
+            .max()
+            .cloned()
+            .unwrap_or_default();
+        if max_height < self.min_height + 2 * KEEP_HEIGHTS {
+            return Ok(());
+        }
+        self.min_height = max_height - KEEP_HEIGHTS;
+        self.blocks = self.blocks.split_off(&self.min_height);
+        self.approvals = self.approvals.split_off(&self.min_height);
+        self.chunks = self.chunks.split_off(&self.min_height);
+        if let Some(path) = &self.path {
+            write_durably(path, &serde_json::to_vec(&self)?)?;
+        }
+        // The log is only emptied once the snapshot with all its records is on disk.
+        if let Some(log) = &self.log {
+            log.set_len(0)?;
+            log.sync_data()?;
+        }
+        Ok(())
+    }
+}
+
+/// Replaces the file at `path` with `content`, so that after a crash or a power loss the file
+/// has either the old or the new content. Both the data and the rename have to reach the disk
+/// before returning, otherwise the rename may survive a power loss without the data.
+fn write_durably(path: &Path, content: &[u8]) -> io::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    let mut file = File::create(&tmp_path)?;
+    file.write_all(content)?;
+    file.sync_all()?;
+    fs::rename(&tmp_path, path)?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(test)]
+mod tests {
+    use near_primitives::hash::hash;
+
+    use super::*;
+
+    #[test]
+    fn test_refuses_conflicting_blocks() {
+        let mut guard = SigningGuard::open(None).unwrap();
+        guard.check_block(10, hash(&[1])).unwrap();
+        guard.check_block(10, hash(&[1])).unwrap();
+        assert!(guard.check_block(10, hash(&[2])).is_err());
+        guard.check_block(11, hash(&[2])).unwrap();
+        // Blocks and approvals are tracked separately.
+        guard.check_approval(10, hash(&[3])).unwrap();
+        assert!(guard.check_approval(10, hash(&[4])).is_err());
+    }
+
+    #[test]
+    fn test_refuses_conflicting_chunks() {
+        let mut guard = SigningGuard::open(None).unwrap();
+        guard.check_chunk(10, 0, hash(&[1])).unwrap();
+        guard.check_chunk(10, 1, hash(&[2])).unwrap();
+        guard.check_chunk(10, 0, hash(&[1])).unwrap();
+        assert!(guard.check_chunk(10, 0, hash(&[2])).is_err());
+        assert!(guard.check_chunk(10, 1, hash(&[1])).is_err());
+        // Chunks and blocks are tracked separately.
+        guard.check_block(10, hash(&[3])).unwrap();
+    }
+
+    #[test]
+    fn test_state_survives_restart() {
+        let dir = tempfile::Builder::new().prefix("remote_signer").tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let mut guard = SigningGuard::open(Some(path.clone())).unwrap();
+        guard.check_block(10, hash(&[1])).unwrap();
+        guard.check_approval(11, hash(&[1])).unwrap();
+        guard.check_chunk(12, 3, hash(&[1])).unwrap();
+
+        let mut guard = SigningGuard::open(Some(path)).unwrap();
+        assert!(guard.check_block(10, hash(&[2])).is_err());
+        assert!(guard.check_approval(11, hash(&[2])).is_err());
+        assert!(guard.check_chunk(12, 3, hash(&[2])).is_err());
+        guard.check_block(10, hash(&[1])).unwrap();
+    }
+
+    #[test]
+    fn test_state_survives_compaction_and_torn_record() {
+        let dir = tempfile::Builder::new().prefix("remote_signer").tempdir().unwrap();
+        let path = dir.path().join("state.json");
+        let log_path = path.with_extension("log");
+        let mut guard = SigningGuard::open(Some(path.clone())).unwrap();
+        guard.check_block(1, hash(&[1])).unwrap();
+        guard.check_block(2 * KEEP_HEIGHTS, hash(&[1])).unwrap();
+        assert_eq!(fs::metadata(&log_path).unwrap().len(), 0);
+        guard.check_approval(2 * KEEP_HEIGHTS + 1, hash(&[1])).unwrap();
+        let log_len = fs::metadata(&log_path).unwrap().len();
+        assert!(log_len > 0);
+        drop(guard);
+
+        // Crash in the middle of appending a record.
+        OpenOptions::new().append(true).open(&log_path).unwrap().write_all(b"{\"Blo").unwrap();
+
+        let mut guard = SigningGuard::open(Some(path.clone())).unwrap();
+        assert_eq!(fs::metadata(&log_path).unwrap().len(), log_len);
+        assert!(guard.check_block(1, hash(&[1])).is_err());
+        assert!(guard.check_block(2 * KEEP_HEIGHTS, hash(&[2])).is_err());
+        assert!(guard.check_approval(2 * KEEP_HEIGHTS + 1, hash(&[2])).is_err());
+        guard.check_chunk(2 * KEEP_HEIGHTS + 2, 0, hash(&[1])).unwrap();
+
+        let mut guard = SigningGuard::open(Some(path)).unwrap();
+        assert!(guard.check_chunk(2 * KEEP_HEIGHTS + 2, 0, hash(&[2])).is_err());
+    }
+
+    #[test]
+    fn test_refuses_pruned_heights() {
+        let mut guard = SigningGuard::open(None).unwrap();
+        guard.check_block(1, hash(&[1])).unwrap();
+        guard.check_block(3 * KEEP_HEIGHTS, hash(&[1])).unwrap();
+        assert!(guard.check_block(1, hash(&[1])).is_err());
+        assert!(guard.check_approval(KEEP_HEIGHTS, hash(&[1])).is_err());
+        guard.check_approval(3 * KEEP_HEIGHTS - 1, hash(&[1])).unwrap();
+    }
+}
@@ -0,0 +1,26 @@
+//! Protocol between the node and a separate process holding the validator key.
+//!
+//! Every message is a borsh serialized `SignerRequest` or `SignerResponse` prefixed with its
+//! length as little endian u32. The signer computes the hashes itself from the data to sign,
+//! so it always knows what exactly it signs and can refuse to sign conflicting blocks,
+//! approvals and chunks.
+//!
+//! Anyone who can connect to the signer can make it sign, so over TCP the node has to present
+//! the token from `NEAR_REMOTE_SIGNER_TOKEN` first. Unix sockets are only accessible to the
+//! user running the signer.
+pub use crate::client::RemoteValidatorSigner;
+pub use crate::guard::SigningGuard;
+pub use crate::protocol::{
+    read_message, write_message, SignerRequest, SignerResponse, SignerStream,
+    MAX_SIGNER_MESSAGE_SIZE,
+};
+pub use crate::service::{SignerListener, SignerService};
+
+/// Environment variable with the token the node authenticates to the signer with.
+pub const AUTH_TOKEN_ENV: &str = "NEAR_REMOTE_SIGNER_TOKEN";
+
+mod client;
+mod domain;
+mod guard;
+mod protocol;
+mod service;
@@ -0,0 +1,157 @@
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use near_crypto::{PublicKey, Signature};
+use near_primitives::block::ApprovalInner;
+use near_primitives::challenge::ChallengeBody;
+use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
+use near_primitives::sharding::{ShardChunkHeaderInner, ShardChunkHeaderVersion};
+use near_primitives::types::{AccountId, BlockHeight, EpochId};
+
+/// Messages above this size are considered malformed.
+pub const MAX_SIGNER_MESSAGE_SIZE: u32 = 64 * 1024 * 1024;
+const SIGNER_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub enum SignerRequest {
+    /// Returns the account and the public key of the validator.
+    ValidatorInfo,
+    BlockHeader {
+        prev_hash: CryptoHash,
+        inner_lite: Vec<u8>,
+        inner_rest: Vec<u8>,
+    },
+    ChunkHeader {
+        inner: ShardChunkHeaderInner,
+        version: ShardChunkHeaderVersion,
+    },
+    Approval {
+        inner: ApprovalInner,
+        target_height: BlockHeight,
+    },
+    Challenge(ChallengeBody),
+    AccountAnnounce {
+        account_id: AccountId,
+        peer_id: PeerId,
+        epoch_id: EpochId,
+    },
+    /// JSON serialized telemetry.
+    Telemetry(String),
+    Vrf(Vec<u8>),
+    /// Must be the first request on the connection if the signer requires a token.
+    Authenticate {
+        token: String,
+    },
+}
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
+pub enum SignerResponse {
+    ValidatorInfo {
+        account_id: AccountId,
+        public_key: PublicKey,
+    },
+    Signature(Signature),
+    Vrf {
+        value: near_crypto::vrf::Value,
+        proof: near_crypto::vrf::Proof,
+    },
+    /// Signer refused to sign, e.g. because it already signed something else at this height.
+    Refused(String),
+    Authenticated,
+}
+
+pub fn write_message<T: BorshSerialize, W: Write>(writer: &mut W, message: &T) -> io::Result<()> {
+    let bytes = message.try_to_vec()?;
+    writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+    writer.write_all(&bytes)?;
+    writer.flush()
+}
+
+pub fn read_message<T: BorshDeserialize, R: Read>(reader: &mut R) -> io::Result<T> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u32::from_le_bytes(len);
+    if len > MAX_SIGNER_MESSAGE_SIZE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "Signer message is too large"));
+    }
+    let mut bytes = vec![0u8; len as usize];
+    reader.read_exact(&mut bytes)?;
+    T::try_from_slice(&bytes)
+}
+
+/// Connection to the signer, either `unix:<path>` or `tcp:<host>:<port>`.
+pub enum SignerStream {
+    Unix(UnixStream),
+    Tcp(TcpStream),
+}
+
+impl SignerStream {
+    /// Connects to the signer and authenticates with `auth_token` if it's given.
+    pub fn connect(address: &str, auth_token: Option<&str>) -> io::Result<Self> {
+        let mut stream = if let Some(path) = strip_prefix(address, "unix:") {
+            let stream = UnixStream::connect(Path::new(path))?;
+            stream.set_read_timeout(Some(SIGNER_TIMEOUT))?;
+            stream.set_write_timeout(Some(SIGNER_TIMEOUT))?;
+            SignerStream::Unix(stream)
+        } else {
+            let address = strip_prefix(address, "tcp:").unwrap_or(address);
+            let stream = TcpStream::connect(address)?;
+            stream.set_read_timeout(Some(SIGNER_TIMEOUT))?;
+            stream.set_write_timeout(Some(SIGNER_TIMEOUT))?;
+            stream.set_nodelay(true)?;
+            SignerStream::Tcp(stream)
+        };
+        if let Some(token) = auth_token {
+            write_message(&mut stream, &SignerRequest::Authenticate { token: token.to_string() })?;
+            match read_message(&mut stream)? {
+                SignerResponse::Authenticated => {}
+                response => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::PermissionDenied,
+                        format!("Signer authentication failed: {:?}", response),
+                    ))
+                }
+            }
+        }
+        Ok(stream)
+    }
+}
+
+fn strip_prefix<'a>(value: &'a str, prefix: &str) -> Option<&'a str> {
+    if value.starts_with(prefix) {
+        Some(&value[prefix.len()..])
+    } else {
+        None
+    }
+}
+
+impl Read for SignerStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Unix(stream) => stream.read(buf),
+            SignerStream::Tcp(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for SignerStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            SignerStream::Unix(stream) => stream.write(buf),
+            SignerStream::Tcp(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            SignerStream::Unix(stream) => stream.flush(),
+            SignerStream::Tcp(stream) => stream.flush(),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use std::fs;
+use std::io;
+use std::net::TcpListener;
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixListener;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use log::{error, info, warn};
+
+use near_crypto::{InMemorySigner, Signer};
+use near_primitives::block::{Approval, BlockHeader, BlockHeaderInnerLite};
+use near_primitives::hash::hash;
+use near_primitives::sharding::{ChunkHash, ShardChunkHeaderVersion};
+use near_primitives::telemetry::TelemetryInfo;
+
+use crate::domain::{signing_hash, Domain};
+use crate::guard::SigningGuard;
+use crate::protocol::{read_message, write_message, SignerRequest, SignerResponse, SignerStream};
+
+/// Socket the signer accepts node connections on, `unix:<path>` or `tcp:<host>:<port>`.
+pub enum SignerListener {
+    Unix(UnixListener),
+    Tcp(TcpListener),
+}
+
+impl SignerListener {
+    pub fn bind(address: &str) -> io::Result<Self> {
+        if address.starts_with("unix:") {
+            let path = &address["unix:".len()..];
+            // Socket file of a previous run is left behind after a crash.
+            let _ = fs::remove_file(path);
+            let listener = UnixListener::bind(path)?;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+            Ok(SignerListener::Unix(listener))
+        } else {
+            let address =
+                if address.starts_with("tcp:") { &address["tcp:".len()..] } else { address };
+            Ok(SignerListener::Tcp(TcpListener::bind(address)?))
+        }
+    }
+}
+
+/// Holds the validator key and signs requests of the node, but never signs two different
+/// blocks, approvals or chunks at the same height.
+pub struct SignerService {
+    signer: InMemorySigner,
+    guard: Mutex<SigningGuard>,
+    /// If set, connections have to authenticate with this token before anything else.
+    auth_token: Option<String>,
+}
+
+impl SignerService {
+    pub fn new(signer: InMemorySigner, guard: SigningGuard, auth_token: Option<String>) -> Self {
+        Self { signer, guard: Mutex::new(guard), auth_token }
+    }
+
+    pub fn signer(&self) -> &InMemorySigner {
+        &self.signer
+    }
+
+    /// Serves connections of the listener until it fails, every connection in its own thread.
+    pub fn serve(self: Arc<Self>, listener: SignerListener) {
+        match listener {
+            SignerListener::Unix(listener) => {
+                for stream in listener.incoming() {
+                    self.spawn_connection(stream.map(SignerStream::Unix));
+                }
+            }
+            SignerListener::Tcp(listener) => {
+                for stream in listener.incoming() {
+                    self.spawn_connection(stream.map(SignerStream::Tcp));
+                }
+            }
+        }
+    }
+
+    fn spawn_connection(self: &Arc<Self>, stream: io::Result<SignerStream>) {
+        match stream {
+            Ok(stream) => {
+                let service = self.clone();
+                thread::spawn(move || service.serve_connection(stream));
+            }
+            Err(err) => error!(target: "signer", "Failed to accept connection: {}", err),
+        }
+    }
+
+    fn serve_connection(&self, mut stream: SignerStream) {
+        let mut authenticated = self.auth_token.is_none();
+        loop {
+            let request = match read_message(&mut stream) {
+                Ok(request) => request,
+                Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return,
+                Err(err) => {
+                    error!(target: "signer", "Failed to read request: {}", err);
+                    return;
+                }
+            };
+            let response = match request {
+                SignerRequest::Authenticate { token } => {
+                    authenticated = self.check_token(&token);
+                    if authenticated {
+                        SignerResponse::Authenticated
+                    } else {
+                        SignerResponse::Refused("invalid token".to_string())
+                    }
+                }
+                request if authenticated => self.handle(request),
+                _ => SignerResponse::Refused("authentication required".to_string()),
+            };
+            if let Err(err) = write_message(&mut stream, &response) {
+                error!(target: "signer", "Failed to send response: {}", err);
+                return;
+            }
+            if !authenticated {
+                warn!(target: "signer", "Closing unauthenticated connection");
+                return;
+            }
+        }
+    }
+
+    fn check_token(&self, token: &str) -> bool {
+        match &self.auth_token {
+            // Compared in constant time to not leak the token through the response time.
+            Some(expected) => {
+                expected.len() == token.len()
+                    && expected.bytes().zip(token.bytes()).fold(0, |acc, (a, b)| acc | (a ^ b)) == 0
+            }
+            None => true,
+        }
+    }
+
+    pub fn handle(&self, request: SignerRequest) -> SignerResponse {
+        match self.sign(request) {
+            Ok(response) => response,
+            Err(reason) => {
+                warn!(target: "signer", "Refused to sign: {}", reason);
+                SignerResponse::Refused(reason)
+            }
+        }
+    }
+
+    fn sign(&self, request: SignerRequest) -> Result<SignerResponse, String> {
+        let signature = match request {
+            SignerRequest::Authenticate { .. } => {
+                return Err("authentication is only accepted by the connection".to_string())
+            }
+            SignerRequest::ValidatorInfo => {
+                return Ok(SignerResponse::ValidatorInfo {
+                    account_id: self.signer.account_id.clone(),
+                    public_key: self.signer.public_key(),
+                })
+            }
+            SignerRequest::BlockHeader { prev_hash, inner_lite, inner_rest } => {
+                let height = BlockHeaderInnerLite::try_from_slice(&inner_lite)
+                    .map_err(|err| format!("invalid block header: {}", err))?
+                    .height;
+                let inner_hash = BlockHeader::compute_inner_hash(&inner_lite, &inner_rest);
+                let preimage = [inner_hash.as_ref(), prev_hash.as_ref()].concat();
+                let block_hash = signing_hash(Domain::CombinedHash, &preimage)?;
+                debug_assert_eq!(
+                    block_hash,
+                    BlockHeader::compute_hash(prev_hash, &inner_lite, &inner_rest)
+                );
+                self.guard.lock().unwrap().check_block(height, block_hash)?;
+                info!(target: "signer", "Signing block {} at height {}", block_hash, height);
+                self.signer.sign(block_hash.as_ref())
+            }
+            SignerRequest::ChunkHeader { inner, version } => {
+                let inner_bytes = inner.try_to_vec().map_err(|err| err.to_string())?;
+                let chunk_hash = match version {
+                    ShardChunkHeaderVersion::V1 => {
+                        signing_hash(Domain::ChunkHeaderInner, &inner_bytes)?
+                    }
+                    ShardChunkHeaderVersion::V2 => {
+                        let preimage =
+                            [hash(&inner_bytes).as_ref(), inner.encoded_merkle_root.as_ref()]
+                                .concat();
+                        signing_hash(Domain::CombinedHash, &preimage)?
+                    }
+                };
+                debug_assert_eq!(ChunkHash(chunk_hash), version.compute_hash(&inner));
+                self.guard.lock().unwrap().check_chunk(
+                    inner.height_created,
+                    inner.shard_id,
+                    chunk_hash,
+                )?;
+                self.signer.sign(chunk_hash.as_ref())
+            }
+            SignerRequest::Approval { inner, target_height } => {
+                let data = Approval::get_data_for_sig(&inner, target_height);
+                self.guard.lock().unwrap().check_approval(target_height, hash(&data))?;
+                self.signer.sign(&data)
+            }
+            SignerRequest::Challenge(challenge_body) => {
+                let data = challenge_body.try_to_vec().map_err(|err| err.to_string())?;
+                self.signer.sign(signing_hash(Domain::Challenge, &data)?.as_ref())
+            }
+            SignerRequest::AccountAnnounce { account_id, peer_id, epoch_id } => {
+                if account_id != self.signer.account_id {
+                    return Err(format!("can't announce account {}", account_id));
+                }
+                let data =
+                    (account_id, peer_id, epoch_id).try_to_vec().map_err(|err| err.to_string())?;
+                self.signer.sign(signing_hash(Domain::AccountAnnounce, &data)?.as_ref())
+            }
+            SignerRequest::Telemetry(content) => {
+                serde_json::from_str::<TelemetryInfo>(&content)
+                    .map_err(|err| format!("invalid telemetry: {}", err))?;
+                self.signer.sign(content.as_bytes())
+            }
+            SignerRequest::Vrf(data) => {
+                let (value, proof) = self.signer.compute_vrf_with_proof(&data);
+                return Ok(SignerResponse::Vrf { value, proof });
+            }
+        };
+        Ok(SignerResponse::Signature(signature))
+    }
+}
@@ -112,10 +112,18 @@ pub enum DBCol {
     ColProcessedBlockHeights = 44,
     /// Receipts
     ColReceipts = 45,
+    /// Serialized compiled contract code, keyed by code hash, VM kind and VM config.
+    ColCachedContractCode = 46,
+    /// Transaction hashes and receipt ids by account id and block height. Only maintained by
+    /// archive nodes with `account_history` enabled.
+    ColAccountHistory = 47,
+    /// Gas profiles of receipt outcomes, keyed by receipt id and block hash. Only written by
+    /// nodes with `gas_profiling` enabled.
+    ColGasProfiles = 48,
 }
 
 // Do not move this line from enum DBCol
-pub const NUM_COLS: usize = 46;
+pub const NUM_COLS: usize = 49;
 
 impl std::fmt::Display for DBCol {
     fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> Result<(), std::fmt::Error> {
@@ -166,6 +174,9 @@ impl std::fmt::Display for DBCol {
             Self::_ColTransactionRefCount => "refcount per transaction (deprecated)",
             Self::ColProcessedBlockHeights => "processed block heights",
             Self::ColReceipts => "receipts",
+            Self::ColCachedContractCode => "cached compiled contract code",
+            Self::ColAccountHistory => "account history",
+            Self::ColGasProfiles => "gas profiles",
         };
         write!(formatter, "{}", desc)
     }
@@ -196,6 +207,8 @@ lazy_static! {
         col_gc[DBCol::ColBlockOrdinal as usize] = false;
         col_gc[DBCol::ColEpochInfo as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
         col_gc[DBCol::ColEpochStart as usize] = false; // https://github.com/nearprotocol/nearcore/pull/2952
+        col_gc[DBCol::ColCachedContractCode as usize] = false; // Cache is keyed by code, not by block
+        col_gc[DBCol::ColAccountHistory as usize] = false; // Only maintained by archive nodes
         col_gc
     };
 }
@@ -233,6 +246,7 @@ pub const HEADER_HEAD_KEY: &[u8; 11] = b"HEADER_HEAD";
 pub const FINAL_HEAD_KEY: &[u8; 10] = b"FINAL_HEAD";
 pub const LATEST_KNOWN_KEY: &[u8; 12] = b"LATEST_KNOWN";
 pub const LARGEST_TARGET_HEIGHT_KEY: &[u8; 21] = b"LARGEST_TARGET_HEIGHT";
+pub const STATE_SNAPSHOT_KEY: &[u8; 14] = b"STATE_SNAPSHOT";
 pub const VERSION_KEY: &[u8; 7] = b"VERSION";
 pub const GENESIS_JSON_HASH_KEY: &[u8; 17] = b"GENESIS_JSON_HASH";
 pub const GENESIS_STATE_ROOTS_KEY: &[u8; 19] = b"GENESIS_STATE_ROOTS";
@@ -304,6 +318,14 @@ pub trait Database: Sync + Send {
         col: DBCol,
         key_prefix: &'a [u8],
     ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
+    /// Iterates over the keys starting with `key_prefix` that are not greater than
+    /// `upper_bound`, in descending order.
+    fn iter_prefix_rev<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        upper_bound: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a>;
     fn write(&self, batch: DBTransaction) -> Result<(), DBError>;
     fn as_rocksdb(&self) -> Option<&RocksDB> {
         None
@@ -363,6 +385,27 @@ impl Database for RocksDB {
         }
     }
 
+    fn iter_prefix_rev<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        upper_bound: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        let read_options = rocksdb_read_options();
+        unsafe {
+            let cf_handle = &*self.cfs[col as usize];
+            let iterator = self
+                .db
+                .iterator_cf_opt(
+                    cf_handle,
+                    read_options,
+                    IteratorMode::From(upper_bound, Direction::Reverse),
+                )
+                .take_while(move |(key, _value)| key.starts_with(key_prefix));
+            RocksDB::iter_with_rc_logic(col, iterator)
+        }
+    }
+
     fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
         let mut batch = WriteBatch::default();
         for op in transaction.ops {
@@ -420,6 +463,20 @@ impl Database for TestDB {
         )
     }
 
+    fn iter_prefix_rev<'a>(
+        &'a self,
+        col: DBCol,
+        key_prefix: &'a [u8],
+        upper_bound: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        let mut items: Vec<_> = self
+            .iter(col)
+            .filter(|(key, _value)| key.starts_with(key_prefix) && key.as_ref() <= upper_bound)
+            .collect();
+        items.sort_by(|(a, _), (b, _)| b.cmp(a));
+        Box::new(items.into_iter())
+    }
+
     fn write(&self, transaction: DBTransaction) -> Result<(), DBError> {
         let mut db = self.db.write().unwrap();
         for op in transaction.ops {
@@ -16,17 +16,23 @@ use cached::{Cached, SizedCache};
 pub use db::DBCol::{self, *};
 pub use db::{
     CHUNK_TAIL_KEY, FINAL_HEAD_KEY, FORK_TAIL_KEY, HEADER_HEAD_KEY, HEAD_KEY,
-    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC, TAIL_KEY,
+    LARGEST_TARGET_HEIGHT_KEY, LATEST_KNOWN_KEY, NUM_COLS, SHOULD_COL_GC, SKIP_COL_GC,
+    STATE_SNAPSHOT_KEY, TAIL_KEY,
 };
 use near_crypto::PublicKey;
 use near_primitives::account::{AccessKey, Account};
+use near_primitives::challenge::PartialState;
 use near_primitives::contract::ContractCode;
 pub use near_primitives::errors::StorageError;
 use near_primitives::hash::CryptoHash;
+use near_primitives::merkle::verify_path;
 use near_primitives::receipt::{Receipt, ReceivedData};
-use near_primitives::serialize::to_base;
+use near_primitives::serialize::{from_base64, to_base};
 use near_primitives::trie_key::{trie_key_parsers, TrieKey};
 use near_primitives::types::{AccountId, StateRoot};
+use near_primitives::views::{
+    AccessKeyWithProofView, AccountWithProofView, StateProofView, ViewStateResult,
+};
 
 pub use crate::db::refcount::decode_value_with_rc;
 use crate::db::refcount::encode_value_with_rc;
@@ -102,6 +108,17 @@ impl Store {
         self.storage.iter_prefix(column, key_prefix)
     }
 
+    /// Iterates over the keys starting with `key_prefix` that are not greater than
+    /// `upper_bound`, in descending order.
+    pub fn iter_prefix_rev<'a>(
+        &'a self,
+        column: DBCol,
+        key_prefix: &'a [u8],
+        upper_bound: &'a [u8],
+    ) -> Box<dyn Iterator<Item = (Box<[u8]>, Box<[u8]>)> + 'a> {
+        self.storage.iter_prefix_rev(column, key_prefix, upper_bound)
+    }
+
     pub fn iter_prefix_ser<'a, T: BorshDeserialize>(
         &'a self,
         column: DBCol,
@@ -468,6 +485,98 @@ pub fn remove_account(
     Ok(())
 }
 
+/// Checks a `ViewStateWithProof` query result for the contract data of `account_id` under
+/// `prefix` against the state root it was computed from. For a query at block B that is the state
+/// root of the chunk extra of B, which is the `prev_state_root` of the next chunk of the shard.
+pub fn verify_view_state_proof(
+    state_root: &StateRoot,
+    account_id: &AccountId,
+    prefix: &[u8],
+    result: &ViewStateResult,
+) -> bool {
+    let key_prefix = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
+    let data_key_start = key_prefix.len() - prefix.len();
+    let values = result
+        .values
+        .iter()
+        .map(|item| {
+            let mut key = key_prefix[..data_key_start].to_vec();
+            key.extend(from_base64(&item.key)?);
+            Ok((key, from_base64(&item.value)?))
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>();
+    let proof = result
+        .proof
+        .iter()
+        .map(|node| from_base64(node))
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>();
+    match (values, proof) {
+        (Ok(values), Ok(proof)) => {
+            Trie::verify_prefix_proof(state_root, &key_prefix, &values, PartialState(proof))
+        }
+        _ => false,
+    }
+}
+
+/// Checks a `ViewAccountWithProof` query result for `account_id` against the `prev_state_root`
+/// of the header of the queried block.
+pub fn verify_account_proof(
+    block_prev_state_root: &CryptoHash,
+    account_id: &AccountId,
+    result: &AccountWithProofView,
+) -> bool {
+    let account = Account::from(result.account.clone());
+    verify_state_item_proof(
+        block_prev_state_root,
+        &result.proof,
+        &TrieKey::Account { account_id: account_id.clone() }.to_vec(),
+        &account.try_to_vec().expect("Failed to serialize"),
+    )
+}
+
+/// Checks a `ViewAccessKeyWithProof` query result for `public_key` of `account_id` against the
+/// `prev_state_root` of the header of the queried block.
+pub fn verify_access_key_proof(
+    block_prev_state_root: &CryptoHash,
+    account_id: &AccountId,
+    public_key: &PublicKey,
+    result: &AccessKeyWithProofView,
+) -> bool {
+    let access_key = AccessKey::from(result.access_key.clone());
+    verify_state_item_proof(
+        block_prev_state_root,
+        &result.proof,
+        &TrieKey::AccessKey { account_id: account_id.clone(), public_key: public_key.clone() }
+            .to_vec(),
+        &access_key.try_to_vec().expect("Failed to serialize"),
+    )
+}
+
+fn verify_state_item_proof(
+    block_prev_state_root: &CryptoHash,
+    proof: &StateProofView,
+    key: &[u8],
+    value: &[u8],
+) -> bool {
+    if !verify_path(*block_prev_state_root, &proof.state_root_proof, &proof.state_root) {
+        return false;
+    }
+    let nodes = match proof
+        .trie_proof
+        .iter()
+        .map(|node| from_base64(node))
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()
+    {
+        Ok(nodes) => nodes,
+        Err(_) => return false,
+    };
+    let trie = Trie::from_recorded_storage(PartialStorage { nodes: PartialState(nodes) });
+    match trie.get(&proof.state_root, key) {
+        Ok(Some(stored_value)) => stored_value == value,
+        _ => false,
+    }
+}
+
 pub fn get_genesis_state_roots(store: &Store) -> Result<Option<Vec<StateRoot>>, std::io::Error> {
     store.get_ser::<Vec<StateRoot>>(DBCol::ColBlockMisc, GENESIS_STATE_ROOTS_KEY)
 }
@@ -26,6 +26,7 @@ use crate::StorageError;
 mod insert_delete;
 pub mod iterator;
 mod nibble_slice;
+mod prefix_proofs;
 mod shard_tries;
 mod state_parts;
 mod trie_storage;
@@ -0,0 +1,105 @@
+use near_primitives::challenge::PartialState;
+use near_primitives::types::StateRoot;
+
+use crate::{PartialStorage, StorageError, Trie};
+
+impl Trie {
+    /// Returns all the key-value pairs whose key starts with `key_prefix`, ordered by key.
+    pub fn get_values_with_prefix(
+        &self,
+        state_root: &StateRoot,
+        key_prefix: &[u8],
+    ) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let mut values = vec![];
+        let mut iter = self.iter(state_root)?;
+        iter.seek(key_prefix)?;
+        for item in iter {
+            let (key, value) = item?;
+            if !key.starts_with(key_prefix) {
+                break;
+            }
+            values.push((key, value));
+        }
+        Ok(values)
+    }
+
+    /// Same as `get_values_with_prefix`, but also returns the trie nodes that were read, which
+    /// are enough to check the values with `Trie::verify_prefix_proof`.
+    ///
+    /// # Panics
+    /// storage must be a TrieCachingStorage
+    pub fn get_values_with_prefix_and_proof(
+        &self,
+        state_root: &StateRoot,
+        key_prefix: &[u8],
+    ) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, PartialState), StorageError> {
+        let trie = self.recording_reads();
+        let values = trie.get_values_with_prefix(state_root, key_prefix)?;
+        let proof = trie.recorded_storage().expect("Storage should be TrieRecordingStorage").nodes;
+        Ok((values, proof))
+    }
+
+    /// Checks that `values` are exactly the key-value pairs stored under `key_prefix` in the trie
+    /// with the given `state_root`, using only the trie nodes from `proof`.
+    /// Returns false if a value is missing, extra or different, or if `proof` lacks a node needed
+    /// to walk over the prefix.
+    pub fn verify_prefix_proof(
+        state_root: &StateRoot,
+        key_prefix: &[u8],
+        values: &[(Vec<u8>, Vec<u8>)],
+        proof: PartialState,
+    ) -> bool {
+        let trie = Trie::from_recorded_storage(PartialStorage { nodes: proof });
+        match trie.get_values_with_prefix(state_root, key_prefix) {
+            Ok(proven_values) => proven_values == values,
+            Err(_) => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_utils::{create_tries, test_populate_trie};
+
+    use super::*;
+
+    #[test]
+    fn test_prefix_proof() {
+        let tries = create_tries();
+        let changes = vec![
+            (b"doge".to_vec(), Some(b"coin".to_vec())),
+            (b"docu".to_vec(), Some(b"value".to_vec())),
+            (b"do".to_vec(), Some(b"verb".to_vec())),
+            (b"horse".to_vec(), Some(b"stallion".to_vec())),
+            (b"dog".to_vec(), Some(b"puppy".to_vec())),
+            (b"h".to_vec(), Some(b"value".to_vec())),
+        ];
+        let root = test_populate_trie(&tries, &Trie::empty_root(), 0, changes);
+        let trie = tries.get_trie_for_shard(0);
+
+        let (values, proof) = trie.get_values_with_prefix_and_proof(&root, b"dog").unwrap();
+        assert_eq!(
+            values,
+            vec![(b"dog".to_vec(), b"puppy".to_vec()), (b"doge".to_vec(), b"coin".to_vec())]
+        );
+        assert!(Trie::verify_prefix_proof(&root, b"dog", &values, proof.clone()));
+
+        // Values can be neither dropped nor altered.
+        assert!(!Trie::verify_prefix_proof(&root, b"dog", &values[..1], proof.clone()));
+        let mut altered = values.clone();
+        altered[1].1 = b"bone".to_vec();
+        assert!(!Trie::verify_prefix_proof(&root, b"dog", &altered, proof.clone()));
+
+        // The proof only covers the requested prefix.
+        assert!(!Trie::verify_prefix_proof(&root, b"h", &[], proof.clone()));
+        assert!(!Trie::verify_prefix_proof(&Trie::empty_root(), b"dog", &values, proof.clone()));
+        let mut truncated = proof;
+        truncated.0.pop();
+        assert!(!Trie::verify_prefix_proof(&root, b"dog", &values, truncated));
+
+        // An empty result is proven as well.
+        let (values, proof) = trie.get_values_with_prefix_and_proof(&root, b"cat").unwrap();
+        assert!(values.is_empty());
+        assert!(Trie::verify_prefix_proof(&root, b"cat", &values, proof));
+    }
+}
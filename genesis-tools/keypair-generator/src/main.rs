@@ -3,14 +3,56 @@ use std::path::{Path, PathBuf};
 
 use clap::{App, AppSettings, Arg, SubCommand};
 
-use near_crypto::{InMemorySigner, KeyType, SecretKey, Signer};
+use near_crypto::{InMemorySigner, KeyFile, KeyType, SecretKey, Signer, KEY_PASSPHRASE_ENV};
 use neard::get_default_home;
 
+/// Environment variable with the new passphrase for `rotate`.
+const NEW_KEY_PASSPHRASE_ENV: &str = "NEAR_NEW_KEY_PASSPHRASE";
+
 fn generate_key_to_file(account_id: &str, key: SecretKey, path: PathBuf) {
     let signer = InMemorySigner::from_secret_key(account_id.to_string(), key);
     signer.write_to_file(path.as_path());
 }
 
+/// Takes the passphrase from the environment variable or asks for it on the terminal.
+fn read_passphrase(env_var: &str, prompt: &str, confirm: bool) -> String {
+    if let Ok(passphrase) = std::env::var(env_var) {
+        return passphrase;
+    }
+    let passphrase =
+        rpassword::read_password_from_tty(Some(prompt)).expect("Failed to read the passphrase");
+    if confirm {
+        let confirmation = rpassword::read_password_from_tty(Some("Repeat passphrase: "))
+            .expect("Failed to read the passphrase");
+        assert_eq!(passphrase, confirmation, "Passphrases don't match");
+    }
+    assert!(!passphrase.is_empty(), "Passphrase must not be empty");
+    passphrase
+}
+
+fn key_file_args<'a, 'b>(subcommand: App<'a, 'b>) -> App<'a, 'b> {
+    subcommand
+        .arg(
+            Arg::with_name("key-file")
+                .long("key-file")
+                .required(true)
+                .takes_value(true)
+                .help("Key file to read"),
+        )
+        .arg(
+            Arg::with_name("output")
+                .long("output")
+                .takes_value(true)
+                .help("Where to write the result (default is the key file, requires --overwrite)"),
+        )
+        .arg(
+            Arg::with_name("overwrite")
+                .long("overwrite")
+                .takes_value(false)
+                .help("Allow replacing the key file with the result"),
+        )
+}
+
 fn main() {
     let default_home = get_default_home();
     let matches = App::new("Key-pairs generator")
@@ -46,6 +88,15 @@ fn main() {
             SubCommand::with_name("node-key").about("Generate key for the node communication."),
         )
         .subcommand(SubCommand::with_name("validator-key").about("Generate staking key."))
+        .subcommand(key_file_args(SubCommand::with_name("encrypt").about(
+            "Encrypt a plain key file with a passphrase (taken from NEAR_KEY_PASSPHRASE if set).",
+        )))
+        .subcommand(key_file_args(SubCommand::with_name("decrypt").about(
+            "Decrypt an encrypted key file (passphrase is taken from NEAR_KEY_PASSPHRASE if set).",
+        )))
+        .subcommand(key_file_args(SubCommand::with_name("rotate").about(
+            "Re-encrypt a key file with a new passphrase (taken from NEAR_KEY_PASSPHRASE and NEAR_NEW_KEY_PASSPHRASE if set).",
+        )))
         .get_matches();
 
     let home_dir = matches.value_of("home").map(|dir| Path::new(dir)).unwrap();
@@ -101,6 +152,41 @@ fn main() {
                 generate_key_to_file("", key, path);
             }
         }
+        (subcommand @ "encrypt", Some(args))
+        | (subcommand @ "decrypt", Some(args))
+        | (subcommand @ "rotate", Some(args)) => {
+            let key_file_path = Path::new(args.value_of("key-file").unwrap());
+            let output_path = args.value_of("output").map(Path::new).unwrap_or(key_file_path);
+            if output_path == key_file_path && !args.is_present("overwrite") {
+                panic!(
+                    "Refusing to overwrite {}, pass --overwrite or another --output",
+                    key_file_path.display()
+                );
+            }
+            let current_passphrase = match subcommand {
+                "encrypt" => None,
+                _ => Some(read_passphrase(KEY_PASSPHRASE_ENV, "Passphrase: ", false)),
+            };
+            let key_file =
+                KeyFile::from_file_with_passphrase(key_file_path, current_passphrase.as_deref())
+                    .unwrap_or_else(|err| {
+                        panic!("Failed to read key file {}: {}", key_file_path.display(), err)
+                    });
+            match subcommand {
+                "encrypt" => {
+                    let passphrase = read_passphrase(KEY_PASSPHRASE_ENV, "New passphrase: ", true);
+                    key_file.encrypt(&passphrase).write_to_file(output_path);
+                }
+                "decrypt" => key_file.write_to_file(output_path),
+                _ => {
+                    let passphrase =
+                        read_passphrase(NEW_KEY_PASSPHRASE_ENV, "New passphrase: ", true);
+                    key_file.encrypt(&passphrase).write_to_file(output_path);
+                }
+            }
+            println!("PK: {}", key_file.public_key);
+            println!("Saved key file to {}", output_path.display());
+        }
         (_, _) => unreachable!(),
     }
 }
@@ -15,14 +15,16 @@ use serde::{Deserialize, Serialize};
 
 use lazy_static::lazy_static;
 use near_chain_configs::{ClientConfig, Genesis, GenesisConfig};
-use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer};
+use near_crypto::{InMemorySigner, KeyFile, KeyType, PublicKey, Signer, KEY_PASSPHRASE_ENV};
 use near_jsonrpc::RpcConfig;
+use near_network::rate_limiter::RateLimits;
 use near_network::test_utils::open_port;
 use near_network::types::ROUTED_MESSAGE_TTL;
 use near_network::utils::blacklist_from_iter;
 use near_network::NetworkConfig;
 use near_primitives::account::{AccessKey, Account};
 use near_primitives::hash::CryptoHash;
+use near_primitives::network::PeerId;
 use near_primitives::state_record::StateRecord;
 use near_primitives::types::{
     AccountId, AccountInfo, Balance, BlockHeightDelta, EpochHeight, Gas, NumBlocks, NumSeats,
@@ -31,6 +33,7 @@ use near_primitives::types::{
 use near_primitives::utils::{generate_random_string, get_num_seats_per_shard};
 use near_primitives::validator_signer::{InMemoryValidatorSigner, ValidatorSigner};
 use near_primitives::version::PROTOCOL_VERSION;
+use near_remote_signer::{RemoteValidatorSigner, AUTH_TOKEN_ENV as REMOTE_SIGNER_AUTH_TOKEN_ENV};
 #[cfg(feature = "rosetta_rpc")]
 use near_rosetta_rpc::RosettaRpcConfig;
 use near_runtime_configs::RuntimeConfig;
@@ -236,12 +239,20 @@ pub struct Network {
     /// It can be IP:Port or IP (to blacklist all connections coming from this address).
     #[serde(default)]
     pub blacklist: Vec<String>,
+    /// Private mode, for validators running behind sentry nodes: if set, only these peers can
+    /// connect to this node, and the node only connects to them. The node doesn't send its
+    /// address to peers, so it's not gossiped. Sentries should not have it in `boot_nodes`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_peers: Option<Vec<PeerId>>,
     /// Time to persist Accounts Id in the router without removing them in seconds.
     #[serde(default = "default_ttl_account_id_router")]
     pub ttl_account_id_router: Duration,
     /// Period to check on peer status
     #[serde(default = "default_peer_stats_period")]
     pub peer_stats_period: Duration,
+    /// Limits on the messages received from each peer.
+    #[serde(default)]
+    pub rate_limits: RateLimits,
 }
 
 impl Default for Network {
@@ -262,8 +273,10 @@ impl Default for Network {
             skip_sync_wait: false,
             ban_window: Duration::from_secs(3 * 60 * 60),
             blacklist: vec![],
+            allowed_peers: None,
             ttl_account_id_router: default_ttl_account_id_router(),
             peer_stats_period: default_peer_stats_period(),
+            rate_limits: RateLimits::default(),
         }
     }
 }
@@ -392,6 +405,11 @@ pub struct Config {
     pub genesis_file: String,
     pub genesis_records_file: Option<String>,
     pub validator_key_file: String,
+    /// Address of the remote signer holding the validator key, `unix:<path>` or
+    /// `tcp:<host>:<port>`. If set, `validator_key_file` is not used. The token to authenticate
+    /// to the signer is taken from `NEAR_REMOTE_SIGNER_TOKEN`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_signer: Option<String>,
     pub node_key_file: String,
     pub rpc: RpcConfig,
     #[cfg(feature = "rosetta_rpc")]
@@ -403,6 +421,15 @@ pub struct Config {
     pub tracked_accounts: Vec<AccountId>,
     pub tracked_shards: Vec<ShardId>,
     pub archive: bool,
+    /// Index transactions and receipts by the accounts they touched to serve
+    /// `EXPERIMENTAL_account_history`: transactions by signer and receiver, receipts by the
+    /// account executing them. Requires `archive`.
+    #[serde(default)]
+    pub account_history: bool,
+    /// Record a breakdown of the gas burnt by every receipt, returned by
+    /// `EXPERIMENTAL_tx_status` on request.
+    #[serde(default)]
+    pub gas_profiling: bool,
     #[serde(default = "default_gc_blocks_limit")]
     pub gc_blocks_limit: NumBlocks,
     #[serde(default = "default_view_client_threads")]
@@ -415,6 +442,7 @@ impl Default for Config {
             genesis_file: GENESIS_CONFIG_FILENAME.to_string(),
             genesis_records_file: None,
             validator_key_file: VALIDATOR_KEY_FILE.to_string(),
+            remote_signer: None,
             node_key_file: NODE_KEY_FILE.to_string(),
             rpc: RpcConfig::default(),
             #[cfg(feature = "rosetta_rpc")]
@@ -425,6 +453,8 @@ impl Default for Config {
             tracked_accounts: vec![],
             tracked_shards: vec![],
             archive: false,
+            account_history: false,
+            gas_profiling: false,
             gc_blocks_limit: default_gc_blocks_limit(),
             view_client_threads: 4,
         }
@@ -592,6 +622,8 @@ impl NearConfig {
                 tracked_accounts: config.tracked_accounts,
                 tracked_shards: config.tracked_shards,
                 archive: config.archive,
+                account_history: config.archive && config.account_history,
+                gas_profiling: config.gas_profiling,
                 gc_blocks_limit: config.gc_blocks_limit,
                 view_client_threads: config.view_client_threads,
             },
@@ -636,8 +668,14 @@ impl NearConfig {
                 highest_peer_horizon: HIGHEST_PEER_HORIZON,
                 push_info_period: Duration::from_millis(100),
                 blacklist: blacklist_from_iter(config.network.blacklist),
+                allowed_peers: config
+                    .network
+                    .allowed_peers
+                    .map(|allowed_peers| allowed_peers.into_iter().collect()),
                 outbound_disabled: false,
                 archive: config.archive,
+                rate_limits: config.network.rate_limits,
+                protocol_version: PROTOCOL_VERSION,
             },
             telemetry_config: config.telemetry,
             rpc_config: config.rpc,
@@ -657,8 +695,11 @@ impl NearConfig {
 
         self.config.write_to_file(&dir.join(CONFIG_FILENAME));
 
-        if let Some(validator_signer) = &self.validator_signer {
-            validator_signer.write_to_file(&dir.join(&self.config.validator_key_file));
+        if let (Some(validator_signer), None) = (&self.validator_signer, &self.config.remote_signer)
+        {
+            validator_signer
+                .write_to_file(&dir.join(&self.config.validator_key_file))
+                .expect("Failed to write validator key");
         }
 
         let network_signer =
@@ -712,7 +753,7 @@ fn state_records_account_with_key(
 fn generate_validator_key(account_id: &str, path: &Path) {
     let signer = InMemoryValidatorSigner::from_random(account_id.to_string(), KeyType::ED25519);
     info!(target: "near", "Use key {} for {} to stake.", signer.public_key(), account_id);
-    signer.write_to_file(path);
+    signer.write_to_file(path).expect("Failed to write validator key");
 }
 
 /// Initializes genesis and client configs and stores in the given folder
@@ -820,7 +861,9 @@ pub fn init_configs(
             } else {
                 InMemoryValidatorSigner::from_random(account_id.clone(), KeyType::ED25519)
             };
-            signer.write_to_file(&dir.join(config.validator_key_file));
+            signer
+                .write_to_file(&dir.join(config.validator_key_file))
+                .expect("Failed to write validator key");
 
             let network_signer = InMemorySigner::from_random("".to_string(), KeyType::ED25519);
             network_signer.write_to_file(&dir.join(config.node_key_file));
@@ -964,7 +1007,9 @@ pub fn init_testnet_configs(
         let node_dir = dir.join(format!("{}{}", prefix, i));
         fs::create_dir_all(node_dir.clone()).expect("Failed to create directory");
 
-        validator_signers[i].write_to_file(&node_dir.join(&configs[i].validator_key_file));
+        validator_signers[i]
+            .write_to_file(&node_dir.join(&configs[i].validator_key_file))
+            .expect("Failed to write validator key");
         network_signers[i].write_to_file(&node_dir.join(&configs[i].node_key_file));
 
         genesis.to_file(&node_dir.join(&configs[i].genesis_file));
@@ -1012,15 +1057,39 @@ pub fn load_config(dir: &Path) -> NearConfig {
     } else {
         Genesis::from_file(&dir.join(&config.genesis_file))
     };
-    let validator_signer = if dir.join(&config.validator_key_file).exists() {
-        let signer =
-            Arc::new(InMemoryValidatorSigner::from_file(&dir.join(&config.validator_key_file)))
-                as Arc<dyn ValidatorSigner>;
-        Some(signer)
+    let passphrase = std::env::var(KEY_PASSPHRASE_ENV).ok();
+    let validator_key_path = dir.join(&config.validator_key_file);
+    let validator_signer = if let Some(remote_signer) = &config.remote_signer {
+        let auth_token = std::env::var(REMOTE_SIGNER_AUTH_TOKEN_ENV).ok();
+        let signer = RemoteValidatorSigner::connect(remote_signer, auth_token.as_deref())
+            .unwrap_or_else(|err| {
+                panic!("Failed to connect to the remote signer at {}: {}", remote_signer, err)
+            });
+        info!(
+            target: "near",
+            "Using remote signer at {} for validator {}",
+            remote_signer,
+            signer.validator_id()
+        );
+        Some(Arc::new(signer) as Arc<dyn ValidatorSigner>)
+    } else if validator_key_path.exists() {
+        let signer = InMemoryValidatorSigner::from_file_with_passphrase(
+            &validator_key_path,
+            passphrase.as_deref(),
+        )
+        .unwrap_or_else(|err| {
+            panic!("Failed to load validator key from {}: {}", validator_key_path.display(), err)
+        });
+        Some(Arc::new(signer) as Arc<dyn ValidatorSigner>)
     } else {
         None
     };
-    let network_signer = InMemorySigner::from_file(&dir.join(&config.node_key_file));
+    let node_key_path = dir.join(&config.node_key_file);
+    let network_signer =
+        InMemorySigner::from_file_with_passphrase(&node_key_path, passphrase.as_deref())
+            .unwrap_or_else(|err| {
+                panic!("Failed to load node key from {}: {}", node_key_path.display(), err)
+            });
     NearConfig::new(config, genesis, (&network_signer).into(), validator_signer)
 }
 
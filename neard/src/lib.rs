@@ -20,6 +20,7 @@ use near_telemetry::TelemetryActor;
 pub use crate::config::{init_configs, load_config, load_test_config, NearConfig, NEAR_BASE};
 use crate::migrations::migrate_12_to_13;
 pub use crate::runtime::NightshadeRuntime;
+pub use crate::shard_tracker::account_id_to_shard_id;
 use near_store::migrations::{
     fill_col_outcomes_by_hash, fill_col_transaction_refcount, get_store_version, migrate_10_to_11,
     migrate_11_to_12, migrate_13_to_14, migrate_14_to_15, migrate_6_to_7, migrate_7_to_8,
@@ -31,6 +32,7 @@ pub mod genesis_validate;
 mod migrations;
 mod runtime;
 mod shard_tracker;
+pub mod state_snapshot;
 
 const STORE_PATH: &str = "data";
 
@@ -168,6 +170,27 @@ pub fn apply_store_migrations(path: &String, near_config: &NearConfig) {
         // Change ColOutcomesByBlockHash to be ordered within each shard
         migrate_14_to_15(path);
     }
+    if db_version <= 15 {
+        info!(target: "near", "Migrate DB from version 15 to 16");
+        // version 15 => 16: add ColCachedContractCode
+        // the cache starts empty and is filled as contracts are compiled
+        let store = create_store(&path);
+        set_store_version(&store, 16);
+    }
+    if db_version <= 16 {
+        info!(target: "near", "Migrate DB from version 16 to 17");
+        // version 16 => 17: add ColAccountHistory
+        // the index only covers blocks processed after it is enabled
+        let store = create_store(&path);
+        set_store_version(&store, 17);
+    }
+    if db_version <= 17 {
+        info!(target: "near", "Migrate DB from version 17 to 18");
+        // version 17 => 18: add ColGasProfiles
+        // profiles are only recorded for chunks applied after gas profiling is enabled
+        let store = create_store(&path);
+        set_store_version(&store, 18);
+    }
 
     let db_version = get_store_version(path);
     debug_assert_eq!(db_version, near_primitives::version::DB_VERSION);
@@ -193,13 +216,15 @@ pub fn start_with_config(
     let store = init_and_migrate_store(home_dir, &config);
     near_actix_utils::init_stop_on_panic();
 
-    let runtime = Arc::new(NightshadeRuntime::new(
+    let mut runtime = NightshadeRuntime::new(
         home_dir,
         Arc::clone(&store),
         &config.genesis,
         config.client_config.tracked_accounts.clone(),
         config.client_config.tracked_shards.clone(),
-    ));
+    );
+    runtime.set_profile_gas(config.client_config.gas_profiling);
+    let runtime = Arc::new(runtime);
 
     let telemetry = TelemetryActor::new(config.telemetry_config.clone()).start();
     let chain_genesis = ChainGenesis::from(&config.genesis);
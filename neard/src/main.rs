@@ -16,6 +16,7 @@ use git_version::git_version;
 use near_primitives::version::{Version, PROTOCOL_VERSION};
 use neard::config::init_testnet_configs;
 use neard::genesis_validate::validate_genesis;
+use neard::state_snapshot::{export_state_snapshot, import_state_snapshot};
 use neard::{get_default_home, get_store_path, init_configs, load_config, start_with_config};
 
 fn init_logging(verbose: Option<&str>) {
@@ -103,6 +104,13 @@ fn main() {
             .arg(Arg::with_name("telemetry-url").long("telemetry-url").help("Customize telemetry url").takes_value(true))
             .arg(Arg::with_name("archive").long("archive").help("Keep old blocks in the storage (default false)").takes_value(false))
         )
+        .subcommand(SubCommand::with_name("export_state_snapshot").about("Exports state parts of all shards at the first block of an epoch for bootstrapping other nodes (node must be stopped)")
+            .arg(Arg::with_name("output").long("output").required(true).takes_value(true).help("Directory to write the snapshot to"))
+            .arg(Arg::with_name("block-hash").long("block-hash").takes_value(true).help("Final block starting an epoch to export the state at (default the first block of the epoch of the last final block)"))
+        )
+        .subcommand(SubCommand::with_name("import_state_snapshot").about("Validates and imports a state snapshot into an empty store, the node state syncs from it on the next run")
+            .arg(Arg::with_name("input").long("input").required(true).takes_value(true).help("Directory with the snapshot created by export_state_snapshot"))
+        )
         .subcommand(SubCommand::with_name("unsafe_reset_data").about("(unsafe) Remove all the data, effectively resetting node to genesis state (keeps genesis and config)"))
         .subcommand(SubCommand::with_name("unsafe_reset_all").about("(unsafe) Remove all the config, keys, data and effectively removing all information about the network"))
         .get_matches();
@@ -233,6 +241,23 @@ fn main() {
             system.run().unwrap();
             arbiters.into_iter().for_each(|mut a| a.join().unwrap());
         }
+        ("export_state_snapshot", Some(args)) => {
+            let near_config = load_config(home_dir);
+            let output_dir = Path::new(args.value_of("output").unwrap());
+            let block_hash = args
+                .value_of("block-hash")
+                .map(|hash| hash.try_into().expect("Failed to parse block hash"));
+            let manifest = export_state_snapshot(home_dir, &near_config, block_hash, output_dir)
+                .expect("Failed to export state snapshot");
+            info!(target: "near", "Exported state snapshot at block #{} {} into {}", manifest.header.height, manifest.header.hash, output_dir.display());
+        }
+        ("import_state_snapshot", Some(args)) => {
+            let near_config = load_config(home_dir);
+            let input_dir = Path::new(args.value_of("input").unwrap());
+            let manifest = import_state_snapshot(home_dir, &near_config, input_dir)
+                .expect("Failed to import state snapshot");
+            info!(target: "near", "Imported state snapshot at block #{} {}, run the node to sync from it", manifest.header.height, manifest.header.hash);
+        }
         ("unsafe_reset_data", Some(_args)) => {
             let store_path = get_store_path(home_dir);
             info!(target: "near", "Removing all data from {}", store_path);
@@ -4,6 +4,7 @@ use std::convert::TryInto;
 use std::fs::File;
 use std::io::Read;
 use std::path::Path;
+use std::rc::Rc;
 use std::sync::{Arc, RwLock};
 
 use borsh::ser::BorshSerialize;
@@ -24,6 +25,7 @@ use near_primitives::epoch_manager::{BlockInfo, EpochConfig};
 use near_primitives::errors::{EpochError, InvalidTxError, RuntimeError};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::receipt::Receipt;
+use near_primitives::serialize::to_base64;
 use near_primitives::sharding::ChunkHash;
 use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::SignedTransaction;
@@ -34,14 +36,17 @@ use near_primitives::types::{
 };
 use near_primitives::version::ProtocolVersion;
 use near_primitives::views::{
-    AccessKeyInfoView, CallResult, EpochValidatorInfo, QueryError, QueryRequest, QueryResponse,
-    QueryResponseKind, ViewStateResult,
+    AccessKeyInfoView, AccessKeyWithProofView, AccountWithProofView, CallResult,
+    EpochValidatorInfo, QueryError, QueryRequest, QueryResponse, QueryResponseKind, StateProofView,
+    ViewStateResult,
 };
 use near_store::{
     get_access_key_raw, get_genesis_hash, get_genesis_state_roots, set_genesis_hash,
-    set_genesis_state_roots, ColState, PartialStorage, ShardTries, Store, Trie, WrappedTrieChanges,
+    set_genesis_state_roots, ColState, PartialStorage, ShardTries, Store, Trie, TrieUpdate,
+    WrappedTrieChanges,
 };
 use node_runtime::adapter::ViewRuntimeAdapter;
+use node_runtime::cache::StoreCompiledContractCache;
 use node_runtime::state_viewer::TrieViewer;
 use node_runtime::{
     validate_transaction, verify_and_charge_transaction, ApplyState, Runtime,
@@ -124,6 +129,11 @@ pub struct NightshadeRuntime {
     epoch_manager: SafeEpochManager,
     shard_tracker: ShardTracker,
     genesis_state_roots: Vec<StateRoot>,
+    /// Whether receipt outcomes produced by this runtime carry a gas profile.
+    profile_gas: bool,
+    /// State records queued by `sandbox_patch_state` that are not in the state yet.
+    #[cfg(feature = "sandbox")]
+    states_to_patch: RwLock<Vec<StateRecord>>,
 }
 
 impl NightshadeRuntime {
@@ -214,9 +224,75 @@ impl NightshadeRuntime {
             epoch_manager: SafeEpochManager(epoch_manager),
             shard_tracker,
             genesis_state_roots: state_roots,
+            profile_gas: false,
+            #[cfg(feature = "sandbox")]
+            states_to_patch: RwLock::new(vec![]),
         }
     }
 
+    /// Enables recording of gas profiles for the receipts applied by this runtime.
+    pub fn set_profile_gas(&mut self, profile_gas: bool) {
+        self.profile_gas = profile_gas;
+    }
+
+    /// Runs `view` over the state with the given root and returns its result together with the
+    /// trie nodes it read. The proof's `state_root_proof` is left empty since it depends on the
+    /// block the state root is taken from, which the caller has to fill in.
+    fn view_with_proof<T>(
+        &self,
+        shard_id: ShardId,
+        state_root: StateRoot,
+        view: impl FnOnce(&TrieViewer, &TrieUpdate) -> Result<T, Box<dyn std::error::Error>>,
+    ) -> Result<(T, StateProofView), Box<dyn std::error::Error>> {
+        let trie = Rc::new(self.get_tries().get_view_trie_for_shard(shard_id).recording_reads());
+        let state_update = TrieUpdate::new(trie.clone(), state_root);
+        let result = view(&self.trie_viewer, &state_update)?;
+        let nodes = trie.recorded_storage().expect("Storage should be TrieRecordingStorage").nodes;
+        let proof = StateProofView {
+            state_root,
+            state_root_proof: vec![],
+            trie_proof: nodes.0.iter().map(to_base64).collect(),
+        };
+        Ok((result, proof))
+    }
+
+    /// Records of the given shard in the sandbox patch queue. They stay queued until
+    /// `remove_patched_states` is called once the chunk is applied with them.
+    #[cfg(feature = "sandbox")]
+    fn get_states_to_patch(&self, shard_id: ShardId) -> Option<Vec<StateRecord>> {
+        let num_shards = self.num_shards();
+        let shard_records: Vec<_> = self
+            .states_to_patch
+            .read()
+            .expect(POISONED_LOCK_ERR)
+            .iter()
+            .filter(|record| state_record_to_shard_id(record, num_shards) == shard_id)
+            .cloned()
+            .collect();
+        if shard_records.is_empty() {
+            None
+        } else {
+            Some(shard_records)
+        }
+    }
+
+    /// Removes the first `num_records` records of the given shard from the sandbox patch queue.
+    /// Records are only appended to the queue, so these are the ones `get_states_to_patch`
+    /// returned even if more records were queued since.
+    #[cfg(feature = "sandbox")]
+    fn remove_patched_states(&self, shard_id: ShardId, num_records: usize) {
+        let num_shards = self.num_shards();
+        let mut remaining = num_records;
+        self.states_to_patch.write().expect(POISONED_LOCK_ERR).retain(|record| {
+            if remaining > 0 && state_record_to_shard_id(record, num_shards) == shard_id {
+                remaining -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+
     fn get_epoch_height_from_prev_block(
         &self,
         prev_block_hash: &CryptoHash,
@@ -430,9 +506,14 @@ impl NightshadeRuntime {
         let epoch_height = self.get_epoch_height_from_prev_block(prev_block_hash)?;
         let epoch_id = self.get_epoch_id_from_prev_block(prev_block_hash)?;
         let current_protocol_version = self.get_epoch_protocol_version(&epoch_id)?;
+        let prev_block_height = {
+            let mut epoch_manager = self.epoch_manager.as_ref().write().expect(POISONED_LOCK_ERR);
+            epoch_manager.get_block_info(prev_block_hash)?.height
+        };
 
         let apply_state = ApplyState {
             block_index: block_height,
+            chunk_height_created: prev_block_height + 1,
             last_block_hash: *prev_block_hash,
             epoch_id,
             epoch_height,
@@ -445,6 +526,12 @@ impl NightshadeRuntime {
                 &self.genesis_runtime_config,
                 current_protocol_version,
             ),
+            cache: Some(Arc::new(StoreCompiledContractCache { store: self.store.clone() })),
+            profile_gas: self.profile_gas,
+            #[cfg(feature = "sandbox")]
+            states_to_patch: self.get_states_to_patch(shard_id),
+            #[cfg(not(feature = "sandbox"))]
+            states_to_patch: None,
         };
 
         let apply_result = self
@@ -471,6 +558,13 @@ impl NightshadeRuntime {
                 RuntimeError::ReceiptValidationError(e) => panic!("{}", e),
                 RuntimeError::ValidatorError(e) => e.into(),
             })?;
+        // The patch is only in the state once the chunk was applied with it.
+        #[cfg(feature = "sandbox")]
+        {
+            if let Some(states_to_patch) = &apply_state.states_to_patch {
+                self.remove_patched_states(shard_id, states_to_patch.len());
+            }
+        }
 
         let total_gas_burnt =
             apply_result.outcomes.iter().map(|tx_result| tx_result.outcome.gas_burnt).sum();
@@ -567,6 +661,7 @@ impl RuntimeAdapter for NightshadeRuntime {
     fn validate_tx(
         &self,
         gas_price: Balance,
+        next_block_height: BlockHeight,
         state_root: Option<StateRoot>,
         transaction: &SignedTransaction,
         verify_signature: bool,
@@ -587,6 +682,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                 gas_price,
                 &transaction,
                 verify_signature,
+                Some(next_block_height),
                 current_protocol_version,
             ) {
                 Ok(_) => Ok(None),
@@ -627,6 +723,7 @@ impl RuntimeAdapter for NightshadeRuntime {
         gas_limit: Gas,
         shard_id: ShardId,
         state_root: StateRoot,
+        next_block_height: BlockHeight,
         pool_iterator: &mut dyn PoolIterator,
         chain_validate: &mut dyn FnMut(&SignedTransaction) -> bool,
         current_protocol_version: ProtocolVersion,
@@ -658,6 +755,7 @@ impl RuntimeAdapter for NightshadeRuntime {
                             gas_price,
                             &tx,
                             false,
+                            Some(next_block_height),
                             current_protocol_version,
                         ) {
                             Ok(verification_result) => {
@@ -1161,8 +1259,16 @@ impl RuntimeAdapter for NightshadeRuntime {
                     }),
                 }
             }
-            QueryRequest::ViewState { account_id, prefix } => {
-                match self.view_state(shard_id, *state_root, account_id, prefix.as_ref()) {
+            QueryRequest::ViewState { account_id, prefix }
+            | QueryRequest::ViewStateWithProof { account_id, prefix } => {
+                let include_proof = matches!(request, QueryRequest::ViewStateWithProof { .. });
+                match self.view_state(
+                    shard_id,
+                    *state_root,
+                    account_id,
+                    prefix.as_ref(),
+                    include_proof,
+                ) {
                     Ok(result) => Ok(QueryResponse {
                         kind: QueryResponseKind::ViewState(result),
                         block_height,
@@ -1220,6 +1326,50 @@ impl RuntimeAdapter for NightshadeRuntime {
                     }),
                 }
             }
+            QueryRequest::ViewAccountWithProof { account_id } => {
+                match self.view_with_proof(shard_id, *state_root, |trie_viewer, state_update| {
+                    trie_viewer.view_account(state_update, account_id)
+                }) {
+                    Ok((account, proof)) => Ok(QueryResponse {
+                        kind: QueryResponseKind::AccountWithProof(AccountWithProofView {
+                            account: account.into(),
+                            proof,
+                        }),
+                        block_height,
+                        block_hash: *block_hash,
+                    }),
+                    Err(err) => Ok(QueryResponse {
+                        kind: QueryResponseKind::Error(QueryError {
+                            error: err.to_string(),
+                            logs: vec![],
+                        }),
+                        block_height,
+                        block_hash: *block_hash,
+                    }),
+                }
+            }
+            QueryRequest::ViewAccessKeyWithProof { account_id, public_key } => {
+                match self.view_with_proof(shard_id, *state_root, |trie_viewer, state_update| {
+                    trie_viewer.view_access_key(state_update, account_id, public_key)
+                }) {
+                    Ok((access_key, proof)) => Ok(QueryResponse {
+                        kind: QueryResponseKind::AccessKeyWithProof(AccessKeyWithProofView {
+                            access_key: access_key.into(),
+                            proof,
+                        }),
+                        block_height,
+                        block_hash: *block_hash,
+                    }),
+                    Err(err) => Ok(QueryResponse {
+                        kind: QueryResponseKind::Error(QueryError {
+                            error: err.to_string(),
+                            logs: vec![],
+                        }),
+                        block_height,
+                        block_hash: *block_hash,
+                    }),
+                }
+            }
         }
     }
 
@@ -1365,6 +1515,16 @@ impl RuntimeAdapter for NightshadeRuntime {
             && chunk_next_epoch_id != head_epoch_id
             && chunk_epoch_id != head_next_epoch_id)
     }
+
+    #[cfg(feature = "sandbox")]
+    fn sandbox_patch_state(&self, records: Vec<StateRecord>) {
+        self.states_to_patch.write().expect(POISONED_LOCK_ERR).extend(records);
+    }
+
+    #[cfg(feature = "sandbox")]
+    fn sandbox_has_pending_state_patch(&self) -> bool {
+        !self.states_to_patch.read().expect(POISONED_LOCK_ERR).is_empty()
+    }
 }
 
 impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
@@ -1454,9 +1614,10 @@ impl node_runtime::adapter::ViewRuntimeAdapter for NightshadeRuntime {
         state_root: MerkleHash,
         account_id: &AccountId,
         prefix: &[u8],
+        include_proof: bool,
     ) -> Result<ViewStateResult, Box<dyn std::error::Error>> {
         let state_update = self.get_tries().new_trie_update_view(shard_id, state_root);
-        self.trie_viewer.view_state(&state_update, account_id, prefix)
+        self.trie_viewer.view_state(&state_update, account_id, prefix, include_proof)
     }
 }
 
@@ -1471,6 +1632,7 @@ mod test {
     use near_logger_utils::init_test_logger;
     use near_primitives::block::Tip;
     use near_primitives::challenge::SlashedValidator;
+    use near_primitives::merkle::merklize;
     use near_primitives::transaction::{
         Action, CreateAccountAction, DeleteAccountAction, StakeAction,
     };
@@ -1479,7 +1641,7 @@ mod test {
     use near_primitives::views::{
         AccountView, CurrentEpochValidatorInfo, NextEpochValidatorInfo, ValidatorKickoutView,
     };
-    use near_store::create_store;
+    use near_store::{create_store, verify_access_key_proof, verify_account_proof};
     use node_runtime::config::RuntimeConfig;
 
     use crate::config::{GenesisExt, TESTING_INIT_BALANCE, TESTING_INIT_STAKE};
@@ -2817,4 +2979,73 @@ mod test {
         assert_eq!(env.last_proposals.len(), 1);
         assert_eq!(env.last_proposals[0].stake, 0);
     }
+
+    #[test]
+    fn test_account_and_access_key_proofs() {
+        init_test_logger();
+        let validators = (0..2).map(|i| format!("test{}", i + 1)).collect::<Vec<_>>();
+        let mut env = TestEnv::new(
+            "test_account_and_access_key_proofs",
+            vec![validators.clone(), validators.clone()],
+            4,
+            vec![],
+            vec![],
+            false,
+        );
+        env.step(vec![vec![], vec![]], vec![true, true], ChallengesResult::default());
+        let (block_prev_state_root, state_root_proofs) = merklize(&env.state_roots);
+        for account_id in validators {
+            let shard_id = env.runtime.account_id_to_shard_id(&account_id);
+            let public_key =
+                InMemorySigner::from_seed(&account_id, KeyType::ED25519, &account_id).public_key;
+            let query = |request| {
+                env.runtime
+                    .query(
+                        shard_id,
+                        &env.state_roots[shard_id as usize],
+                        env.head.height,
+                        0,
+                        &env.head.last_block_hash,
+                        &env.head.epoch_id,
+                        &request,
+                    )
+                    .unwrap()
+                    .kind
+            };
+
+            let mut result = match query(QueryRequest::ViewAccountWithProof {
+                account_id: account_id.clone(),
+            }) {
+                QueryResponseKind::AccountWithProof(result) => result,
+                kind => panic!("unexpected query response: {:?}", kind),
+            };
+            result.proof.state_root_proof = state_root_proofs[shard_id as usize].clone();
+            assert_eq!(result.account, env.view_account(&account_id));
+            assert!(verify_account_proof(&block_prev_state_root, &account_id, &result));
+            result.account.amount += 1;
+            assert!(!verify_account_proof(&block_prev_state_root, &account_id, &result));
+
+            let mut result = match query(QueryRequest::ViewAccessKeyWithProof {
+                account_id: account_id.clone(),
+                public_key: public_key.clone(),
+            }) {
+                QueryResponseKind::AccessKeyWithProof(result) => result,
+                kind => panic!("unexpected query response: {:?}", kind),
+            };
+            result.proof.state_root_proof = state_root_proofs[shard_id as usize].clone();
+            assert!(verify_access_key_proof(
+                &block_prev_state_root,
+                &account_id,
+                &public_key,
+                &result
+            ));
+            result.proof.state_root_proof = state_root_proofs[1 - shard_id as usize].clone();
+            assert!(!verify_access_key_proof(
+                &block_prev_state_root,
+                &account_id,
+                &public_key,
+                &result
+            ));
+        }
+    }
 }
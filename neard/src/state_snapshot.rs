@@ -0,0 +1,239 @@
+//! Offline export and import of the state for bootstrapping a node without copying its database.
+//!
+//! A snapshot contains exactly what state sync downloads from peers: for every shard the
+//! `ShardStateSyncResponseHeader` and all the state parts at the first block of an epoch.
+//! After the import the node state syncs to this block using the imported data, so it only
+//! needs headers and a couple of blocks from the network.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use near_chain::{Chain, ChainGenesis, DoomslugThresholdMode, Error, ErrorKind, RuntimeAdapter};
+use near_primitives::block::BlockHeader;
+use near_primitives::hash::{hash, CryptoHash};
+use near_primitives::syncing::{
+    get_num_state_parts, ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey,
+};
+use near_primitives::types::{ShardId, StateRoot};
+use near_primitives::views::BlockHeaderView;
+use near_store::{ColBlockMisc, ColStateHeaders, ColStateParts, STATE_SNAPSHOT_KEY};
+
+use crate::{init_and_migrate_store, NearConfig, NightshadeRuntime};
+
+const MANIFEST_FILE: &str = "manifest.json";
+const SHARD_HEADER_FILE: &str = "header";
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct StateSnapshotManifest {
+    pub chain_id: String,
+    /// Header of the first block of the epoch. The snapshot contains the state before
+    /// applying this block.
+    pub header: BlockHeaderView,
+    pub shards: Vec<ShardStateSnapshot>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ShardStateSnapshot {
+    pub shard_id: ShardId,
+    pub state_root: StateRoot,
+    /// Hash of the borsh serialized `ShardStateSyncResponseHeader`.
+    pub header_hash: CryptoHash,
+    pub part_hashes: Vec<CryptoHash>,
+}
+
+fn shard_dir(dir: &Path, shard_id: ShardId) -> PathBuf {
+    dir.join(format!("shard{}", shard_id))
+}
+
+fn part_file(part_id: u64) -> String {
+    format!("part{}", part_id)
+}
+
+fn open_chain(home_dir: &Path, near_config: &NearConfig) -> Result<Chain, Error> {
+    let store = init_and_migrate_store(home_dir, near_config);
+    let runtime = Arc::new(NightshadeRuntime::new(
+        home_dir,
+        store,
+        &near_config.genesis,
+        near_config.client_config.tracked_accounts.clone(),
+        near_config.client_config.tracked_shards.clone(),
+    ));
+    Chain::new(runtime, &ChainGenesis::from(&near_config.genesis), DoomslugThresholdMode::TwoThirds)
+}
+
+/// Writes the state snapshot into `output_dir`.
+/// State sync can only start at the first block of an epoch, so `block_hash` must be such a
+/// block and final. By default the first block of the epoch of the last final block is used.
+pub fn export_state_snapshot(
+    home_dir: &Path,
+    near_config: &NearConfig,
+    block_hash: Option<CryptoHash>,
+    output_dir: &Path,
+) -> Result<StateSnapshotManifest, Error> {
+    let mut chain = open_chain(home_dir, near_config)?;
+    let final_head = chain.final_head()?;
+    let sync_hash = match block_hash {
+        Some(block_hash) => {
+            let height = chain.get_block_header(&block_hash)?.height();
+            if height > final_head.height
+                || chain.get_header_by_height(height)?.hash() != &block_hash
+            {
+                return Err(ErrorKind::Other(format!("Block {} is not final", block_hash)).into());
+            }
+            if chain.runtime_adapter().get_epoch_start_height(&block_hash)? != height {
+                return Err(ErrorKind::Other(format!(
+                    "Block {} is not the first block of an epoch",
+                    block_hash
+                ))
+                .into());
+            }
+            block_hash
+        }
+        None => {
+            let epoch_start_height =
+                chain.runtime_adapter().get_epoch_start_height(&final_head.last_block_hash)?;
+            *chain.get_header_by_height(epoch_start_height)?.hash()
+        }
+    };
+    if &sync_hash == chain.genesis().hash() {
+        return Err(ErrorKind::Other(
+            "State can't be exported before the first epoch is finished".to_string(),
+        )
+        .into());
+    }
+    let header = chain.get_block_header(&sync_hash)?.clone();
+
+    let mut shards = vec![];
+    for shard_id in 0..chain.runtime_adapter().num_shards() {
+        let shard_state_header = chain.get_state_response_header(shard_id, sync_hash)?;
+        let num_parts = get_num_state_parts(shard_state_header.state_root_node().memory_usage);
+        let dir = shard_dir(output_dir, shard_id);
+        fs::create_dir_all(&dir)?;
+        let header_bytes = shard_state_header.try_to_vec()?;
+        fs::write(dir.join(SHARD_HEADER_FILE), &header_bytes)?;
+
+        let mut part_hashes = vec![];
+        for part_id in 0..num_parts {
+            let part = chain.get_state_response_part(shard_id, part_id, sync_hash)?;
+            fs::write(dir.join(part_file(part_id)), &part)?;
+            part_hashes.push(hash(&part));
+        }
+        info!(target: "near", "Exported {} state parts of shard {}", num_parts, shard_id);
+        shards.push(ShardStateSnapshot {
+            shard_id,
+            state_root: shard_state_header.chunk_prev_state_root(),
+            header_hash: hash(&header_bytes),
+            part_hashes,
+        });
+    }
+
+    let manifest = StateSnapshotManifest {
+        chain_id: near_config.genesis.config.chain_id.clone(),
+        header: header.into(),
+        shards,
+    };
+    let manifest_json = serde_json::to_string_pretty(&manifest).map_err(|err| err.to_string())?;
+    fs::write(output_dir.join(MANIFEST_FILE), manifest_json)?;
+    Ok(manifest)
+}
+
+/// Validates the snapshot in `input_dir` and saves it into a store that doesn't have any blocks
+/// past genesis yet. The node state syncs from the imported state on the next start.
+pub fn import_state_snapshot(
+    home_dir: &Path,
+    near_config: &NearConfig,
+    input_dir: &Path,
+) -> Result<StateSnapshotManifest, Error> {
+    let manifest: StateSnapshotManifest =
+        serde_json::from_slice(&fs::read(input_dir.join(MANIFEST_FILE))?)
+            .map_err(|err| err.to_string())?;
+    if manifest.chain_id != near_config.genesis.config.chain_id {
+        return Err(ErrorKind::Other(format!(
+            "Snapshot is made for chain {}, but the node is configured for {}",
+            manifest.chain_id, near_config.genesis.config.chain_id
+        ))
+        .into());
+    }
+    let sync_hash = manifest.header.hash;
+    if BlockHeader::from(manifest.header.clone()).hash() != &sync_hash {
+        return Err(ErrorKind::Other("Snapshot block header hash mismatch".to_string()).into());
+    }
+
+    let chain = open_chain(home_dir, near_config)?;
+    if chain.head()?.height != chain.genesis().height() {
+        return Err(ErrorKind::Other(
+            "Store already contains blocks, run `neard unsafe_reset_data` first".to_string(),
+        )
+        .into());
+    }
+    let runtime_adapter = chain.runtime_adapter();
+    if manifest.shards.len() as ShardId != runtime_adapter.num_shards() {
+        return Err(ErrorKind::Other(format!(
+            "Snapshot contains {} shards, expected {}",
+            manifest.shards.len(),
+            runtime_adapter.num_shards()
+        ))
+        .into());
+    }
+
+    let store = chain.store().owned_store();
+    for (shard_id, shard) in manifest.shards.iter().enumerate() {
+        let shard_id = shard_id as ShardId;
+        let invalid_snapshot = |reason: &str| -> Error {
+            format!("Invalid snapshot of shard {}: {}", shard_id, reason).into()
+        };
+        if shard.shard_id != shard_id {
+            return Err(invalid_snapshot("shards are out of order"));
+        }
+        let dir = shard_dir(input_dir, shard_id);
+        let header_bytes = fs::read(dir.join(SHARD_HEADER_FILE))?;
+        if hash(&header_bytes) != shard.header_hash {
+            return Err(invalid_snapshot("header hash mismatch"));
+        }
+        let shard_state_header = ShardStateSyncResponseHeader::try_from_slice(&header_bytes)?;
+        if shard_state_header.chunk_prev_state_root() != shard.state_root
+            || !runtime_adapter
+                .validate_state_root_node(shard_state_header.state_root_node(), &shard.state_root)
+        {
+            return Err(invalid_snapshot("state root mismatch"));
+        }
+        let num_parts = get_num_state_parts(shard_state_header.state_root_node().memory_usage);
+        if num_parts != shard.part_hashes.len() as u64 {
+            return Err(invalid_snapshot("wrong number of state parts"));
+        }
+
+        // Parts are committed one by one to avoid keeping the whole state in memory.
+        for (part_id, part_hash) in shard.part_hashes.iter().enumerate() {
+            let part_id = part_id as u64;
+            let part = fs::read(dir.join(part_file(part_id)))?;
+            if &hash(&part) != part_hash
+                || !runtime_adapter.validate_state_part(
+                    &shard.state_root,
+                    part_id,
+                    num_parts,
+                    &part,
+                )
+            {
+                return Err(invalid_snapshot(&format!("part {} is invalid", part_id)));
+            }
+            let mut store_update = store.store_update();
+            let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec()?;
+            store_update.set(ColStateParts, &key, &part);
+            store_update.commit()?;
+        }
+        let mut store_update = store.store_update();
+        let key = StateHeaderKey(shard_id, sync_hash).try_to_vec()?;
+        store_update.set(ColStateHeaders, &key, &header_bytes);
+        store_update.commit()?;
+        info!(target: "near", "Imported {} state parts of shard {}", num_parts, shard_id);
+    }
+
+    let mut store_update = store.store_update();
+    store_update.set_ser(ColBlockMisc, STATE_SNAPSHOT_KEY, &sync_hash)?;
+    store_update.commit()?;
+    Ok(manifest)
+}
@@ -0,0 +1,75 @@
+use std::sync::Arc;
+use std::thread;
+
+use actix::{Actor, System};
+use futures::{future, FutureExt};
+
+use near_chain_configs::Genesis;
+use near_client::GetBlock;
+use near_crypto::{InMemorySigner, KeyType, Signer};
+use near_logger_utils::init_integration_logger;
+use near_network::test_utils::{open_port, WaitOrTimeout};
+use near_primitives::validator_signer::ValidatorSigner;
+use near_remote_signer::{RemoteValidatorSigner, SignerListener, SignerService, SigningGuard};
+use neard::config::GenesisExt;
+use neard::{load_test_config, start_with_config};
+use testlib::test_helpers::heavy_test;
+
+/// Node produces blocks and chunks with the key held by a signer it talks to over a unix socket.
+#[test]
+fn produce_blocks_with_remote_signer() {
+    heavy_test(|| {
+        init_integration_logger();
+
+        let dir = tempfile::Builder::new().prefix("remote_signer").tempdir().unwrap();
+        let address = format!("unix:{}", dir.path().join("signer.sock").display());
+
+        let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
+        let public_key = signer.public_key();
+        let service = Arc::new(SignerService::new(signer, SigningGuard::open(None).unwrap(), None));
+        let listener = SignerListener::bind(&address).unwrap();
+        thread::spawn(move || service.serve(listener));
+
+        let genesis = Genesis::test(vec!["test1"], 1);
+        let mut near_config = load_test_config("test1", open_port(), genesis);
+        let remote_signer = RemoteValidatorSigner::connect(&address, None).unwrap();
+        assert_eq!(remote_signer.validator_id(), "test1");
+        assert_eq!(remote_signer.public_key(), public_key);
+        near_config.config.remote_signer = Some(address);
+        near_config.validator_signer = Some(Arc::new(remote_signer) as Arc<dyn ValidatorSigner>);
+
+        let system = System::new("NEAR");
+        let (_, view_client, arbiters) = start_with_config(dir.path(), near_config);
+
+        WaitOrTimeout::new(
+            Box::new(move |_ctx| {
+                let public_key = public_key.clone();
+                actix::spawn(view_client.send(GetBlock::latest()).then(move |res| {
+                    match &res {
+                        Ok(Ok(b)) if b.header.height > 5 => {
+                            assert_eq!(b.author, "test1");
+                            assert!(b.header.signature.verify(b.header.hash.as_ref(), &public_key));
+                            for chunk in
+                                b.chunks.iter().filter(|c| c.height_included == b.header.height)
+                            {
+                                assert!(chunk
+                                    .signature
+                                    .verify(chunk.chunk_hash.as_ref(), &public_key));
+                            }
+                            System::current().stop();
+                        }
+                        Err(_) => return future::ready(()),
+                        _ => {}
+                    };
+                    future::ready(())
+                }));
+            }),
+            100,
+            60000,
+        )
+        .start();
+
+        system.run().unwrap();
+        arbiters.into_iter().for_each(|mut a| a.join().unwrap());
+    });
+}
@@ -6,13 +6,15 @@ use borsh::BorshSerialize;
 use futures::future::join_all;
 use futures::{future, FutureExt, TryFutureExt};
 
+use near_chain_configs::Genesis;
 use near_client::{GetBlock, GetExecutionOutcome, TxStatus};
 use near_crypto::{InMemorySigner, KeyType};
 use near_jsonrpc::client::new_client;
 use near_logger_utils::init_integration_logger;
-use near_network::test_utils::WaitOrTimeout;
+use near_network::test_utils::{open_port, WaitOrTimeout};
 use near_primitives::hash::{hash, CryptoHash};
 use near_primitives::merkle::{compute_root_from_path_and_item, verify_path};
+use near_primitives::rpc::RpcTxStatusOptions;
 use near_primitives::serialize::{from_base64, to_base64};
 use near_primitives::transaction::{PartialExecutionStatus, SignedTransaction};
 use near_primitives::types::{BlockId, BlockReference, TransactionOrReceiptId};
@@ -20,7 +22,8 @@ use near_primitives::views::{
     ExecutionOutcomeView, ExecutionStatusView, FinalExecutionOutcomeViewEnum, FinalExecutionStatus,
     QueryResponseKind,
 };
-use neard::config::TESTING_INIT_BALANCE;
+use neard::config::{GenesisExt, TESTING_INIT_BALANCE};
+use neard::{load_test_config, start_with_config};
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::SeqCst;
 use std::sync::Arc;
@@ -95,6 +98,7 @@ fn test_tx_propagation() {
                             tx_hash,
                             signer_account_id: "near.1".to_string(),
                             fetch_receipt: false,
+                            fetch_gas_profile: false,
                         })
                         .then(move |res| {
                             match &res {
@@ -620,3 +624,100 @@ fn test_get_execution_outcome_tx_success() {
 fn test_get_execution_outcome_tx_failure() {
     test_get_execution_outcome(false);
 }
+
+/// Node with gas profiling enabled returns the gas profiles of the receipts from
+/// `EXPERIMENTAL_tx_status` only when they are asked for.
+#[test]
+fn test_tx_status_gas_profile() {
+    init_integration_logger();
+    heavy_test(|| {
+        let system = System::new("NEAR");
+        let genesis = Genesis::test(vec!["test1", "test2"], 1);
+        let mut near_config = load_test_config("test1", open_port(), genesis.clone());
+        near_config.client_config.gas_profiling = true;
+        let rpc_addr = near_config.rpc_config.addr.clone();
+        let dir = tempfile::Builder::new().prefix("tx_status_gas_profile").tempdir().unwrap();
+        let (_, view_client, arbiters) = start_with_config(dir.path(), near_config);
+
+        let genesis_hash = *genesis_block(&genesis).hash();
+        let signer = InMemorySigner::from_seed("test1", KeyType::ED25519, "test1");
+        let transaction = SignedTransaction::send_money(
+            1,
+            "test1".to_string(),
+            "test2".to_string(),
+            &signer,
+            10000,
+            genesis_hash,
+        );
+        let has_sent_tx = Arc::new(AtomicBool::new(false));
+
+        WaitOrTimeout::new(
+            Box::new(move |_ctx| {
+                let client = new_client(&format!("http://{}", rpc_addr));
+                let transaction_copy = transaction.clone();
+                let has_sent_tx1 = has_sent_tx.clone();
+                actix::spawn(view_client.send(GetBlock::latest()).then(move |res| {
+                    if res.unwrap().unwrap().header.height > 1 && !has_sent_tx1.swap(true, SeqCst) {
+                        let tx_hash = String::from(&transaction_copy.get_hash());
+                        let bytes = transaction_copy.try_to_vec().unwrap();
+                        actix::spawn(async move {
+                            let result =
+                                client.broadcast_tx_commit(to_base64(&bytes)).await.unwrap();
+                            assert_eq!(
+                                result.status,
+                                FinalExecutionStatus::SuccessValue("".to_string())
+                            );
+                            assert!(!result.receipts_outcome.is_empty());
+
+                            let options = RpcTxStatusOptions { gas_profile: true };
+                            let result = client
+                                .EXPERIMENTAL_tx_status(
+                                    tx_hash.clone(),
+                                    "test1".to_string(),
+                                    options,
+                                )
+                                .await
+                                .unwrap();
+                            let gas_profiles = result
+                                .final_outcome
+                                .receipts_outcome
+                                .iter()
+                                .map(|outcome| outcome.outcome.gas_profile.clone().unwrap())
+                                .collect::<Vec<_>>();
+                            assert!(gas_profiles
+                                .iter()
+                                .any(|profile| profile.action_costs.contains_key("transfer")));
+
+                            let options = RpcTxStatusOptions::default();
+                            let result = client
+                                .EXPERIMENTAL_tx_status(
+                                    tx_hash.clone(),
+                                    "test1".to_string(),
+                                    options,
+                                )
+                                .await
+                                .unwrap();
+                            for receipt_outcome in result.final_outcome.receipts_outcome.iter() {
+                                assert_eq!(receipt_outcome.outcome.gas_profile, None);
+                            }
+
+                            // `tx` never returns gas profiles.
+                            let result = client.tx(tx_hash, "test1".to_string()).await.unwrap();
+                            for receipt_outcome in result.receipts_outcome.iter() {
+                                assert_eq!(receipt_outcome.outcome.gas_profile, None);
+                            }
+                            System::current().stop();
+                        });
+                    }
+                    future::ready(())
+                }));
+            }),
+            100,
+            20000,
+        )
+        .start();
+
+        system.run().unwrap();
+        arbiters.into_iter().for_each(|mut a| a.join().unwrap());
+    });
+}
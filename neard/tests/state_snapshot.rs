@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+
+use actix::{Actor, System};
+use borsh::BorshSerialize;
+use futures::{future, FutureExt};
+use tempfile::TempDir;
+
+use near_chain::{ChainStore, ChainStoreAccess, RuntimeAdapter};
+use near_chain_configs::Genesis;
+use near_client::GetBlock;
+use near_logger_utils::init_integration_logger;
+use near_network::test_utils::{open_port, WaitOrTimeout};
+use near_primitives::hash::CryptoHash;
+use near_primitives::syncing::{ShardStateSyncResponseHeader, StateHeaderKey, StatePartKey};
+use near_store::{ColBlockMisc, ColStateHeaders, ColStateParts, Store, STATE_SNAPSHOT_KEY};
+use neard::config::GenesisExt;
+use neard::state_snapshot::{export_state_snapshot, import_state_snapshot, StateSnapshotManifest};
+use neard::{
+    init_and_migrate_store, load_test_config, start_with_config, NearConfig, NightshadeRuntime,
+};
+use testlib::test_helpers::heavy_test;
+
+const EPOCH_LENGTH: u64 = 5;
+
+fn tempdir(prefix: &str) -> TempDir {
+    tempfile::Builder::new().prefix(prefix).tempdir().unwrap()
+}
+
+/// Runs a single validator until the first block of its third epoch is final and exports the
+/// state snapshot of the node.
+fn export_snapshot(prefix: &str) -> (TempDir, NearConfig, TempDir, StateSnapshotManifest) {
+    let mut genesis = Genesis::test(vec!["test1"], 1);
+    genesis.config.epoch_length = EPOCH_LENGTH;
+    let near_config = load_test_config("test1", open_port(), genesis);
+    let home_dir = tempdir(&format!("{}_home", prefix));
+
+    // The node runs in its own thread, so that the store is closed before the export opens it.
+    let home_path = home_dir.path().to_path_buf();
+    let config = near_config.clone();
+    thread::spawn(move || {
+        let system = System::new("NEAR");
+        let (_, view_client, arbiters) = start_with_config(&home_path, config);
+        WaitOrTimeout::new(
+            Box::new(move |_ctx| {
+                actix::spawn(view_client.send(GetBlock::latest()).then(|res| {
+                    match &res {
+                        Ok(Ok(b)) if b.header.height > 3 * EPOCH_LENGTH => System::current().stop(),
+                        Err(_) => return future::ready(()),
+                        _ => {}
+                    };
+                    future::ready(())
+                }));
+            }),
+            100,
+            60000,
+        )
+        .start();
+        system.run().unwrap();
+        arbiters.into_iter().for_each(|mut a| a.join().unwrap());
+    })
+    .join()
+    .unwrap();
+
+    let snapshot_dir = tempdir(&format!("{}_snapshot", prefix));
+    let manifest =
+        export_state_snapshot(home_dir.path(), &near_config, None, snapshot_dir.path()).unwrap();
+    (home_dir, near_config, snapshot_dir, manifest)
+}
+
+fn open_runtime(
+    home_dir: &Path,
+    near_config: &NearConfig,
+    store: &Arc<Store>,
+) -> NightshadeRuntime {
+    NightshadeRuntime::new(home_dir, store.clone(), &near_config.genesis, vec![], vec![])
+}
+
+/// Snapshot imported into an empty node contains the state of the exporting node.
+#[test]
+fn test_state_snapshot_roundtrip() {
+    heavy_test(|| {
+        init_integration_logger();
+        let (home_dir, near_config, snapshot_dir, manifest) = export_snapshot("roundtrip");
+        let sync_hash = manifest.header.hash;
+        assert_ne!(manifest.header.height, near_config.genesis.config.genesis_height);
+
+        let import_dir = tempdir("roundtrip_import");
+        let imported =
+            import_state_snapshot(import_dir.path(), &near_config, snapshot_dir.path()).unwrap();
+        assert_eq!(imported.header.hash, sync_hash);
+
+        let source_store = init_and_migrate_store(home_dir.path(), &near_config);
+        let source_runtime = open_runtime(home_dir.path(), &near_config, &source_store);
+        let mut chain_store =
+            ChainStore::new(source_store.clone(), near_config.genesis.config.genesis_height);
+        let sync_prev_block = chain_store.get_block(&manifest.header.prev_hash).unwrap().clone();
+
+        let target_store = init_and_migrate_store(import_dir.path(), &near_config);
+        let target_runtime = open_runtime(import_dir.path(), &near_config, &target_store);
+        assert_eq!(
+            target_store.get_ser::<CryptoHash>(ColBlockMisc, STATE_SNAPSHOT_KEY).unwrap(),
+            Some(sync_hash)
+        );
+
+        assert_eq!(manifest.shards.len(), imported.shards.len());
+        for (shard, imported_shard) in manifest.shards.iter().zip(imported.shards.iter()) {
+            let shard_id = shard.shard_id;
+            let state_root = sync_prev_block.chunks()[shard_id as usize].prev_state_root();
+            assert_eq!(shard.state_root, state_root);
+            assert_eq!(imported_shard.state_root, state_root);
+
+            let header_key = StateHeaderKey(shard_id, sync_hash).try_to_vec().unwrap();
+            let header: ShardStateSyncResponseHeader =
+                target_store.get_ser(ColStateHeaders, &header_key).unwrap().unwrap();
+            assert_eq!(header.chunk_prev_state_root(), state_root);
+
+            let parts = (0..shard.part_hashes.len() as u64)
+                .map(|part_id| {
+                    let key = StatePartKey(sync_hash, shard_id, part_id).try_to_vec().unwrap();
+                    target_store.get(ColStateParts, &key).unwrap().unwrap()
+                })
+                .collect::<Vec<_>>();
+            target_runtime.confirm_state(shard_id, &state_root, &parts).unwrap();
+            assert_eq!(
+                target_runtime.get_state_root_node(shard_id, &state_root).unwrap(),
+                source_runtime.get_state_root_node(shard_id, &state_root).unwrap()
+            );
+        }
+    });
+}
+
+/// Imports `snapshot_dir` into an empty node, expecting it to be rejected with `reason`.
+fn assert_import_rejected(near_config: &NearConfig, snapshot_dir: &Path, reason: &str) {
+    let import_dir = tempdir("rejected_import");
+    let err = import_state_snapshot(import_dir.path(), near_config, snapshot_dir).unwrap_err();
+    assert!(err.to_string().contains(reason), "unexpected error: {}", err);
+    let store = init_and_migrate_store(import_dir.path(), near_config);
+    assert_eq!(store.get_ser::<CryptoHash>(ColBlockMisc, STATE_SNAPSHOT_KEY).unwrap(), None);
+}
+
+/// Snapshot with a modified part or a manifest not matching the parts is not imported.
+#[test]
+fn test_state_snapshot_rejects_invalid_data() {
+    heavy_test(|| {
+        init_integration_logger();
+        let (_home_dir, near_config, snapshot_dir, manifest) = export_snapshot("invalid");
+        let manifest_path = snapshot_dir.path().join("manifest.json");
+        let part_path = snapshot_dir.path().join("shard0").join("part0");
+
+        let part = fs::read(&part_path).unwrap();
+        let mut corrupted_part = part.clone();
+        *corrupted_part.last_mut().unwrap() ^= 1;
+        fs::write(&part_path, &corrupted_part).unwrap();
+        assert_import_rejected(&near_config, snapshot_dir.path(), "part 0 is invalid");
+        fs::write(&part_path, &part).unwrap();
+
+        let mut mismatched =
+            serde_json::from_slice::<StateSnapshotManifest>(&fs::read(&manifest_path).unwrap())
+                .unwrap();
+        mismatched.shards[0].state_root = CryptoHash::default();
+        fs::write(&manifest_path, serde_json::to_vec(&mismatched).unwrap()).unwrap();
+        assert_import_rejected(&near_config, snapshot_dir.path(), "state root mismatch");
+
+        mismatched.shards[0].state_root = manifest.shards[0].state_root;
+        mismatched.chain_id = format!("{}-other", manifest.chain_id);
+        fs::write(&manifest_path, serde_json::to_vec(&mismatched).unwrap()).unwrap();
+        assert_import_rejected(&near_config, snapshot_dir.path(), "is made for chain");
+    });
+}
@@ -60,6 +60,7 @@ fn add_blocks(
                     prev.header().height() + 1,
                     signer,
                 )
+                .unwrap()
                 .signature,
             )],
             Rational::from_integer(0),
@@ -76,7 +77,8 @@ fn add_blocks(
             }])
             .unwrap(),
             block_merkle_tree.root(),
-        );
+        )
+        .unwrap();
         block_merkle_tree.insert(*block.hash());
         let _ = client.do_send(NetworkClientMessages::Block(
             block.clone(),
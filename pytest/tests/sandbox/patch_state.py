@@ -0,0 +1,39 @@
+# Patches the balance of an account on a single sandbox node, then fast
+# forwards the chain and checks that the patched state is kept.
+# Requires neard built with the `sandbox` feature.
+
+import sys, time
+
+sys.path.append('lib')
+
+from cluster import start_cluster
+
+NEW_BALANCE = str(10**33)
+DELTA_HEIGHT = 50
+
+nodes = start_cluster(1, 0, 1, None, [["epoch_length", 10]], {})
+time.sleep(3)
+
+account = nodes[0].get_account('test0')['result']
+res = nodes[0].json_rpc('sandbox_patch_state', {
+    'records': [{
+        'Account': {
+            'account_id': 'test0',
+            'account': {
+                'amount': NEW_BALANCE,
+                'locked': account['locked'],
+                'code_hash': account['code_hash'],
+                'storage_usage': account['storage_usage'],
+            }
+        }
+    }]
+}, timeout=10)
+assert 'result' in res, res
+assert nodes[0].get_account('test0')['result']['amount'] == NEW_BALANCE
+
+height = nodes[0].get_status()['sync_info']['latest_block_height']
+res = nodes[0].json_rpc('sandbox_fast_forward', {'delta_height': DELTA_HEIGHT},
+                        timeout=60)
+assert 'result' in res, res
+assert res['result'] >= height + DELTA_HEIGHT, res
+assert nodes[0].get_account('test0')['result']['amount'] == NEW_BALANCE
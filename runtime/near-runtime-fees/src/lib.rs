@@ -113,6 +113,15 @@ pub struct ActionCreationConfig {
 
     /// Base cost of deleting an account.
     pub delete_account_cost: Fee,
+
+    /// Base cost of a delegate action. The inner actions are charged on top of it.
+    /// Configs written before delegate actions take the `RuntimeFeesConfig::default` value.
+    #[serde(default = "default_delegate_cost")]
+    pub delegate_cost: Fee,
+}
+
+fn default_delegate_cost() -> Fee {
+    RuntimeFeesConfig::default().action_creation_config.delegate_cost
 }
 
 /// Describes the cost of creating an access key.
@@ -219,6 +228,11 @@ impl Default for RuntimeFeesConfig {
                     send_not_sir: 147489000000,
                     execution: 147489000000,
                 },
+                delegate_cost: Fee {
+                    send_sir: 200000000000,
+                    send_not_sir: 200000000000,
+                    execution: 200000000000,
+                },
             },
             storage_usage_config: StorageUsageConfig {
                 // See Account in core/primitives/src/account.rs for the data structure.
@@ -255,7 +269,8 @@ impl RuntimeFeesConfig {
                     function_call_cost_per_byte: free.clone(),
                 },
                 delete_key_cost: free.clone(),
-                delete_account_cost: free,
+                delete_account_cost: free.clone(),
+                delegate_cost: free,
             },
             storage_usage_config: StorageUsageConfig {
                 num_bytes_account: 0,
@@ -166,6 +166,12 @@ pub enum HostError {
     ContractSizeExceeded { size: u64, limit: u64 },
     /// The host function was deprecated.
     Deprecated { method_name: String },
+    /// Invalid input to ed25519 signature verification.
+    Ed25519VerifyInvalidInput { msg: String },
+    /// Invalid input to secp256k1 public key recovery.
+    ECRecoverError { msg: String },
+    /// Invalid input to alt_bn128 operations.
+    AltBn128InvalidInput { msg: String },
 }
 
 #[derive(Debug, Clone, PartialEq, BorshDeserialize, BorshSerialize, Deserialize, Serialize)]
@@ -332,6 +338,9 @@ impl std::fmt::Display for HostError {
             ReturnedValueLengthExceeded { length, limit } => write!(f, "The length of a returned value {} exceeds the limit {}", length, limit),
             ContractSizeExceeded { size, limit } => write!(f, "The size of a contract code in DeployContract action {} exceeds the limit {}", size, limit),
             Deprecated {method_name}=> write!(f, "Attempted to call deprecated host function {}", method_name),
+            Ed25519VerifyInvalidInput { msg } => write!(f, "ed25519 signature verification error: {}", msg),
+            ECRecoverError { msg } => write!(f, "ECDSA recover error: {}", msg),
+            AltBn128InvalidInput { msg } => write!(f, "alt_bn128 error: {}", msg),
         }
     }
 }
@@ -0,0 +1,96 @@
+//! Operations on the alt_bn128 (bn254) curve with the encoding of the Ethereum precompiles from
+//! EIP-196 and EIP-197: all numbers are 32 bytes big-endian and the point at infinity is
+//! encoded as zeros.
+use bn::{AffineG1, AffineG2, Fq, Fq2, Fr, Group, Gt, G1, G2};
+use near_vm_errors::HostError;
+
+const FIELD_LENGTH: usize = 32;
+pub const G1_POINT_LENGTH: usize = 2 * FIELD_LENGTH;
+pub const G1_ADD_INPUT_LENGTH: usize = 2 * G1_POINT_LENGTH;
+pub const G1_MUL_INPUT_LENGTH: usize = G1_POINT_LENGTH + FIELD_LENGTH;
+/// G1 point followed by a G2 point.
+pub const PAIRING_ELEMENT_LENGTH: usize = G1_POINT_LENGTH + 4 * FIELD_LENGTH;
+
+fn invalid_input(msg: &str) -> HostError {
+    HostError::AltBn128InvalidInput { msg: msg.to_string() }
+}
+
+fn read_fq(data: &[u8]) -> Result<Fq, HostError> {
+    Fq::from_slice(data).map_err(|_| invalid_input("invalid field element"))
+}
+
+fn read_g1(data: &[u8]) -> Result<G1, HostError> {
+    let x = read_fq(&data[..FIELD_LENGTH])?;
+    let y = read_fq(&data[FIELD_LENGTH..G1_POINT_LENGTH])?;
+    if x == Fq::zero() && y == Fq::zero() {
+        Ok(G1::zero())
+    } else {
+        AffineG1::new(x, y).map(Into::into).map_err(|_| invalid_input("invalid G1 point"))
+    }
+}
+
+/// Coordinates of G2 points are encoded with the imaginary part first.
+fn read_g2(data: &[u8]) -> Result<G2, HostError> {
+    let x =
+        Fq2::new(read_fq(&data[FIELD_LENGTH..2 * FIELD_LENGTH])?, read_fq(&data[..FIELD_LENGTH])?);
+    let y = Fq2::new(
+        read_fq(&data[3 * FIELD_LENGTH..4 * FIELD_LENGTH])?,
+        read_fq(&data[2 * FIELD_LENGTH..3 * FIELD_LENGTH])?,
+    );
+    if x.is_zero() && y.is_zero() {
+        Ok(G2::zero())
+    } else {
+        AffineG2::new(x, y).map(Into::into).map_err(|_| invalid_input("invalid G2 point"))
+    }
+}
+
+fn write_g1(point: G1) -> Vec<u8> {
+    let mut res = vec![0u8; G1_POINT_LENGTH];
+    if let Some(point) = AffineG1::from_jacobian(point) {
+        point.x().to_big_endian(&mut res[..FIELD_LENGTH]).expect("buffer has the field size");
+        point.y().to_big_endian(&mut res[FIELD_LENGTH..]).expect("buffer has the field size");
+    }
+    res
+}
+
+/// Adds two G1 points given as 128 bytes and returns the 64 bytes of the sum.
+pub fn g1_add(data: &[u8]) -> Result<Vec<u8>, HostError> {
+    if data.len() != G1_ADD_INPUT_LENGTH {
+        return Err(invalid_input("expected two G1 points"));
+    }
+    let a = read_g1(&data[..G1_POINT_LENGTH])?;
+    let b = read_g1(&data[G1_POINT_LENGTH..])?;
+    Ok(write_g1(a + b))
+}
+
+/// Multiplies a G1 point by a scalar given as 96 bytes and returns the 64 bytes of the product.
+/// The scalar must be less than the order of the group.
+pub fn g1_mul(data: &[u8]) -> Result<Vec<u8>, HostError> {
+    if data.len() != G1_MUL_INPUT_LENGTH {
+        return Err(invalid_input("expected a G1 point and a scalar"));
+    }
+    let point = read_g1(&data[..G1_POINT_LENGTH])?;
+    let scalar =
+        Fr::from_slice(&data[G1_POINT_LENGTH..]).map_err(|_| invalid_input("invalid scalar"))?;
+    Ok(write_g1(point * scalar))
+}
+
+/// Returns the number of (G1, G2) pairs in the input of `pairing_check`.
+pub fn pairing_check_num_elements(data: &[u8]) -> Result<u64, HostError> {
+    if data.len() % PAIRING_ELEMENT_LENGTH != 0 {
+        return Err(invalid_input("expected a sequence of (G1, G2) pairs"));
+    }
+    Ok((data.len() / PAIRING_ELEMENT_LENGTH) as u64)
+}
+
+/// Checks that the product of pairings of the given (G1, G2) pairs equals one.
+pub fn pairing_check(data: &[u8]) -> Result<bool, HostError> {
+    pairing_check_num_elements(data)?;
+    let mut product = Gt::one();
+    for element in data.chunks(PAIRING_ELEMENT_LENGTH) {
+        let a = read_g1(&element[..G1_POINT_LENGTH])?;
+        let b = read_g2(&element[G1_POINT_LENGTH..])?;
+        product = product * bn::pairing(a, b);
+    }
+    Ok(product == Gt::one())
+}
@@ -184,7 +184,10 @@ impl Default for VMLimitConfig {
     }
 }
 
+/// Costs missing from a config, e.g. costs added after the genesis configs were published,
+/// take their `ExtCostsConfig::default` values.
 #[derive(Debug, Serialize, Deserialize, Clone, Hash, PartialEq, Eq)]
+#[serde(default)]
 pub struct ExtCostsConfig {
     /// Base cost for calling a host function.
     pub base: Gas,
@@ -239,6 +242,30 @@ pub struct ExtCostsConfig {
     /// Cost of getting sha256 per byte
     pub keccak512_byte: Gas,
 
+    /// Cost of verifying an ed25519 signature base
+    pub ed25519_verify_base: Gas,
+    /// Cost of verifying an ed25519 signature per byte of the message
+    pub ed25519_verify_byte: Gas,
+    /// Cost of recovering a secp256k1 public key from a signature
+    pub ecrecover_base: Gas,
+
+    /// Cost of getting ripemd160 base
+    pub ripemd160_base: Gas,
+    /// Cost of getting ripemd160 per byte
+    pub ripemd160_byte: Gas,
+    /// Cost of getting blake2b base
+    pub blake2b_base: Gas,
+    /// Cost of getting blake2b per byte
+    pub blake2b_byte: Gas,
+    /// Cost of adding two alt_bn128 G1 points
+    pub alt_bn128_g1_add_base: Gas,
+    /// Cost of multiplying an alt_bn128 G1 point by a scalar
+    pub alt_bn128_g1_mul_base: Gas,
+    /// Cost of the alt_bn128 pairing check base
+    pub alt_bn128_pairing_check_base: Gas,
+    /// Cost of the alt_bn128 pairing check per pair of points
+    pub alt_bn128_pairing_check_element: Gas,
+
     /// Cost for calling logging.
     pub log_base: Gas,
     /// Cost for logging per byte
@@ -344,6 +371,19 @@ impl Default for ExtCostsConfig {
             keccak256_byte: SAFETY_MULTIPLIER * 7157035,
             keccak512_base: SAFETY_MULTIPLIER * 1937129412,
             keccak512_byte: SAFETY_MULTIPLIER * 12216567,
+            ed25519_verify_base: SAFETY_MULTIPLIER * 70000000000,
+            ed25519_verify_byte: SAFETY_MULTIPLIER * 3000000,
+            ecrecover_base: SAFETY_MULTIPLIER * 92940662819,
+            // The costs of `ripemd160`, `blake2b` and the alt_bn128 functions are not estimated
+            // yet, they have to be before `protocol_feature_math_extension` becomes stable.
+            ripemd160_base: SAFETY_MULTIPLIER * 1513656750,
+            ripemd160_byte: SAFETY_MULTIPLIER * 8039117,
+            blake2b_base: SAFETY_MULTIPLIER * 1513656750,
+            blake2b_byte: SAFETY_MULTIPLIER * 5046233,
+            alt_bn128_g1_add_base: SAFETY_MULTIPLIER * 4650000000,
+            alt_bn128_g1_mul_base: SAFETY_MULTIPLIER * 186000000000,
+            alt_bn128_pairing_check_base: SAFETY_MULTIPLIER * 1395000000000,
+            alt_bn128_pairing_check_element: SAFETY_MULTIPLIER * 1054000000000,
             log_base: SAFETY_MULTIPLIER * 1181104350,
             log_byte: SAFETY_MULTIPLIER * 4399597,
             storage_write_base: SAFETY_MULTIPLIER * 21398912000,
@@ -400,6 +440,17 @@ impl ExtCostsConfig {
             keccak256_byte: 0,
             keccak512_base: 0,
             keccak512_byte: 0,
+            ed25519_verify_base: 0,
+            ed25519_verify_byte: 0,
+            ecrecover_base: 0,
+            ripemd160_base: 0,
+            ripemd160_byte: 0,
+            blake2b_base: 0,
+            blake2b_byte: 0,
+            alt_bn128_g1_add_base: 0,
+            alt_bn128_g1_mul_base: 0,
+            alt_bn128_pairing_check_base: 0,
+            alt_bn128_pairing_check_element: 0,
             log_base: 0,
             log_byte: 0,
             storage_write_base: 0,
@@ -485,6 +536,17 @@ pub enum ExtCosts {
     promise_return,
     validator_stake_base,
     validator_total_stake_base,
+    ed25519_verify_base,
+    ed25519_verify_byte,
+    ecrecover_base,
+    ripemd160_base,
+    ripemd160_byte,
+    blake2b_base,
+    blake2b_byte,
+    alt_bn128_g1_add_base,
+    alt_bn128_g1_mul_base,
+    alt_bn128_pairing_check_base,
+    alt_bn128_pairing_check_element,
 }
 
 // Type of an action, used in fees logic.
@@ -589,11 +651,22 @@ impl ExtCosts {
             promise_return => config.promise_return,
             validator_stake_base => config.validator_stake_base,
             validator_total_stake_base => config.validator_total_stake_base,
+            ed25519_verify_base => config.ed25519_verify_base,
+            ed25519_verify_byte => config.ed25519_verify_byte,
+            ecrecover_base => config.ecrecover_base,
+            ripemd160_base => config.ripemd160_base,
+            ripemd160_byte => config.ripemd160_byte,
+            blake2b_base => config.blake2b_base,
+            blake2b_byte => config.blake2b_byte,
+            alt_bn128_g1_add_base => config.alt_bn128_g1_add_base,
+            alt_bn128_g1_mul_base => config.alt_bn128_g1_mul_base,
+            alt_bn128_pairing_check_base => config.alt_bn128_pairing_check_base,
+            alt_bn128_pairing_check_element => config.alt_bn128_pairing_check_element,
         }
     }
 
     pub const fn count() -> usize {
-        ExtCosts::validator_total_stake_base as usize + 1
+        ExtCosts::alt_bn128_pairing_check_element as usize + 1
     }
 
     pub fn name_of(index: usize) -> &'static str {
@@ -647,6 +720,17 @@ impl ExtCosts {
             "promise_return",
             "validator_stake_base",
             "validator_total_stake_base",
+            "ed25519_verify_base",
+            "ed25519_verify_byte",
+            "ecrecover_base",
+            "ripemd160_base",
+            "ripemd160_byte",
+            "blake2b_base",
+            "blake2b_byte",
+            "alt_bn128_g1_add_base",
+            "alt_bn128_g1_mul_base",
+            "alt_bn128_pairing_check_base",
+            "alt_bn128_pairing_check_element",
         ][index]
     }
 }
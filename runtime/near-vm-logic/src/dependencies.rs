@@ -1,6 +1,6 @@
 //! External dependencies of the near-vm-logic.
 
-use crate::types::{AccountId, Balance, Gas, PublicKey, ReceiptIndex};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, PublicKey, ReceiptIndex};
 use near_vm_errors::VMLogicError;
 
 /// An abstraction over the memory of the smart contract.
@@ -386,6 +386,54 @@ pub trait External {
         method_names: Vec<Vec<u8>>,
     ) -> Result<()>;
 
+    /// Attaches an `AddKeyAction` action to an existing receipt with
+    /// `AccessKeyPermission::FunctionCallV2`
+    ///
+    /// # Arguments
+    ///
+    /// * `receipt_index` - an index of Receipt to append an action
+    /// * `public_key` - a public key for an access key
+    /// * `nonce` - a nonce
+    /// * `allowance` - amount of tokens allowed to spend by this access key
+    /// * `receiver_id` - a contract witch will be allowed to call with this access key
+    /// * `method_names` - a list of method names is allowed to call with this access key (empty = any method)
+    /// * `max_deposit_per_call` - the largest deposit that can be attached to a single call
+    /// * `expires_at_height` - the height of the first block in which the key can't be used
+    ///
+    /// # Example
+    /// ```
+    /// # use near_vm_logic::mocks::mock_external::MockedExternal;
+    /// # use near_vm_logic::External;
+    ///
+    /// # let mut external = MockedExternal::new();
+    /// let receipt_index = external.create_receipt(vec![], "charli.near".to_owned()).unwrap();
+    /// external.append_action_add_key_with_function_call_v2(
+    ///     receipt_index,
+    ///     b"some public key".to_vec(),
+    ///     0u64,
+    ///     None,
+    ///     "bob.near".to_owned(),
+    ///     vec![b"foo".to_vec(), b"bar".to_vec()],
+    ///     1_000,
+    ///     Some(100),
+    /// ).unwrap();
+    ///
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if `receipt_index` is missing
+    fn append_action_add_key_with_function_call_v2(
+        &mut self,
+        receipt_index: ReceiptIndex,
+        public_key: PublicKey,
+        nonce: u64,
+        allowance: Option<Balance>,
+        receiver_id: AccountId,
+        method_names: Vec<Vec<u8>>,
+        max_deposit_per_call: Balance,
+        expires_at_height: Option<BlockHeight>,
+    ) -> Result<()>;
+
     /// Attaches an `DeleteKeyAction` action to an existing receipt
     ///
     /// # Arguments
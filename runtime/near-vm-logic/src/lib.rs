@@ -1,3 +1,4 @@
+mod alt_bn128;
 mod config;
 mod context;
 mod dependencies;
@@ -1,3 +1,4 @@
+use crate::alt_bn128;
 use crate::config::ActionCosts;
 use crate::config::ExtCosts::*;
 use crate::config::VMConfig;
@@ -11,6 +12,7 @@ use crate::types::{
 use crate::utils::split_method_names;
 use crate::{ExtCosts, HostError, VMLogicError, ValuePtr};
 use byteorder::ByteOrder;
+use near_crypto::{ED25519PublicKey, KeyType, PublicKey, Secp256K1Signature, Signature};
 use near_runtime_fees::RuntimeFeesConfig;
 use near_runtime_utils::is_account_id_64_len_hex;
 use near_vm_errors::InconsistentStateError;
@@ -23,6 +25,9 @@ type Result<T> = ::std::result::Result<T, VMLogicError>;
 const LEGACY_DEFAULT_PROTOCOL_VERSION: ProtocolVersion = 34;
 const IMPLICIT_ACCOUNT_CREATION_PROTOCOL_VERSION: ProtocolVersion = 35;
 
+const ED25519_SIGNATURE_LENGTH: usize = 64;
+const ED25519_PUBLIC_KEY_LENGTH: usize = 32;
+
 pub struct VMLogic<'a> {
     /// Provides access to the components outside the Wasm runtime for operations on the trie and
     /// receipts creation.
@@ -856,6 +861,247 @@ impl<'a> VMLogic<'a> {
         self.internal_write_register(register_id, value_hash.as_ref().to_vec())
     }
 
+    /// Verifies that the ed25519 `signature` of the message is made by `public_key`. Returns 1 if
+    /// the signature is valid and 0 otherwise.
+    ///
+    /// # Errors
+    ///
+    /// * If any of the inputs points outside the memory or the registers use more memory than
+    ///   the limit returns `MemoryAccessViolation`;
+    /// * If the signature is not 64 bytes or the public key is not 32 bytes returns
+    ///   `Ed25519VerifyInvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `ed25519_verify_base + ed25519_verify_byte * message_len`
+    pub fn ed25519_verify(
+        &mut self,
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(ed25519_verify_base)?;
+        let signature_data = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        if signature_data.len() != ED25519_SIGNATURE_LENGTH {
+            return Err(HostError::Ed25519VerifyInvalidInput {
+                msg: format!("invalid signature length {}", signature_data.len()),
+            }
+            .into());
+        }
+        let message = self.get_vec_from_memory_or_register(message_ptr, message_len)?;
+        self.gas_counter.pay_per_byte(ed25519_verify_byte, message.len() as u64)?;
+        let public_key_data =
+            self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+        if public_key_data.len() != ED25519_PUBLIC_KEY_LENGTH {
+            return Err(HostError::Ed25519VerifyInvalidInput {
+                msg: format!("invalid public key length {}", public_key_data.len()),
+            }
+            .into());
+        }
+
+        let signature = match Signature::from_parts(KeyType::ED25519, &signature_data) {
+            Ok(signature) => signature,
+            Err(_) => return Ok(false as u64),
+        };
+        let mut public_key = [0u8; ED25519_PUBLIC_KEY_LENGTH];
+        public_key.copy_from_slice(&public_key_data);
+        let public_key = PublicKey::ED25519(ED25519PublicKey(public_key));
+        Ok(signature.verify(&message, &public_key) as u64)
+    }
+
+    /// Recovers the secp256k1 public key from the 64 bytes `signature` (`r` and `s`) and the
+    /// recovery id `v` of the 32 bytes `hash`. On success writes the 64 bytes of the uncompressed
+    /// public key without the prefix into `register_id` and returns 1, otherwise returns 0.
+    ///
+    /// If `malleability_flag` is 1, signatures with `s` in the upper half of the curve order are
+    /// rejected as malleable, like Ethereum does for transactions.
+    ///
+    /// # Errors
+    ///
+    /// * If any of the inputs points outside the memory or the registers use more memory than
+    ///   the limit returns `MemoryAccessViolation`;
+    /// * If `hash` is not 32 bytes, the signature is not 64 bytes, `v` is not in `0..4` or
+    ///   `malleability_flag` is not 0 or 1 returns `ECRecoverError`.
+    ///
+    /// # Cost
+    ///
+    /// `ecrecover_base + write_register_base + write_register_byte * 64`
+    pub fn ecrecover(
+        &mut self,
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64,
+    ) -> Result<u64> {
+        self.gas_counter.pay_base(ecrecover_base)?;
+        let hash_data = self.get_vec_from_memory_or_register(hash_ptr, hash_len)?;
+        if hash_data.len() != 32 {
+            return Err(HostError::ECRecoverError {
+                msg: format!("invalid hash length {}", hash_data.len()),
+            }
+            .into());
+        }
+        let signature_data = self.get_vec_from_memory_or_register(signature_ptr, signature_len)?;
+        if signature_data.len() != 64 {
+            return Err(HostError::ECRecoverError {
+                msg: format!("invalid signature length {}", signature_data.len()),
+            }
+            .into());
+        }
+        if v >= 4 {
+            return Err(
+                HostError::ECRecoverError { msg: format!("invalid recovery id {}", v) }.into()
+            );
+        }
+        if malleability_flag > 1 {
+            return Err(HostError::ECRecoverError {
+                msg: format!("invalid malleability flag {}", malleability_flag),
+            }
+            .into());
+        }
+
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&hash_data);
+        let mut signature = [0u8; 65];
+        signature[..64].copy_from_slice(&signature_data);
+        signature[64] = v as u8;
+        let signature = Secp256K1Signature::from(signature);
+        if !signature.check_signature_values(malleability_flag == 1) {
+            return Ok(false as u64);
+        }
+        match signature.recover(hash) {
+            Ok(public_key) => {
+                self.internal_write_register(register_id, public_key.as_ref().to_vec())?;
+                Ok(true as u64)
+            }
+            Err(_) => Ok(false as u64),
+        }
+    }
+
+    /// Hashes the given value using ripemd160 and returns it into `register_id`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + ripemd160_base + ripemd160_byte * num_bytes`
+    pub fn ripemd160(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> Result<()> {
+        self.gas_counter.pay_base(ripemd160_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        self.gas_counter.pay_per_byte(ripemd160_byte, value.len() as u64)?;
+
+        use ripemd160::Digest;
+
+        let value_hash = ripemd160::Ripemd160::digest(&value);
+        self.internal_write_register(register_id, value_hash.as_ref().to_vec())
+    }
+
+    /// Hashes the given value using blake2b with 64 bytes output and returns it into
+    /// `register_id`.
+    ///
+    /// # Errors
+    ///
+    /// If `value_len + value_ptr` points outside the memory or the registers use more memory than
+    /// the limit with `MemoryAccessViolation`.
+    ///
+    /// # Cost
+    ///
+    /// `base + write_register_base + write_register_byte * num_bytes + blake2b_base + blake2b_byte * num_bytes`
+    pub fn blake2b(&mut self, value_len: u64, value_ptr: u64, register_id: u64) -> Result<()> {
+        self.gas_counter.pay_base(blake2b_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        self.gas_counter.pay_per_byte(blake2b_byte, value.len() as u64)?;
+
+        use blake2::Digest;
+
+        let value_hash = blake2::Blake2b::digest(&value);
+        self.internal_write_register(register_id, value_hash.as_ref().to_vec())
+    }
+
+    /// Adds two alt_bn128 G1 points given as 128 bytes `x1, y1, x2, y2` and writes the 64 bytes
+    /// of the sum into `register_id`. Encoding follows EIP-196: 32 bytes big-endian numbers and
+    /// zeros for the point at infinity.
+    ///
+    /// # Errors
+    ///
+    /// * If `value_len + value_ptr` points outside the memory or the registers use more memory
+    ///   than the limit returns `MemoryAccessViolation`;
+    /// * If the input is not 128 bytes or any of the points is not on the curve returns
+    ///   `AltBn128InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + alt_bn128_g1_add_base + write_register_base + write_register_byte * 64`
+    pub fn alt_bn128_g1_add(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(alt_bn128_g1_add_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let res = alt_bn128::g1_add(&value)?;
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Multiplies an alt_bn128 G1 point by a scalar given as 96 bytes `x, y, scalar` and writes
+    /// the 64 bytes of the product into `register_id`. The scalar must be less than the order of
+    /// the group.
+    ///
+    /// # Errors
+    ///
+    /// * If `value_len + value_ptr` points outside the memory or the registers use more memory
+    ///   than the limit returns `MemoryAccessViolation`;
+    /// * If the input is not 96 bytes, the point is not on the curve or the scalar is too big
+    ///   returns `AltBn128InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + alt_bn128_g1_mul_base + write_register_base + write_register_byte * 64`
+    pub fn alt_bn128_g1_mul(
+        &mut self,
+        value_len: u64,
+        value_ptr: u64,
+        register_id: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(alt_bn128_g1_mul_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let res = alt_bn128::g1_mul(&value)?;
+        self.internal_write_register(register_id, res)
+    }
+
+    /// Checks that the product of the pairings of the given `(G1, G2)` pairs of alt_bn128 points
+    /// is one. Every pair is 192 bytes: `x, y` of the G1 point followed by `x_im, x_re, y_im,
+    /// y_re` of the G2 point, like in EIP-197. Returns 1 if the check passes and 0 otherwise,
+    /// the check of no pairs passes.
+    ///
+    /// # Errors
+    ///
+    /// * If `value_len + value_ptr` points outside the memory or the registers use more memory
+    ///   than the limit returns `MemoryAccessViolation`;
+    /// * If the input length is not a multiple of 192 or any of the points is invalid returns
+    ///   `AltBn128InvalidInput`.
+    ///
+    /// # Cost
+    ///
+    /// `base + alt_bn128_pairing_check_base + alt_bn128_pairing_check_element * num_pairs`
+    pub fn alt_bn128_pairing_check(&mut self, value_len: u64, value_ptr: u64) -> Result<u64> {
+        self.gas_counter.pay_base(alt_bn128_pairing_check_base)?;
+        let value = self.get_vec_from_memory_or_register(value_ptr, value_len)?;
+        let num_elements = alt_bn128::pairing_check_num_elements(&value)?;
+        self.gas_counter.pay_per_byte(alt_bn128_pairing_check_element, num_elements)?;
+        Ok(alt_bn128::pairing_check(&value)? as u64)
+    }
+
     /// Called by gas metering injected into Wasm. Counts both towards `burnt_gas` and `used_gas`.
     ///
     /// # Errors
@@ -1585,6 +1831,87 @@ impl<'a> VMLogic<'a> {
         Ok(())
     }
 
+    /// Appends `AddKey` action to the batch of actions for the given promise pointed by
+    /// `promise_idx`. The access key will have `FunctionCallV2` permission, which also allows
+    /// attaching up to `max_deposit_per_call` to a function call and stops working at the block
+    /// height `expires_at_height`. `expires_at_height` equal to 0 means the key never expires.
+    ///
+    /// # Errors
+    ///
+    /// * If `promise_idx` does not correspond to an existing promise returns `InvalidPromiseIndex`.
+    /// * If the promise pointed by the `promise_idx` is an ephemeral promise created by
+    /// `promise_and` returns `CannotAppendActionToJointPromise`.
+    /// * If the given public key is not a valid (e.g. wrong length) returns `InvalidPublicKey`.
+    /// * If `public_key_len + public_key_ptr`, `allowance_ptr + 16`,
+    /// `receiver_id_len + receiver_id_ptr`, `method_names_len + method_names_ptr` or
+    /// `max_deposit_per_call_ptr + 16` points outside the memory of the guest or host returns
+    /// `MemoryAccessViolation`.
+    /// * If called as view function returns `ProhibitedInView`.
+    ///
+    /// # Cost
+    ///
+    /// Same as `promise_batch_action_add_key_with_function_call` plus the cost of reading one more
+    /// u128 from the memory.
+    pub fn promise_batch_action_add_key_with_function_call_v2(
+        &mut self,
+        promise_idx: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        nonce: u64,
+        allowance_ptr: u64,
+        receiver_id_len: u64,
+        receiver_id_ptr: u64,
+        method_names_len: u64,
+        method_names_ptr: u64,
+        max_deposit_per_call_ptr: u64,
+        expires_at_height: u64,
+    ) -> Result<()> {
+        self.gas_counter.pay_base(base)?;
+        if self.context.is_view {
+            return Err(HostError::ProhibitedInView {
+                method_name: "promise_batch_action_add_key_with_function_call_v2".to_string(),
+            }
+            .into());
+        }
+        let public_key = self.get_vec_from_memory_or_register(public_key_ptr, public_key_len)?;
+        let allowance = self.memory_get_u128(allowance_ptr)?;
+        let allowance = if allowance > 0 { Some(allowance) } else { None };
+        let receiver_id = self.read_and_parse_account_id(receiver_id_ptr, receiver_id_len)?;
+        let raw_method_names =
+            self.get_vec_from_memory_or_register(method_names_ptr, method_names_len)?;
+        let method_names = split_method_names(&raw_method_names)?;
+        let max_deposit_per_call = self.memory_get_u128(max_deposit_per_call_ptr)?;
+        let expires_at_height = if expires_at_height > 0 { Some(expires_at_height) } else { None };
+
+        let (receipt_idx, sir) = self.promise_idx_to_receipt_idx_with_sir(promise_idx)?;
+
+        // +1 is to account for null-terminating characters.
+        let num_bytes = method_names.iter().map(|v| v.len() as u64 + 1).sum::<u64>();
+        self.gas_counter.pay_action_base(
+            &self.fees_config.action_creation_config.add_key_cost.function_call_cost,
+            sir,
+            ActionCosts::function_call,
+        )?;
+        self.gas_counter.pay_action_per_byte(
+            &self.fees_config.action_creation_config.add_key_cost.function_call_cost_per_byte,
+            num_bytes,
+            sir,
+            ActionCosts::function_call,
+        )?;
+
+        self.ext.append_action_add_key_with_function_call_v2(
+            receipt_idx,
+            public_key,
+            nonce,
+            allowance,
+            receiver_id,
+            method_names,
+            max_deposit_per_call,
+            expires_at_height,
+        )?;
+        Ok(())
+    }
+
     /// Appends `DeleteKey` action to the batch of actions for the given promise pointed by
     /// `promise_idx`.
     ///
@@ -1,4 +1,4 @@
-use crate::types::{AccountId, Balance, Gas, PublicKey};
+use crate::types::{AccountId, Balance, BlockHeight, Gas, PublicKey};
 use crate::{External, ValuePtr};
 use near_vm_errors::HostError;
 use serde::{Deserialize, Serialize};
@@ -170,6 +170,31 @@ impl External for MockedExternal {
         Ok(())
     }
 
+    fn append_action_add_key_with_function_call_v2(
+        &mut self,
+        receipt_index: u64,
+        public_key: Vec<u8>,
+        nonce: u64,
+        allowance: Option<u128>,
+        receiver_id: String,
+        method_names: Vec<Vec<u8>>,
+        max_deposit_per_call: Balance,
+        expires_at_height: Option<BlockHeight>,
+    ) -> Result<()> {
+        self.receipts.get_mut(receipt_index as usize).unwrap().actions.push(
+            Action::AddKeyWithFunctionCallV2(AddKeyWithFunctionCallV2Action {
+                public_key,
+                nonce,
+                allowance,
+                receiver_id,
+                method_names,
+                max_deposit_per_call,
+                expires_at_height,
+            }),
+        );
+        Ok(())
+    }
+
     fn append_action_delete_key(&mut self, receipt_index: u64, public_key: Vec<u8>) -> Result<()> {
         self.receipts
             .get_mut(receipt_index as usize)
@@ -223,6 +248,7 @@ pub enum Action {
     Stake(StakeAction),
     AddKeyWithFullAccess(AddKeyWithFullAccessAction),
     AddKeyWithFunctionCall(AddKeyWithFunctionCallAction),
+    AddKeyWithFunctionCallV2(AddKeyWithFunctionCallV2Action),
     DeleteKey(DeleteKeyAction),
     DeleteAccount(DeleteAccountAction),
 }
@@ -276,6 +302,19 @@ pub struct AddKeyWithFunctionCallAction {
     method_names: Vec<Vec<u8>>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AddKeyWithFunctionCallV2Action {
+    #[serde(with = "crate::serde_with::bytes_as_base58")]
+    public_key: PublicKey,
+    nonce: u64,
+    allowance: Option<Balance>,
+    receiver_id: AccountId,
+    #[serde(with = "crate::serde_with::vec_bytes_as_str")]
+    method_names: Vec<Vec<u8>>,
+    max_deposit_per_call: Balance,
+    expires_at_height: Option<BlockHeight>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct DeleteKeyAction {
     #[serde(with = "crate::serde_with::bytes_as_base58")]
@@ -1,7 +1,7 @@
 use fixtures::get_context;
 use helpers::*;
 use near_vm_errors::HostError;
-use near_vm_logic::ExtCosts;
+use near_vm_logic::{ExtCosts, VMLogic};
 use vm_logic_builder::VMLogicBuilder;
 
 mod fixtures;
@@ -530,6 +530,326 @@ fn test_keccak512() {
     });
 }
 
+#[test]
+fn test_ed25519_verify() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let signature: [u8; 64] = [
+        144, 169, 190, 25, 216, 170, 51, 247, 99, 143, 53, 232, 240, 122, 191, 26, 78, 67, 43, 237,
+        89, 215, 145, 21, 59, 240, 59, 167, 178, 84, 214, 225, 148, 136, 3, 203, 183, 51, 50, 16,
+        191, 198, 119, 196, 231, 117, 26, 66, 96, 212, 202, 218, 129, 30, 16, 220, 142, 190, 168,
+        204, 55, 175, 188, 1,
+    ];
+    let public_key: [u8; 32] = [
+        3, 161, 7, 191, 243, 206, 16, 190, 29, 112, 221, 24, 231, 75, 192, 153, 103, 228, 214, 48,
+        155, 165, 13, 95, 29, 220, 134, 100, 18, 85, 49, 184,
+    ];
+    let message = [65u8; 32];
+    let wrong_message = [66u8; 32];
+
+    let verify = |logic: &mut VMLogic<'_>, signature: &[u8], message: &[u8]| {
+        logic.ed25519_verify(
+            signature.len() as _,
+            signature.as_ptr() as _,
+            message.len() as _,
+            message.as_ptr() as _,
+            public_key.len() as _,
+            public_key.as_ptr() as _,
+        )
+    };
+    assert_eq!(verify(&mut logic, &signature, &message), Ok(1));
+    assert_costs(map! {
+        ExtCosts::read_memory_base: 3,
+        ExtCosts::read_memory_byte: 128,
+        ExtCosts::ed25519_verify_base: 1,
+        ExtCosts::ed25519_verify_byte: 32,
+    });
+    assert_eq!(verify(&mut logic, &signature, &wrong_message), Ok(0));
+    assert_eq!(
+        verify(&mut logic, &signature[..63], &message),
+        Err(HostError::Ed25519VerifyInvalidInput {
+            msg: "invalid signature length 63".to_string()
+        }
+        .into())
+    );
+    reset_costs_counter();
+}
+
+#[test]
+fn test_ecrecover() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    // Signature of sha256 of `[65u8; 32]`.
+    let hash: [u8; 32] = [
+        34, 164, 128, 81, 89, 76, 25, 73, 222, 237, 112, 64, 133, 12, 31, 15, 135, 100, 83, 127,
+        81, 145, 190, 86, 115, 45, 22, 165, 76, 29, 129, 83,
+    ];
+    let signature: [u8; 64] = [
+        200, 2, 31, 218, 8, 50, 210, 214, 207, 117, 164, 217, 169, 30, 80, 248, 70, 16, 170, 157,
+        37, 203, 91, 158, 87, 181, 58, 173, 202, 236, 154, 162, 83, 157, 135, 191, 108, 45, 242,
+        104, 97, 188, 251, 219, 124, 207, 191, 196, 136, 142, 166, 96, 226, 143, 1, 21, 117, 33,
+        170, 136, 182, 59, 72, 109,
+    ];
+    let public_key: [u8; 64] = [
+        187, 80, 226, 216, 154, 78, 215, 6, 99, 208, 128, 101, 159, 224, 173, 75, 155, 195, 224,
+        108, 23, 162, 39, 67, 57, 102, 203, 89, 206, 238, 2, 13, 236, 221, 191, 110, 0, 25, 32, 17,
+        100, 141, 19, 177, 192, 10, 247, 112, 192, 193, 187, 96, 157, 77, 58, 92, 152, 164, 55,
+        114, 224, 225, 142, 244,
+    ];
+
+    let ecrecover = |logic: &mut VMLogic<'_>, v: u64, malleability_flag: u64| {
+        logic.ecrecover(
+            hash.len() as _,
+            hash.as_ptr() as _,
+            signature.len() as _,
+            signature.as_ptr() as _,
+            v,
+            malleability_flag,
+            0,
+        )
+    };
+    assert_eq!(ecrecover(&mut logic, 1, 1), Ok(1));
+    let res = &vec![0u8; 64];
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(res, &public_key.to_vec());
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 2,
+        ExtCosts::read_memory_byte: 96,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 64,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::ecrecover_base: 1,
+    });
+
+    // The other recovery id gives a different public key.
+    assert_eq!(ecrecover(&mut logic, 0, 1), Ok(1));
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_ne!(res, &public_key.to_vec());
+    assert_eq!(
+        ecrecover(&mut logic, 4, 1),
+        Err(HostError::ECRecoverError { msg: "invalid recovery id 4".to_string() }.into())
+    );
+    reset_costs_counter();
+}
+
+#[test]
+fn test_ripemd160() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let data = b"tesdsst";
+
+    logic.ripemd160(data.len() as _, data.as_ptr() as _, 0).unwrap();
+    let res = &vec![0u8; 20];
+    logic.read_register(0, res.as_ptr() as _).expect("OK");
+    assert_eq!(
+        res,
+        &[21, 102, 156, 115, 232, 3, 58, 215, 35, 84, 129, 30, 143, 86, 212, 104, 70, 97, 14, 225]
+            .to_vec()
+    );
+    let len = data.len() as u64;
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: len,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 20,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 20,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 20,
+        ExtCosts::ripemd160_base: 1,
+        ExtCosts::ripemd160_byte: len,
+    });
+}
+
+#[test]
+fn test_blake2b() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let data = b"tesdsst";
+
+    logic.blake2b(data.len() as _, data.as_ptr() as _, 0).unwrap();
+    let res = &vec![0u8; 64];
+    logic.read_register(0, res.as_ptr() as _).expect("OK");
+    assert_eq!(
+        res,
+        &[
+            144, 12, 165, 192, 98, 246, 37, 228, 134, 61, 43, 212, 111, 32, 204, 204, 186, 212, 47,
+            44, 209, 53, 167, 80, 195, 200, 226, 84, 34, 162, 249, 135, 172, 3, 90, 122, 205, 96,
+            211, 100, 188, 18, 134, 125, 111, 130, 31, 143, 25, 108, 194, 209, 205, 73, 169, 10,
+            132, 222, 75, 219, 103, 234, 67, 180
+        ]
+        .to_vec()
+    );
+    let len = data.len() as u64;
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: len,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 64,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::blake2b_base: 1,
+        ExtCosts::blake2b_byte: len,
+    });
+}
+
+/// Concatenates big-endian 32 bytes encodings of the given hex numbers.
+fn alt_bn128_input(numbers: &[&str]) -> Vec<u8> {
+    let mut res = vec![];
+    for number in numbers {
+        let number = format!("{:0>64}", number);
+        for i in 0..32 {
+            res.push(u8::from_str_radix(&number[2 * i..2 * i + 2], 16).unwrap());
+        }
+    }
+    res
+}
+
+const ALT_BN128_G2: [&str; 4] = [
+    "198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+    "1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed",
+    "090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+    "12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa",
+];
+
+#[test]
+fn test_alt_bn128_g1_add() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let input = alt_bn128_input(&["1", "2", "1", "2"]);
+
+    logic.alt_bn128_g1_add(input.len() as _, input.as_ptr() as _, 0).unwrap();
+    let res = &vec![0u8; 64];
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(
+        res,
+        &alt_bn128_input(&[
+            "030644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd3",
+            "15ed738c0e0a7c92e7845f96b2ae9c0a68a6a449e3538fc7ff3ebf7a5a18a2c4",
+        ])
+    );
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: 128,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 64,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::alt_bn128_g1_add_base: 1,
+    });
+
+    // Adding the point at infinity.
+    let input = alt_bn128_input(&["1", "2", "0", "0"]);
+    logic.alt_bn128_g1_add(input.len() as _, input.as_ptr() as _, 0).unwrap();
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(res, &alt_bn128_input(&["1", "2"]));
+
+    let input = alt_bn128_input(&["1", "3", "1", "2"]);
+    assert_eq!(
+        logic.alt_bn128_g1_add(input.len() as _, input.as_ptr() as _, 0),
+        Err(HostError::AltBn128InvalidInput { msg: "invalid G1 point".to_string() }.into())
+    );
+    reset_costs_counter();
+}
+
+#[test]
+fn test_alt_bn128_g1_mul() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    let input = alt_bn128_input(&["1", "2", "3"]);
+
+    logic.alt_bn128_g1_mul(input.len() as _, input.as_ptr() as _, 0).unwrap();
+    let res = &vec![0u8; 64];
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(
+        res,
+        &alt_bn128_input(&[
+            "0769bf9ac56bea3ff40232bcb1b6bd159315d84715b8e679f2d355961915abf0",
+            "2ab799bee0489429554fdb7c8d086475319e63b40b9c5b57cdf1ff3dd9fe2261",
+        ])
+    );
+    assert_costs(map! {
+        ExtCosts::base: 1,
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: 96,
+        ExtCosts::write_memory_base: 1,
+        ExtCosts::write_memory_byte: 64,
+        ExtCosts::read_register_base: 1,
+        ExtCosts::read_register_byte: 64,
+        ExtCosts::write_register_base: 1,
+        ExtCosts::write_register_byte: 64,
+        ExtCosts::alt_bn128_g1_mul_base: 1,
+    });
+
+    // Multiplying by zero gives the point at infinity.
+    let input = alt_bn128_input(&["1", "2", "0"]);
+    logic.alt_bn128_g1_mul(input.len() as _, input.as_ptr() as _, 0).unwrap();
+    logic.read_register(0, res.as_ptr() as _).unwrap();
+    assert_eq!(res, &vec![0u8; 64]);
+
+    let input = alt_bn128_input(&["1", "2"]);
+    assert_eq!(
+        logic.alt_bn128_g1_mul(input.len() as _, input.as_ptr() as _, 0),
+        Err(HostError::AltBn128InvalidInput {
+            msg: "expected a G1 point and a scalar".to_string()
+        }
+        .into())
+    );
+    reset_costs_counter();
+}
+
+#[test]
+fn test_alt_bn128_pairing_check() {
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(get_context(vec![], false));
+    // e(G1, G2) * e(-G1, G2) = 1
+    let mut numbers = vec!["1", "2"];
+    numbers.extend_from_slice(&ALT_BN128_G2);
+    numbers.extend_from_slice(&[
+        "1",
+        "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd45",
+    ]);
+    numbers.extend_from_slice(&ALT_BN128_G2);
+    let input = alt_bn128_input(&numbers);
+
+    assert_eq!(logic.alt_bn128_pairing_check(input.len() as _, input.as_ptr() as _), Ok(1));
+    assert_costs(map! {
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: 384,
+        ExtCosts::alt_bn128_pairing_check_base: 1,
+        ExtCosts::alt_bn128_pairing_check_element: 2,
+    });
+
+    assert_eq!(logic.alt_bn128_pairing_check(192, input.as_ptr() as _), Ok(0));
+    assert_eq!(logic.alt_bn128_pairing_check(0, input.as_ptr() as _), Ok(1));
+    reset_costs_counter();
+
+    // The length is checked before the elements are charged.
+    assert_eq!(
+        logic.alt_bn128_pairing_check(200, input.as_ptr() as _),
+        Err(HostError::AltBn128InvalidInput {
+            msg: "expected a sequence of (G1, G2) pairs".to_string()
+        }
+        .into())
+    );
+    assert_costs(map! {
+        ExtCosts::read_memory_base: 1,
+        ExtCosts::read_memory_byte: 200,
+        ExtCosts::alt_bn128_pairing_check_base: 1,
+    });
+}
+
 #[test]
 fn test_hash256_register() {
     let mut logic_builder = VMLogicBuilder::default();
@@ -363,6 +363,76 @@ fn test_promise_batch_action_add_key_with_function_call() {
     );
 }
 
+#[test]
+fn test_promise_batch_action_add_key_with_function_call_v2() {
+    let mut context = get_context(vec![], false);
+    context.account_balance = 100;
+
+    let mut logic_builder = VMLogicBuilder::default();
+    let mut logic = logic_builder.build(context);
+    let index = promise_create(&mut logic, b"rick.test", 0, 0).expect("should create a promise");
+    let key = b"ed25519:5do5nkAEVhL8iteDvXNgxi4pWK78Y7DDadX11ArFNyrf";
+
+    promise_batch_action_add_key_with_function_call_v2(
+        &mut logic, 123, key, 1, 999, b"sam", b"foo,bar", 10, 100,
+    )
+    .expect_err("shouldn't accept non-existent promise index");
+    promise_batch_action_add_key_with_function_call_v2(
+        &mut logic, index, key, 1, 999, b"sam", b"foo,bar", 10, 100,
+    )
+    .expect("should add a key expiring at height 100");
+    promise_batch_action_add_key_with_function_call_v2(
+        &mut logic, index, key, 2, 0, b"sam", b"", 0, 0,
+    )
+    .expect("should add a key that never expires");
+    let expected = serde_json::json!(
+    [
+        {
+            "receipt_indices": [],
+            "receiver_id": "rick.test",
+            "actions": [
+                {
+                    "FunctionCall": {
+                        "method_name": "promise_create",
+                        "args": "args",
+                        "gas": 0,
+                        "deposit": 0
+                    }
+                },
+                {
+                    "AddKeyWithFunctionCallV2": {
+                        "public_key": "RLb4qQXoZPAFqzZhiLFAcGFPFC7JWcDd8xKvQHHEqLUgDXuQkr2ehKAN28MNGQN9vUZ1qGZ",
+                        "nonce": 1,
+                        "allowance": 999,
+                        "receiver_id": "sam",
+                        "method_names": [
+                            "foo",
+                            "bar"
+                        ],
+                        "max_deposit_per_call": 10,
+                        "expires_at_height": 100
+                    }
+                },
+                {
+                    "AddKeyWithFunctionCallV2": {
+                        "public_key": "RLb4qQXoZPAFqzZhiLFAcGFPFC7JWcDd8xKvQHHEqLUgDXuQkr2ehKAN28MNGQN9vUZ1qGZ",
+                        "nonce": 2,
+                        "allowance": null,
+                        "receiver_id": "sam",
+                        "method_names": [],
+                        "max_deposit_per_call": 0,
+                        "expires_at_height": null
+                    }
+                }
+            ]
+        }
+    ]);
+    assert_eq!(
+        &serde_json::to_string(logic_builder.ext.get_receipt_create_calls()).unwrap(),
+        &expected.to_string()
+    );
+}
+
 #[test]
 fn test_promise_batch_then() {
     let mut context = get_context(vec![], false);
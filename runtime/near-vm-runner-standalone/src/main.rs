@@ -261,6 +261,7 @@ fn main() {
             vm_kind,
             Rc::clone(&profile_data),
             protocol_version,
+            None,
         )
     } else {
         run_vm(
@@ -274,6 +275,7 @@ fn main() {
             &promise_results,
             vm_kind,
             protocol_version,
+            None,
         )
     };
     let all_gas = match outcome.clone() {
@@ -76,6 +76,7 @@ fn pass_through(bench: &mut Bencher) {
             &fees_config,
             &promise_results,
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, 42);
     });
@@ -94,6 +95,7 @@ fn benchmark_fake_storage_8b_1000(bench: &mut Bencher) {
             &fees_config,
             &promise_results,
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, 999 * 1000 / 2);
     });
@@ -112,6 +114,7 @@ fn benchmark_fake_storage_10kib_1000(bench: &mut Bencher) {
             &fees_config,
             &promise_results,
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, 999 * 1000 / 2);
     });
@@ -130,6 +133,7 @@ fn sum_n_1000000(bench: &mut Bencher) {
             &fees_config,
             &promise_results,
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, (1000000 - 1) * 1000000 / 2);
     });
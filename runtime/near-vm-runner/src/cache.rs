@@ -4,12 +4,78 @@ use cached::{cached_key, SizedCache};
 use crate::errors::IntoVMError;
 use crate::prepare;
 use near_vm_errors::VMError;
-use near_vm_logic::VMConfig;
+use near_vm_logic::{VMConfig, VMKind};
 
 /// Cache size in number of cached modules to hold.
 #[cfg(not(feature = "no_cache"))]
 const CACHE_SIZE: usize = 128;
-// TODO: store a larger on-disk cache
+
+/// Version of the on-disk cache records. Must be bumped whenever the serialization format of
+/// compiled artifacts changes, e.g. when the Wasmer or Wasmtime version is upgraded, so that
+/// stale artifacts are never loaded.
+const CACHE_RECORD_VERSION: u32 = 1;
+
+/// Persistent storage for compiled contracts that outlives the in-memory cache and node
+/// restarts. Implementations must be safe to share between threads.
+pub trait CompiledContractCache: Send + Sync {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error>;
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error>;
+}
+
+impl std::fmt::Debug for dyn CompiledContractCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CompiledContractCache")
+    }
+}
+
+/// Key under which the compiled contract is stored. Contains the code hash together with
+/// everything that invalidates a compiled artifact: record version, VM kind and VM config.
+fn get_key(code_hash: &[u8], vm_kind: VMKind, config: &VMConfig) -> Vec<u8> {
+    let mut key = Vec::with_capacity(code_hash.len() + 13);
+    key.extend_from_slice(&CACHE_RECORD_VERSION.to_le_bytes());
+    key.push(vm_kind as u8);
+    key.extend_from_slice(&config.non_crypto_hash().to_le_bytes());
+    key.extend_from_slice(code_hash);
+    key
+}
+
+/// Compiles the module with Wasmer, or loads it from the persistent cache if it is there.
+/// Failures to read or write the persistent cache are not fatal: the module is compiled from
+/// scratch instead.
+fn compile_module_with_persistent_cache(
+    code_hash: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
+) -> Result<wasmer_runtime::Module, VMError> {
+    let key = cache.map(|_| get_key(code_hash, VMKind::Wasmer, config));
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Some(module) = cache.get(key).ok().flatten().and_then(|s| deserialize_wasmer(&s)) {
+            return Ok(module);
+        }
+    }
+    let prepared_code = prepare::prepare_contract(code, config)?;
+    let module = wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())?;
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Some(serialized) = serialize_wasmer(&module) {
+            let _ = cache.put(key, &serialized);
+        }
+    }
+    Ok(module)
+}
+
+fn serialize_wasmer(module: &wasmer_runtime::Module) -> Option<Vec<u8>> {
+    module.cache().ok()?.serialize().ok()
+}
+
+fn deserialize_wasmer(serialized: &[u8]) -> Option<wasmer_runtime::Module> {
+    let artifact = wasmer_runtime_core::cache::Artifact::deserialize(serialized).ok()?;
+    // Safe because artifacts are only written by `serialize_wasmer` of this crate, and the key
+    // contains `CACHE_RECORD_VERSION` which is bumped together with the Wasmer version.
+    unsafe {
+        wasmer_runtime_core::load_cache_with(artifact, &wasmer_runtime::default_compiler()).ok()
+    }
+}
 
 #[cfg(not(feature = "no_cache"))]
 cached_key! {
@@ -19,19 +85,129 @@ cached_key! {
         (code_hash, config.non_crypto_hash())
     };
 
-    fn compile_module(code_hash: Vec<u8>, code: &[u8], config: &VMConfig
+    fn compile_module(code_hash: Vec<u8>, code: &[u8], config: &VMConfig,
+        cache: Option<&dyn CompiledContractCache>
         ) -> Result<wasmer_runtime::Module, VMError> = {
-        let prepared_code = prepare::prepare_contract(code, config)?;
-        wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())
+        compile_module_with_persistent_cache(&code_hash, code, config, cache)
     }
 }
 
 #[cfg(feature = "no_cache")]
 pub(crate) fn compile_module(
-    _code_hash: Vec<u8>,
+    code_hash: Vec<u8>,
     code: &[u8],
     config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
 ) -> Result<wasmer_runtime::Module, VMError> {
+    compile_module_with_persistent_cache(&code_hash, code, config, cache)
+}
+
+/// Wasmtime 0.17 can't serialize compiled modules, so the persistent cache stores the prepared
+/// (gas-instrumented) code for them, and the compiled modules are only kept in memory.
+#[cfg(feature = "wasmtime_vm")]
+fn prepare_contract_with_persistent_cache(
+    code_hash: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
+) -> Result<Vec<u8>, VMError> {
+    let key = cache.map(|_| get_key(code_hash, VMKind::Wasmtime, config));
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        if let Ok(Some(prepared_code)) = cache.get(key) {
+            return Ok(prepared_code);
+        }
+    }
     let prepared_code = prepare::prepare_contract(code, config)?;
-    wasmer_runtime::compile(&prepared_code).map_err(|err| err.into_vm_error())
+    if let (Some(cache), Some(key)) = (cache, &key) {
+        let _ = cache.put(key, &prepared_code);
+    }
+    Ok(prepared_code)
+}
+
+#[cfg(feature = "wasmtime_vm")]
+fn compile_wasmtime_module_with_persistent_cache(
+    code_hash: &[u8],
+    code: &[u8],
+    config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
+) -> Result<wasmtime::Module, VMError> {
+    let prepared_code = prepare_contract_with_persistent_cache(code_hash, code, config, cache)?;
+    wasmtime::Module::new(&crate::wasmtime_runner::wasmtime_runner::ENGINE, prepared_code)
+        .map_err(|err| err.into_vm_error())
+}
+
+#[cfg(all(feature = "wasmtime_vm", not(feature = "no_cache")))]
+cached_key! {
+    WASMTIME_MODULES: SizedCache<(Vec<u8>, u64), Result<wasmtime::Module, VMError>>
+        = SizedCache::with_size(CACHE_SIZE);
+    Key = {
+        (code_hash, config.non_crypto_hash())
+    };
+
+    fn compile_wasmtime_module(code_hash: Vec<u8>, code: &[u8], config: &VMConfig,
+        cache: Option<&dyn CompiledContractCache>
+        ) -> Result<wasmtime::Module, VMError> = {
+        compile_wasmtime_module_with_persistent_cache(&code_hash, code, config, cache)
+    }
+}
+
+#[cfg(all(feature = "wasmtime_vm", feature = "no_cache"))]
+pub(crate) fn compile_wasmtime_module(
+    code_hash: Vec<u8>,
+    code: &[u8],
+    config: &VMConfig,
+    cache: Option<&dyn CompiledContractCache>,
+) -> Result<wasmtime::Module, VMError> {
+    compile_wasmtime_module_with_persistent_cache(&code_hash, code, config, cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct MockCompiledContractCache {
+        store: Mutex<HashMap<Vec<u8>, Vec<u8>>>,
+    }
+
+    impl CompiledContractCache for MockCompiledContractCache {
+        fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error> {
+            self.store.lock().unwrap().insert(key.to_vec(), value.to_vec());
+            Ok(())
+        }
+
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error> {
+            Ok(self.store.lock().unwrap().get(key).cloned())
+        }
+    }
+
+    #[test]
+    fn test_persistent_cache_roundtrip() {
+        let code = wabt::wat2wasm(r#"(module (func (export "main")))"#).unwrap();
+        let config = VMConfig::default();
+        let cache = MockCompiledContractCache::default();
+
+        compile_module_with_persistent_cache(b"hash", &code, &config, Some(&cache)).unwrap();
+        assert_eq!(cache.store.lock().unwrap().len(), 1);
+        let serialized = cache.get(&get_key(b"hash", VMKind::Wasmer, &config)).unwrap().unwrap();
+        let module = deserialize_wasmer(&serialized).unwrap();
+        assert!(module.info().exports.contains_key("main"));
+
+        // Loading from the cache must not write a new record.
+        compile_module_with_persistent_cache(b"hash", &code, &config, Some(&cache)).unwrap();
+        assert_eq!(cache.store.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_cache_key_depends_on_vm_and_config() {
+        let config = VMConfig::default();
+        let mut other_config = VMConfig::default();
+        other_config.limit_config.max_memory_pages += 1;
+        let key = get_key(b"hash", VMKind::Wasmer, &config);
+        assert_ne!(key, get_key(b"hash", VMKind::Wasmtime, &config));
+        assert_ne!(key, get_key(b"hash", VMKind::Wasmer, &other_config));
+        assert_ne!(key, get_key(b"other_hash", VMKind::Wasmer, &config));
+    }
 }
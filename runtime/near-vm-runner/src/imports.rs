@@ -1,3 +1,4 @@
+use near_vm_logic::types::ProtocolVersion;
 use near_vm_logic::VMLogic;
 
 use std::ffi::c_void;
@@ -23,14 +24,18 @@ macro_rules! rust2wasm {
     };
 }
 
+// Imports prefixed with `#[<feature name>, <ProtocolFeature>]` are only linked once the protocol
+// feature is enabled. Before that, contracts importing them fail to instantiate like with any other
+// unknown import.
 macro_rules! wrapped_imports {
-        ( $( $func:ident < [ $( $arg_name:ident : $arg_type:ident ),* ] -> [ $( $returns:ident ),* ] >, )* ) => {
+        ( $( $( #[$feature_name:tt, $feature:ident] )* $func:ident < [ $( $arg_name:ident : $arg_type:ident ),* ] -> [ $( $returns:ident ),* ] >, )* ) => {
             pub mod wasmer_ext {
             use near_vm_logic::VMLogic;
             use wasmer_runtime::Ctx;
             type VMResult<T> = ::std::result::Result<T, near_vm_logic::VMLogicError>;
             $(
                 #[allow(unused_parens)]
+                $(#[cfg(feature = $feature_name)])*
                 pub fn $func( ctx: &mut Ctx, $( $arg_name: $arg_type ),* ) -> VMResult<($( $returns ),*)> {
                     let logic: &mut VMLogic<'_> = unsafe { &mut *(ctx.data as *mut VMLogic<'_>) };
                     logic.$func( $( $arg_name, )* )
@@ -54,6 +59,7 @@ macro_rules! wrapped_imports {
             $(
                 #[allow(unused_parens)]
                 #[cfg(feature = "wasmtime_vm")]
+                $(#[cfg(feature = $feature_name)])*
                 pub fn $func( $( $arg_name: rust2wasm!($arg_type) ),* ) -> VMResult<($( rust2wasm!($returns)),*)> {
                     let data = CALLER_CONTEXT.with(|caller_context| {
                         unsafe {
@@ -76,22 +82,31 @@ macro_rules! wrapped_imports {
             )*
             }
 
-            pub(crate) fn build_wasmer(memory: wasmer_runtime::memory::Memory, logic: &mut VMLogic<'_>) ->
-                wasmer_runtime::ImportObject {
+            pub(crate) fn build_wasmer(
+                memory: wasmer_runtime::memory::Memory,
+                logic: &mut VMLogic<'_>,
+                protocol_version: ProtocolVersion,
+            ) -> wasmer_runtime::ImportObject {
                 let raw_ptr = logic as *mut _ as *mut c_void;
                 let import_reference = ImportReference(raw_ptr);
-                wasmer_runtime::imports! {
-                    move || {
-                        let dtor = (|_: *mut c_void| {}) as fn(*mut c_void);
-                        (import_reference.0, dtor)
-                    },
-                    "env" => {
-                        "memory" => memory,
-                        $(
-                            stringify!($func) => wasmer_runtime::func!(wasmer_ext::$func),
-                        )*
-                    },
-                }
+                let mut import_object = wasmer_runtime::ImportObject::new_with_data(move || {
+                    let dtor = (|_: *mut c_void| {}) as fn(*mut c_void);
+                    (import_reference.0, dtor)
+                });
+                let mut namespace = wasmer_runtime_core::import::Namespace::new();
+                namespace.insert("memory", memory);
+                // Unused when all the protocol features are disabled.
+                let _ = protocol_version;
+                $(
+                    $(#[cfg(feature = $feature_name)])*
+                    if true
+                        $( && near_primitives::checked_feature!($feature_name, $feature, protocol_version) )*
+                    {
+                        namespace.insert(stringify!($func), wasmer_runtime::func!(wasmer_ext::$func));
+                    }
+                )*
+                import_object.register("env", namespace);
+                import_object
             }
 
             #[cfg(feature = "wasmtime_vm")]
@@ -99,6 +114,7 @@ macro_rules! wrapped_imports {
                     linker: &mut wasmtime::Linker,
                     memory: wasmtime::Memory,
                     raw_logic: *mut c_void,
+                    protocol_version: ProtocolVersion,
              ) {
                 wasmtime_ext::CALLER_CONTEXT.with(|caller_context| {
                     unsafe {
@@ -107,9 +123,16 @@ macro_rules! wrapped_imports {
                 });
                 linker.define("env", "memory", memory).
                     expect("cannot define memory");
+                // Unused when all the protocol features are disabled.
+                let _ = protocol_version;
                 $(
-                   linker.func("env", stringify!($func), wasmtime_ext::$func).
-                    expect("cannot link external");
+                    $(#[cfg(feature = $feature_name)])*
+                    if true
+                        $( && near_primitives::checked_feature!($feature_name, $feature, protocol_version) )*
+                    {
+                        linker.func("env", stringify!($func), wasmtime_ext::$func).
+                            expect("cannot link external");
+                    }
                   )*
             }
 
@@ -157,6 +180,28 @@ wrapped_imports! {
     sha256<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     keccak256<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
     keccak512<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_signature_verification", SignatureVerification] ed25519_verify<[
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64
+    ] -> [u64]>,
+    #["protocol_feature_signature_verification", SignatureVerification] ecrecover<[
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64
+    ] -> [u64]>,
+    #["protocol_feature_math_extension", MathExtension] ripemd160<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_math_extension", MathExtension] blake2b<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_math_extension", MathExtension] alt_bn128_g1_add<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_math_extension", MathExtension] alt_bn128_g1_mul<[value_len: u64, value_ptr: u64, register_id: u64] -> []>,
+    #["protocol_feature_math_extension", MathExtension] alt_bn128_pairing_check<[value_len: u64, value_ptr: u64] -> [u64]>,
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -231,6 +276,19 @@ wrapped_imports! {
         method_names_len: u64,
         method_names_ptr: u64
     ] -> []>,
+    #["protocol_feature_function_call_permission_v2", FunctionCallPermissionV2] promise_batch_action_add_key_with_function_call_v2<[
+        promise_index: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+        nonce: u64,
+        allowance_ptr: u64,
+        receiver_id_len: u64,
+        receiver_id_ptr: u64,
+        method_names_len: u64,
+        method_names_ptr: u64,
+        max_deposit_per_call_ptr: u64,
+        expires_at_height: u64
+    ] -> []>,
     promise_batch_action_delete_key<[
         promise_index: u64,
         public_key_len: u64,
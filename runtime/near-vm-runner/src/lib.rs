@@ -7,8 +7,10 @@ mod runner;
 mod wasmer_runner;
 #[cfg(feature = "wasmtime_vm")]
 mod wasmtime_runner;
+pub use cache::CompiledContractCache;
 pub use near_vm_errors::VMError;
 pub use runner::compile_module;
+pub use runner::precompile_contract;
 pub use runner::run;
 pub use runner::run_vm;
 pub use runner::run_vm_profiled;
@@ -1,3 +1,4 @@
+use crate::cache::CompiledContractCache;
 use near_runtime_fees::RuntimeFeesConfig;
 use near_vm_errors::VMError;
 use near_vm_logic::types::{ProfileData, PromiseResult, ProtocolVersion};
@@ -25,6 +26,7 @@ pub fn run<'a>(
     fees_config: &'a RuntimeFeesConfig,
     promise_results: &'a [PromiseResult],
     current_protocol_version: ProtocolVersion,
+    cache: Option<&'a dyn CompiledContractCache>,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     run_vm(
         code_hash,
@@ -37,6 +39,7 @@ pub fn run<'a>(
         promise_results,
         VMKind::default(),
         current_protocol_version,
+        cache,
     )
 }
 pub fn run_vm<'a>(
@@ -50,6 +53,7 @@ pub fn run_vm<'a>(
     promise_results: &'a [PromiseResult],
     vm_kind: VMKind,
     current_protocol_version: ProtocolVersion,
+    cache: Option<&'a dyn CompiledContractCache>,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     use crate::wasmer_runner::run_wasmer;
     #[cfg(feature = "wasmtime_vm")]
@@ -66,6 +70,7 @@ pub fn run_vm<'a>(
             promise_results,
             None,
             current_protocol_version,
+            cache,
         ),
         #[cfg(feature = "wasmtime_vm")]
         VMKind::Wasmtime => run_wasmtime(
@@ -79,6 +84,7 @@ pub fn run_vm<'a>(
             promise_results,
             None,
             current_protocol_version,
+            cache,
         ),
         #[cfg(not(feature = "wasmtime_vm"))]
         VMKind::Wasmtime => {
@@ -99,6 +105,7 @@ pub fn run_vm_profiled<'a>(
     vm_kind: VMKind,
     profile: ProfileData,
     current_protocol_version: ProtocolVersion,
+    cache: Option<&'a dyn CompiledContractCache>,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     use crate::wasmer_runner::run_wasmer;
     #[cfg(feature = "wasmtime_vm")]
@@ -115,6 +122,7 @@ pub fn run_vm_profiled<'a>(
             promise_results,
             Some(profile),
             current_protocol_version,
+            cache,
         ),
         #[cfg(feature = "wasmtime_vm")]
         VMKind::Wasmtime => run_wasmtime(
@@ -128,6 +136,7 @@ pub fn run_vm_profiled<'a>(
             promise_results,
             Some(profile),
             current_protocol_version,
+            cache,
         ),
         #[cfg(not(feature = "wasmtime_vm"))]
         VMKind::Wasmtime => {
@@ -136,6 +145,31 @@ pub fn run_vm_profiled<'a>(
     }
 }
 
+/// Compiles the contract and stores the result in the persistent `cache`, so that the first
+/// call to the contract does not have to compile it. Used to prewarm the cache on deploy.
+pub fn precompile_contract(
+    code_hash: Vec<u8>,
+    code: &[u8],
+    wasm_config: &VMConfig,
+    vm_kind: VMKind,
+    cache: &dyn CompiledContractCache,
+) -> Result<(), VMError> {
+    match vm_kind {
+        VMKind::Wasmer => {
+            crate::cache::compile_module(code_hash, code, wasm_config, Some(cache)).map(|_| ())
+        }
+        #[cfg(feature = "wasmtime_vm")]
+        VMKind::Wasmtime => {
+            crate::cache::compile_wasmtime_module(code_hash, code, wasm_config, Some(cache))
+                .map(|_| ())
+        }
+        #[cfg(not(feature = "wasmtime_vm"))]
+        VMKind::Wasmtime => {
+            panic!("Wasmtime is not supported, compile with '--features wasmtime_vm'")
+        }
+    }
+}
+
 pub fn with_vm_variants(runner: fn(VMKind) -> ()) {
     runner(VMKind::Wasmer);
     #[cfg(feature = "wasmtime_vm")]
@@ -1,3 +1,4 @@
+use crate::cache::CompiledContractCache;
 use crate::errors::IntoVMError;
 use crate::memory::WasmerMemory;
 use crate::{cache, imports};
@@ -184,6 +185,7 @@ pub fn run_wasmer<'a>(
     promise_results: &'a [PromiseResult],
     profile: Option<ProfileData>,
     current_protocol_version: ProtocolVersion,
+    cache: Option<&'a dyn CompiledContractCache>,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     if !cfg!(target_arch = "x86") && !cfg!(target_arch = "x86_64") {
         // TODO(#1940): Remove once NaN is standardized by the VM.
@@ -204,7 +206,7 @@ pub fn run_wasmer<'a>(
         );
     }
 
-    let module = match cache::compile_module(code_hash, code, wasm_config) {
+    let module = match cache::compile_module(code_hash, code, wasm_config, cache) {
         Ok(x) => x,
         Err(err) => return (None, Some(err)),
     };
@@ -236,7 +238,7 @@ pub fn run_wasmer<'a>(
         );
     }
 
-    let import_object = imports::build_wasmer(memory_copy, &mut logic);
+    let import_object = imports::build_wasmer(memory_copy, &mut logic, current_protocol_version);
 
     let method_name = match std::str::from_utf8(method_name) {
         Ok(x) => x,
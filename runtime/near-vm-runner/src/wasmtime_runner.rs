@@ -3,8 +3,9 @@ use wasmtime::Module;
 // mod only to apply feature to it. Is it possible to avoid it?
 #[cfg(feature = "wasmtime_vm")]
 pub mod wasmtime_runner {
+    use crate::cache::{compile_wasmtime_module, CompiledContractCache};
     use crate::errors::IntoVMError;
-    use crate::{imports, prepare};
+    use crate::imports;
     use near_runtime_fees::RuntimeFeesConfig;
     use near_vm_errors::FunctionCallError::{LinkError, WasmUnknownError};
     use near_vm_errors::{FunctionCallError, MethodResolveError, VMError, VMLogicError};
@@ -13,7 +14,7 @@ pub mod wasmtime_runner {
     use std::ffi::c_void;
     use std::str;
     use wasmtime::ExternType::Func;
-    use wasmtime::{Config, Engine, Limits, Linker, Memory, MemoryType, Module, Store};
+    use wasmtime::{Config, Engine, Limits, Linker, Memory, MemoryType, Store};
 
     pub struct WasmtimeMemory(Memory);
 
@@ -105,8 +106,14 @@ pub mod wasmtime_runner {
         }
     }
 
+    lazy_static::lazy_static! {
+        /// Compiled modules are bound to the engine, so it is shared by all the calls for the
+        /// modules to be reused from the cache.
+        pub(crate) static ref ENGINE: Engine = get_engine(&mut Config::default());
+    }
+
     pub fn run_wasmtime<'a>(
-        _code_hash: Vec<u8>,
+        code_hash: Vec<u8>,
         code: &[u8],
         method_name: &[u8],
         ext: &mut dyn External,
@@ -116,21 +123,19 @@ pub mod wasmtime_runner {
         promise_results: &'a [PromiseResult],
         profile: Option<ProfileData>,
         current_protocol_version: ProtocolVersion,
+        cache: Option<&'a dyn CompiledContractCache>,
     ) -> (Option<VMOutcome>, Option<VMError>) {
-        let mut config = Config::default();
-        let engine = get_engine(&mut config);
-        let store = Store::new(&engine);
+        let store = Store::new(&ENGINE);
         let mut memory = WasmtimeMemory::new(
             &store,
             wasm_config.limit_config.initial_memory_pages,
             wasm_config.limit_config.max_memory_pages,
         )
         .unwrap();
-        let prepared_code = match prepare::prepare_contract(code, wasm_config) {
-            Ok(code) => code,
-            Err(err) => return (None, Some(VMError::from(err))),
+        let module = match compile_wasmtime_module(code_hash, code, wasm_config, cache) {
+            Ok(module) => module,
+            Err(err) => return (None, Some(err)),
         };
-        let module = Module::new(&engine, prepared_code).unwrap();
         // Note that we don't clone the actual backing memory, just increase the RC.
         let memory_copy = memory.clone();
         let mut linker = Linker::new(&store);
@@ -155,7 +160,7 @@ pub mod wasmtime_runner {
         // Unfortunately, due to the Wasmtime implementation we have to do tricks with the
         // lifetimes of the logic instance and pass raw pointers here.
         let raw_logic = &mut logic as *mut _ as *mut c_void;
-        imports::link_wasmtime(&mut linker, memory_copy, raw_logic);
+        imports::link_wasmtime(&mut linker, memory_copy, raw_logic, current_protocol_version);
         let func_name = match str::from_utf8(method_name) {
             Ok(name) => name,
             Err(_) => {
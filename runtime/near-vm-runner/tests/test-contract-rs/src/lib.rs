@@ -35,6 +35,16 @@ extern "C" {
     // ############
     fn random_seed(register_id: u64);
     fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "nightly_protocol_features")]
+    fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "nightly_protocol_features")]
+    fn blake2b(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "nightly_protocol_features")]
+    fn alt_bn128_g1_add(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "nightly_protocol_features")]
+    fn alt_bn128_g1_mul(value_len: u64, value_ptr: u64, register_id: u64);
+    #[cfg(feature = "nightly_protocol_features")]
+    fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -210,6 +220,43 @@ pub unsafe fn ext_sha256() {
     value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
 }
 
+// Calls a host function on the input and returns the content of the register it writes to.
+#[cfg(feature = "nightly_protocol_features")]
+macro_rules! ext_test_input {
+    ($export_func:ident, $call_ext:expr) => {
+        #[no_mangle]
+        pub unsafe fn $export_func() {
+            input(0);
+            let bytes = vec![0; register_len(0) as usize];
+            read_register(0, bytes.as_ptr() as *const u64 as u64);
+            $call_ext(bytes.len() as u64, bytes.as_ptr() as *const u64 as u64, 0);
+            let result = vec![0; register_len(0) as usize];
+            read_register(0, result.as_ptr() as *const u64 as u64);
+            value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
+        }
+    };
+}
+
+#[cfg(feature = "nightly_protocol_features")]
+ext_test_input!(ext_ripemd160, ripemd160);
+#[cfg(feature = "nightly_protocol_features")]
+ext_test_input!(ext_blake2b, blake2b);
+#[cfg(feature = "nightly_protocol_features")]
+ext_test_input!(ext_alt_bn128_g1_add, alt_bn128_g1_add);
+#[cfg(feature = "nightly_protocol_features")]
+ext_test_input!(ext_alt_bn128_g1_mul, alt_bn128_g1_mul);
+
+#[cfg(feature = "nightly_protocol_features")]
+#[no_mangle]
+pub unsafe fn ext_alt_bn128_pairing_check() {
+    input(0);
+    let bytes = vec![0; register_len(0) as usize];
+    read_register(0, bytes.as_ptr() as *const u64 as u64);
+    let result = alt_bn128_pairing_check(bytes.len() as u64, bytes.as_ptr() as *const u64 as u64)
+        .to_le_bytes();
+    value_return(result.len() as u64, result.as_ptr() as *const u64 as u64);
+}
+
 #[no_mangle]
 pub unsafe fn ext_validator_stake() {
     input(0);
@@ -525,3 +525,43 @@ fn test_external_call_error() {
         );
     });
 }
+
+#[cfg(feature = "protocol_feature_signature_verification")]
+fn ed25519_verify_import_contract() -> Vec<u8> {
+    wabt::wat2wasm(
+        r#"
+            (module
+              (type (;0;) (func (param i64 i64 i64 i64 i64 i64) (result i64)))
+              (type (;1;) (func))
+              (import "env" "ed25519_verify" (func (;0;) (type 0)))
+              (func (;1;) (type 1))
+              (export "hello" (func 1))
+            )"#,
+    )
+    .unwrap()
+}
+
+#[cfg(feature = "protocol_feature_signature_verification")]
+#[test]
+fn test_signature_verification_import_protocol_version() {
+    use near_primitives::version::{ProtocolFeature, PROTOCOL_FEATURES_TO_VERSION_MAPPING};
+    let feature_version =
+        PROTOCOL_FEATURES_TO_VERSION_MAPPING[&ProtocolFeature::SignatureVerification];
+    with_vm_variants(|vm_kind: VMKind| {
+        // `ed25519_verify` can't be linked before the feature is enabled.
+        let (_, err) = test_utils::make_simple_contract_call_with_protocol_version_vm(
+            &ed25519_verify_import_contract(),
+            b"hello",
+            10u64.pow(14),
+            feature_version - 1,
+            vm_kind,
+        );
+        match err {
+            Some(VMError::FunctionCallError(FunctionCallError::LinkError { .. })) => {}
+            err => panic!("Expected a link error, got {:?}", err),
+        }
+        let (_, err) =
+            make_simple_contract_call_vm(&ed25519_verify_import_contract(), b"hello", vm_kind);
+        assert_eq!(err, None);
+    });
+}
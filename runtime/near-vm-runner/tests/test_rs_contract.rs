@@ -14,6 +14,10 @@ use self::test_utils::{
 };
 
 const TEST_CONTRACT: &'static [u8] = include_bytes!("../tests/res/test_contract_rs.wasm");
+/// The test contract built with the `nightly_protocol_features` feature.
+#[cfg(feature = "nightly_protocol_features")]
+const NIGHTLY_TEST_CONTRACT: &'static [u8] =
+    include_bytes!("../tests/res/nightly_test_contract_rs.wasm");
 
 fn assert_run_result((outcome, err): (Option<VMOutcome>, Option<VMError>), expected_value: u64) {
     if let Some(_) = err {
@@ -68,6 +72,7 @@ pub fn test_read_write() {
             &promise_results,
             vm_kind.clone(),
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, 0);
 
@@ -83,6 +88,7 @@ pub fn test_read_write() {
             &promise_results,
             vm_kind,
             LATEST_PROTOCOL_VERSION,
+            None,
         );
         assert_run_result(result, 20);
     });
@@ -122,7 +128,17 @@ fn run_test_ext(
     validators: Vec<(&str, Balance)>,
     vm_kind: VMKind,
 ) {
-    let code = &TEST_CONTRACT;
+    run_test_ext_with_code(&TEST_CONTRACT, method, expected, input, validators, vm_kind)
+}
+
+fn run_test_ext_with_code(
+    code: &[u8],
+    method: &[u8],
+    expected: &[u8],
+    input: &[u8],
+    validators: Vec<(&str, Balance)>,
+    vm_kind: VMKind,
+) {
     let mut fake_external = MockedExternal::new();
     fake_external.validators = validators.into_iter().map(|(s, b)| (s.to_string(), b)).collect();
     let config = VMConfig::default();
@@ -140,6 +156,7 @@ fn run_test_ext(
         &[],
         vm_kind,
         LATEST_PROTOCOL_VERSION,
+        None,
     );
 
     if let Some(_) = err {
@@ -240,6 +257,101 @@ pub fn test_out_of_memory() {
         &promise_results,
         VMKind::Wasmer,
         LATEST_PROTOCOL_VERSION,
+        None,
     );
     assert_eq!(result.1, Some(VMError::FunctionCallError(FunctionCallError::WasmUnknownError)));
 }
+
+/// Tests of the host functions that are only available with the nightly protocol features.
+#[cfg(feature = "nightly_protocol_features")]
+mod nightly {
+    use super::*;
+
+    macro_rules! def_nightly_test_ext {
+        ($name:ident, $method:expr, $expected:expr, $input:expr) => {
+            #[test]
+            pub fn $name() {
+                with_vm_variants(|vm_kind: VMKind| {
+                    run_test_ext_with_code(
+                        &NIGHTLY_TEST_CONTRACT,
+                        $method,
+                        $expected,
+                        $input,
+                        vec![],
+                        vm_kind,
+                    )
+                });
+            }
+        };
+    }
+
+    def_nightly_test_ext!(
+        ext_ripemd160,
+        b"ext_ripemd160",
+        &[21, 102, 156, 115, 232, 3, 58, 215, 35, 84, 129, 30, 143, 86, 212, 104, 70, 97, 14, 225],
+        b"tesdsst"
+    );
+    def_nightly_test_ext!(
+        ext_blake2b,
+        b"ext_blake2b",
+        &[
+            144, 12, 165, 192, 98, 246, 37, 228, 134, 61, 43, 212, 111, 32, 204, 204, 186, 212, 47,
+            44, 209, 53, 167, 80, 195, 200, 226, 84, 34, 162, 249, 135, 172, 3, 90, 122, 205, 96,
+            211, 100, 188, 18, 134, 125, 111, 130, 31, 143, 25, 108, 194, 209, 205, 73, 169, 10,
+            132, 222, 75, 219, 103, 234, 67, 180,
+        ],
+        b"tesdsst"
+    );
+
+    // Encodings of the alt_bn128 G1 generator, its doubling and tripling.
+    const ALT_BN128_G1: [u8; 64] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+        0, 0, 0, 2,
+    ];
+    const ALT_BN128_2G1: [u8; 64] = [
+        3, 6, 68, 231, 46, 19, 26, 2, 155, 133, 4, 91, 104, 24, 21, 133, 217, 120, 22, 169, 22,
+        135, 28, 168, 211, 194, 8, 193, 109, 135, 207, 211, 21, 237, 115, 140, 14, 10, 124, 146,
+        231, 132, 95, 150, 178, 174, 156, 10, 104, 166, 164, 73, 227, 83, 143, 199, 255, 62, 191,
+        122, 90, 24, 162, 196,
+    ];
+    const ALT_BN128_3G1: [u8; 64] = [
+        7, 105, 191, 154, 197, 107, 234, 63, 244, 2, 50, 188, 177, 182, 189, 21, 147, 21, 216, 71,
+        21, 184, 230, 121, 242, 211, 85, 150, 25, 21, 171, 240, 42, 183, 153, 190, 224, 72, 148,
+        41, 85, 79, 219, 124, 141, 8, 100, 117, 49, 158, 99, 180, 11, 156, 91, 87, 205, 241, 255,
+        61, 217, 254, 34, 97,
+    ];
+
+    fn alt_bn128_g1_add_input() -> Vec<u8> {
+        let mut input = ALT_BN128_G1.to_vec();
+        input.extend_from_slice(&ALT_BN128_2G1);
+        input
+    }
+
+    fn alt_bn128_g1_mul_input() -> Vec<u8> {
+        let mut input = ALT_BN128_G1.to_vec();
+        input.extend_from_slice(&[0u8; 31]);
+        input.push(3);
+        input
+    }
+
+    def_nightly_test_ext!(
+        ext_alt_bn128_g1_add,
+        b"ext_alt_bn128_g1_add",
+        &ALT_BN128_3G1,
+        &alt_bn128_g1_add_input()
+    );
+    def_nightly_test_ext!(
+        ext_alt_bn128_g1_mul,
+        b"ext_alt_bn128_g1_mul",
+        &ALT_BN128_3G1,
+        &alt_bn128_g1_mul_input()
+    );
+    // Pairing check of no pairs is trivially satisfied.
+    def_nightly_test_ext!(
+        ext_alt_bn128_pairing_check,
+        b"ext_alt_bn128_pairing_check",
+        &1u64.to_le_bytes(),
+        &[]
+    );
+}
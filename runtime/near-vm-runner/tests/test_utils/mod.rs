@@ -42,6 +42,22 @@ pub fn make_simple_contract_call_with_gas_vm(
     method_name: &[u8],
     prepaid_gas: u64,
     vm_kind: VMKind,
+) -> (Option<VMOutcome>, Option<VMError>) {
+    make_simple_contract_call_with_protocol_version_vm(
+        code,
+        method_name,
+        prepaid_gas,
+        LATEST_PROTOCOL_VERSION,
+        vm_kind,
+    )
+}
+
+pub fn make_simple_contract_call_with_protocol_version_vm(
+    code: &[u8],
+    method_name: &[u8],
+    prepaid_gas: u64,
+    protocol_version: ProtocolVersion,
+    vm_kind: VMKind,
 ) -> (Option<VMOutcome>, Option<VMError>) {
     let mut fake_external = MockedExternal::new();
     let mut context = create_context(vec![]);
@@ -64,7 +80,8 @@ pub fn make_simple_contract_call_with_gas_vm(
         &fees,
         &promise_results,
         vm_kind,
-        LATEST_PROTOCOL_VERSION,
+        protocol_version,
+        None,
     )
 }
 
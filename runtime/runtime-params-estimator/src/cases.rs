@@ -123,6 +123,17 @@ pub enum Metric {
     keccak256_10kib_10k,
     keccak512_10b_10k,
     keccak512_10kib_10k,
+    ed25519_verify_32b_500,
+    ed25519_verify_16kib_64,
+    ecrecover_10k,
+    ripemd160_10b_10k,
+    ripemd160_10kib_10k,
+    blake2b_10b_10k,
+    blake2b_10kib_10k,
+    alt_bn128_g1_add_1k,
+    alt_bn128_g1_mul_1k,
+    alt_bn128_pairing_check_1pair_10,
+    alt_bn128_pairing_check_10pairs_10,
     storage_write_10b_key_10b_value_1k,
     storage_write_10kib_key_10b_value_1k,
     storage_write_10b_key_10kib_value_1k,
@@ -477,6 +488,17 @@ pub fn run(mut config: Config, only_compile: bool) -> RuntimeConfig {
     keccak256_10kib_10k => keccak256_10kib_10k,
     keccak512_10b_10k => keccak512_10b_10k,
     keccak512_10kib_10k => keccak512_10kib_10k,
+    ed25519_verify_32b_500 => ed25519_verify_32b_500,
+    ed25519_verify_16kib_64 => ed25519_verify_16kib_64,
+    ecrecover_10k => ecrecover_10k,
+    ripemd160_10b_10k => ripemd160_10b_10k,
+    ripemd160_10kib_10k => ripemd160_10kib_10k,
+    blake2b_10b_10k => blake2b_10b_10k,
+    blake2b_10kib_10k => blake2b_10kib_10k,
+    alt_bn128_g1_add_1k => alt_bn128_g1_add_1k,
+    alt_bn128_g1_mul_1k => alt_bn128_g1_mul_1k,
+    alt_bn128_pairing_check_1pair_10 => alt_bn128_pairing_check_1pair_10,
+    alt_bn128_pairing_check_10pairs_10 => alt_bn128_pairing_check_10pairs_10,
     storage_write_10b_key_10b_value_1k => storage_write_10b_key_10b_value_1k,
     storage_read_10b_key_10b_value_1k => storage_read_10b_key_10b_value_1k,
     storage_has_key_10b_key_10b_value_1k => storage_has_key_10b_key_10b_value_1k,
@@ -606,6 +628,8 @@ fn get_runtime_fees_config(
             },
             delete_key_cost: measured_to_fee(metric, measured[&ActionDeleteKey]),
             delete_account_cost: measured_to_fee(metric, measured[&ActionDeleteAccount]),
+            // Not measured yet, the inner actions are charged separately.
+            delegate_cost: RuntimeFeesConfig::default().action_creation_config.delegate_cost,
         },
         ..Default::default()
     }
@@ -639,6 +663,25 @@ fn get_ext_costs_config(measurement: &Measurements, config: &Config) -> ExtCosts
         keccak256_byte: measured_to_gas(metric, &measured, keccak256_byte),
         keccak512_base: measured_to_gas(metric, &measured, keccak512_base),
         keccak512_byte: measured_to_gas(metric, &measured, keccak512_byte),
+        ed25519_verify_base: measured_to_gas(metric, &measured, ed25519_verify_base),
+        ed25519_verify_byte: measured_to_gas(metric, &measured, ed25519_verify_byte),
+        ecrecover_base: measured_to_gas(metric, &measured, ecrecover_base),
+        ripemd160_base: measured_to_gas(metric, &measured, ripemd160_base),
+        ripemd160_byte: measured_to_gas(metric, &measured, ripemd160_byte),
+        blake2b_base: measured_to_gas(metric, &measured, blake2b_base),
+        blake2b_byte: measured_to_gas(metric, &measured, blake2b_byte),
+        alt_bn128_g1_add_base: measured_to_gas(metric, &measured, alt_bn128_g1_add_base),
+        alt_bn128_g1_mul_base: measured_to_gas(metric, &measured, alt_bn128_g1_mul_base),
+        alt_bn128_pairing_check_base: measured_to_gas(
+            metric,
+            &measured,
+            alt_bn128_pairing_check_base,
+        ),
+        alt_bn128_pairing_check_element: measured_to_gas(
+            metric,
+            &measured,
+            alt_bn128_pairing_check_element,
+        ),
         log_base: measured_to_gas(metric, &measured, log_base),
         log_byte: measured_to_gas(metric, &measured, log_byte),
         storage_write_base: measured_to_gas(metric, &measured, storage_write_base),
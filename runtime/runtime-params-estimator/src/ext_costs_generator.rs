@@ -65,6 +65,22 @@ impl ExtCostsGenerator {
         self.extract(keccak512_10b_10k, keccak512_base);
         self.extract(keccak512_10kib_10k, keccak512_byte);
 
+        self.extract(ed25519_verify_32b_500, ed25519_verify_base);
+        self.extract(ed25519_verify_16kib_64, ed25519_verify_byte);
+
+        self.extract(ecrecover_10k, ecrecover_base);
+
+        self.extract(ripemd160_10b_10k, ripemd160_base);
+        self.extract(ripemd160_10kib_10k, ripemd160_byte);
+
+        self.extract(blake2b_10b_10k, blake2b_base);
+        self.extract(blake2b_10kib_10k, blake2b_byte);
+
+        self.extract(alt_bn128_g1_add_1k, alt_bn128_g1_add_base);
+        self.extract(alt_bn128_g1_mul_1k, alt_bn128_g1_mul_base);
+        self.extract(alt_bn128_pairing_check_1pair_10, alt_bn128_pairing_check_base);
+        self.extract(alt_bn128_pairing_check_10pairs_10, alt_bn128_pairing_check_element);
+
         // TODO: Redo storage costs once we have counting of nodes and we have size peek.
         self.extract(storage_write_10b_key_10b_value_1k, storage_write_base);
         self.extract(storage_write_10kib_key_10b_value_1k, storage_write_key_byte);
@@ -78,6 +78,7 @@ impl RuntimeTestbed {
         let apply_state = ApplyState {
             // Put each runtime into a separate shard.
             block_index: 0,
+            chunk_height_created: 0,
             // Epoch length is long enough to avoid corner cases.
             last_block_hash: Default::default(),
             epoch_id: Default::default(),
@@ -88,6 +89,9 @@ impl RuntimeTestbed {
             random_seed: Default::default(),
             current_protocol_version: PROTOCOL_VERSION,
             config: Arc::new(runtime_config),
+            cache: None,
+            profile_gas: false,
+            states_to_patch: None,
         };
         Self {
             workdir,
@@ -122,6 +126,7 @@ impl RuntimeTestbed {
         self.root = root;
         store_update.commit().unwrap();
         self.apply_state.block_index += 1;
+        self.apply_state.chunk_height_created += 1;
 
         let mut total_burnt_gas = 0;
         if !allow_failures {
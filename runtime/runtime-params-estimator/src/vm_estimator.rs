@@ -64,6 +64,7 @@ fn call() -> (Option<VMOutcome>, Option<VMError>) {
         &fees,
         &promise_results,
         PROTOCOL_VERSION,
+        None,
     )
 }
 
@@ -44,6 +44,28 @@ extern "C" {
     fn sha256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak256(value_len: u64, value_ptr: u64, register_id: u64);
     fn keccak512(value_len: u64, value_ptr: u64, register_id: u64);
+    fn ripemd160(value_len: u64, value_ptr: u64, register_id: u64);
+    fn blake2b(value_len: u64, value_ptr: u64, register_id: u64);
+    fn alt_bn128_g1_add(value_len: u64, value_ptr: u64, register_id: u64);
+    fn alt_bn128_g1_mul(value_len: u64, value_ptr: u64, register_id: u64);
+    fn alt_bn128_pairing_check(value_len: u64, value_ptr: u64) -> u64;
+    fn ed25519_verify(
+        signature_len: u64,
+        signature_ptr: u64,
+        message_len: u64,
+        message_ptr: u64,
+        public_key_len: u64,
+        public_key_ptr: u64,
+    ) -> u64;
+    fn ecrecover(
+        hash_len: u64,
+        hash_ptr: u64,
+        signature_len: u64,
+        signature_ptr: u64,
+        v: u64,
+        malleability_flag: u64,
+        register_id: u64,
+    ) -> u64;
     // #####################
     // # Miscellaneous API #
     // #####################
@@ -439,6 +461,229 @@ pub unsafe fn keccak512_10kib_10k() {
     }
 }
 
+// Function to measure `ripemd160_base` and `ripemd160_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `ripemd160` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute ripemd160 on 10b 10k times.
+#[no_mangle]
+pub unsafe fn ripemd160_10b_10k() {
+    let buffer = [65u8; 10];
+    for _ in 0..10_000 {
+        ripemd160(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Function to measure `ripemd160_base` and `ripemd160_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `ripemd160` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute ripemd160 on 10kib 10k times.
+#[no_mangle]
+pub unsafe fn ripemd160_10kib_10k() {
+    let buffer = [65u8; 10240];
+    for _ in 0..10_000 {
+        ripemd160(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+
+// Function to measure `blake2b_base` and `blake2b_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `blake2b` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute blake2b on 10b 10k times.
+#[no_mangle]
+pub unsafe fn blake2b_10b_10k() {
+    let buffer = [65u8; 10];
+    for _ in 0..10_000 {
+        blake2b(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+// Function to measure `blake2b_base` and `blake2b_byte`. Also measures `base`, `write_register_base`,
+// and `write_register_byte`. However `blake2b` computation is more expensive than register writing
+// so we are okay overcharging it.
+// Compute blake2b on 10kib 10k times.
+#[no_mangle]
+pub unsafe fn blake2b_10kib_10k() {
+    let buffer = [65u8; 10240];
+    for _ in 0..10_000 {
+        blake2b(buffer.len() as u64, buffer.as_ptr() as *const u64 as u64, 0);
+    }
+}
+
+// The alt_bn128 G1 generator followed by its doubling.
+const ALT_BN128_G1_ADD_INPUT: [u8; 128] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
+    3, 6, 68, 231, 46, 19, 26, 2, 155, 133, 4, 91, 104, 24, 21, 133, 217, 120, 22, 169, 22, 135,
+    28, 168, 211, 194, 8, 193, 109, 135, 207, 211, 21, 237, 115, 140, 14, 10, 124, 146, 231, 132,
+    95, 150, 178, 174, 156, 10, 104, 166, 164, 73, 227, 83, 143, 199, 255, 62, 191, 122, 90, 24,
+    162, 196,
+];
+
+// Function to measure `alt_bn128_g1_add_base`. Also measures `read_memory_base`, `read_memory_byte`,
+// `write_register_base` and `write_register_byte`, but the addition is more expensive.
+// Add two G1 points 1k times.
+#[no_mangle]
+pub unsafe fn alt_bn128_g1_add_1k() {
+    for _ in 0..1_000 {
+        alt_bn128_g1_add(
+            ALT_BN128_G1_ADD_INPUT.len() as u64,
+            ALT_BN128_G1_ADD_INPUT.as_ptr() as *const u64 as u64,
+            0,
+        );
+    }
+}
+
+// Function to measure `alt_bn128_g1_mul_base`. Also measures `read_memory_base`, `read_memory_byte`,
+// `write_register_base` and `write_register_byte`, but the multiplication is much more expensive.
+// Multiply the G1 generator by a 254 bit scalar 1k times.
+#[no_mangle]
+pub unsafe fn alt_bn128_g1_mul_1k() {
+    let mut input = [255u8; 96];
+    input[..64].copy_from_slice(&ALT_BN128_G1_ADD_INPUT[..64]);
+    // Keep the scalar below the group order.
+    input[64] = 0x20;
+    for _ in 0..1_000 {
+        alt_bn128_g1_mul(input.len() as u64, input.as_ptr() as *const u64 as u64, 0);
+    }
+}
+
+// Two (G1, G2) pairs whose pairings cancel each other: e(P, Q) * e(-P, Q) = 1.
+const ALT_BN128_PAIRING_INPUT: [u8; 384] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1,
+    0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2,
+    25, 142, 147, 147, 146, 13, 72, 58, 114, 96, 191, 183, 49, 251, 93, 37, 241, 170, 73, 51, 53,
+    169, 231, 18, 151, 228, 133, 183, 174, 243, 18, 194, 24, 0, 222, 239, 18, 31, 30, 118, 66, 106,
+    0, 102, 94, 92, 68, 121, 103, 67, 34, 212, 247, 94, 218, 221, 70, 222, 189, 92, 217, 146, 246,
+    237, 9, 6, 137, 208, 88, 95, 240, 117, 236, 158, 153, 173, 105, 12, 51, 149, 188, 75, 49, 51,
+    112, 179, 142, 243, 85, 172, 218, 220, 209, 34, 151, 91, 18, 200, 94, 165, 219, 140, 109, 235,
+    74, 171, 113, 128, 141, 203, 64, 143, 227, 209, 231, 105, 12, 67, 211, 123, 76, 230, 204, 1,
+    102, 250, 125, 170, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0, 0, 0, 1, 48, 100, 78, 114, 225, 49, 160, 41, 184, 80, 69, 182, 129, 129, 88, 93,
+    151, 129, 106, 145, 104, 113, 202, 141, 60, 32, 140, 22, 216, 124, 253, 69, 25, 142, 147, 147,
+    146, 13, 72, 58, 114, 96, 191, 183, 49, 251, 93, 37, 241, 170, 73, 51, 53, 169, 231, 18, 151,
+    228, 133, 183, 174, 243, 18, 194, 24, 0, 222, 239, 18, 31, 30, 118, 66, 106, 0, 102, 94, 92,
+    68, 121, 103, 67, 34, 212, 247, 94, 218, 221, 70, 222, 189, 92, 217, 146, 246, 237, 9, 6, 137,
+    208, 88, 95, 240, 117, 236, 158, 153, 173, 105, 12, 51, 149, 188, 75, 49, 51, 112, 179, 142,
+    243, 85, 172, 218, 220, 209, 34, 151, 91, 18, 200, 94, 165, 219, 140, 109, 235, 74, 171, 113,
+    128, 141, 203, 64, 143, 227, 209, 231, 105, 12, 67, 211, 123, 76, 230, 204, 1, 102, 250, 125,
+    170,
+];
+
+// Function to measure `alt_bn128_pairing_check_base`. Also measures `alt_bn128_pairing_check_element`,
+// but only for a single pair.
+// Check the pairing of one pair 10 times.
+#[no_mangle]
+pub unsafe fn alt_bn128_pairing_check_1pair_10() {
+    for _ in 0..10 {
+        alt_bn128_pairing_check(192, ALT_BN128_PAIRING_INPUT.as_ptr() as *const u64 as u64);
+    }
+}
+
+// Function to measure `alt_bn128_pairing_check_element`. Also measures `alt_bn128_pairing_check_base`.
+// Check the pairings of 10 pairs 10 times.
+#[no_mangle]
+pub unsafe fn alt_bn128_pairing_check_10pairs_10() {
+    let mut input = [0u8; 1920];
+    for chunk in input.chunks_mut(ALT_BN128_PAIRING_INPUT.len()) {
+        chunk.copy_from_slice(&ALT_BN128_PAIRING_INPUT);
+    }
+    for _ in 0..10 {
+        let result =
+            alt_bn128_pairing_check(input.len() as u64, input.as_ptr() as *const u64 as u64);
+        if result != 1 {
+            ::core::intrinsics::abort();
+        }
+    }
+}
+
+// Public key of the ed25519 signatures below.
+const ED25519_PUBLIC_KEY: [u8; 32] = [
+    3, 161, 7, 191, 243, 206, 16, 190, 29, 112, 221, 24, 231, 75, 192, 153, 103, 228, 214, 48, 155,
+    165, 13, 95, 29, 220, 134, 100, 18, 85, 49, 184,
+];
+
+// Function to measure `ed25519_verify_base`. Also measures `read_memory_base` and
+// `read_memory_byte`, but verification is much more expensive than reading memory.
+// Verify the signature of a 32b message 500 times.
+#[no_mangle]
+pub unsafe fn ed25519_verify_32b_500() {
+    let message = [65u8; 32];
+    let signature: [u8; 64] = [
+        144, 169, 190, 25, 216, 170, 51, 247, 99, 143, 53, 232, 240, 122, 191, 26, 78, 67, 43, 237,
+        89, 215, 145, 21, 59, 240, 59, 167, 178, 84, 214, 225, 148, 136, 3, 203, 183, 51, 50, 16,
+        191, 198, 119, 196, 231, 117, 26, 66, 96, 212, 202, 218, 129, 30, 16, 220, 142, 190, 168,
+        204, 55, 175, 188, 1,
+    ];
+    for _ in 0..500 {
+        let result = ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            message.len() as u64,
+            message.as_ptr() as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as u64,
+        );
+        if result != 1 {
+            ::core::intrinsics::abort();
+        }
+    }
+}
+
+// Function to measure `ed25519_verify_byte`. Also measures `ed25519_verify_base`.
+// Verify the signature of a 16kib message 64 times.
+#[no_mangle]
+pub unsafe fn ed25519_verify_16kib_64() {
+    let message = [65u8; 16384];
+    let signature: [u8; 64] = [
+        17, 205, 249, 200, 113, 147, 244, 236, 164, 216, 217, 199, 17, 136, 151, 195, 97, 250, 178,
+        32, 204, 239, 116, 183, 72, 47, 219, 70, 46, 34, 160, 235, 222, 221, 204, 19, 2, 28, 149,
+        131, 172, 254, 218, 125, 245, 238, 98, 12, 182, 79, 130, 34, 137, 175, 126, 130, 26, 27,
+        36, 203, 41, 18, 99, 4,
+    ];
+    for _ in 0..64 {
+        let result = ed25519_verify(
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            message.len() as u64,
+            message.as_ptr() as u64,
+            ED25519_PUBLIC_KEY.len() as u64,
+            ED25519_PUBLIC_KEY.as_ptr() as u64,
+        );
+        if result != 1 {
+            ::core::intrinsics::abort();
+        }
+    }
+}
+
+// Function to measure `ecrecover_base`. Also measures `write_register_base` and
+// `write_register_byte`, but the recovery is much more expensive than writing a register.
+// Recover the public key 10k times.
+#[no_mangle]
+pub unsafe fn ecrecover_10k() {
+    let hash: [u8; 32] = [
+        34, 164, 128, 81, 89, 76, 25, 73, 222, 237, 112, 64, 133, 12, 31, 15, 135, 100, 83, 127,
+        81, 145, 190, 86, 115, 45, 22, 165, 76, 29, 129, 83,
+    ];
+    let signature: [u8; 64] = [
+        200, 2, 31, 218, 8, 50, 210, 214, 207, 117, 164, 217, 169, 30, 80, 248, 70, 16, 170, 157,
+        37, 203, 91, 158, 87, 181, 58, 173, 202, 236, 154, 162, 83, 157, 135, 191, 108, 45, 242,
+        104, 97, 188, 251, 219, 124, 207, 191, 196, 136, 142, 166, 96, 226, 143, 1, 21, 117, 33,
+        170, 136, 182, 59, 72, 109,
+    ];
+    for _ in 0..10_000 {
+        let result = ecrecover(
+            hash.len() as u64,
+            hash.as_ptr() as u64,
+            signature.len() as u64,
+            signature.as_ptr() as u64,
+            1,
+            1,
+            0,
+        );
+        if result != 1 {
+            ::core::intrinsics::abort();
+        }
+    }
+}
+
 // ###############
 // # Storage API #
 // ###############
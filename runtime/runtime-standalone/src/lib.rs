@@ -4,26 +4,60 @@ use std::sync::Arc;
 use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
 use near_pool::{types::PoolIterator, TransactionPool};
 use near_primitives::account::{AccessKey, Account};
-use near_primitives::errors::RuntimeError;
+use near_primitives::errors::{RuntimeError, TxExecutionError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::Receipt;
 use near_primitives::state_record::StateRecord;
 use near_primitives::test_utils::account_new;
 use near_primitives::test_utils::MockEpochInfoProvider;
 use near_primitives::transaction::{ExecutionOutcome, ExecutionStatus, SignedTransaction};
+use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
     AccountId, AccountInfo, Balance, BlockHeight, EpochHeight, EpochId, EpochInfoProvider, Gas,
-    StateChangeCause,
+    RawStateChangesWithTrieKey, StateChangeCause, StateChangeWithCause, StateChanges,
+    StateChangesExt,
 };
 use near_primitives::version::PROTOCOL_VERSION;
+use near_primitives::views::{
+    ExecutionOutcomeWithIdView, ExecutionStatusView, FinalExecutionOutcomeView,
+    FinalExecutionStatus,
+};
 use near_runtime_configs::RuntimeConfig;
 use near_store::{
     get_access_key, get_account, set_account, test_utils::create_test_store, ShardTries, Store,
+    StoreUpdate, TrieChanges,
 };
 use node_runtime::{state_viewer::TrieViewer, ApplyState, Runtime};
 
 const DEFAULT_EPOCH_LENGTH: u64 = 3;
 
+/// Error returned when a transaction can't be executed to completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExecutionError {
+    /// Applying a block failed.
+    Runtime(RuntimeError),
+    /// Nothing is left to execute, but the outcome with the given hash was never produced, e.g.
+    /// because the transaction or receipt was dropped or the hash is unknown.
+    LostOutcome(CryptoHash),
+}
+
+impl From<RuntimeError> for ExecutionError {
+    fn from(error: RuntimeError) -> Self {
+        ExecutionError::Runtime(error)
+    }
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::Runtime(err) => write!(f, "Runtime error: {:?}", err),
+            ExecutionError::LostOutcome(hash) => write!(f, "Lost an outcome for {}", hash),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
 pub fn init_runtime_and_signer(root_account_id: &AccountId) -> (RuntimeStandalone, InMemorySigner) {
     let mut genesis = GenesisConfig::default();
     let signer = genesis.init_root_signer(root_account_id);
@@ -113,17 +147,30 @@ impl Block {
     }
 }
 
+/// State of a `RuntimeStandalone` taken with `snapshot`. The trie nodes are shared with the
+/// runtime, so a snapshot only holds the state root.
+#[derive(Clone)]
+pub struct StateSnapshot {
+    cur_block: Block,
+    pending_receipts: Vec<Receipt>,
+    transactions: HashMap<CryptoHash, SignedTransaction>,
+    outcomes: HashMap<CryptoHash, ExecutionOutcome>,
+    state_changes: HashMap<CryptoHash, StateChanges>,
+}
+
 pub struct RuntimeStandalone {
     genesis: GenesisConfig,
     runtime_config: Arc<RuntimeConfig>,
     tx_pool: TransactionPool,
     transactions: HashMap<CryptoHash, SignedTransaction>,
     outcomes: HashMap<CryptoHash, ExecutionOutcome>,
+    state_changes: HashMap<CryptoHash, StateChanges>,
     cur_block: Block,
     runtime: Runtime,
     tries: ShardTries,
     pending_receipts: Vec<Receipt>,
     epoch_info_provider: Box<dyn EpochInfoProvider>,
+    profile_gas: bool,
 }
 
 impl RuntimeStandalone {
@@ -151,12 +198,14 @@ impl RuntimeStandalone {
             runtime,
             transactions: HashMap::new(),
             outcomes: HashMap::new(),
+            state_changes: HashMap::new(),
             cur_block: genesis_block,
             tx_pool: TransactionPool::new(),
             pending_receipts: vec![],
             epoch_info_provider: Box::new(MockEpochInfoProvider::new(
                 validators.into_iter().map(|info| (info.account_id, info.amount)),
             )),
+            profile_gas: false,
         }
     }
 
@@ -189,6 +238,32 @@ impl RuntimeStandalone {
         }
     }
 
+    /// Processes blocks until the transaction and all the receipts it spawned, refunds
+    /// included, are executed. Returns their outcomes the same way `tx_status` does.
+    pub fn execute_tx(
+        &mut self,
+        tx: SignedTransaction,
+    ) -> Result<FinalExecutionOutcomeView, ExecutionError> {
+        let tx_hash = self.send_tx(tx);
+        loop {
+            self.produce_block()?;
+            if let Some(outcome) = self.final_outcome(&tx_hash) {
+                let executed_all = outcome
+                    .receipts_outcome
+                    .iter()
+                    .chain(std::iter::once(&outcome.transaction_outcome))
+                    .flat_map(|outcome| outcome.outcome.receipt_ids.iter())
+                    .all(|receipt_id| self.outcomes.contains_key(receipt_id));
+                if executed_all {
+                    return Ok(outcome);
+                }
+            }
+            if self.pending_receipts.is_empty() && self.tx_pool.len() == 0 {
+                return Err(ExecutionError::LostOutcome(tx_hash));
+            }
+        }
+    }
+
     /// Just puts tx into the transaction pool
     pub fn send_tx(&mut self, tx: SignedTransaction) -> CryptoHash {
         let tx_hash = tx.get_hash();
@@ -197,10 +272,115 @@ impl RuntimeStandalone {
         tx_hash
     }
 
+    /// Records a breakdown of the gas burnt by every receipt in its outcome (see `outcome`).
+    pub fn set_profile_gas(&mut self, profile_gas: bool) {
+        self.profile_gas = profile_gas;
+    }
+
     pub fn outcome(&self, hash: &CryptoHash) -> Option<ExecutionOutcome> {
         self.outcomes.get(hash).cloned()
     }
 
+    /// Outcome of the transaction and of the receipts it spawned that are executed so far, in the
+    /// shape of `tx_status`. The status is `Started` until the receipt that produces the result
+    /// is executed. Standalone blocks have no hashes, so outcomes have a default block hash and
+    /// no proofs. Returns None until the transaction itself is executed.
+    pub fn final_outcome(&self, tx_hash: &CryptoHash) -> Option<FinalExecutionOutcomeView> {
+        let transaction = self.transactions.get(tx_hash)?.clone();
+        let transaction_outcome = self.outcome_view(tx_hash)?;
+        let mut receipts_outcome = vec![];
+        self.collect_receipt_outcomes(
+            &transaction_outcome.outcome.receipt_ids,
+            &mut receipts_outcome,
+        );
+
+        let mut outcome = &transaction_outcome.outcome;
+        let status = loop {
+            match &outcome.status {
+                ExecutionStatusView::SuccessReceiptId(receipt_id) => {
+                    match receipts_outcome.iter().find(|outcome| outcome.id == *receipt_id) {
+                        Some(receipt_outcome) => outcome = &receipt_outcome.outcome,
+                        None => break FinalExecutionStatus::Started,
+                    }
+                }
+                ExecutionStatusView::SuccessValue(value) => {
+                    break FinalExecutionStatus::SuccessValue(value.clone())
+                }
+                ExecutionStatusView::Failure(err) => {
+                    break FinalExecutionStatus::Failure(err.clone())
+                }
+                ExecutionStatusView::Unknown => break FinalExecutionStatus::Started,
+            }
+        };
+        Some(FinalExecutionOutcomeView {
+            status,
+            transaction: transaction.into(),
+            transaction_outcome,
+            receipts_outcome,
+        })
+    }
+
+    /// Changes to accounts, access keys, contract code and data made by the given transaction or
+    /// receipt, including the ones made before its execution such as gas purchase.
+    pub fn state_changes(&self, hash: &CryptoHash) -> &[StateChangeWithCause] {
+        self.state_changes.get(hash).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn outcome_view(&self, hash: &CryptoHash) -> Option<ExecutionOutcomeWithIdView> {
+        self.outcomes.get(hash).map(|outcome| ExecutionOutcomeWithIdView {
+            proof: vec![],
+            block_hash: CryptoHash::default(),
+            id: *hash,
+            outcome: outcome.clone().into(),
+        })
+    }
+
+    /// Appends the outcomes of the receipts and of the receipts they spawned, depth first.
+    fn collect_receipt_outcomes(
+        &self,
+        receipt_ids: &[CryptoHash],
+        outcomes: &mut Vec<ExecutionOutcomeWithIdView>,
+    ) {
+        for receipt_id in receipt_ids {
+            if let Some(outcome) = self.outcome_view(receipt_id) {
+                let receipt_ids = outcome.outcome.receipt_ids.clone();
+                outcomes.push(outcome);
+                self.collect_receipt_outcomes(&receipt_ids, outcomes);
+            }
+        }
+    }
+
+    /// Files the changes of a trie key under the transactions and receipts that caused them.
+    fn record_state_changes(&mut self, raw_changes: RawStateChangesWithTrieKey) {
+        let state_changes = match raw_changes.trie_key {
+            TrieKey::Account { .. } => {
+                StateChanges::from_account_changes(std::iter::once(Ok(raw_changes)))
+            }
+            TrieKey::AccessKey { .. } => {
+                StateChanges::from_access_key_changes(std::iter::once(Ok(raw_changes)))
+            }
+            TrieKey::ContractCode { .. } => {
+                StateChanges::from_contract_code_changes(std::iter::once(Ok(raw_changes)))
+            }
+            TrieKey::ContractData { .. } => {
+                StateChanges::from_data_changes(std::iter::once(Ok(raw_changes)))
+            }
+            _ => return,
+        }
+        .expect("No io errors expected");
+        for state_change in state_changes {
+            let hash = match state_change.cause {
+                StateChangeCause::TransactionProcessing { tx_hash } => tx_hash,
+                StateChangeCause::ActionReceiptProcessingStarted { receipt_hash }
+                | StateChangeCause::ActionReceiptGasReward { receipt_hash }
+                | StateChangeCause::ReceiptProcessing { receipt_hash }
+                | StateChangeCause::PostponedReceipt { receipt_hash } => receipt_hash,
+                _ => continue,
+            };
+            self.state_changes.entry(hash).or_default().push(state_change);
+        }
+    }
+
     /// Processes all transactions and pending receipts until there is no pending_receipts left
     pub fn process_all(&mut self) -> Result<(), RuntimeError> {
         loop {
@@ -215,6 +395,7 @@ impl RuntimeStandalone {
     pub fn produce_block(&mut self) -> Result<(), RuntimeError> {
         let apply_state = ApplyState {
             block_index: self.cur_block.block_height,
+            chunk_height_created: self.cur_block.block_height,
             epoch_height: self.cur_block.epoch_height,
             gas_price: self.cur_block.gas_price,
             block_timestamp: self.cur_block.block_timestamp,
@@ -225,6 +406,9 @@ impl RuntimeStandalone {
             epoch_id: EpochId::default(),
             current_protocol_version: PROTOCOL_VERSION,
             config: self.runtime_config.clone(),
+            cache: None,
+            profile_gas: self.profile_gas,
+            states_to_patch: None,
         };
 
         let apply_result = self.runtime.apply(
@@ -240,9 +424,10 @@ impl RuntimeStandalone {
         apply_result.outcomes.iter().for_each(|outcome| {
             self.outcomes.insert(outcome.id, outcome.outcome.clone());
         });
-        let (update, _) =
-            self.tries.apply_all(&apply_result.trie_changes, 0).expect("Unexpected Storage error");
-        update.commit().expect("Unexpected io error");
+        for raw_changes in apply_result.state_changes {
+            self.record_state_changes(raw_changes);
+        }
+        self.apply_trie_changes(&apply_result.trie_changes);
         self.cur_block = self.cur_block.produce(apply_result.state_root, self.genesis.epoch_length);
 
         Ok(())
@@ -272,9 +457,48 @@ impl RuntimeStandalone {
         set_account(&mut trie_update, account_id, account);
         trie_update.commit(StateChangeCause::ValidatorAccountsUpdate);
         let (trie_changes, _) = trie_update.finalize().expect("Unexpected Storage error");
-        let (store_update, new_root) = self.tries.apply_all(&trie_changes, 0).unwrap();
-        store_update.commit().expect("No io errors expected");
-        self.cur_block.state_root = new_root;
+        self.cur_block.state_root = self.apply_trie_changes(&trie_changes);
+    }
+
+    /// Captures the state, the current block, the pending receipts and transactions and the
+    /// recorded outcomes, so that tests can share an expensive setup.
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            cur_block: self.cur_block.clone(),
+            pending_receipts: self.pending_receipts.clone(),
+            transactions: self.transactions.clone(),
+            outcomes: self.outcomes.clone(),
+            state_changes: self.state_changes.clone(),
+        }
+    }
+
+    /// Rolls the runtime back (or forward) to the given snapshot. Transactions that were sent
+    /// but not yet executed at the time of the snapshot go back to the transaction pool.
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        let StateSnapshot { cur_block, pending_receipts, transactions, outcomes, state_changes } =
+            snapshot;
+        self.tx_pool = TransactionPool::new();
+        for (tx_hash, tx) in transactions.iter() {
+            if !outcomes.contains_key(tx_hash) {
+                self.tx_pool.insert_transaction(tx.clone());
+            }
+        }
+        self.cur_block = cur_block;
+        self.pending_receipts = pending_receipts;
+        self.transactions = transactions;
+        self.outcomes = outcomes;
+        self.state_changes = state_changes;
+    }
+
+    /// Writes the nodes of the new state. Nodes of the old states are never removed, so that
+    /// every snapshot stays readable; the standalone store lives in memory for a single test.
+    fn apply_trie_changes(&self, trie_changes: &TrieChanges) -> CryptoHash {
+        let mut store_update = StoreUpdate::new_with_tries(self.tries.clone());
+        self.tries
+            .apply_insertions(trie_changes, 0, &mut store_update)
+            .expect("Unexpected Storage error");
+        store_update.commit().expect("Unexpected io error");
+        trie_changes.new_root
     }
 
     pub fn view_account(&self, account_id: &AccountId) -> Option<Account> {
@@ -342,8 +566,43 @@ impl RuntimeStandalone {
     }
 }
 
+/// Failures of the transaction and of all the receipts it spawned, with their ids.
+pub fn outcome_failures(
+    outcome: &FinalExecutionOutcomeView,
+) -> Vec<(CryptoHash, TxExecutionError)> {
+    std::iter::once(&outcome.transaction_outcome)
+        .chain(outcome.receipts_outcome.iter())
+        .filter_map(|outcome_with_id| match &outcome_with_id.outcome.status {
+            ExecutionStatusView::Failure(err) => Some((outcome_with_id.id, err.clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Panics if the transaction or any receipt it spawned failed.
+pub fn assert_no_failures(outcome: &FinalExecutionOutcomeView) {
+    let failures = outcome_failures(outcome);
+    assert!(failures.is_empty(), "Unexpected failures: {:?}", failures);
+}
+
+/// Panics unless the transaction or some receipt it spawned failed with an error matching
+/// `predicate`.
+pub fn assert_failure(
+    outcome: &FinalExecutionOutcomeView,
+    predicate: impl Fn(&TxExecutionError) -> bool,
+) {
+    let failures = outcome_failures(outcome);
+    assert!(
+        failures.iter().any(|(_, err)| predicate(err)),
+        "No matching failure among {:?}",
+        failures
+    );
+}
+
 #[cfg(test)]
 mod tests {
+    use near_primitives::types::StateChangeValue;
+
     use super::*;
 
     #[test]
@@ -464,6 +723,137 @@ mod tests {
         assert_eq!("\"caller status is ok!\"", caller_status);
     }
 
+    #[test]
+    fn test_execute_tx() {
+        let (mut runtime, signer) = init_runtime_and_signer(&"root".into());
+        for (nonce, account_id, code) in vec![
+            (
+                1,
+                "status",
+                include_bytes!("../contracts/status-message/res/status_message.wasm").as_ref(),
+            ),
+            (
+                2,
+                "caller",
+                include_bytes!(
+                    "../contracts/cross-contract-high-level/res/cross_contract_high_level.wasm"
+                )
+                .as_ref(),
+            ),
+        ] {
+            let outcome = runtime
+                .execute_tx(SignedTransaction::create_contract(
+                    nonce,
+                    signer.account_id.clone(),
+                    account_id.into(),
+                    code.into(),
+                    23082408900000000000001000,
+                    signer.public_key(),
+                    &signer,
+                    CryptoHash::default(),
+                ))
+                .unwrap();
+            assert_no_failures(&outcome);
+        }
+
+        let tx = SignedTransaction::call(
+            3,
+            signer.account_id.clone(),
+            "caller".into(),
+            &signer,
+            0,
+            "simple_call".into(),
+            "{\"account_id\": \"status\", \"message\": \"caller status is ok!\"}"
+                .as_bytes()
+                .to_vec(),
+            300_000_000_000_000,
+            CryptoHash::default(),
+        );
+        let tx_hash = tx.get_hash();
+        let outcome = runtime.execute_tx(tx).unwrap();
+        assert!(matches!(outcome.status, FinalExecutionStatus::SuccessValue(_)));
+        assert_no_failures(&outcome);
+        assert_eq!(outcome.transaction_outcome.id, tx_hash);
+        // The call to `caller`, the call to `status` it made and the refunds of both.
+        assert!(outcome.receipts_outcome.len() >= 3);
+        assert!(runtime.pending_receipts().is_empty());
+
+        assert!(runtime.state_changes(&tx_hash).iter().any(|change| matches!(
+            &change.value,
+            StateChangeValue::AccountUpdate { account_id, .. } if account_id == "root"
+        )));
+        let status_receipt = outcome
+            .receipts_outcome
+            .iter()
+            .find(|receipt| receipt.outcome.executor_id == "status")
+            .unwrap();
+        assert!(runtime.state_changes(&status_receipt.id).iter().any(|change| matches!(
+            &change.value,
+            StateChangeValue::DataUpdate { account_id, .. } if account_id == "status"
+        )));
+
+        let outcome = runtime
+            .execute_tx(SignedTransaction::call(
+                4,
+                signer.account_id.clone(),
+                "caller".into(),
+                &signer,
+                0,
+                "simple_call".into(),
+                "{\"account_id\": \"missing\", \"message\": \"hi\"}".as_bytes().to_vec(),
+                300_000_000_000_000,
+                CryptoHash::default(),
+            ))
+            .unwrap();
+        // The call to `caller` succeeds, the promise it made to a missing account fails.
+        assert!(matches!(outcome.status, FinalExecutionStatus::SuccessValue(_)));
+        assert_failure(&outcome, |err| matches!(err, TxExecutionError::ActionError(_)));
+    }
+
+    #[test]
+    fn test_gas_profile() {
+        let (mut runtime, signer) = init_runtime_and_signer(&"root".into());
+        let outcome = runtime
+            .resolve_tx(SignedTransaction::create_contract(
+                1,
+                signer.account_id.clone(),
+                "status".into(),
+                include_bytes!("../contracts/status-message/res/status_message.wasm")
+                    .as_ref()
+                    .into(),
+                23082408900000000000001000,
+                signer.public_key(),
+                &signer,
+                CryptoHash::default(),
+            ))
+            .unwrap();
+        assert_eq!(outcome.gas_profile, None);
+
+        runtime.set_profile_gas(true);
+        let outcome = runtime
+            .resolve_tx(SignedTransaction::call(
+                2,
+                signer.account_id.clone(),
+                "status".into(),
+                &signer,
+                0,
+                "set_status".into(),
+                "{\"message\": \"hello\"}".as_bytes().to_vec(),
+                300_000_000_000_000,
+                CryptoHash::default(),
+            ))
+            .unwrap();
+        let profile = outcome.gas_profile.unwrap();
+        assert!(profile.wasm_gas > 0);
+        assert!(profile.host_costs["storage_write_base"] > 0);
+        assert!(profile.action_costs["function_call"] > 0);
+        assert!(profile.action_costs["new_receipt"] > 0);
+        let total = profile.wasm_gas
+            + profile.host_costs.values().sum::<Gas>()
+            + profile.action_costs.values().sum::<Gas>();
+        assert_eq!(total, outcome.gas_burnt);
+    }
+
     #[test]
     fn test_force_update_account() {
         let (mut runtime, _) = init_runtime_and_signer(&"root".into());
@@ -472,4 +862,47 @@ mod tests {
         runtime.force_account_update("root".into(), &bob_account);
         assert_eq!(runtime.view_account(&"root".into()).unwrap().locked, 10000);
     }
+
+    #[test]
+    fn test_snapshot_restore() {
+        let (mut runtime, signer) = init_runtime_and_signer(&"root".into());
+        let create_alice = SignedTransaction::create_account(
+            1,
+            signer.account_id.clone(),
+            "alice".into(),
+            165437999999999999999000,
+            signer.public_key(),
+            &signer,
+            CryptoHash::default(),
+        );
+        let snapshot = runtime.snapshot();
+        let root_account = runtime.view_account(&"root".into()).unwrap();
+
+        let outcome = runtime.execute_tx(create_alice.clone()).unwrap();
+        assert_no_failures(&outcome);
+        assert!(runtime.view_account(&"alice".into()).is_some());
+        let after_alice = runtime.snapshot();
+
+        runtime.restore(snapshot.clone());
+        assert_eq!(runtime.view_account(&"alice".into()), None);
+        assert_eq!(runtime.view_account(&"root".into()).unwrap(), root_account);
+        assert_eq!(runtime.current_block().block_height, 0);
+        assert!(runtime.final_outcome(&create_alice.get_hash()).is_none());
+
+        // The same transaction can be executed again from the restored state.
+        let outcome = runtime.execute_tx(create_alice.clone()).unwrap();
+        assert_no_failures(&outcome);
+        assert!(runtime.view_account(&"alice".into()).is_some());
+
+        // Transactions pending at the time of the snapshot are executed after restoring it.
+        runtime.restore(snapshot);
+        let tx_hash = runtime.send_tx(create_alice);
+        let pending = runtime.snapshot();
+        runtime.restore(after_alice);
+        assert!(runtime.view_account(&"alice".into()).is_some());
+        runtime.restore(pending);
+        runtime.process_all().unwrap();
+        assert!(runtime.outcome(&tx_hash).is_some());
+        assert!(runtime.view_account(&"alice".into()).is_some());
+    }
 }
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use borsh::{BorshDeserialize, BorshSerialize};
@@ -6,12 +8,12 @@ use log::debug;
 use near_primitives::account::{AccessKey, AccessKeyPermission, Account};
 use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
-use near_primitives::receipt::{ActionReceipt, Receipt};
+use near_primitives::receipt::{ActionReceipt, Receipt, ReceiptEnum};
 use near_primitives::transaction::{
     Action, AddKeyAction, DeleteAccountAction, DeleteKeyAction, DeployContractAction,
-    FunctionCallAction, StakeAction, TransferAction,
+    FunctionCallAction, GasProfile, SignedDelegateAction, StakeAction, TransferAction,
 };
-use near_primitives::types::{AccountId, EpochInfoProvider, ValidatorStake};
+use near_primitives::types::{AccountId, EpochInfoProvider, Gas, ValidatorStake};
 use near_primitives::utils::{
     create_random_seed, is_valid_account_id, is_valid_sub_account_id, is_valid_top_level_account_id,
 };
@@ -21,14 +23,20 @@ use near_store::{
     get_access_key, get_code, remove_access_key, remove_account, set_access_key, set_code,
     StorageError, TrieUpdate,
 };
-use near_vm_logic::types::PromiseResult;
-use near_vm_logic::VMContext;
+use near_vm_logic::types::{ProfileData, PromiseResult};
+use near_vm_logic::{ActionCosts, ExtCosts, VMContext, VMKind};
 
-use crate::config::{safe_add_gas, RuntimeConfig};
+use crate::cache::precompile_contract_in_background;
+use crate::config::{
+    delegate_exec_fees, delegate_send_fees, safe_add_gas, total_prepaid_gas, RuntimeConfig,
+};
 use crate::ext::RuntimeExt;
+use crate::verifier::check_access_key_permission;
 use crate::{ActionResult, ApplyState};
 use near_crypto::PublicKey;
-use near_primitives::errors::{ActionError, ActionErrorKind, ExternalError, RuntimeError};
+use near_primitives::errors::{
+    ActionError, ActionErrorKind, ExternalError, InvalidAccessKeyError, RuntimeError,
+};
 use near_primitives::version::{ProtocolVersion, IMPLICIT_ACCOUNT_CREATION_PROTOCOL_VERSION};
 use near_runtime_configs::AccountCreationConfig;
 use near_vm_errors::{CompilationError, FunctionCallError};
@@ -127,17 +135,39 @@ pub(crate) fn action_function_call(
         output_data_receivers,
     };
 
-    let (outcome, err) = near_vm_runner::run(
-        code.hash.as_ref().to_vec(),
-        &code.code,
-        function_call.method_name.as_bytes(),
-        &mut runtime_ext,
-        context,
-        &config.wasm_config,
-        &config.transaction_costs,
-        promise_results,
-        apply_state.current_protocol_version,
-    );
+    let profile: Option<ProfileData> = if apply_state.profile_gas {
+        Some(Rc::new(RefCell::new([0; ExtCosts::count() + ActionCosts::count()])))
+    } else {
+        None
+    };
+    let (outcome, err) = match &profile {
+        Some(profile) => near_vm_runner::run_vm_profiled(
+            code.hash.as_ref().to_vec(),
+            &code.code,
+            function_call.method_name.as_bytes(),
+            &mut runtime_ext,
+            context,
+            &config.wasm_config,
+            &config.transaction_costs,
+            promise_results,
+            VMKind::default(),
+            profile.clone(),
+            apply_state.current_protocol_version,
+            apply_state.cache.as_deref(),
+        ),
+        None => near_vm_runner::run(
+            code.hash.as_ref().to_vec(),
+            &code.code,
+            function_call.method_name.as_bytes(),
+            &mut runtime_ext,
+            context,
+            &config.wasm_config,
+            &config.transaction_costs,
+            promise_results,
+            apply_state.current_protocol_version,
+            apply_state.cache.as_deref(),
+        ),
+    };
     let execution_succeeded = match err {
         Some(VMError::FunctionCallError(err)) => {
             result.result = Err(ActionErrorKind::FunctionCallError(err).into());
@@ -165,6 +195,9 @@ pub(crate) fn action_function_call(
         // return a real `gas_used` instead of the `gas_burnt` into `ActionResult` even for
         // `FunctionCall`s error.
         result.gas_used = safe_add_gas(result.gas_used, outcome.used_gas)?;
+        if let Some(profile) = &profile {
+            add_vm_profile(&mut result.gas_profile, profile, outcome.burnt_gas);
+        }
         result.logs.extend(outcome.logs.into_iter());
         if execution_succeeded {
             account.amount = outcome.balance;
@@ -178,6 +211,46 @@ pub(crate) fn action_function_call(
     Ok(())
 }
 
+/// Adds the costs recorded by the VM to the gas profile. Whatever the VM burnt on top of the
+/// host functions and the actions it created was spent executing Wasm instructions.
+fn add_vm_profile(gas_profile: &mut GasProfile, profile: &ProfileData, burnt_gas: Gas) {
+    let mut profiled_gas: Gas = 0;
+    for (index, &gas) in profile.borrow().iter().enumerate() {
+        if gas == 0 {
+            continue;
+        }
+        profiled_gas = profiled_gas.saturating_add(gas);
+        if index < ExtCosts::count() {
+            gas_profile.add_host_cost(ExtCosts::name_of(index), gas);
+        } else {
+            gas_profile.add_action_cost(ActionCosts::name_of(index - ExtCosts::count()), gas);
+        }
+    }
+    // Host costs are recorded before the gas limit is checked, so the last one may exceed what
+    // was actually burnt.
+    gas_profile.wasm_gas += burnt_gas.saturating_sub(profiled_gas);
+}
+
+/// Name under which the fees of delegate actions are recorded in a `GasProfile`. Delegate actions
+/// can't be created by contracts, so they don't have an `ActionCosts` counterpart.
+const DELEGATE_ACTION_COST_NAME: &str = "delegate";
+
+/// Name under which the execution fee of the action is recorded in a `GasProfile`.
+pub(crate) fn action_cost_name(action: &Action) -> &'static str {
+    let cost = match action {
+        Action::Delegate(_) => return DELEGATE_ACTION_COST_NAME,
+        Action::CreateAccount(_) => ActionCosts::create_account,
+        Action::DeployContract(_) => ActionCosts::deploy_contract,
+        Action::FunctionCall(_) => ActionCosts::function_call,
+        Action::Transfer(_) => ActionCosts::transfer,
+        Action::Stake(_) => ActionCosts::stake,
+        Action::AddKey(_) => ActionCosts::add_key,
+        Action::DeleteKey(_) => ActionCosts::delete_key,
+        Action::DeleteAccount(_) => ActionCosts::delete_account,
+    };
+    ActionCosts::name_of(cost as usize)
+}
+
 pub(crate) fn action_stake(
     account: &mut Account,
     result: &mut ActionResult,
@@ -240,15 +313,16 @@ pub(crate) fn try_refund_allowance(
 ) -> Result<(), StorageError> {
     if let Some(mut access_key) = get_access_key(state_update, account_id, public_key)? {
         let mut updated = false;
-        if let AccessKeyPermission::FunctionCall(function_call_permission) =
-            &mut access_key.permission
-        {
-            if let Some(allowance) = function_call_permission.allowance.as_mut() {
-                let new_allowance = allowance.saturating_add(transfer.deposit);
-                if new_allowance > *allowance {
-                    *allowance = new_allowance;
-                    updated = true;
-                }
+        let allowance = match &mut access_key.permission {
+            AccessKeyPermission::FunctionCall(permission) => permission.allowance.as_mut(),
+            AccessKeyPermission::FunctionCallV2(permission) => permission.allowance.as_mut(),
+            AccessKeyPermission::FullAccess => None,
+        };
+        if let Some(allowance) = allowance {
+            let new_allowance = allowance.saturating_add(transfer.deposit);
+            if new_allowance > *allowance {
+                *allowance = new_allowance;
+                updated = true;
             }
         }
         if updated {
@@ -359,6 +433,7 @@ pub(crate) fn action_deploy_contract(
     account: &mut Account,
     account_id: &AccountId,
     deploy_contract: &DeployContractAction,
+    apply_state: &ApplyState,
 ) -> Result<(), StorageError> {
     let code = ContractCode::new(deploy_contract.code.clone(), None);
     let prev_code = get_code(state_update, account_id, Some(account.code_hash))?;
@@ -379,6 +454,13 @@ pub(crate) fn action_deploy_contract(
         })?;
     account.code_hash = code.get_hash();
     set_code(state_update, account_id.clone(), &code);
+    if let Some(cache) = &apply_state.cache {
+        precompile_contract_in_background(
+            Arc::new(code),
+            &apply_state.config.wasm_config,
+            cache.clone(),
+        );
+    }
     Ok(())
 }
 
@@ -479,6 +561,104 @@ pub(crate) fn action_add_key(
     Ok(())
 }
 
+/// Verifies the delegate action signed by `sender_id`, bumps the nonce of its access key and
+/// creates a receipt from `sender_id` to the `receiver_id` of the delegate action with the inner
+/// actions. The gas for the new receipt was prepaid by the relayer, who also receives the gas
+/// refunds, while deposit refunds go to `sender_id` as the predecessor of the new receipt.
+pub(crate) fn action_delegate(
+    state_update: &mut TrieUpdate,
+    apply_state: &ApplyState,
+    action_receipt: &ActionReceipt,
+    result: &mut ActionResult,
+    account_id: &AccountId,
+    signed_delegate_action: &SignedDelegateAction,
+) -> Result<(), RuntimeError> {
+    let delegate_action = &signed_delegate_action.delegate_action;
+    if !signed_delegate_action.verify() {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidSignature.into());
+        return Ok(());
+    }
+    if &delegate_action.sender_id != account_id {
+        result.result = Err(ActionErrorKind::DelegateActionSenderDoesNotMatchReceiver {
+            sender_id: delegate_action.sender_id.clone(),
+            receiver_id: account_id.clone(),
+        }
+        .into());
+        return Ok(());
+    }
+    if apply_state.block_index > delegate_action.max_block_height {
+        result.result = Err(ActionErrorKind::DelegateActionExpired {
+            max_block_height: delegate_action.max_block_height,
+            block_height: apply_state.block_index,
+        }
+        .into());
+        return Ok(());
+    }
+    let public_key = &delegate_action.public_key;
+    let mut access_key = match get_access_key(state_update, account_id, public_key)? {
+        Some(access_key) => access_key,
+        None => {
+            result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(
+                InvalidAccessKeyError::AccessKeyNotFound {
+                    account_id: account_id.clone(),
+                    public_key: public_key.clone(),
+                },
+            )
+            .into());
+            return Ok(());
+        }
+    };
+    if delegate_action.nonce <= access_key.nonce {
+        result.result = Err(ActionErrorKind::DelegateActionInvalidNonce {
+            delegate_nonce: delegate_action.nonce,
+            ak_nonce: access_key.nonce,
+        }
+        .into());
+        return Ok(());
+    }
+    if let Err(e) = check_access_key_permission(
+        &access_key,
+        account_id,
+        public_key,
+        &delegate_action.receiver_id,
+        &delegate_action.actions,
+        Some(apply_state.block_index),
+    ) {
+        result.result = Err(ActionErrorKind::DelegateActionAccessKeyError(e).into());
+        return Ok(());
+    }
+    access_key.nonce = delegate_action.nonce;
+    set_access_key(state_update, account_id.clone(), public_key.clone(), &access_key);
+
+    // The send fees of the new receipt are burnt now, the rest is prepaid to the new receipt.
+    let fees_config = &apply_state.config.transaction_costs;
+    let send_fees =
+        delegate_send_fees(fees_config, delegate_action, apply_state.current_protocol_version)?;
+    let prepaid_gas = safe_add_gas(
+        delegate_exec_fees(fees_config, delegate_action, apply_state.current_protocol_version)?,
+        total_prepaid_gas(&delegate_action.actions)?,
+    )?;
+    result.gas_burnt = safe_add_gas(result.gas_burnt, send_fees)?;
+    result.gas_used = safe_add_gas(result.gas_used, safe_add_gas(send_fees, prepaid_gas)?)?;
+    if apply_state.profile_gas {
+        result.gas_profile.add_action_cost(DELEGATE_ACTION_COST_NAME, send_fees);
+    }
+    result.new_receipts.push(Receipt {
+        predecessor_id: account_id.clone(),
+        receiver_id: delegate_action.receiver_id.clone(),
+        receipt_id: CryptoHash::default(),
+        receipt: ReceiptEnum::Action(ActionReceipt {
+            signer_id: action_receipt.signer_id.clone(),
+            signer_public_key: action_receipt.signer_public_key.clone(),
+            gas_price: action_receipt.gas_price,
+            output_data_receivers: vec![],
+            input_data_ids: vec![],
+            actions: delegate_action.actions.clone(),
+        }),
+    });
+    Ok(())
+}
+
 pub(crate) fn check_actor_permissions(
     action: &Action,
     account: &Option<Account>,
@@ -511,7 +691,10 @@ pub(crate) fn check_actor_permissions(
                 .into());
             }
         }
-        Action::CreateAccount(_) | Action::FunctionCall(_) | Action::Transfer(_) => (),
+        Action::CreateAccount(_)
+        | Action::FunctionCall(_)
+        | Action::Transfer(_)
+        | Action::Delegate(_) => (),
     };
     Ok(())
 }
@@ -580,7 +763,8 @@ pub(crate) fn check_account_existence(
         | Action::Stake(_)
         | Action::AddKey(_)
         | Action::DeleteKey(_)
-        | Action::DeleteAccount(_) => {
+        | Action::DeleteAccount(_)
+        | Action::Delegate(_) => {
             if account.is_none() {
                 return Err(ActionErrorKind::AccountDoesNotExist {
                     account_id: account_id.clone(),
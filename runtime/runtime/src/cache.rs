@@ -1,11 +1,18 @@
-use std::sync::Arc;
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
 
 #[cfg(not(feature = "no_cache"))]
 use cached::{cached_key, SizedCache};
 
 use near_primitives::contract::ContractCode;
 use near_primitives::hash::CryptoHash;
-use near_store::StorageError;
+use near_store::{DBCol, StorageError, Store};
+use near_vm_logic::{VMConfig, VMKind};
+use near_vm_runner::{precompile_contract, CompiledContractCache};
+
+/// Maximum number of deployed contracts waiting to be compiled in the background. Contracts
+/// deployed while the queue is full are compiled on their first call instead.
+const PRECOMPILE_QUEUE_SIZE: usize = 16;
 
 /// Cache size in number of cached modules to hold.
 #[cfg(not(feature = "no_cache"))]
@@ -39,3 +46,64 @@ pub(crate) fn get_code(
         Arc::new(code)
     }))
 }
+
+/// Persistent compiled contract cache backed by `ColCachedContractCode` of the node store.
+pub struct StoreCompiledContractCache {
+    pub store: Arc<Store>,
+}
+
+impl CompiledContractCache for StoreCompiledContractCache {
+    fn put(&self, key: &[u8], value: &[u8]) -> Result<(), std::io::Error> {
+        let mut store_update = self.store.store_update();
+        store_update.set(DBCol::ColCachedContractCode, key, value);
+        store_update.commit()
+    }
+
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, std::io::Error> {
+        self.store.get(DBCol::ColCachedContractCode, key)
+    }
+}
+
+struct PrecompileTask {
+    code: Arc<ContractCode>,
+    config: VMConfig,
+    cache: Arc<dyn CompiledContractCache>,
+}
+
+lazy_static::lazy_static! {
+    static ref PRECOMPILE_QUEUE: Mutex<SyncSender<PrecompileTask>> = {
+        let (sender, receiver) = sync_channel::<PrecompileTask>(PRECOMPILE_QUEUE_SIZE);
+        std::thread::Builder::new()
+            .name("precompile_contracts".to_string())
+            .spawn(move || {
+                for task in receiver {
+                    let _ = precompile_contract(
+                        task.code.get_hash().as_ref().to_vec(),
+                        &task.code.code,
+                        &task.config,
+                        VMKind::default(),
+                        task.cache.as_ref(),
+                    );
+                }
+            })
+            .expect("Failed to start the contract precompilation thread");
+        Mutex::new(sender)
+    };
+}
+
+/// Compiles the deployed contract on a background thread to prewarm the `cache`, so that the
+/// compilation neither slows down applying the chunk nor has to be charged. A contract that
+/// fails to compile is still deployed and the error is reported when it is called.
+pub(crate) fn precompile_contract_in_background(
+    code: Arc<ContractCode>,
+    config: &VMConfig,
+    cache: Arc<dyn CompiledContractCache>,
+) {
+    let task = PrecompileTask { code, config: config.clone(), cache };
+    match PRECOMPILE_QUEUE.lock().expect("Precompile queue lock is poisoned").try_send(task) {
+        Ok(()) | Err(TrySendError::Full(_)) => {}
+        Err(TrySendError::Disconnected(_)) => {
+            log::warn!(target: "runtime", "Contract precompilation thread is not running")
+        }
+    }
+}
@@ -1,8 +1,10 @@
 //! Settings of the parameters of the runtime.
-use near_primitives::account::AccessKeyPermission;
+use near_primitives::account::{
+    AccessKeyPermission, FunctionCallPermission, FunctionCallPermissionV2,
+};
 use near_primitives::errors::IntegerOverflowError;
 use near_primitives::transaction::{
-    Action, AddKeyAction, DeployContractAction, FunctionCallAction, Transaction,
+    Action, AddKeyAction, DelegateAction, DeployContractAction, FunctionCallAction, Transaction,
 };
 use near_primitives::types::{AccountId, Balance, Gas};
 use near_runtime_fees::RuntimeFeesConfig;
@@ -108,9 +110,14 @@ pub fn total_send_fees(
             }
             Stake(_) => cfg.stake_cost.send_fee(sender_is_receiver),
             AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-                AccessKeyPermission::FunctionCall(call_perm) => {
-                    let num_bytes = call_perm
-                        .method_names
+                AccessKeyPermission::FunctionCall(FunctionCallPermission {
+                    method_names, ..
+                })
+                | AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                    method_names,
+                    ..
+                }) => {
+                    let num_bytes = method_names
                         .iter()
                         // Account for null-terminating characters.
                         .map(|name| name.as_bytes().len() as u64 + 1)
@@ -128,6 +135,7 @@ pub fn total_send_fees(
             },
             DeleteKey(_) => cfg.delete_key_cost.send_fee(sender_is_receiver),
             DeleteAccount(_) => cfg.delete_account_cost.send_fee(sender_is_receiver),
+            Delegate(_) => cfg.delegate_cost.send_fee(sender_is_receiver),
         };
         result = safe_add_gas(result, delta)?;
     }
@@ -168,9 +176,11 @@ pub fn exec_fee(
         }
         Stake(_) => cfg.stake_cost.exec_fee(),
         AddKey(AddKeyAction { access_key, .. }) => match &access_key.permission {
-            AccessKeyPermission::FunctionCall(call_perm) => {
-                let num_bytes = call_perm
-                    .method_names
+            AccessKeyPermission::FunctionCall(FunctionCallPermission { method_names, .. })
+            | AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                method_names, ..
+            }) => {
+                let num_bytes = method_names
                     .iter()
                     // Account for null-terminating characters.
                     .map(|name| name.as_bytes().len() as u64 + 1)
@@ -182,8 +192,48 @@ pub fn exec_fee(
         },
         DeleteKey(_) => cfg.delete_key_cost.exec_fee(),
         DeleteAccount(_) => cfg.delete_account_cost.exec_fee(),
+        Delegate(_) => cfg.delegate_cost.exec_fee(),
     }
 }
+
+/// Total send fees of the receipt that a delegate action creates for its inner actions.
+/// They are burnt when the delegate action is executed.
+pub fn delegate_send_fees(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    let sender_is_receiver = delegate_action.sender_id == delegate_action.receiver_id;
+    safe_add_gas(
+        config.action_receipt_creation_config.send_fee(sender_is_receiver),
+        total_send_fees(
+            config,
+            sender_is_receiver,
+            &delegate_action.actions,
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )
+}
+
+/// Total execution fees of the receipt that a delegate action creates for its inner actions.
+/// They are prepaid to the new receipt when the delegate action is executed.
+pub fn delegate_exec_fees(
+    config: &RuntimeFeesConfig,
+    delegate_action: &DelegateAction,
+    current_protocol_version: ProtocolVersion,
+) -> Result<Gas, IntegerOverflowError> {
+    safe_add_gas(
+        config.action_receipt_creation_config.exec_fee(),
+        total_exec_fees(
+            config,
+            &delegate_action.actions,
+            &delegate_action.receiver_id,
+            current_protocol_version,
+        )?,
+    )
+}
+
 /// Returns transaction costs for a given transaction.
 pub fn tx_cost(
     config: &RuntimeFeesConfig,
@@ -206,7 +256,12 @@ pub fn tx_cost(
     let prepaid_gas = total_prepaid_gas(&transaction.actions)?;
     // If signer is equals to receiver the receipt will be processed at the same block as this
     // transaction. Otherwise it will processed in the next block and the gas might be inflated.
-    let initial_receipt_hop = if transaction.signer_id == transaction.receiver_id { 0 } else { 1 };
+    let mut initial_receipt_hop =
+        if transaction.signer_id == transaction.receiver_id { 0 } else { 1 };
+    // Inner actions of delegate actions are executed in a separate receipt one hop later.
+    if transaction.actions.iter().any(|action| matches!(action, Action::Delegate(_))) {
+        initial_receipt_hop += 1;
+    }
     let minimum_new_receipt_gas = config.min_receipt_with_function_call_gas();
     // In case the config is free, we don't care about the maximum depth.
     let receipt_gas_price = if gas_price == 0 {
@@ -250,23 +305,46 @@ pub fn total_exec_fees(
 ) -> Result<Gas, IntegerOverflowError> {
     let mut result = 0;
     for action in actions {
-        let delta = exec_fee(&config, action, receiver_id, current_protocol_version);
+        let mut delta = exec_fee(&config, action, receiver_id, current_protocol_version);
+        // The receipt of the inner actions of a delegate action is paid upfront as well.
+        if let Action::Delegate(signed_delegate_action) = action {
+            let delegate_action = &signed_delegate_action.delegate_action;
+            delta = safe_add_gas(
+                delta,
+                delegate_send_fees(config, delegate_action, current_protocol_version)?,
+            )?;
+            delta = safe_add_gas(
+                delta,
+                delegate_exec_fees(config, delegate_action, current_protocol_version)?,
+            )?;
+        }
         result = safe_add_gas(result, delta)?;
     }
     Ok(result)
 }
-/// Get the total sum of deposits for given actions.
+/// Get the total sum of deposits for given actions, including the inner actions of delegate actions.
 pub fn total_deposit(actions: &[Action]) -> Result<Balance, IntegerOverflowError> {
     let mut total_balance: Balance = 0;
     for action in actions {
-        total_balance = safe_add_balance(total_balance, action.get_deposit_balance())?;
+        let deposit = match action {
+            Action::Delegate(a) => total_deposit(&a.delegate_action.actions)?,
+            _ => action.get_deposit_balance(),
+        };
+        total_balance = safe_add_balance(total_balance, deposit)?;
     }
     Ok(total_balance)
 }
 
-/// Get the total sum of prepaid gas for given actions.
+/// Get the total sum of prepaid gas for given actions, including the inner actions of delegate
+/// actions.
 pub fn total_prepaid_gas(actions: &[Action]) -> Result<Gas, IntegerOverflowError> {
-    actions.iter().try_fold(0, |acc, action| safe_add_gas(acc, action.get_prepaid_gas()))
+    actions.iter().try_fold(0, |acc, action| {
+        let prepaid_gas = match action {
+            Action::Delegate(a) => total_prepaid_gas(&a.delegate_action.actions)?,
+            _ => action.get_prepaid_gas(),
+        };
+        safe_add_gas(acc, prepaid_gas)
+    })
 }
 
 #[cfg(test)]
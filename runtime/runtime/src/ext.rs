@@ -1,6 +1,8 @@
 use borsh::BorshDeserialize;
 use near_crypto::PublicKey;
-use near_primitives::account::{AccessKey, AccessKeyPermission, FunctionCallPermission};
+use near_primitives::account::{
+    AccessKey, AccessKeyPermission, FunctionCallPermission, FunctionCallPermissionV2,
+};
 use near_primitives::errors::{ExternalError, StorageError};
 use near_primitives::hash::CryptoHash;
 use near_primitives::receipt::{ActionReceipt, DataReceiver, Receipt, ReceiptEnum};
@@ -9,7 +11,7 @@ use near_primitives::transaction::{
     DeployContractAction, FunctionCallAction, StakeAction, TransferAction,
 };
 use near_primitives::trie_key::TrieKey;
-use near_primitives::types::{AccountId, Balance, EpochId, EpochInfoProvider};
+use near_primitives::types::{AccountId, Balance, BlockHeight, EpochId, EpochInfoProvider};
 use near_primitives::utils::create_data_id;
 use near_primitives::version::ProtocolVersion;
 use near_store::{TrieUpdate, TrieUpdateValuePtr};
@@ -276,6 +278,43 @@ impl<'a> External for RuntimeExt<'a> {
         Ok(())
     }
 
+    fn append_action_add_key_with_function_call_v2(
+        &mut self,
+        receipt_index: u64,
+        public_key: Vec<u8>,
+        nonce: u64,
+        allowance: Option<u128>,
+        receiver_id: AccountId,
+        method_names: Vec<Vec<u8>>,
+        max_deposit_per_call: Balance,
+        expires_at_height: Option<BlockHeight>,
+    ) -> ExtResult<()> {
+        self.append_action(
+            receipt_index,
+            Action::AddKey(AddKeyAction {
+                public_key: PublicKey::try_from_slice(&public_key)
+                    .map_err(|_| HostError::InvalidPublicKey)?,
+                access_key: AccessKey {
+                    nonce,
+                    permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                        allowance,
+                        receiver_id,
+                        method_names: method_names
+                            .into_iter()
+                            .map(|method_name| {
+                                String::from_utf8(method_name)
+                                    .map_err(|_| HostError::InvalidMethodName)
+                            })
+                            .collect::<std::result::Result<Vec<_>, _>>()?,
+                        max_deposit_per_call,
+                        expires_at_height,
+                    }),
+                },
+            }),
+        );
+        Ok(())
+    }
+
     fn append_action_delete_key(
         &mut self,
         receipt_index: u64,
@@ -14,7 +14,8 @@ use near_primitives::receipt::{
 };
 use near_primitives::state_record::StateRecord;
 use near_primitives::transaction::{
-    Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, LogEntry, SignedTransaction,
+    Action, ExecutionOutcome, ExecutionOutcomeWithId, ExecutionStatus, GasProfile, LogEntry,
+    SignedTransaction,
 };
 use near_primitives::trie_key::TrieKey;
 use near_primitives::types::{
@@ -32,7 +33,9 @@ use near_store::{
     PartialStorage, ShardTries, StorageError, StoreUpdate, Trie, TrieChanges, TrieUpdate,
 };
 use near_vm_logic::types::PromiseResult;
+use near_vm_logic::ActionCosts;
 use near_vm_logic::ReturnData;
+use near_vm_runner::CompiledContractCache;
 #[cfg(feature = "costs_counting")]
 pub use near_vm_runner::EXT_COSTS_COUNTER;
 
@@ -67,6 +70,10 @@ pub struct ApplyState {
     /// Currently building block height.
     // TODO #1903 pub block_height: BlockHeight,
     pub block_index: BlockHeight,
+    /// Height following the prev block, which is the height the applied chunk is created at.
+    /// Unlike `block_index` it doesn't depend on the skipped heights, so it is known to the
+    /// chunk producer and transactions are checked for access key expiration against it.
+    pub chunk_height_created: BlockHeight,
     /// Prev block hash
     pub last_block_hash: CryptoHash,
     /// Current epoch id
@@ -86,6 +93,12 @@ pub struct ApplyState {
     pub current_protocol_version: ProtocolVersion,
     /// The Runtime config to use for the current transition.
     pub config: Arc<RuntimeConfig>,
+    /// Persistent cache of compiled contracts, if any.
+    pub cache: Option<Arc<dyn CompiledContractCache>>,
+    /// Whether to record a `GasProfile` in the outcome of every applied receipt.
+    pub profile_gas: bool,
+    /// Records to write into the state after the chunk is applied, see `Runtime::apply_state_patch`.
+    pub states_to_patch: Option<Vec<StateRecord>>,
 }
 
 /// Contains information to update validators accounts at the first block of a new epoch.
@@ -145,6 +158,7 @@ pub struct ActionResult {
     pub logs: Vec<LogEntry>,
     pub new_receipts: Vec<Receipt>,
     pub validator_proposals: Vec<ValidatorStake>,
+    pub gas_profile: GasProfile,
 }
 
 impl ActionResult {
@@ -162,6 +176,7 @@ impl ActionResult {
             next_result.gas_burnt_for_function_call,
         )?;
         self.gas_used = safe_add_gas(self.gas_used, next_result.gas_used)?;
+        self.gas_profile.merge(next_result.gas_profile);
         self.result = next_result.result;
         self.logs.append(&mut next_result.logs);
         if let Ok(ReturnData::ReceiptIndex(ref mut receipt_index)) = self.result {
@@ -189,6 +204,7 @@ impl Default for ActionResult {
             logs: vec![],
             new_receipts: vec![],
             validator_proposals: vec![],
+            gas_profile: GasProfile::default(),
         }
     }
 }
@@ -237,6 +253,7 @@ impl Runtime {
             apply_state.gas_price,
             signed_transaction,
             true,
+            Some(apply_state.chunk_height_created),
             apply_state.current_protocol_version,
         ) {
             Ok(verification_result) => {
@@ -274,6 +291,7 @@ impl Runtime {
                         gas_burnt: verification_result.gas_burnt,
                         tokens_burnt: verification_result.burnt_amount,
                         executor_id: transaction.signer_id.clone(),
+                        gas_profile: None,
                     },
                 };
                 Ok((receipt, outcome))
@@ -310,6 +328,9 @@ impl Runtime {
         );
         result.gas_burnt += exec_fees;
         result.gas_used += exec_fees;
+        if apply_state.profile_gas {
+            result.gas_profile.add_action_cost(action_cost_name(action), exec_fees);
+        }
         let account_id = &receipt.receiver_id;
         let is_the_only_action = actions.len() == 1;
         let is_refund = receipt.predecessor_id == system_account();
@@ -350,6 +371,7 @@ impl Runtime {
                     account.as_mut().expect(EXPECT_ACCOUNT_EXISTS),
                     &account_id,
                     deploy_contract,
+                    apply_state,
                 )?;
             }
             Action::FunctionCall(function_call) => {
@@ -445,6 +467,17 @@ impl Runtime {
                     delete_account,
                 )?;
             }
+            Action::Delegate(signed_delegate_action) => {
+                near_metrics::inc_counter(&metrics::ACTION_DELEGATE_TOTAL);
+                action_delegate(
+                    state_update,
+                    apply_state,
+                    action_receipt,
+                    &mut result,
+                    account_id,
+                    signed_delegate_action,
+                )?;
+            }
         };
         Ok(result)
     }
@@ -500,6 +533,11 @@ impl Runtime {
             apply_state.config.transaction_costs.action_receipt_creation_config.exec_fee();
         result.gas_used = exec_fee;
         result.gas_burnt = exec_fee;
+        if apply_state.profile_gas {
+            result
+                .gas_profile
+                .add_action_cost(ActionCosts::name_of(ActionCosts::new_receipt as usize), exec_fee);
+        }
         // Executing actions one by one
         for (action_index, action) in action_receipt.actions.iter().enumerate() {
             let action_hash = create_action_hash(
@@ -563,6 +601,7 @@ impl Runtime {
         let gas_deficit_amount = if receipt.predecessor_id == system_account() {
             result.gas_burnt = 0;
             result.gas_used = 0;
+            result.gas_profile = GasProfile::default();
             // If the refund fails tokens are burned.
             if result.result.is_err() {
                 stats.other_burnt_amount = safe_add_balance(
@@ -723,6 +762,7 @@ impl Runtime {
                 gas_burnt: result.gas_burnt,
                 tokens_burnt,
                 executor_id: account_id.clone(),
+                gas_profile: if apply_state.profile_gas { Some(result.gas_profile) } else { None },
             },
         })
     }
@@ -1228,6 +1268,10 @@ impl Runtime {
 
         state_update.commit(StateChangeCause::UpdatedDelayedReceipts);
 
+        if let Some(states_to_patch) = &apply_state.states_to_patch {
+            Self::apply_state_patch(&mut state_update, states_to_patch)?;
+        }
+
         let (trie_changes, state_changes) = state_update.finalize()?;
 
         // Dedup proposals from the same account.
@@ -1255,6 +1299,43 @@ impl Runtime {
         })
     }
 
+    /// Writes the given records into the state as is, the same way genesis records are written,
+    /// except that the storage usage of the accounts is not recomputed. The code hash of an
+    /// existing account is updated to match its patched contract. Used by the sandbox to patch
+    /// the state of a running chain, so receipts can't be patched in.
+    fn apply_state_patch(
+        state_update: &mut TrieUpdate,
+        records: &[StateRecord],
+    ) -> Result<(), StorageError> {
+        for record in records {
+            match record.clone() {
+                StateRecord::Account { account_id, account } => {
+                    set_account(state_update, account_id, &account);
+                }
+                StateRecord::Data { account_id, data_key, value } => {
+                    state_update.set(TrieKey::ContractData { key: data_key, account_id }, value);
+                }
+                StateRecord::Contract { account_id, code } => {
+                    let code = ContractCode::new(code, None);
+                    if let Some(mut account) = get_account(state_update, &account_id)? {
+                        account.code_hash = code.get_hash();
+                        set_account(state_update, account_id.clone(), &account);
+                    }
+                    set_code(state_update, account_id, &code);
+                }
+                StateRecord::AccessKey { account_id, public_key, access_key } => {
+                    set_access_key(state_update, account_id, public_key, &access_key);
+                }
+                // The receipts depend on the rest of the state, the sandbox RPC rejects them.
+                StateRecord::ReceivedData { .. }
+                | StateRecord::PostponedReceipt(_)
+                | StateRecord::DelayedReceipt(_) => {}
+            }
+        }
+        state_update.commit(StateChangeCause::InitialState);
+        Ok(())
+    }
+
     // Adds the given receipt into the end of the delayed receipt queue in the state.
     fn delay_receipt(
         state_update: &mut TrieUpdate,
@@ -1442,13 +1523,18 @@ mod tests {
     use near_primitives::errors::ReceiptValidationError;
     use near_primitives::hash::hash;
     use near_primitives::test_utils::{account_new, MockEpochInfoProvider};
-    use near_primitives::transaction::{FunctionCallAction, TransferAction};
+    use near_primitives::transaction::{
+        DelegateAction, FunctionCallAction, SignedDelegateAction, TransferAction,
+    };
     use near_primitives::types::MerkleHash;
     use near_primitives::version::PROTOCOL_VERSION;
+    use near_store::get_access_key;
     use near_store::test_utils::create_tries;
     use std::sync::Arc;
     use testlib::runtime_utils::{alice_account, bob_account};
 
+    use crate::config::delegate_exec_fees;
+
     const GAS_PRICE: Balance = 5000;
 
     fn to_yocto(near: Balance) -> Balance {
@@ -1518,6 +1604,7 @@ mod tests {
 
         let apply_state = ApplyState {
             block_index: 0,
+            chunk_height_created: 0,
             last_block_hash: Default::default(),
             epoch_id: Default::default(),
             epoch_height: 0,
@@ -1527,6 +1614,9 @@ mod tests {
             random_seed: Default::default(),
             current_protocol_version: PROTOCOL_VERSION,
             config: Arc::new(RuntimeConfig::default()),
+            cache: None,
+            profile_gas: false,
+            states_to_patch: None,
         };
 
         (runtime, tries, root, apply_state, signer, MockEpochInfoProvider::default())
@@ -2209,4 +2299,238 @@ mod tests {
         // Burnt all the fees + all prepaid gas.
         assert_eq!(result.stats.tx_burnt_amount, total_receipt_cost);
     }
+
+    #[test]
+    fn test_apply_state_patch() {
+        let (runtime, tries, root, mut apply_state, _, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+        let code = vec![1, 2, 3];
+        apply_state.states_to_patch = Some(vec![
+            StateRecord::Account {
+                account_id: bob_account(),
+                account: account_new(to_yocto(10), CryptoHash::default()),
+            },
+            StateRecord::Contract { account_id: bob_account(), code: code.clone() },
+            StateRecord::Data {
+                account_id: bob_account(),
+                data_key: b"key".to_vec(),
+                value: b"value".to_vec(),
+            },
+        ]);
+
+        let result = runtime
+            .apply(
+                tries.get_trie_for_shard(0),
+                root,
+                &None,
+                &apply_state,
+                &[],
+                &[],
+                &epoch_info_provider,
+            )
+            .unwrap();
+        let (store_update, root) = tries.apply_all(&result.trie_changes, 0).unwrap();
+        store_update.commit().unwrap();
+
+        let state_update = tries.new_trie_update(0, root);
+        let account = get_account(&state_update, &bob_account()).unwrap().unwrap();
+        assert_eq!(account.amount, to_yocto(10));
+        assert_eq!(account.code_hash, ContractCode::new(code, None).get_hash());
+        assert_eq!(
+            state_update
+                .get(&TrieKey::ContractData { account_id: bob_account(), key: b"key".to_vec() })
+                .unwrap(),
+            Some(b"value".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_apply_delegate_action() {
+        let (runtime, tries, root, apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+
+        let inner_actions = vec![Action::Transfer(TransferAction { deposit: to_yocto(1) })];
+        let delegate_action = DelegateAction {
+            sender_id: alice_account(),
+            receiver_id: bob_account(),
+            actions: inner_actions.clone(),
+            nonce: 1,
+            max_block_height: 10,
+            public_key: signer.public_key(),
+        };
+        let signature = signer.sign(delegate_action.get_hash().as_ref());
+        let actions = vec![Action::Delegate(SignedDelegateAction { delegate_action, signature })];
+        let receipt_gas = safe_add_gas(
+            apply_state.config.transaction_costs.action_receipt_creation_config.exec_fee(),
+            total_exec_fees(
+                &apply_state.config.transaction_costs,
+                &actions,
+                &alice_account(),
+                PROTOCOL_VERSION,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let receipts = vec![Receipt {
+            predecessor_id: bob_account(),
+            receiver_id: alice_account(),
+            receipt_id: CryptoHash::default(),
+            receipt: ReceiptEnum::Action(ActionReceipt {
+                signer_id: bob_account(),
+                signer_public_key: PublicKey::empty(KeyType::ED25519),
+                gas_price: GAS_PRICE,
+                output_data_receivers: vec![],
+                input_data_ids: vec![],
+                actions,
+            }),
+        }];
+
+        let result = runtime
+            .apply(
+                tries.get_trie_for_shard(0),
+                root,
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &epoch_info_provider,
+            )
+            .unwrap();
+        assert!(matches!(result.outcomes[0].outcome.status, ExecutionStatus::SuccessValue(_)));
+        // The whole prepaid gas is either burnt or forwarded, so there is no refund.
+        assert_eq!(result.outgoing_receipts.len(), 1);
+        let inner_receipt = &result.outgoing_receipts[0];
+        assert_eq!(inner_receipt.predecessor_id, alice_account());
+        assert_eq!(inner_receipt.receiver_id, bob_account());
+        match &inner_receipt.receipt {
+            ReceiptEnum::Action(action_receipt) => {
+                assert_eq!(action_receipt.signer_id, bob_account());
+                assert_eq!(action_receipt.actions, inner_actions);
+            }
+            _ => unreachable!(),
+        }
+        // Only the execution fees of the inner receipt are forwarded to it.
+        let delegate_action = match &receipts[0].receipt {
+            ReceiptEnum::Action(action_receipt) => match &action_receipt.actions[0] {
+                Action::Delegate(a) => a.delegate_action.clone(),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+        let forwarded_gas = delegate_exec_fees(
+            &apply_state.config.transaction_costs,
+            &delegate_action,
+            PROTOCOL_VERSION,
+        )
+        .unwrap();
+        assert_eq!(result.outcomes[0].outcome.gas_burnt, receipt_gas - forwarded_gas);
+
+        let (store_update, root) = tries.apply_all(&result.trie_changes, 0).unwrap();
+        store_update.commit().unwrap();
+        let state_update = tries.new_trie_update(0, root);
+        let access_key =
+            get_access_key(&state_update, &alice_account(), &signer.public_key()).unwrap().unwrap();
+        assert_eq!(access_key.nonce, 1);
+
+        // The same delegate action can't be executed twice.
+        let result = runtime
+            .apply(
+                tries.get_trie_for_shard(0),
+                root,
+                &None,
+                &apply_state,
+                &receipts,
+                &[],
+                &epoch_info_provider,
+            )
+            .unwrap();
+        match &result.outcomes[0].outcome.status {
+            ExecutionStatus::Failure(TxExecutionError::ActionError(e)) => assert_eq!(
+                e.kind,
+                ActionErrorKind::DelegateActionInvalidNonce { delegate_nonce: 1, ak_nonce: 1 }
+            ),
+            status => panic!("unexpected status {:?}", status),
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "protocol_feature_function_call_permission_v2")]
+    fn test_apply_access_key_expiration_checked_at_chunk_height() {
+        use near_primitives::account::{AccessKeyPermission, FunctionCallPermissionV2};
+        use near_primitives::errors::{InvalidAccessKeyError, InvalidTxError};
+
+        let (runtime, tries, root, mut apply_state, signer, epoch_info_provider) =
+            setup_runtime(to_yocto(1_000_000), 0, 10u64.pow(15));
+
+        let mut state_update = tries.new_trie_update(0, root);
+        set_access_key(
+            &mut state_update,
+            alice_account(),
+            signer.public_key(),
+            &AccessKey {
+                nonce: 0,
+                permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                    allowance: None,
+                    receiver_id: bob_account(),
+                    method_names: vec![],
+                    max_deposit_per_call: 0,
+                    expires_at_height: Some(11),
+                }),
+            },
+        );
+        state_update.commit(StateChangeCause::InitialState);
+        let trie_changes = state_update.finalize().unwrap().0;
+        let (store_update, root) = tries.apply_all(&trie_changes, 0).unwrap();
+        store_update.commit().unwrap();
+
+        let transactions = vec![SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            &*signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "hello".to_string(),
+                args: vec![],
+                gas: 10u64.pow(12),
+                deposit: 0,
+            })],
+            CryptoHash::default(),
+        )];
+
+        // The chunk is created at height 10, but included into a block at height 12 because of
+        // the skipped heights. The key is still valid at the height the chunk was produced at.
+        apply_state.block_index = 12;
+        apply_state.chunk_height_created = 10;
+        let result = runtime
+            .apply(
+                tries.get_trie_for_shard(0),
+                root,
+                &None,
+                &apply_state,
+                &[],
+                &transactions,
+                &epoch_info_provider,
+            )
+            .unwrap();
+        assert_eq!(result.outcomes.len(), 1);
+
+        apply_state.chunk_height_created = 11;
+        let err = runtime
+            .apply(
+                tries.get_trie_for_shard(0),
+                root,
+                &None,
+                &apply_state,
+                &[],
+                &transactions,
+                &epoch_info_provider,
+            )
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired { expires_at_height: 11, .. }
+            ))
+        ));
+    }
 }
@@ -38,6 +38,11 @@ lazy_static::lazy_static! {
             "near_action_delete_account_total",
             "The number of DeleteAccount actions called since starting this node"
         );
+    pub static ref ACTION_DELEGATE_TOTAL: near_metrics::Result<IntCounter> =
+        try_create_int_counter(
+            "near_action_delegate_total",
+            "The number of Delegate actions called since starting this node"
+        );
     pub static ref TRANSACTION_PROCESSED_TOTAL: near_metrics::Result<IntCounter> =
         try_create_int_counter(
             "near_transaction_processed_total",
@@ -55,33 +55,37 @@ impl TrieViewer {
             .ok_or_else(|| format!("access key {} does not exist while viewing", public_key).into())
     }
 
+    /// Returns the contract data of `account_id` under `prefix`. With `include_proof`, the result
+    /// also carries the trie nodes needed to check it with `near_store::verify_view_state_proof`.
     pub fn view_state(
         &self,
         state_update: &TrieUpdate,
         account_id: &AccountId,
         prefix: &[u8],
+        include_proof: bool,
     ) -> Result<ViewStateResult, Box<dyn std::error::Error>> {
         if !is_valid_account_id(account_id) {
             return Err(format!("Account ID '{}' is not valid", account_id).into());
         }
-        let mut values = vec![];
         let query = trie_key_parsers::get_raw_prefix_for_contract_data(account_id, prefix);
         let acc_sep_len = query.len() - prefix.len();
-        let mut iter = state_update.trie.iter(&state_update.get_root())?;
-        iter.seek(&query)?;
-        for item in iter {
-            let (key, value) = item?;
-            if !key.starts_with(&query.as_ref()) {
-                break;
-            }
-            values.push(StateItem {
+        let (values, proof) = if include_proof {
+            let (values, proof) = state_update
+                .trie
+                .get_values_with_prefix_and_proof(&state_update.get_root(), &query)?;
+            (values, proof.0.iter().map(to_base64).collect())
+        } else {
+            (state_update.trie.get_values_with_prefix(&state_update.get_root(), &query)?, vec![])
+        };
+        let values = values
+            .into_iter()
+            .map(|(key, value)| StateItem {
                 key: to_base64(&key[acc_sep_len..]),
                 value: to_base64(&value),
                 proof: vec![],
-            });
-        }
-        // TODO(2076): Add proofs for the storage items.
-        Ok(ViewStateResult { values, proof: vec![] })
+            })
+            .collect();
+        Ok(ViewStateResult { values, proof })
     }
 
     pub fn call_function(
@@ -156,6 +160,7 @@ impl TrieViewer {
                 &RuntimeFeesConfig::default(),
                 &[],
                 current_protocol_version,
+                None,
             )
         };
         let elapsed = now.elapsed();
@@ -188,6 +193,7 @@ mod tests {
     use near_primitives::trie_key::TrieKey;
     use near_primitives::types::StateChangeCause;
     use near_primitives::views::StateItem;
+    use near_store::verify_view_state_proof;
     use testlib::runtime_utils::{
         alice_account, encode_int, get_runtime_and_trie, get_test_trie_viewer,
     };
@@ -321,7 +327,7 @@ mod tests {
 
         let state_update = tries.new_trie_update(0, new_root);
         let trie_viewer = TrieViewer::new();
-        let result = trie_viewer.view_state(&state_update, &alice_account(), b"").unwrap();
+        let result = trie_viewer.view_state(&state_update, &alice_account(), b"", false).unwrap();
         assert_eq!(result.proof, Vec::<String>::new());
         assert_eq!(
             result.values,
@@ -338,9 +344,11 @@ mod tests {
                 }
             ]
         );
-        let result = trie_viewer.view_state(&state_update, &alice_account(), b"xyz").unwrap();
+        let result =
+            trie_viewer.view_state(&state_update, &alice_account(), b"xyz", false).unwrap();
         assert_eq!(result.values, []);
-        let result = trie_viewer.view_state(&state_update, &alice_account(), b"test123").unwrap();
+        let result =
+            trie_viewer.view_state(&state_update, &alice_account(), b"test123", false).unwrap();
         assert_eq!(
             result.values,
             [StateItem {
@@ -349,6 +357,17 @@ mod tests {
                 proof: vec![]
             }]
         );
+
+        let result =
+            trie_viewer.view_state(&state_update, &alice_account(), b"test", true).unwrap();
+        assert_eq!(result.values.len(), 2);
+        assert!(!result.proof.is_empty());
+        assert!(verify_view_state_proof(&new_root, &alice_account(), b"test", &result));
+        assert!(!verify_view_state_proof(&new_root, &alice_account(), b"test3", &result));
+        assert!(!verify_view_state_proof(&new_root, &"alina".to_string(), b"test", &result));
+        let mut tampered = result.clone();
+        tampered.values[0].value = "MTI0".to_string();
+        assert!(!verify_view_state_proof(&new_root, &alice_account(), b"test", &tampered));
     }
 
     #[test]
@@ -1,21 +1,24 @@
 use near_crypto::key_conversion::is_valid_staking_key;
-use near_primitives::account::AccessKeyPermission;
+use near_crypto::PublicKey;
+use near_primitives::account::{AccessKey, AccessKeyPermission};
+use near_primitives::checked_feature;
 use near_primitives::errors::{
     ActionsValidationError, InvalidAccessKeyError, InvalidTxError, ReceiptValidationError,
     RuntimeError,
 };
 use near_primitives::receipt::{ActionReceipt, DataReceipt, Receipt, ReceiptEnum};
 use near_primitives::transaction::{
-    Action, AddKeyAction, DeleteAccountAction, DeployContractAction, FunctionCallAction,
-    SignedTransaction, StakeAction,
+    Action, AddKeyAction, DelegateAction, DeleteAccountAction, DeployContractAction,
+    FunctionCallAction, SignedTransaction, StakeAction,
 };
+use near_primitives::types::AccountId;
 use near_primitives::utils::is_valid_account_id;
 use near_primitives::version::ProtocolVersion;
 use near_runtime_configs::get_insufficient_storage_stake;
 use near_store::{
     get_access_key, get_account, set_access_key, set_account, StorageError, TrieUpdate,
 };
-use near_vm_logic::types::Balance;
+use near_vm_logic::types::{Balance, BlockHeight};
 use near_vm_logic::VMLimitConfig;
 
 use crate::config::{total_prepaid_gas, tx_cost, RuntimeConfig, TransactionCost};
@@ -52,6 +55,8 @@ pub fn validate_transaction(
 
     validate_actions(&config.wasm_config.limit_config, &transaction.actions)
         .map_err(|e| InvalidTxError::ActionsValidation(e))?;
+    validate_actions_protocol_version(&transaction.actions, current_protocol_version)
+        .map_err(|e| InvalidTxError::ActionsValidation(e))?;
 
     let sender_is_receiver = &transaction.receiver_id == signer_id;
 
@@ -67,12 +72,15 @@ pub fn validate_transaction(
 
 /// Verifies the signed transaction on top of given state, charges transaction fees
 /// and balances, and updates the state for the used account and access keys.
+/// `block_height` is the height of the block that includes the transaction. Expiration of access
+/// keys is only checked when it's known.
 pub fn verify_and_charge_transaction(
     config: &RuntimeConfig,
     state_update: &mut TrieUpdate,
     gas_price: Balance,
     signed_transaction: &SignedTransaction,
     verify_signature: bool,
+    block_height: Option<BlockHeight>,
     current_protocol_version: ProtocolVersion,
 ) -> Result<VerificationResult, RuntimeError> {
     let TransactionCost { gas_burnt, gas_remaining, receipt_gas_price, total_cost, burnt_amount } =
@@ -122,19 +130,20 @@ pub fn verify_and_charge_transaction(
             cost: total_cost,
         })?;
 
-    if let AccessKeyPermission::FunctionCall(ref mut function_call_permission) =
-        access_key.permission
-    {
-        if let Some(ref mut allowance) = function_call_permission.allowance {
-            *allowance = allowance.checked_sub(total_cost).ok_or_else(|| {
-                InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::NotEnoughAllowance {
-                    account_id: signer_id.clone(),
-                    public_key: transaction.public_key.clone(),
-                    allowance: *allowance,
-                    cost: total_cost,
-                })
-            })?;
-        }
+    let allowance = match access_key.permission {
+        AccessKeyPermission::FunctionCall(ref mut permission) => permission.allowance.as_mut(),
+        AccessKeyPermission::FunctionCallV2(ref mut permission) => permission.allowance.as_mut(),
+        AccessKeyPermission::FullAccess => None,
+    };
+    if let Some(allowance) = allowance {
+        *allowance = allowance.checked_sub(total_cost).ok_or_else(|| {
+            InvalidTxError::InvalidAccessKeyError(InvalidAccessKeyError::NotEnoughAllowance {
+                account_id: signer_id.clone(),
+                public_key: transaction.public_key.clone(),
+                allowance: *allowance,
+                cost: total_cost,
+            })
+        })?;
     }
 
     match get_insufficient_storage_stake(&signer, &config) {
@@ -151,49 +160,15 @@ pub fn verify_and_charge_transaction(
         }
     };
 
-    if let AccessKeyPermission::FunctionCall(ref function_call_permission) = access_key.permission {
-        if transaction.actions.len() != 1 {
-            return Err(InvalidTxError::InvalidAccessKeyError(
-                InvalidAccessKeyError::RequiresFullAccess,
-            )
-            .into());
-        }
-        if let Some(Action::FunctionCall(ref function_call)) = transaction.actions.get(0) {
-            if function_call.deposit > 0 {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::DepositWithFunctionCall,
-                )
-                .into());
-            }
-            if transaction.receiver_id != function_call_permission.receiver_id {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::ReceiverMismatch {
-                        tx_receiver: transaction.receiver_id.clone(),
-                        ak_receiver: function_call_permission.receiver_id.clone(),
-                    },
-                )
-                .into());
-            }
-            if !function_call_permission.method_names.is_empty()
-                && function_call_permission
-                    .method_names
-                    .iter()
-                    .all(|method_name| &function_call.method_name != method_name)
-            {
-                return Err(InvalidTxError::InvalidAccessKeyError(
-                    InvalidAccessKeyError::MethodNameMismatch {
-                        method_name: function_call.method_name.clone(),
-                    },
-                )
-                .into());
-            }
-        } else {
-            return Err(InvalidTxError::InvalidAccessKeyError(
-                InvalidAccessKeyError::RequiresFullAccess,
-            )
-            .into());
-        }
-    };
+    check_access_key_permission(
+        &access_key,
+        signer_id,
+        &transaction.public_key,
+        &transaction.receiver_id,
+        &transaction.actions,
+        block_height,
+    )
+    .map_err(InvalidTxError::InvalidAccessKeyError)?;
 
     set_access_key(state_update, signer_id.clone(), transaction.public_key.clone(), &access_key);
     set_account(state_update, signer_id.clone(), &signer);
@@ -201,6 +176,76 @@ pub fn verify_and_charge_transaction(
     Ok(VerificationResult { gas_burnt, gas_remaining, receipt_gas_price, burnt_amount })
 }
 
+/// Checks that the access key `public_key` of `account_id` permits sending the given actions to
+/// `receiver_id`. Function call access keys only permit a single function call action. Expiration
+/// of the access key is only checked when `block_height` is known.
+pub(crate) fn check_access_key_permission(
+    access_key: &AccessKey,
+    account_id: &AccountId,
+    public_key: &PublicKey,
+    receiver_id: &AccountId,
+    actions: &[Action],
+    block_height: Option<BlockHeight>,
+) -> Result<(), InvalidAccessKeyError> {
+    // The receiver, the allowed method names, the deposit limit and the expiration height of
+    // a function call access key.
+    let (ak_receiver_id, method_names, max_deposit_per_call, expires_at_height) =
+        match access_key.permission {
+            AccessKeyPermission::FunctionCall(ref permission) => {
+                (&permission.receiver_id, &permission.method_names, None, None)
+            }
+            AccessKeyPermission::FunctionCallV2(ref permission) => (
+                &permission.receiver_id,
+                &permission.method_names,
+                Some(permission.max_deposit_per_call),
+                permission.expires_at_height,
+            ),
+            AccessKeyPermission::FullAccess => return Ok(()),
+        };
+    if let (Some(expires_at_height), Some(block_height)) = (expires_at_height, block_height) {
+        if block_height >= expires_at_height {
+            return Err(InvalidAccessKeyError::AccessKeyExpired {
+                account_id: account_id.clone(),
+                public_key: public_key.clone(),
+                expires_at_height,
+            });
+        }
+    }
+    if actions.len() != 1 {
+        return Err(InvalidAccessKeyError::RequiresFullAccess);
+    }
+    if let Some(Action::FunctionCall(ref function_call)) = actions.get(0) {
+        match max_deposit_per_call {
+            None if function_call.deposit > 0 => {
+                return Err(InvalidAccessKeyError::DepositWithFunctionCall);
+            }
+            Some(max_deposit_per_call) if function_call.deposit > max_deposit_per_call => {
+                return Err(InvalidAccessKeyError::DepositExceedsLimit {
+                    deposit: function_call.deposit,
+                    max_deposit_per_call,
+                });
+            }
+            _ => {}
+        }
+        if receiver_id != ak_receiver_id {
+            return Err(InvalidAccessKeyError::ReceiverMismatch {
+                tx_receiver: receiver_id.clone(),
+                ak_receiver: ak_receiver_id.clone(),
+            });
+        }
+        if !method_names.is_empty()
+            && method_names.iter().all(|method_name| &function_call.method_name != method_name)
+        {
+            return Err(InvalidAccessKeyError::MethodNameMismatch {
+                method_name: function_call.method_name.clone(),
+            });
+        }
+        Ok(())
+    } else {
+        Err(InvalidAccessKeyError::RequiresFullAccess)
+    }
+}
+
 /// Validates a given receipt. Checks validity of the predecessor and receiver account IDs and
 /// the validity of the Action or Data receipt.
 pub(crate) fn validate_receipt(
@@ -319,6 +364,7 @@ pub fn validate_action(
         Action::AddKey(a) => validate_add_key_action(limit_config, a),
         Action::DeleteKey(_) => Ok(()),
         Action::DeleteAccount(a) => validate_delete_account_action(a),
+        Action::Delegate(a) => validate_delegate_action(limit_config, &a.delegate_action),
     }
 }
 
@@ -375,40 +421,88 @@ fn validate_stake_action(action: &StakeAction) -> Result<(), ActionsValidationEr
     Ok(())
 }
 
-/// Validates `AddKeyAction`. If the access key permission is `FunctionCall` checks that the
-/// `receiver_id` is a valid account ID, checks the total number of bytes of the method names
-/// doesn't exceed the limit and every method name length doesn't exceed the limit.
+/// Validates `AddKeyAction`. If the access key permission is `FunctionCall` or `FunctionCallV2`
+/// checks that the `receiver_id` is a valid account ID, checks the total number of bytes of the
+/// method names doesn't exceed the limit and every method name length doesn't exceed the limit.
 fn validate_add_key_action(
     limit_config: &VMLimitConfig,
     action: &AddKeyAction,
 ) -> Result<(), ActionsValidationError> {
-    if let AccessKeyPermission::FunctionCall(fc) = &action.access_key.permission {
-        if !is_valid_account_id(&fc.receiver_id) {
-            return Err(ActionsValidationError::InvalidAccountId {
-                account_id: fc.receiver_id.clone(),
+    let (receiver_id, method_names) = match &action.access_key.permission {
+        AccessKeyPermission::FunctionCall(fc) => (&fc.receiver_id, &fc.method_names),
+        AccessKeyPermission::FunctionCallV2(fc) => (&fc.receiver_id, &fc.method_names),
+        AccessKeyPermission::FullAccess => return Ok(()),
+    };
+    if !is_valid_account_id(receiver_id) {
+        return Err(ActionsValidationError::InvalidAccountId { account_id: receiver_id.clone() });
+    }
+    // Checking method name length limits
+    let mut total_number_of_bytes = 0;
+    for method_name in method_names {
+        let length = method_name.len() as u64;
+        if length > limit_config.max_length_method_name {
+            return Err(ActionsValidationError::AddKeyMethodNameLengthExceeded {
+                length,
+                limit: limit_config.max_length_method_name,
             });
         }
-        // Checking method name length limits
-        let mut total_number_of_bytes = 0;
-        for method_name in &fc.method_names {
-            let length = method_name.len() as u64;
-            if length > limit_config.max_length_method_name {
-                return Err(ActionsValidationError::AddKeyMethodNameLengthExceeded {
-                    length,
-                    limit: limit_config.max_length_method_name,
+        // Adding terminating character to the total number of bytes
+        total_number_of_bytes += length + 1;
+    }
+    if total_number_of_bytes > limit_config.max_number_bytes_method_names {
+        return Err(ActionsValidationError::AddKeyMethodNamesNumberOfBytesExceeded {
+            total_number_of_bytes,
+            limit: limit_config.max_number_bytes_method_names,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that the actions of a transaction only use features enabled in the given protocol
+/// version. Receipts are not checked, since their actions come from already validated
+/// transactions or from host functions that are gated by the protocol version themselves.
+fn validate_actions_protocol_version(
+    actions: &[Action],
+    current_protocol_version: ProtocolVersion,
+) -> Result<(), ActionsValidationError> {
+    for action in actions {
+        match action {
+            Action::AddKey(add_key)
+                if !checked_feature!(
+                    "protocol_feature_function_call_permission_v2",
+                    FunctionCallPermissionV2,
+                    current_protocol_version
+                ) =>
+            {
+                if let AccessKeyPermission::FunctionCallV2(_) = add_key.access_key.permission {
+                    return Err(ActionsValidationError::UnsupportedProtocolFeature {
+                        protocol_feature: "FunctionCallV2 access key permission".to_string(),
+                        version: current_protocol_version,
+                    });
+                }
+            }
+            Action::Delegate(_)
+                if !checked_feature!(
+                    "protocol_feature_delegate_action",
+                    DelegateAction,
+                    current_protocol_version
+                ) =>
+            {
+                return Err(ActionsValidationError::UnsupportedProtocolFeature {
+                    protocol_feature: "Delegate action".to_string(),
+                    version: current_protocol_version,
                 });
             }
-            // Adding terminating character to the total number of bytes
-            total_number_of_bytes += length + 1;
-        }
-        if total_number_of_bytes > limit_config.max_number_bytes_method_names {
-            return Err(ActionsValidationError::AddKeyMethodNamesNumberOfBytesExceeded {
-                total_number_of_bytes,
-                limit: limit_config.max_number_bytes_method_names,
-            });
+            Action::Delegate(delegate) => {
+                validate_actions_protocol_version(
+                    &delegate.delegate_action.actions,
+                    current_protocol_version,
+                )?;
+            }
+            _ => {}
         }
     }
-
     Ok(())
 }
 
@@ -425,20 +519,46 @@ fn validate_delete_account_action(
     Ok(())
 }
 
+/// Validates `DelegateAction`. Checks that the `sender_id` and the `receiver_id` are valid
+/// account IDs, that the inner actions don't contain delegate actions and validates them.
+fn validate_delegate_action(
+    limit_config: &VMLimitConfig,
+    action: &DelegateAction,
+) -> Result<(), ActionsValidationError> {
+    for account_id in &[&action.sender_id, &action.receiver_id] {
+        if !is_valid_account_id(account_id) {
+            return Err(ActionsValidationError::InvalidAccountId {
+                account_id: (*account_id).clone(),
+            });
+        }
+    }
+    if action.actions.iter().any(|a| matches!(a, Action::Delegate(_))) {
+        return Err(ActionsValidationError::DelegateActionMustNotBeNested);
+    }
+    validate_actions(limit_config, &action.actions)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
 
-    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signer};
-    use near_primitives::account::{AccessKey, Account, FunctionCallPermission};
+    use near_crypto::{InMemorySigner, KeyType, PublicKey, Signature, Signer};
+    use near_primitives::account::{
+        AccessKey, Account, FunctionCallPermission, FunctionCallPermissionV2,
+    };
     use near_primitives::hash::{hash, CryptoHash};
     use near_primitives::receipt::DataReceiver;
     use near_primitives::test_utils::account_new;
     use near_primitives::transaction::{
-        CreateAccountAction, DeleteKeyAction, StakeAction, TransferAction,
+        CreateAccountAction, DeleteKeyAction, SignedDelegateAction, StakeAction, TransferAction,
     };
     use near_primitives::types::{AccountId, Balance, MerkleHash, StateChangeCause};
     use near_primitives::version::PROTOCOL_VERSION;
+    #[cfg(any(
+        feature = "protocol_feature_function_call_permission_v2",
+        feature = "protocol_feature_delegate_action"
+    ))]
+    use near_primitives::version::{ProtocolFeature, PROTOCOL_FEATURES_TO_VERSION_MAPPING};
     use near_store::test_utils::create_tries;
     use testlib::runtime_utils::{alice_account, bob_account, eve_dot_alice_account};
 
@@ -509,6 +629,7 @@ mod tests {
                 gas_price,
                 &signed_transaction,
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -541,6 +662,7 @@ mod tests {
             gas_price,
             &transaction,
             true,
+            None,
             PROTOCOL_VERSION,
         )
         .expect("valid transaction");
@@ -662,6 +784,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 false,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -728,6 +851,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -760,6 +884,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -808,6 +933,7 @@ mod tests {
                 CryptoHash::default(),
             ),
             true,
+            None,
             PROTOCOL_VERSION,
         )
         .expect_err("expected an error");
@@ -859,6 +985,7 @@ mod tests {
                 CryptoHash::default(),
             ),
             true,
+            None,
             PROTOCOL_VERSION,
         )
         .expect_err("expected an error");
@@ -900,6 +1027,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -950,6 +1078,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -972,6 +1101,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -994,6 +1124,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -1038,6 +1169,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -1085,6 +1217,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -1129,6 +1262,7 @@ mod tests {
                     CryptoHash::default(),
                 ),
                 true,
+                None,
                 PROTOCOL_VERSION,
             )
             .expect_err("expected an error"),
@@ -1138,6 +1272,140 @@ mod tests {
         );
     }
 
+    fn function_call_v2_access_key() -> AccessKey {
+        AccessKey {
+            nonce: 0,
+            permission: AccessKeyPermission::FunctionCallV2(FunctionCallPermissionV2 {
+                allowance: None,
+                receiver_id: bob_account(),
+                method_names: vec![],
+                max_deposit_per_call: 100,
+                expires_at_height: Some(10),
+            }),
+        }
+    }
+
+    fn function_call_with_deposit(signer: &dyn Signer, deposit: Balance) -> SignedTransaction {
+        SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            bob_account(),
+            signer,
+            vec![Action::FunctionCall(FunctionCallAction {
+                method_name: "hello".to_string(),
+                args: b"abc".to_vec(),
+                gas: 100,
+                deposit,
+            })],
+            CryptoHash::default(),
+        )
+    }
+
+    #[test]
+    fn test_validate_transaction_function_call_v2_deposit() {
+        let config = RuntimeConfig::default();
+        let (signer, mut state_update, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(function_call_v2_access_key()));
+
+        verify_and_charge_transaction(
+            &config,
+            &mut state_update,
+            gas_price,
+            &function_call_with_deposit(&*signer, 100),
+            true,
+            Some(9),
+            PROTOCOL_VERSION,
+        )
+        .expect("deposit within the limit");
+        state_update.rollback();
+
+        assert_eq!(
+            verify_and_charge_transaction(
+                &config,
+                &mut state_update,
+                gas_price,
+                &function_call_with_deposit(&*signer, 101),
+                true,
+                Some(9),
+                PROTOCOL_VERSION,
+            )
+            .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::DepositExceedsLimit {
+                    deposit: 101,
+                    max_deposit_per_call: 100,
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_function_call_v2_expired() {
+        let config = RuntimeConfig::default();
+        let (signer, mut state_update, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(function_call_v2_access_key()));
+
+        assert_eq!(
+            verify_and_charge_transaction(
+                &config,
+                &mut state_update,
+                gas_price,
+                &function_call_with_deposit(&*signer, 0),
+                true,
+                Some(10),
+                PROTOCOL_VERSION,
+            )
+            .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::InvalidAccessKeyError(
+                InvalidAccessKeyError::AccessKeyExpired {
+                    account_id: alice_account(),
+                    public_key: signer.public_key(),
+                    expires_at_height: 10,
+                },
+            )),
+        );
+    }
+
+    #[test]
+    fn test_validate_transaction_add_function_call_v2_key_protocol_version() {
+        let config = RuntimeConfig::default();
+        let (signer, _, gas_price) =
+            setup_common(TESTING_INIT_BALANCE, 0, Some(AccessKey::full_access()));
+        let transaction = SignedTransaction::from_actions(
+            1,
+            alice_account(),
+            alice_account(),
+            &*signer,
+            vec![Action::AddKey(AddKeyAction {
+                public_key: PublicKey::empty(KeyType::ED25519),
+                access_key: function_call_v2_access_key(),
+            })],
+            CryptoHash::default(),
+        );
+
+        #[cfg(feature = "protocol_feature_function_call_permission_v2")]
+        let unsupported_version = {
+            let feature_version =
+                PROTOCOL_FEATURES_TO_VERSION_MAPPING[&ProtocolFeature::FunctionCallPermissionV2];
+            validate_transaction(&config, gas_price, &transaction, true, feature_version)
+                .expect("valid transaction");
+            feature_version - 1
+        };
+        // The key can't be added at any protocol version while the feature is disabled.
+        #[cfg(not(feature = "protocol_feature_function_call_permission_v2"))]
+        let unsupported_version = PROTOCOL_VERSION;
+        assert_eq!(
+            validate_transaction(&config, gas_price, &transaction, true, unsupported_version)
+                .expect_err("expected an error"),
+            RuntimeError::InvalidTxError(InvalidTxError::ActionsValidation(
+                ActionsValidationError::UnsupportedProtocolFeature {
+                    protocol_feature: "FunctionCallV2 access key permission".to_string(),
+                    version: unsupported_version,
+                }
+            )),
+        );
+    }
+
     // Receipts
 
     #[test]
@@ -1532,4 +1800,74 @@ mod tests {
         )
         .expect("valid action");
     }
+
+    fn delegate_action(actions: Vec<Action>) -> Action {
+        Action::Delegate(SignedDelegateAction {
+            delegate_action: DelegateAction {
+                sender_id: alice_account(),
+                receiver_id: bob_account(),
+                actions,
+                nonce: 1,
+                max_block_height: 100,
+                public_key: PublicKey::empty(KeyType::ED25519),
+            },
+            signature: Signature::empty(KeyType::ED25519),
+        })
+    }
+
+    #[test]
+    fn test_validate_action_valid_delegate() {
+        validate_action(
+            &VMLimitConfig::default(),
+            &delegate_action(vec![Action::Transfer(TransferAction { deposit: 10 })]),
+        )
+        .expect("valid action");
+    }
+
+    #[test]
+    fn test_validate_action_invalid_delegate_receiver_id() {
+        let mut action = delegate_action(vec![]);
+        if let Action::Delegate(delegate) = &mut action {
+            delegate.delegate_action.receiver_id = "WHAT?".to_string();
+        }
+        assert_eq!(
+            validate_action(&VMLimitConfig::default(), &action).expect_err("expected an error"),
+            ActionsValidationError::InvalidAccountId { account_id: "WHAT?".to_string() },
+        );
+    }
+
+    #[test]
+    fn test_validate_action_nested_delegate() {
+        assert_eq!(
+            validate_action(
+                &VMLimitConfig::default(),
+                &delegate_action(vec![delegate_action(vec![])]),
+            )
+            .expect_err("expected an error"),
+            ActionsValidationError::DelegateActionMustNotBeNested,
+        );
+    }
+
+    #[test]
+    fn test_validate_actions_delegate_protocol_version() {
+        let actions = vec![delegate_action(vec![Action::Transfer(TransferAction { deposit: 10 })])];
+        #[cfg(feature = "protocol_feature_delegate_action")]
+        let unsupported_version = {
+            let feature_version =
+                PROTOCOL_FEATURES_TO_VERSION_MAPPING[&ProtocolFeature::DelegateAction];
+            validate_actions_protocol_version(&actions, feature_version).expect("valid actions");
+            feature_version - 1
+        };
+        // The action is rejected at any protocol version while the feature is disabled.
+        #[cfg(not(feature = "protocol_feature_delegate_action"))]
+        let unsupported_version = PROTOCOL_VERSION;
+        assert_eq!(
+            validate_actions_protocol_version(&actions, unsupported_version)
+                .expect_err("expected an error"),
+            ActionsValidationError::UnsupportedProtocolFeature {
+                protocol_feature: "Delegate action".to_string(),
+                version: unsupported_version,
+            },
+        );
+    }
 }
@@ -58,6 +58,7 @@ impl StandaloneRuntime {
 
         let apply_state = ApplyState {
             block_index: 0,
+            chunk_height_created: 0,
             last_block_hash: Default::default(),
             epoch_id: Default::default(),
             epoch_height: 0,
@@ -67,6 +68,9 @@ impl StandaloneRuntime {
             random_seed: Default::default(),
             current_protocol_version: PROTOCOL_VERSION,
             config: Arc::new(runtime_config),
+            cache: None,
+            profile_gas: false,
+            states_to_patch: None,
         };
 
         Self {
@@ -101,6 +105,7 @@ impl StandaloneRuntime {
         self.root = root;
         store_update.commit().unwrap();
         self.apply_state.block_index += 1;
+        self.apply_state.chunk_height_created += 1;
 
         (apply_result.outgoing_receipts, apply_result.outcomes)
     }
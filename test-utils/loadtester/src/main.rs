@@ -182,7 +182,9 @@ fn create_genesis(matches: &clap::ArgMatches<'_>) {
         let node_dir = dir.join(format!("{}.{}", prefix, i));
         fs::create_dir_all(node_dir.clone()).expect("Failed to create directory");
 
-        validator_signers[i].write_to_file(&node_dir.join(&configs[i].validator_key_file));
+        validator_signers[i]
+            .write_to_file(&node_dir.join(&configs[i].validator_key_file))
+            .expect("Failed to write validator key");
         network_signers[i].write_to_file(&node_dir.join(&configs[i].node_key_file));
 
         genesis.to_file(&node_dir.join(&configs[i].genesis_file));
@@ -126,6 +126,7 @@ impl RuntimeUser {
     fn apply_state(&self) -> ApplyState {
         ApplyState {
             block_index: 0,
+            chunk_height_created: 0,
             last_block_hash: Default::default(),
             block_timestamp: 0,
             epoch_height: 0,
@@ -135,6 +136,9 @@ impl RuntimeUser {
             epoch_id: Default::default(),
             current_protocol_version: PROTOCOL_VERSION,
             config: self.runtime_config.clone(),
+            cache: None,
+            profile_gas: false,
+            states_to_patch: None,
         }
     }
 
@@ -209,7 +213,7 @@ impl User for RuntimeUser {
     fn view_state(&self, account_id: &AccountId, prefix: &[u8]) -> Result<ViewStateResult, String> {
         let state_update = self.client.read().expect(POISONED_LOCK_ERR).get_state_update();
         self.trie_viewer
-            .view_state(&state_update, account_id, prefix)
+            .view_state(&state_update, account_id, prefix, false)
             .map_err(|err| err.to_string())
     }
 
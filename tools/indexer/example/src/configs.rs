@@ -17,11 +17,24 @@ pub(crate) struct Opts {
 #[derive(Clap, Debug)]
 pub(crate) enum SubCommand {
     /// Run NEAR Indexer Example. Start observe the network
-    Run,
+    Run(RunArgs),
     /// Initialize necessary configs
     Init(InitConfigArgs),
 }
 
+#[derive(Clap, Debug)]
+pub(crate) struct RunArgs {
+    /// Write blocks into rotating JSON-lines files in the given directory instead of logging them
+    #[clap(long, conflicts_with = "sqlite")]
+    pub jsonl_dir: Option<std::path::PathBuf>,
+    /// Size in bytes after which a new JSON-lines file is started
+    #[clap(long, default_value = "104857600")]
+    pub jsonl_max_file_size: u64,
+    /// Write blocks into the SQLite database at the given path instead of logging them
+    #[clap(long)]
+    pub sqlite: Option<std::path::PathBuf>,
+}
+
 #[derive(Clap, Debug)]
 pub(crate) struct InitConfigArgs {
     /// chain/network id (localnet, testnet, devnet, betanet)
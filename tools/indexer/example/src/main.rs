@@ -6,10 +6,14 @@ use tracing::info;
 
 use configs::{init_logging, Opts, SubCommand};
 use near_indexer;
+use near_indexer::sinks::{run_sink, JsonLinesSink, SqliteSink};
 
 mod configs;
 
-async fn listen_blocks(mut stream: mpsc::Receiver<near_indexer::StreamerMessage>) {
+async fn listen_blocks(
+    mut stream: mpsc::Receiver<near_indexer::StreamerMessage>,
+    acknowledger: near_indexer::BlockAcknowledger,
+) {
     while let Some(streamer_message) = stream.recv().await {
         // TODO: handle data as you need
         // Example of `block` with all the data
@@ -187,6 +191,10 @@ async fn listen_blocks(mut stream: mpsc::Receiver<near_indexer::StreamerMessage>
             streamer_message.chunks.iter().map(|chunk| chunk.receipts.len()).sum::<usize>(),
             streamer_message.receipt_execution_outcomes.len(),
         );
+        // Acknowledge the block once it is fully handled, so it is not replayed after a restart
+        acknowledger
+            .ack(streamer_message.block.header.height)
+            .expect("Failed to acknowledge the block");
     }
 }
 
@@ -202,14 +210,24 @@ fn main() {
         opts.home_dir.unwrap_or(std::path::PathBuf::from(near_indexer::get_default_home()));
 
     match opts.subcmd {
-        SubCommand::Run => {
+        SubCommand::Run(args) => {
             let indexer_config = near_indexer::IndexerConfig {
                 home_dir,
                 sync_mode: near_indexer::SyncModeEnum::FromInterruption,
+                filter: near_indexer::IndexerFilter::default(),
             };
             let indexer = near_indexer::Indexer::new(indexer_config);
-            let stream = indexer.streamer();
-            actix::spawn(listen_blocks(stream));
+            let (stream, acknowledger) = indexer.streamer_with_ack();
+            if let Some(jsonl_dir) = args.jsonl_dir {
+                let sink = JsonLinesSink::new(jsonl_dir, args.jsonl_max_file_size)
+                    .expect("Failed to create JSON-lines sink");
+                actix::spawn(run_sink(stream, sink, acknowledger));
+            } else if let Some(sqlite) = args.sqlite {
+                let sink = SqliteSink::open(&sqlite).expect("Failed to open SQLite database");
+                actix::spawn(run_sink(stream, sink, acknowledger));
+            } else {
+                actix::spawn(listen_blocks(stream, acknowledger));
+            }
             indexer.start();
         }
         SubCommand::Init(config) => near_indexer::init_configs(
@@ -0,0 +1,61 @@
+//! Reference signer daemon for `RemoteValidatorSigner`, mostly intended for local testing.
+//!
+//! Holds the validator key and signs requests of the node, but never signs two different
+//! blocks, approvals or chunks at the same height.
+use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use clap::{App, Arg};
+use log::{info, warn, LevelFilter};
+
+use near_crypto::{InMemorySigner, KEY_PASSPHRASE_ENV};
+use near_remote_signer::{SignerListener, SignerService, SigningGuard, AUTH_TOKEN_ENV};
+
+fn main() {
+    env_logger::Builder::new().filter(None, LevelFilter::Info).init();
+    let matches = App::new("Remote signer")
+        .about("Signs blocks, approvals and chunks for a validator node with double-sign protection")
+        .arg(
+            Arg::with_name("key-file")
+                .long("key-file")
+                .required(true)
+                .help("Validator key file, encrypted key files are decrypted with $NEAR_KEY_PASSPHRASE")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("listen")
+                .long("listen")
+                .default_value("unix:remote-signer.sock")
+                .help("Address to listen on: unix:<path> or tcp:<host>:<port>, TCP requires a token in $NEAR_REMOTE_SIGNER_TOKEN")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("state-file")
+                .long("state-file")
+                .help("File to persist signed heights to, without it the protection is lost on restart")
+                .takes_value(true),
+        )
+        .get_matches();
+
+    let key_file = PathBuf::from(matches.value_of("key-file").unwrap());
+    let passphrase = env::var(KEY_PASSPHRASE_ENV).ok();
+    let signer = InMemorySigner::from_file_with_passphrase(&key_file, passphrase.as_deref())
+        .unwrap_or_else(|err| panic!("Failed to load key file {}: {}", key_file.display(), err));
+    let state_file = matches.value_of("state-file").map(PathBuf::from);
+    if state_file.is_none() {
+        warn!(target: "signer", "No --state-file given, double-sign protection is lost on restart");
+    }
+    let guard = SigningGuard::open(state_file).expect("Failed to load the signer state");
+    let address = matches.value_of("listen").unwrap();
+    let auth_token = env::var(AUTH_TOKEN_ENV).ok().filter(|token| !token.is_empty());
+    if auth_token.is_none() && !address.starts_with("unix:") {
+        panic!("Listening on {} requires a token in ${}", address, AUTH_TOKEN_ENV);
+    }
+    let service = Arc::new(SignerService::new(signer, guard, auth_token));
+
+    info!(target: "signer", "Signing for {} on {}", service.signer().account_id, address);
+    let listener = SignerListener::bind(address)
+        .unwrap_or_else(|err| panic!("Failed to listen on {}: {}", address, err));
+    service.serve(listener);
+}